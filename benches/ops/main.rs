@@ -13,10 +13,11 @@
 // limitations under the License.
 mod read;
 mod utils;
+mod workloads;
 mod write;
 
 use criterion::criterion_group;
 use criterion::criterion_main;
 
-criterion_group!(benches, read::bench, write::bench);
+criterion_group!(benches, read::bench, write::bench, workloads::bench);
 criterion_main!(benches);
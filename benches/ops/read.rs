@@ -11,9 +11,13 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::time::Duration;
+use std::time::Instant;
+
 use criterion::Criterion;
 use futures::AsyncReadExt;
 use opendal::Operator;
+use opendal_test::services::fs;
 use rand::prelude::*;
 use size::Base;
 use size::Size;
@@ -33,6 +37,17 @@ pub fn bench(c: &mut Criterion) {
         bench_read_full(c, op.clone());
         bench_read_part(c, op.clone());
         bench_read_parallel(c, op.clone());
+        bench_read_small(c, op.clone());
+        bench_observations(c, op.clone());
+
+        if case.0 == "fs" {
+            bench_read_block_size(c);
+        }
+        // Only worth it for remote services which limit the bandwidth of
+        // a single connection.
+        if case.0 == "s3" {
+            bench_fetch_concurrent(c, op.clone());
+        }
     }
 }
 
@@ -161,3 +176,166 @@ fn bench_read_parallel(c: &mut Criterion, op: Operator) {
 
     group.finish()
 }
+
+/// Read 4 MiB in 512 bytes with and without the reader's buffer.
+fn bench_read_small(c: &mut Criterion, op: Operator) {
+    let mut group = c.benchmark_group("read_small");
+
+    let mut rng = thread_rng();
+    let size = Size::Mebibytes(4_usize);
+    let content = gen_bytes(&mut rng, size.bytes() as usize);
+    let path = uuid::Uuid::new_v4().to_string();
+    let temp_data = TempData::generate(op.clone(), &path, content);
+
+    group.throughput(criterion::Throughput::Bytes(size.bytes()));
+    for (name, buffer) in [("unbuffered", 0), ("buffered", 64 * 1024)] {
+        group.bench_with_input(name, &(op.clone(), &path), |b, (op, path)| {
+            b.to_async(&*TOKIO).iter(|| async {
+                let mut r = op.object(path).reader().buffer(buffer);
+                let mut buf = [0; 512];
+                while r.read(&mut buf).await.unwrap() > 0 {}
+            })
+        });
+    }
+
+    std::mem::drop(temp_data);
+    group.finish()
+}
+
+/// Read 4 KiB with observations enabled and disabled.
+///
+/// Medians of both are compared before benching, recording observations
+/// must not be slower than the noise.
+fn bench_observations(c: &mut Criterion, op: Operator) {
+    let mut group = c.benchmark_group("observations");
+
+    let mut rng = thread_rng();
+    let size = Size::Kibibytes(4_usize);
+    let content = gen_bytes(&mut rng, size.bytes() as usize);
+    let path = uuid::Uuid::new_v4().to_string();
+    let temp_data = TempData::generate(op.clone(), &path, content);
+
+    let read = |op: &Operator| {
+        let mut buf = Vec::with_capacity(size.bytes() as usize);
+        TOKIO.block_on(async {
+            op.object(&path)
+                .reader()
+                .read_to_end(&mut buf)
+                .await
+                .unwrap();
+        });
+    };
+    let median = |enabled: bool| {
+        op.set_observations_enabled(enabled);
+        let mut samples: Vec<_> = (0..1000)
+            .map(|_| {
+                let start = Instant::now();
+                read(&op);
+                start.elapsed()
+            })
+            .collect();
+        samples.sort();
+        samples[samples.len() / 2]
+    };
+    // Interleave rounds to cancel the drift, and keep the best of them.
+    let (mut enabled, mut disabled) = (Duration::MAX, Duration::MAX);
+    for _ in 0..5 {
+        enabled = enabled.min(median(true));
+        disabled = disabled.min(median(false));
+    }
+    assert!(
+        enabled.as_secs_f64() <= disabled.as_secs_f64() * 1.1,
+        "observations overhead is too high: enabled {:?}, disabled {:?}",
+        enabled,
+        disabled
+    );
+
+    group.throughput(criterion::Throughput::Bytes(size.bytes()));
+    for (name, enabled) in [("enabled", true), ("disabled", false)] {
+        op.set_observations_enabled(enabled);
+        group.bench_with_input(name, &op, |b, op| {
+            b.iter(|| read(op));
+        });
+    }
+    op.set_observations_enabled(true);
+
+    std::mem::drop(temp_data);
+    group.finish()
+}
+
+/// Fetch 1 GiB in 16 MiB ranges with 1 and 8 ranges in flight.
+fn bench_fetch_concurrent(c: &mut Criterion, op: Operator) {
+    let mut group = c.benchmark_group("fetch_concurrent");
+    // Every iteration downloads 1 GiB, keep the samples as less as possible.
+    group.sample_size(10);
+
+    let mut rng = thread_rng();
+    let size = Size::Gibibytes(1_usize);
+    let part_size = Size::Mebibytes(16_usize).bytes();
+    let content = gen_bytes(&mut rng, size.bytes() as usize);
+    let path = uuid::Uuid::new_v4().to_string();
+    let temp_data = TempData::generate(op.clone(), &path, content);
+
+    group.throughput(criterion::Throughput::Bytes(size.bytes()));
+    for concurrency in [1, 8] {
+        group.bench_with_input(
+            format!("concurrency_{}", concurrency),
+            &(op.clone(), &path),
+            |b, (op, path)| {
+                b.to_async(&*TOKIO).iter(|| async {
+                    op.object(path)
+                        .fetch_range_concurrent(futures::io::sink(), part_size, concurrency)
+                        .await
+                        .unwrap();
+                })
+            },
+        );
+    }
+
+    std::mem::drop(temp_data);
+    group.finish()
+}
+
+/// Read 16 MiB with different io block sizes of fs.
+fn bench_read_block_size(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_block_size");
+
+    let mut rng = thread_rng();
+    let size = Size::Mebibytes(16_usize);
+    let content = gen_bytes(&mut rng, size.bytes() as usize);
+
+    for block_size in [
+        Size::Kibibytes(8_usize),
+        Size::Kibibytes(64),
+        Size::Kibibytes(256),
+        Size::Mebibytes(1),
+    ] {
+        let acc = TOKIO
+            .block_on(fs::new_with_io_block_size(
+                Some(block_size.bytes() as usize),
+            ))
+            .expect("init fs")
+            .expect("fs must be enabled");
+        let op = Operator::new(acc);
+        let path = uuid::Uuid::new_v4().to_string();
+        let buf = vec![0; size.bytes() as usize];
+        let temp_data = TempData::generate(op.clone(), &path, content.clone());
+
+        group.throughput(criterion::Throughput::Bytes(size.bytes()));
+        group.bench_with_input(
+            block_size.to_string(Base::Base2, Style::Abbreviated),
+            &(op.clone(), &path, buf.clone()),
+            |b, (op, path, buf)| {
+                b.to_async(&*TOKIO).iter(|| async {
+                    let mut buf = buf.clone();
+                    let mut r = op.object(path).reader();
+                    r.read_exact(&mut buf).await.unwrap();
+                })
+            },
+        );
+
+        std::mem::drop(temp_data);
+    }
+
+    group.finish()
+}
@@ -33,6 +33,38 @@ pub fn services() -> Vec<(&'static str, Option<Arc<dyn Accessor>>)> {
     })
 }
 
+/// Like [`services`], but memory and fs are always benched: the ones
+/// configured by env are used if set, otherwise memory without latency and
+/// fs under a temp dir.
+pub fn services_always() -> Vec<(&'static str, Option<Arc<dyn Accessor>>)> {
+    TOKIO.block_on(async {
+        let memory = match memory::new().await.expect("init memory") {
+            Some(acc) => acc,
+            None => opendal::services::memory::Backend::build()
+                .finish()
+                .await
+                .expect("init memory"),
+        };
+        let fs = match fs::new().await.expect("init fs") {
+            Some(acc) => acc,
+            None => {
+                let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+                opendal::services::fs::Backend::build()
+                    .root(&root.to_string_lossy())
+                    .finish()
+                    .await
+                    .expect("init fs")
+            }
+        };
+
+        vec![
+            ("memory", Some(memory)),
+            ("fs", Some(fs)),
+            ("s3", s3::new().await.expect("init s3")),
+        ]
+    })
+}
+
 pub fn gen_bytes(rng: &mut ThreadRng, size: usize) -> Vec<u8> {
     let mut content = vec![0; size as usize];
     rng.fill_bytes(&mut content);
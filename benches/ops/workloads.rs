@@ -0,0 +1,58 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use criterion::Criterion;
+use criterion::Throughput;
+use opendal::testing::bench::BenchFixture;
+use opendal::testing::bench::Workload;
+use opendal::Operator;
+
+use super::utils::*;
+
+/// Run all workloads of [`opendal::testing::bench`] on every service.
+pub fn bench(c: &mut Criterion) {
+    for case in services_always() {
+        if case.1.is_none() {
+            println!("{} not set, ignore", case.0);
+            continue;
+        }
+
+        let fixture = BenchFixture::new(Operator::new(case.1.unwrap()));
+        for workload in Workload::all() {
+            let bench = TOKIO
+                .block_on(fixture.prepare(workload))
+                .expect("prepare workload");
+
+            let mut group = c.benchmark_group(workload.group());
+            if let Some(n) = workload.throughput() {
+                group.throughput(Throughput::Bytes(n));
+            }
+            // Every iteration of them takes a while on remote services.
+            let large = workload.throughput().unwrap_or_default() >= 16 * 1024 * 1024;
+            if large || matches!(workload, Workload::List(_)) {
+                group.sample_size(10);
+            }
+            let bench = &bench;
+            group.bench_function(format!("{}/{}", case.0, workload.param()), |b| {
+                b.to_async(&*TOKIO).iter_custom(|iters| async move {
+                    bench.measure(iters).await.expect("measure workload")
+                })
+            });
+            group.finish();
+        }
+
+        TOKIO
+            .block_on(fixture.cleanup())
+            .expect("cleanup workloads");
+    }
+}
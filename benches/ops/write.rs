@@ -30,6 +30,11 @@ pub fn bench(c: &mut Criterion) {
         let op = Operator::new(case.1.unwrap());
 
         bench_write_once(c, op.clone());
+
+        // Only s3 supports multipart upload for now.
+        if case.0 == "s3" {
+            bench_write_multipart(c, op.clone());
+        }
     }
 }
 
@@ -65,3 +70,38 @@ fn bench_write_once(c: &mut Criterion, op: Operator) {
 
     group.finish()
 }
+
+fn bench_write_multipart(c: &mut Criterion, op: Operator) {
+    let mut group = c.benchmark_group("write_multipart");
+    // Every iteration uploads 1 GiB, keep the samples as less as possible.
+    group.sample_size(10);
+
+    let mut rng = thread_rng();
+
+    let size = Size::Gibibytes(1_usize);
+    let part_size = Size::Mebibytes(16_usize).bytes() as u64;
+    let content = gen_bytes(&mut rng, size.bytes() as usize);
+    let path = uuid::Uuid::new_v4().to_string();
+    let temp_data = TempData::existing(op.clone(), &path);
+
+    for parallelism in [1, 8] {
+        group.throughput(criterion::Throughput::Bytes(size.bytes()));
+        group.bench_with_input(
+            format!("parallelism_{}", parallelism),
+            &(op.clone(), &path, content.clone()),
+            |b, (op, path, content)| {
+                b.to_async(&*TOKIO).iter(|| async {
+                    let w = op
+                        .object(path)
+                        .writer()
+                        .part_size(part_size)
+                        .parallelism(parallelism);
+                    w.write_bytes(content.clone()).await.unwrap();
+                })
+            },
+        );
+    }
+
+    std::mem::drop(temp_data);
+    group.finish()
+}
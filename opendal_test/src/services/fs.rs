@@ -24,6 +24,14 @@ use opendal::Accessor;
 /// - `OPENDAL_FS_TEST=on`: set to `on` to enable the test.
 /// - `OPENDAL_FS_ROOT=<path>`: set the root directory of the test.
 pub async fn new() -> Result<Option<Arc<dyn Accessor>>> {
+    new_with_io_block_size(None).await
+}
+
+/// Same as [`new`], but use the given io block size instead of the default
+/// one.
+pub async fn new_with_io_block_size(
+    io_block_size: Option<usize>,
+) -> Result<Option<Arc<dyn Accessor>>> {
     dotenv::from_filename(".env").ok();
 
     if env::var("OPENDAL_FS_TEST").is_err() || env::var("OPENDAL_FS_TEST").unwrap() != "on" {
@@ -35,10 +43,10 @@ pub async fn new() -> Result<Option<Arc<dyn Accessor>>> {
 
     let root = PathBuf::from(root).join(uuid::Uuid::new_v4().to_string());
 
-    Ok(Some(
-        fs::Backend::build()
-            .root(root.to_str().unwrap())
-            .finish()
-            .await?,
-    ))
+    let mut builder = fs::Backend::build();
+    builder.root(root.to_str().unwrap());
+    if let Some(size) = io_block_size {
+        builder.io_block_size(size);
+    }
+    Ok(Some(builder.finish().await?))
 }
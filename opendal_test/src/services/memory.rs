@@ -13,6 +13,7 @@
 // limitations under the License.
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
 
 use opendal::error::Result;
 use opendal::services::memory;
@@ -21,6 +22,10 @@ use opendal::Accessor;
 /// In order to test memory service, please set the following environment variables:
 ///
 /// - `OPENDAL_MEMORY_TEST=on`: set to `on` to enable the test.
+/// - `OPENDAL_MEMORY_LATENCY_MS=<ms>`: optional, latency of every operation.
+/// - `OPENDAL_MEMORY_JITTER_MS=<ms>`: optional, max random extra latency.
+/// - `OPENDAL_MEMORY_THROUGHPUT=<bytes_per_sec>`: optional, transfer speed.
+/// - `OPENDAL_MEMORY_SEED=<u64>`: optional, seed of the jitter.
 pub async fn new() -> Result<Option<Arc<dyn Accessor>>> {
     dotenv::from_filename(".env").ok();
 
@@ -29,5 +34,25 @@ pub async fn new() -> Result<Option<Arc<dyn Accessor>>> {
         return Ok(None);
     }
 
-    Ok(Some(memory::Backend::build().finish().await?))
+    let mut builder = memory::Backend::build();
+    if let Some(ms) = parse_env("OPENDAL_MEMORY_LATENCY_MS") {
+        builder.latency(Duration::from_millis(ms));
+    }
+    if let Some(ms) = parse_env("OPENDAL_MEMORY_JITTER_MS") {
+        builder.jitter(Duration::from_millis(ms));
+    }
+    if let Some(n) = parse_env("OPENDAL_MEMORY_THROUGHPUT") {
+        builder.throughput(n);
+    }
+    if let Some(seed) = parse_env("OPENDAL_MEMORY_SEED") {
+        builder.seed(seed);
+    }
+    Ok(Some(builder.finish().await?))
+}
+
+fn parse_env(key: &str) -> Option<u64> {
+    env::var(key).ok().map(|v| {
+        v.parse()
+            .unwrap_or_else(|_| panic!("{} must be an integer", key))
+    })
 }
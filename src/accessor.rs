@@ -15,17 +15,34 @@
 use std::fmt::Debug;
 use std::sync::Arc;
 
+use anyhow::anyhow;
 use async_trait::async_trait;
 
+use crate::error::Error;
+use crate::error::Kind;
 use crate::error::Result;
 use crate::object::BoxedObjectStream;
 use crate::object::Metadata;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
 use crate::ops::OpDelete;
 use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
 use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
 use crate::ops::OpStat;
 use crate::ops::OpWrite;
+use crate::ops::PresignOperation;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
 use crate::BoxedAsyncReader;
+use crate::ObjectReader;
+use crate::Scheme;
 
 /// Underlying trait of all backends for implementors.
 ///
@@ -36,7 +53,10 @@ use crate::BoxedAsyncReader;
 #[async_trait]
 pub trait Accessor: Send + Sync + Debug {
     /// Read data from the underlying storage into input writer.
-    async fn read(&self, args: &OpRead) -> Result<BoxedAsyncReader> {
+    ///
+    /// Backends should fill the metadata they learned while reading into
+    /// the returned [`ObjectReader`].
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
         let _ = args;
         unimplemented!()
     }
@@ -45,6 +65,20 @@ pub trait Accessor: Send + Sync + Debug {
         let (_, _) = (r, args);
         unimplemented!()
     }
+    /// Append data from input reader to the end of the object, the object
+    /// will be created if not exist.
+    ///
+    /// Most backends don't support this, so we return an error with
+    /// [`Kind::Unsupported`] by default.
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        let _ = r;
+        Err(Error::Object {
+            kind: Kind::Unsupported,
+            op: "append",
+            path: args.path.clone(),
+            source: anyhow!("append is not supported by this backend"),
+        })
+    }
     /// Invoke the `stat` operation on the specified path.
     async fn stat(&self, args: &OpStat) -> Result<Metadata> {
         let _ = args;
@@ -65,18 +99,135 @@ pub trait Accessor: Send + Sync + Debug {
         let _ = args;
         unimplemented!()
     }
+    /// Check if the bucket (or root dir for fs) that this accessor works on
+    /// exists.
+    ///
+    /// Returns an error instead of `false` if we don't have the permission
+    /// to access it.
+    async fn bucket_exists(&self) -> Result<bool> {
+        Err(Error::Object {
+            kind: Kind::Unsupported,
+            op: "bucket_exists",
+            path: "/".to_string(),
+            source: anyhow!("bucket_exists is not supported by this backend"),
+        })
+    }
+
+    /// Return the metadata of this accessor, which describes the guarantees
+    /// it could provide.
+    fn metadata(&self) -> AccessorMetadata {
+        AccessorMetadata::default()
+    }
+
+    /// Run a query on the object and read the matched rows.
+    ///
+    /// Most backends don't support this, so we return an error with
+    /// [`Kind::Unsupported`] by default.
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        Err(Error::Object {
+            kind: Kind::Unsupported,
+            op: "select",
+            path: args.path.clone(),
+            source: anyhow!("select is not supported by this backend"),
+        })
+    }
+
+    /// Generate a presigned request for the operation.
+    ///
+    /// Most backends don't support this, so we return an error with
+    /// [`Kind::Unsupported`] by default. Backends should advertise the
+    /// operations they support by [`AccessorMetadata::can_presign`].
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        Err(Error::Object {
+            kind: Kind::Unsupported,
+            op: "presign",
+            path: args.path.clone(),
+            source: anyhow!("presign is not supported by this backend"),
+        })
+    }
+
+    /// List multipart uploads that have been created but not completed or
+    /// aborted.
+    ///
+    /// Most backends don't support multipart upload, so we return an error
+    /// with [`Kind::Unsupported`] by default.
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        Err(Error::Object {
+            kind: Kind::Unsupported,
+            op: "list_multipart_uploads",
+            path: args.prefix.clone(),
+            source: anyhow!("multipart upload is not supported by this backend"),
+        })
+    }
+
+    /// Abort a multipart upload and free the parts uploaded.
+    ///
+    /// Most backends don't support multipart upload, so we return an error
+    /// with [`Kind::Unsupported`] by default.
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        Err(Error::Object {
+            kind: Kind::Unsupported,
+            op: "abort_multipart_upload",
+            path: args.path.clone(),
+            source: anyhow!("multipart upload is not supported by this backend"),
+        })
+    }
+
+    /// Get the lock status of an object, like the object lock of s3.
+    ///
+    /// Most backends don't support object lock, so we return an error
+    /// with [`Kind::Unsupported`] by default.
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        Err(Error::Object {
+            kind: Kind::Unsupported,
+            op: "retention",
+            path: args.path.clone(),
+            source: anyhow!("object lock is not supported by this backend"),
+        })
+    }
+
+    /// Copy an object to another path inside the backend without
+    /// transferring data through the client.
+    ///
+    /// Not all backends support server-side copy, so we return an error
+    /// with [`Kind::Unsupported`] by default.
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        Err(Error::Object {
+            kind: Kind::Unsupported,
+            op: "copy",
+            path: args.from.clone(),
+            source: anyhow!("server-side copy is not supported by this backend"),
+        })
+    }
+
+    /// Create an empty file or a dir at the path.
+    ///
+    /// Flat backends don't have real dirs, so we write an empty object at
+    /// the path (like `abc/` for dirs) as the marker by default. Backends
+    /// with real dirs must create them instead.
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        let r = Box::new(futures::io::Cursor::new(Vec::new()));
+        self.write(r, &OpWrite::new(&args.path, 0)).await?;
+        Ok(())
+    }
 }
 
 /// All functions in `Accessor` only requires `&self`, so it's safe to implement
 /// `Accessor` for `Arc<dyn Accessor>`.
 #[async_trait]
 impl<T: Accessor> Accessor for Arc<T> {
-    async fn read(&self, args: &OpRead) -> Result<BoxedAsyncReader> {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
         self.as_ref().read(args).await
     }
     async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
         self.as_ref().write(r, args).await
     }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        self.as_ref().append(r, args).await
+    }
     async fn stat(&self, args: &OpStat) -> Result<Metadata> {
         self.as_ref().stat(args).await
     }
@@ -86,4 +237,228 @@ impl<T: Accessor> Accessor for Arc<T> {
     async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
         self.as_ref().list(args).await
     }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.as_ref().bucket_exists().await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.as_ref().metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.as_ref().select(args).await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        self.as_ref().presign(args).await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.as_ref().list_multipart_uploads(args).await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.as_ref().abort_multipart_upload(args).await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.as_ref().retention(args).await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        self.as_ref().copy(args).await
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        self.as_ref().create(args).await
+    }
+}
+
+/// AccessorBuilder is implemented by the builders of all services, so that
+/// the construction of backend could be deferred, see
+/// [`Operator::lazy`][crate::Operator::lazy].
+#[async_trait]
+pub trait AccessorBuilder: Send + Sync + 'static {
+    /// Consume the config in builder to construct an accessor.
+    async fn finish(&mut self) -> Result<Arc<dyn Accessor>>;
+}
+
+/// What a read may observe while the same object is being overwritten
+/// concurrently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReadConsistency {
+    /// A read may observe a partially written object, mixing the old and
+    /// the new content.
+    #[default]
+    Torn,
+    /// A read always observes a complete version: either the one before
+    /// the write or the one after it. A reader that has been opened keeps
+    /// reading the version it opened even if the object is replaced.
+    ///
+    /// Only covers `write`, an `append` may still be observed partially.
+    Committed,
+}
+
+/// Metadata of an accessor, frameworks can check it at startup to make sure
+/// the backend provides the guarantees they depend on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AccessorMetadata {
+    scheme: Option<Scheme>,
+    commit_visible: bool,
+    read_consistency: ReadConsistency,
+    read_version: bool,
+    write_if_not_exists: bool,
+    max_path_len: Option<usize>,
+    max_segment_len: Option<usize>,
+    presign_read: bool,
+    presign_write: bool,
+    presign_stat: bool,
+    presign_delete: bool,
+    delete_all_versions: bool,
+}
+
+impl AccessorMetadata {
+    /// Scheme of the backend, `None` if it's not one of [`Scheme`].
+    pub fn scheme(&self) -> Option<Scheme> {
+        self.scheme
+    }
+
+    pub fn set_scheme(&mut self, v: Scheme) -> &mut Self {
+        self.scheme = Some(v);
+        self
+    }
+
+    /// Whether the accessor can guarantee that an object is not observable
+    /// at its final path until the write finished.
+    ///
+    /// Writes with `commit_visible` will fail with [`Kind::Unsupported`] if
+    /// this is `false`.
+    pub fn can_commit_visible(&self) -> bool {
+        self.commit_visible
+    }
+
+    pub fn set_commit_visible(&mut self, v: bool) -> &mut Self {
+        self.commit_visible = v;
+        self
+    }
+
+    /// What a read may observe while the object is being overwritten, see
+    /// [`ReadConsistency`].
+    ///
+    /// Backends that can't tell default to [`ReadConsistency::Torn`].
+    pub fn read_consistency(&self) -> ReadConsistency {
+        self.read_consistency
+    }
+
+    pub fn set_read_consistency(&mut self, v: ReadConsistency) -> &mut Self {
+        self.read_consistency = v;
+        self
+    }
+
+    /// Whether the accessor can read a specific version of an object via
+    /// [`OpRead::version_id`], and list in [`OpList::snapshot`] mode.
+    pub fn can_read_version(&self) -> bool {
+        self.read_version
+    }
+
+    pub fn set_read_version(&mut self, v: bool) -> &mut Self {
+        self.read_version = v;
+        self
+    }
+
+    /// Whether the accessor can atomically check the existence of an object
+    /// while writing it via [`OpWrite::if_not_exists`].
+    pub fn can_write_if_not_exists(&self) -> bool {
+        self.write_if_not_exists
+    }
+
+    pub fn set_write_if_not_exists(&mut self, v: bool) -> &mut Self {
+        self.write_if_not_exists = v;
+        self
+    }
+
+    /// Max length in bytes of a path relative to the backend root, `None`
+    /// means unlimited.
+    ///
+    /// Backends have already taken their root into account.
+    pub fn max_path_len(&self) -> Option<usize> {
+        self.max_path_len
+    }
+
+    pub fn set_max_path_len(&mut self, v: usize) -> &mut Self {
+        self.max_path_len = Some(v);
+        self
+    }
+
+    /// Max length in bytes of every `/` separated segment of a path, `None`
+    /// means unlimited.
+    pub fn max_segment_len(&self) -> Option<usize> {
+        self.max_segment_len
+    }
+
+    pub fn set_max_segment_len(&mut self, v: usize) -> &mut Self {
+        self.max_segment_len = Some(v);
+        self
+    }
+
+    /// Whether the accessor can remove all versions of an object via
+    /// [`DeleteMode::AllVersions`][crate::ops::DeleteMode::AllVersions].
+    pub fn can_delete_all_versions(&self) -> bool {
+        self.delete_all_versions
+    }
+
+    pub fn set_delete_all_versions(&mut self, v: bool) -> &mut Self {
+        self.delete_all_versions = v;
+        self
+    }
+
+    /// Whether the accessor can generate presigned requests for `op` via
+    /// [`Accessor::presign`].
+    pub fn can_presign(&self, op: PresignOperation) -> bool {
+        match op {
+            PresignOperation::Read => self.presign_read,
+            PresignOperation::Write => self.presign_write,
+            PresignOperation::Stat => self.presign_stat,
+            PresignOperation::Delete => self.presign_delete,
+        }
+    }
+
+    pub fn set_presign(&mut self, op: PresignOperation, v: bool) -> &mut Self {
+        match op {
+            PresignOperation::Read => self.presign_read = v,
+            PresignOperation::Write => self.presign_write = v,
+            PresignOperation::Stat => self.presign_stat = v,
+            PresignOperation::Delete => self.presign_delete = v,
+        }
+        self
+    }
+
+    /// Check the path against the limits of this accessor.
+    ///
+    /// Returns an error with [`Kind::ObjectPathInvalid`] which contains the
+    /// offending length and the limit.
+    pub(crate) fn check_path(&self, op: &'static str, path: &str) -> Result<()> {
+        let err = |source| Error::Object {
+            kind: Kind::ObjectPathInvalid,
+            op,
+            path: path.to_string(),
+            source,
+        };
+
+        if let Some(limit) = self.max_path_len {
+            if path.len() > limit {
+                return Err(err(anyhow!(
+                    "path length {} exceeds the limit {}",
+                    path.len(),
+                    limit
+                )));
+            }
+        }
+        if let Some(limit) = self.max_segment_len {
+            if let Some(seg) = path.split('/').find(|v| v.len() > limit) {
+                return Err(err(anyhow!(
+                    "path segment length {} exceeds the limit {}",
+                    seg.len(),
+                    limit
+                )));
+            }
+        }
+
+        Ok(())
+    }
 }
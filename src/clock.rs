@@ -0,0 +1,54 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures::future::BoxFuture;
+
+/// Clock is the source of time used by layers, like the delay between
+/// retries and the ttl of cache entries.
+///
+/// The default [`TokioClock`] follows tokio's time, so tests could pause
+/// and advance it via `#[tokio::test(start_paused = true)]` instead of
+/// sleeping for real. Other clocks could be injected by `with_clock` of
+/// layers and [`Writer`][crate::Writer] with the `testing` feature.
+///
+/// # TODO
+///
+/// The throttle layer, the ttl of memory backend and the lock utility
+/// should use it once they are added. Presign expiry is signed with wall
+/// time which tokio can't pause, it needs a wall time source here first.
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current monotonic time.
+    fn now(&self) -> Instant;
+
+    /// Wait until `dur` has elapsed.
+    fn sleep(&self, dur: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// TokioClock reads and waits on tokio's time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
+    fn sleep(&self, dur: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(dur))
+    }
+}
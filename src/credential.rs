@@ -13,10 +13,26 @@
 // limitations under the License.
 
 //! Different credential types that used to signing requests.
+//!
+//! Every service could have its own credential type, they are loaded via
+//! [`ProvideCredential`]. Credentials that expire (like tokens exchanged
+//! from service account) should be wrapped by [`CachedCredential`], which
+//! caches them and refreshes before expiry.
 
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use futures::lock::Mutex;
+use log::warn;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
 
 #[derive(Clone)]
 pub enum Credential {
@@ -95,3 +111,113 @@ impl Credential {
         Credential::Token(token.to_string())
     }
 }
+
+/// ProvideCredential loads the credential of a service.
+///
+/// # Note
+///
+/// Credentials carry secrets, implementors must not print them in `Debug`.
+///
+/// # TODO
+///
+/// Only s3 implements this for now. The service account and workload
+/// identity providers of gcs, and the shared key, SAS and AAD providers of
+/// azblob, will be added along with their backends.
+#[async_trait]
+pub trait ProvideCredential: Debug + Send + Sync + 'static {
+    /// Credential type of the service, like [`Credential`] for s3.
+    type Credential: Clone + Send + Sync + 'static;
+
+    /// Load the credential along with the time it expires at, `None` means
+    /// it never expires.
+    async fn load(&self) -> Result<(Self::Credential, Option<SystemTime>)>;
+}
+
+/// Static credentials never expire, s3 uses `Credential::HMAC` in this way.
+#[async_trait]
+impl ProvideCredential for Credential {
+    type Credential = Credential;
+
+    async fn load(&self) -> Result<(Credential, Option<SystemTime>)> {
+        Ok((self.clone(), None))
+    }
+}
+
+/// CachedCredential caches the credential loaded by the inner provider, and
+/// refreshes it before expiry.
+///
+/// If refreshing failed, the cached credential will still be used until it
+/// expired. After that, an error with [`Kind::Temporary`] will be returned
+/// so that callers could retry later.
+pub struct CachedCredential<P: ProvideCredential> {
+    provider: P,
+    refresh_before: Duration,
+    cache: Mutex<Option<(P::Credential, Option<SystemTime>)>>,
+}
+
+impl<P: ProvideCredential> CachedCredential<P> {
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            refresh_before: Duration::from_secs(300),
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Refresh the credential if it will expire within `d`, default to 5
+    /// minutes.
+    #[must_use]
+    pub fn refresh_before(mut self, d: Duration) -> Self {
+        self.refresh_before = d;
+        self
+    }
+
+    /// Get the cached credential, load a new one if it's missing or about
+    /// to expire.
+    pub async fn get(&self) -> Result<P::Credential> {
+        // Hold the lock while loading, so that concurrent callers wait for
+        // the same refresh instead of loading many times.
+        let mut cache = self.cache.lock().await;
+        let now = SystemTime::now();
+
+        if let Some((cred, expires_at)) = cache.as_ref() {
+            match expires_at {
+                None => return Ok(cred.clone()),
+                Some(t) if now + self.refresh_before < *t => return Ok(cred.clone()),
+                _ => {}
+            }
+        }
+
+        match self.provider.load().await {
+            Ok((cred, expires_at)) => {
+                *cache = Some((cred.clone(), expires_at));
+                Ok(cred)
+            }
+            Err(err) => match cache.as_ref() {
+                // Still valid, use it and refresh next time.
+                Some((cred, Some(t))) if now < *t => {
+                    warn!("credential {:?} refresh failed: {:?}", self.provider, err);
+                    Ok(cred.clone())
+                }
+                _ => Err(Error::Backend {
+                    kind: Kind::Temporary,
+                    context: HashMap::from([(
+                        "credential".to_string(),
+                        format!("{:?}", self.provider),
+                    )]),
+                    source: anyhow::Error::from(err),
+                }),
+            },
+        }
+    }
+}
+
+// Credential has sensitive data, we should not print it out in anyway.
+impl<P: ProvideCredential> Debug for CachedCredential<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedCredential")
+            .field("provider", &self.provider)
+            .field("refresh_before", &self.refresh_before)
+            .finish()
+    }
+}
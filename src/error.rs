@@ -65,6 +65,38 @@ pub enum Kind {
     ObjectNotExist,
     #[error("object permission denied")]
     ObjectPermissionDenied,
+    /// The path violates the limits of the backend, like being too long.
+    #[error("object path invalid")]
+    ObjectPathInvalid,
+    #[error("precondition failed")]
+    PreconditionFailed,
+    /// The requested range starts at or beyond the end of the object.
+    #[error("range not satisfiable")]
+    RangeNotSatisfiable,
+    #[error("checksum mismatch")]
+    ChecksumMismatch,
+    /// The data source produced a different size of bytes than declared.
+    #[error("content length mismatch")]
+    ContentLengthMismatch,
+    #[error("operation unsupported")]
+    Unsupported,
+    /// The written object didn't become visible to `stat` in time, see
+    /// [`WriteOptions::await_visibility`][crate::ops::WriteOptions::await_visibility].
+    #[error("visibility timeout")]
+    VisibilityTimeout,
+    /// The operation failed temporarily (like refreshing credential),
+    /// retrying it later could succeed.
+    #[error("temporary failure")]
+    Temporary,
+    /// The resume token of listing is malformed, or it's returned by
+    /// another backend or path, see
+    /// [`ObjectStream::resume_from`][crate::ObjectStream::resume_from].
+    #[error("resume token invalid")]
+    ResumeTokenInvalid,
+    /// The write would exceed the quota, see
+    /// [`QuotaLayer`][crate::layers::QuotaLayer].
+    #[error("quota exceeded")]
+    QuotaExceeded,
 
     #[error("unexpected")]
     Unexpected,
@@ -104,6 +136,33 @@ impl Error {
             Error::Unexpected(_) => Kind::Unexpected,
         }
     }
+
+    /// Returns the delete marker hit by `stat` or `read`, so that callers
+    /// could tell deleted objects on versioned s3 buckets from objects
+    /// that never existed.
+    pub fn delete_marker(&self) -> Option<&DeleteMarker> {
+        match self {
+            Error::Object { source, .. } => source.downcast_ref::<DeleteMarker>(),
+            _ => None,
+        }
+    }
+}
+
+/// DeleteMarker is attached to [`Kind::ObjectNotExist`] errors if the
+/// object is hidden by a delete marker, see [`Error::delete_marker`].
+#[derive(Debug, Clone)]
+pub struct DeleteMarker {
+    /// Version id of the delete marker, if returned by the backend.
+    pub version_id: Option<String>,
+}
+
+impl std::fmt::Display for DeleteMarker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.version_id {
+            Some(v) => write!(f, "object is hidden by delete marker {}", v),
+            None => write!(f, "object is hidden by delete marker"),
+        }
+    }
 }
 
 // Make it easier to convert to `std::io::Error`
@@ -116,6 +175,9 @@ impl From<Error> for io::Error {
                 Kind::ObjectPermissionDenied => {
                     io::Error::new(io::ErrorKind::PermissionDenied, err)
                 }
+                Kind::Unsupported => io::Error::new(io::ErrorKind::Unsupported, err),
+                Kind::ObjectPathInvalid => io::Error::new(io::ErrorKind::InvalidInput, err),
+                Kind::VisibilityTimeout => io::Error::new(io::ErrorKind::TimedOut, err),
                 _ => io::Error::new(io::ErrorKind::Other, err),
             },
             Error::Unexpected(_) => io::Error::new(io::ErrorKind::Other, err),
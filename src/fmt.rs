@@ -0,0 +1,262 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Human-readable listing like `ls -l`, built on [`ObjectStream`][crate::ObjectStream].
+//!
+//! Only available with the `fmt` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use anyhow::Result;
+//! use opendal::fmt::ListFormatter;
+//! use opendal::services::memory;
+//! use opendal::Operator;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let op = Operator::new(memory::Backend::build().finish().await?);
+//!     op.object("dir/test").writer().write_bytes(vec![0; 1536]).await?;
+//!
+//!     let mut out = Vec::new();
+//!     ListFormatter::new().write(op.objects("dir/"), &mut out).await?;
+//!     // Like `f 1.5 KiB 2022-01-01T00:00:00Z dir/test`.
+//!     print!("{}", String::from_utf8(out)?);
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::io;
+use std::time::SystemTime;
+
+use futures::Stream;
+use futures::StreamExt;
+
+use crate::error::Result;
+use crate::MetaField;
+use crate::Metadata;
+use crate::Object;
+use crate::ObjectMode;
+
+/// How [`ListFormatter`] renders the last modified time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeFormat {
+    /// RFC 3339 in UTC with seconds precision, like `2022-01-01T00:00:00Z`.
+    #[default]
+    Iso,
+    /// Relative to now, like `5m ago`.
+    Relative,
+}
+
+/// ListFormatter renders listed objects into rows of mode, size, last
+/// modified time and path, separated by spaces.
+///
+/// - Mode is `d` for dirs, `f` for files and `?` for unknown.
+/// - Size is in `B`, `KiB`, `MiB`, `GiB` or `TiB`.
+/// - Fields missing in the metadata returned by list will be printed as
+///   `-`, no `stat` will be sent for them.
+///
+/// Columns are aligned over the first `window` rows. Listings larger than
+/// that will be streamed with the widths of the first window, longer
+/// values will push the following columns instead of being truncated.
+#[derive(Debug, Clone)]
+pub struct ListFormatter {
+    time_format: TimeFormat,
+    window: usize,
+    now: Option<SystemTime>,
+}
+
+impl Default for ListFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ListFormatter {
+    pub fn new() -> Self {
+        Self {
+            time_format: TimeFormat::Iso,
+            window: 1024,
+            now: None,
+        }
+    }
+
+    /// Set the format of last modified time, default to [`TimeFormat::Iso`].
+    #[must_use]
+    pub fn time_format(mut self, format: TimeFormat) -> Self {
+        self.time_format = format;
+        self
+    }
+
+    /// Set the number of rows buffered to compute the column widths,
+    /// default to `1024`.
+    #[must_use]
+    pub fn window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Render [`TimeFormat::Relative`] against `now` instead of the current
+    /// time.
+    #[must_use]
+    pub fn now(mut self, now: SystemTime) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Render all objects of the stream into `w`, returns the number of rows
+    /// written.
+    ///
+    /// Errors returned by the stream will stop the rendering, rows buffered
+    /// before it will be written first.
+    pub async fn write<S, W>(&self, mut obs: S, w: &mut W) -> io::Result<usize>
+    where
+        S: Stream<Item = Result<Object>> + Unpin,
+        W: io::Write,
+    {
+        let now = self.now.unwrap_or_else(SystemTime::now);
+
+        let mut rows = Vec::with_capacity(self.window.min(1024));
+        let mut widths = None;
+        let mut count = 0;
+        loop {
+            let mut o = match obs.next().await {
+                Some(Ok(o)) => o,
+                Some(Err(e)) => {
+                    self.flush(&mut rows, &mut widths, w)?;
+                    return Err(e.into());
+                }
+                None => break,
+            };
+            // Empty fields never trigger a stat, only the metadata carried
+            // by list will be used.
+            let meta = o.metadata_cached_for(&[]).await?;
+            rows.push(self.row(meta, now));
+            count += 1;
+
+            if widths.is_some() || rows.len() >= self.window {
+                self.flush(&mut rows, &mut widths, w)?;
+            }
+        }
+        self.flush(&mut rows, &mut widths, w)?;
+
+        Ok(count)
+    }
+
+    /// Write buffered rows, the widths will be computed over them if not
+    /// computed yet.
+    fn flush<W: io::Write>(
+        &self,
+        rows: &mut Vec<Row>,
+        widths: &mut Option<(usize, usize)>,
+        w: &mut W,
+    ) -> io::Result<()> {
+        let (size_width, time_width) = *widths.get_or_insert_with(|| {
+            rows.iter().fold((0, 0), |(sw, tw), row| {
+                (sw.max(row.size.len()), tw.max(row.time.len()))
+            })
+        });
+
+        for row in rows.drain(..) {
+            writeln!(
+                w,
+                "{} {:>sw$} {:<tw$} {}",
+                row.mode,
+                row.size,
+                row.time,
+                row.path,
+                sw = size_width,
+                tw = time_width
+            )?;
+        }
+        Ok(())
+    }
+
+    fn row(&self, meta: &Metadata, now: SystemTime) -> Row {
+        let mode = if meta.has(MetaField::Mode) {
+            match meta.mode() {
+                ObjectMode::DIR => 'd',
+                ObjectMode::FILE => 'f',
+                ObjectMode::Unknown => '?',
+            }
+        } else {
+            '-'
+        };
+        let size = if meta.has(MetaField::ContentLength) {
+            human_size(meta.content_length())
+        } else {
+            "-".to_string()
+        };
+        let time = match meta.last_modified() {
+            Some(t) => match self.time_format {
+                TimeFormat::Iso => humantime::format_rfc3339_seconds(t).to_string(),
+                TimeFormat::Relative => relative_time(t, now),
+            },
+            None => "-".to_string(),
+        };
+
+        Row {
+            mode,
+            size,
+            time,
+            path: meta.path().to_string(),
+        }
+    }
+}
+
+struct Row {
+    mode: char,
+    size: String,
+    time: String,
+    path: String,
+}
+
+/// Format `size` in binary units with one decimal, bytes are kept as is.
+fn human_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+
+    let mut v = size as f64 / 1024.0;
+    let mut unit = 0;
+    while v >= 1024.0 && unit < UNITS.len() - 1 {
+        v /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", v, UNITS[unit])
+}
+
+/// Format `t` relative to `now` in its largest unit, like `5m ago`.
+fn relative_time(t: SystemTime, now: SystemTime) -> String {
+    let (secs, future) = match now.duration_since(t) {
+        Ok(d) => (d.as_secs(), false),
+        Err(e) => (e.duration().as_secs(), true),
+    };
+
+    let v = match secs {
+        0 => return "just now".to_string(),
+        1..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    };
+    if future {
+        format!("in {}", v)
+    } else {
+        format!("{} ago", v)
+    }
+}
@@ -0,0 +1,346 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Periodic reachability probes for services embedding opendal, like
+//! the storage part of `/healthz`.
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use anyhow::Result;
+//! use opendal::health::Checker;
+//! use opendal::health::Health;
+//! use opendal::health::ProbeKind;
+//! use opendal::services::memory;
+//! use opendal::Operator;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let op = Operator::new(memory::Backend::build().finish().await?);
+//!     let checker = Checker::new(op)
+//!         .interval(Duration::from_secs(30))
+//!         .timeout(Duration::from_secs(5))
+//!         .probe(ProbeKind::List)
+//!         .probe(ProbeKind::WriteReadDelete("healthz/".to_string()))
+//!         .start();
+//!
+//!     // In the `/healthz` handler.
+//!     let status = checker.status();
+//!     if status.health != Health::Healthy {
+//!         println!("storage is {:?}: {:?}", status.health, status.last_error);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+use futures::future::join_all;
+use futures::AsyncReadExt;
+use futures::TryStreamExt;
+use log::warn;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::Operator;
+
+/// Content written by [`ProbeKind::WriteReadDelete`].
+const PROBE_CONTENT: &[u8] = b"opendal health probe";
+
+/// Operations used to probe the storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProbeKind {
+    /// Stat the given path, which must exist.
+    Stat(String),
+    /// List the root and take the first entry.
+    List,
+    /// Write an object with a unique key under the given prefix, read it
+    /// back and delete it.
+    ///
+    /// The object is deleted even if the write or read failed or timed out.
+    WriteReadDelete(String),
+}
+
+/// Overall health computed from the last result of every probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    /// All probes succeeded within the degraded latency.
+    Healthy,
+    /// Some probes failed or were slower than the degraded latency.
+    Degraded,
+    /// All probes failed, or no probe has finished yet.
+    Unhealthy,
+}
+
+/// Last result of a probe.
+#[derive(Debug, Clone)]
+pub struct ProbeResult {
+    pub kind: ProbeKind,
+    pub checked_at: SystemTime,
+    pub latency: Duration,
+    pub error: Option<Arc<Error>>,
+}
+
+/// Status returned by [`Checker::status`].
+#[derive(Debug, Clone)]
+pub struct Status {
+    pub health: Health,
+    /// Last result of every probe that has finished at least once.
+    pub probes: Vec<ProbeResult>,
+    /// The most recent error of any probe, kept after recovering so that
+    /// flapping storage could be diagnosed.
+    pub last_error: Option<Arc<Error>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    results: Vec<Option<ProbeResult>>,
+    last_error: Option<Arc<Error>>,
+}
+
+/// Checker runs probes against an operator on a background task and
+/// caches the results.
+///
+/// Every probe is bounded by `timeout`, so a hung request will never wedge
+/// the checker. The background task is cancelled while the checker dropped.
+pub struct Checker {
+    op: Operator,
+    interval: Duration,
+    timeout: Duration,
+    degraded_latency: Option<Duration>,
+    probes: Vec<ProbeKind>,
+
+    state: Arc<Mutex<State>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Checker {
+    /// Create a checker of `op`, which probes with [`ProbeKind::List`]
+    /// every 30s if no probe is added.
+    pub fn new(op: Operator) -> Self {
+        Self {
+            op,
+            interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(5),
+            degraded_latency: None,
+            probes: Vec::new(),
+            state: Arc::default(),
+            handle: None,
+        }
+    }
+
+    /// Interval between two rounds of probes, default to 30s.
+    #[must_use]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Max duration of a probe, default to 5s.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Report [`Health::Degraded`] if any probe succeeded but took longer
+    /// than `latency`.
+    #[must_use]
+    pub fn degraded_latency(mut self, latency: Duration) -> Self {
+        self.degraded_latency = Some(latency);
+        self
+    }
+
+    /// Add a probe, all probes of a round run concurrently.
+    #[must_use]
+    pub fn probe(mut self, kind: ProbeKind) -> Self {
+        self.probes.push(kind);
+        self
+    }
+
+    /// Start probing on a background task, the first round runs
+    /// immediately.
+    ///
+    /// Must be called inside a tokio runtime.
+    #[must_use]
+    pub fn start(mut self) -> Self {
+        if self.probes.is_empty() {
+            self.probes.push(ProbeKind::List);
+        }
+        self.state.lock().expect("lock poisoned").results = vec![None; self.probes.len()];
+
+        let runner = Runner {
+            op: self.op.clone(),
+            timeout: self.timeout,
+            probes: self.probes.clone(),
+            state: self.state.clone(),
+        };
+        let interval = self.interval;
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+        self.handle = Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                runner.round().await;
+            }
+        }));
+        self
+    }
+
+    /// Return the cached status without probing.
+    pub fn status(&self) -> Status {
+        let state = self.state.lock().expect("lock poisoned");
+        let probes: Vec<ProbeResult> = state.results.iter().flatten().cloned().collect();
+
+        let failed = probes.iter().filter(|v| v.error.is_some()).count();
+        let slow = probes.iter().any(|v| {
+            v.error.is_none() && matches!(self.degraded_latency, Some(d) if v.latency > d)
+        });
+        let health = if probes.is_empty() || failed == probes.len() {
+            Health::Unhealthy
+        } else if failed > 0 || slow {
+            Health::Degraded
+        } else {
+            Health::Healthy
+        };
+
+        Status {
+            health,
+            probes,
+            last_error: state.last_error.clone(),
+        }
+    }
+}
+
+impl Drop for Checker {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+struct Runner {
+    op: Operator,
+    timeout: Duration,
+    probes: Vec<ProbeKind>,
+    state: Arc<Mutex<State>>,
+}
+
+impl Runner {
+    async fn round(&self) {
+        let results = join_all(self.probes.iter().map(|v| self.probe(v))).await;
+
+        let mut state = self.state.lock().expect("lock poisoned");
+        for (idx, result) in results.into_iter().enumerate() {
+            if let Some(err) = &result.error {
+                state.last_error = Some(err.clone());
+            }
+            state.results[idx] = Some(result);
+        }
+    }
+
+    async fn probe(&self, kind: &ProbeKind) -> ProbeResult {
+        let checked_at = SystemTime::now();
+        let start = Instant::now();
+
+        let result = match kind {
+            ProbeKind::Stat(path) => {
+                self.timed(path, async {
+                    self.op.object(path).metadata().await.map(|_| ())
+                })
+                .await
+            }
+            ProbeKind::List => {
+                self.timed("/", async {
+                    self.op.objects("/").try_next().await.map(|_| ())
+                })
+                .await
+            }
+            ProbeKind::WriteReadDelete(prefix) => {
+                let path = format!("{}{}", prefix, Uuid::new_v4());
+                let result = self.timed(&path, self.write_read(&path)).await;
+                // Always clean up, even if the write timed out since it
+                // could have been done on the storage side.
+                let cleaned = self
+                    .timed(&path, async { self.op.object(&path).delete().await })
+                    .await;
+                if let Err(e) = &cleaned {
+                    warn!("object {} health probe cleanup: {:?}", &path, e);
+                }
+                result.and(cleaned)
+            }
+        };
+
+        ProbeResult {
+            kind: kind.clone(),
+            checked_at,
+            latency: start.elapsed(),
+            error: result.err().map(Arc::new),
+        }
+    }
+
+    async fn write_read(&self, path: &str) -> Result<()> {
+        let o = self.op.object(path);
+        o.writer().write_bytes(PROBE_CONTENT.to_vec()).await?;
+
+        let mut buf = Vec::with_capacity(PROBE_CONTENT.len());
+        o.reader()
+            .read_to_end(&mut buf)
+            .await
+            .map_err(|e| Error::Object {
+                kind: Kind::Unexpected,
+                op: "health",
+                path: path.to_string(),
+                source: anyhow::Error::from(e),
+            })?;
+        if buf != PROBE_CONTENT {
+            return Err(Error::Object {
+                kind: Kind::Unexpected,
+                op: "health",
+                path: path.to_string(),
+                source: anyhow!("read content mismatch"),
+            });
+        }
+        Ok(())
+    }
+
+    async fn timed<F>(&self, path: &str, fut: F) -> Result<()>
+    where
+        F: std::future::Future<Output = Result<()>>,
+    {
+        match tokio::time::timeout(self.timeout, fut).await {
+            Ok(r) => r,
+            Err(_) => Err(Error::Object {
+                kind: Kind::Temporary,
+                op: "health",
+                path: path.to_string(),
+                source: anyhow!("probe timed out after {:?}", self.timeout),
+            }),
+        }
+    }
+}
@@ -19,22 +19,138 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+use std::time::SystemTime;
 
+use anyhow::anyhow;
 use futures::future::BoxFuture;
+use futures::io::BufReader;
 use futures::ready;
 use futures::AsyncRead;
 use futures::AsyncSeek;
+use log::debug;
+use log::warn;
 
+use crate::clock::Clock;
+use crate::clock::TokioClock;
+use crate::error::Error;
+use crate::error::Kind;
 use crate::error::Result;
+use crate::layers::Backoff;
+use crate::object::is_root;
 use crate::ops::OpRead;
 use crate::ops::OpStat;
 use crate::ops::OpWrite;
+use crate::ops::WriteOptions;
+use crate::readers::CompressAlgorithm;
+use crate::readers::DecompressReader;
+use crate::readers::Digest;
+use crate::readers::DigestKind;
+use crate::readers::DigestReader;
+use crate::readers::Hasher;
+use crate::readers::LengthCheckedReader;
 use crate::Accessor;
+use crate::MetaField;
 use crate::Metadata;
+use crate::Provenance;
 
 /// BoxedAsyncReader is a boxed AsyncRead.
 pub type BoxedAsyncReader = Box<dyn AsyncRead + Unpin + Send>;
 
+/// Default buffer size of [`Reader`], see [`Reader::buffer`].
+const DEFAULT_READ_BUFFER: usize = 64 * 1024;
+
+/// ObjectReader is returned by [`Accessor::read`], it carries the metadata
+/// that backend learned while reading along with the reader.
+///
+/// For example, s3's `GetObject` returns the content length, etag and last
+/// modified time, callers don't need to send another `HeadObject`.
+pub struct ObjectReader {
+    inner: BoxedAsyncReader,
+    meta: Metadata,
+    provenance: Provenance,
+    buffered: bool,
+}
+
+impl ObjectReader {
+    /// Create a new ObjectReader without any metadata.
+    pub fn new(r: BoxedAsyncReader) -> Self {
+        Self {
+            inner: r,
+            meta: Metadata::default(),
+            provenance: Provenance::default(),
+            buffered: false,
+        }
+    }
+
+    /// Mark the reader as serving reads from chunks already in memory,
+    /// like s3's `ByteStream`, so that [`Reader`] won't buffer it again.
+    #[must_use]
+    pub fn with_buffered(mut self) -> Self {
+        self.buffered = true;
+        self
+    }
+
+    /// Whether the reader is marked by [`ObjectReader::with_buffered`].
+    pub fn is_buffered(&self) -> bool {
+        self.buffered
+    }
+
+    /// Set the metadata learned while reading.
+    #[must_use]
+    pub fn with_metadata(mut self, meta: Metadata) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    /// Metadata learned while reading, fields could be missing.
+    pub fn metadata(&self) -> &Metadata {
+        &self.meta
+    }
+
+    /// Replace the provenance, layers rebuilding the reader via
+    /// [`ObjectReader::into_parts`] must carry the old one over.
+    #[must_use]
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// Attach a tag describing how the read is served, see [`Provenance`].
+    #[must_use]
+    pub fn with_tag(mut self, key: &str, value: &str) -> Self {
+        self.provenance.set(key, value);
+        self
+    }
+
+    /// Tags attached by layers, see [`Provenance`].
+    pub fn provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+
+    pub fn provenance_mut(&mut self) -> &mut Provenance {
+        &mut self.provenance
+    }
+
+    pub fn into_reader(self) -> BoxedAsyncReader {
+        self.inner
+    }
+
+    pub fn into_parts(self) -> (BoxedAsyncReader, Metadata) {
+        (self.inner, self.meta)
+    }
+}
+
+impl AsyncRead for ObjectReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
 /// Reader is used for reading data from underlying backend.
 ///
 /// # Lazy Stat
@@ -46,6 +162,26 @@ pub struct Reader {
     path: String,
     offset: Option<u64>,
     size: Option<u64>,
+    version_id: Option<String>,
+    hasher: Option<Hasher>,
+    digest: Option<Digest>,
+    max_resumes: usize,
+    resumes: usize,
+    etag: Option<String>,
+    /// Total length of the object if known.
+    total: Option<u64>,
+    strict_range: bool,
+    allow_trailing_slash: bool,
+    buffer: usize,
+    decode_content: bool,
+    /// Whether the content is being decoded.
+    decoding: bool,
+    /// Whether the range is set by the caller.
+    ranged: bool,
+    /// Whether the content is a range of the object, see
+    /// [`Reader::from_object_reader`].
+    seekable: bool,
+    provenance: Provenance,
 
     pos: u64,
     state: ReadState,
@@ -53,7 +189,7 @@ pub struct Reader {
 
 enum ReadState {
     Idle,
-    Sending(BoxFuture<'static, Result<BoxedAsyncReader>>),
+    Sending(BoxFuture<'static, Result<ObjectReader>>),
     Seeking(BoxFuture<'static, Result<Metadata>>),
     Reading(BoxedAsyncReader),
 }
@@ -65,18 +201,517 @@ impl Reader {
             path: path.to_string(),
             offset,
             size,
+            version_id: None,
+            hasher: None,
+            digest: None,
+            max_resumes: 0,
+            resumes: 0,
+            etag: None,
+            total: None,
+            strict_range: false,
+            allow_trailing_slash: false,
+            buffer: DEFAULT_READ_BUFFER,
+            decode_content: false,
+            decoding: false,
+            ranged: offset.unwrap_or_default() > 0 || size.is_some(),
+            seekable: true,
+            provenance: Provenance::default(),
 
             pos: 0,
             state: ReadState::Idle,
         }
     }
 
+    /// Create a reader over `r` which has been opened already, like the
+    /// result of select.
+    ///
+    /// The content is not a range of the object, so the reader can't seek
+    /// or resume from failures.
+    pub(crate) fn from_object_reader(
+        acc: Arc<dyn Accessor>,
+        path: &str,
+        r: ObjectReader,
+    ) -> Result<Self> {
+        let mut reader = Reader::new(acc, path, None, None);
+        reader.seekable = false;
+        reader.start_reading(r)?;
+        Ok(reader)
+    }
+
+    /// Hash bytes as they are read, the digest will be available via
+    /// [`Reader::digest`] after EOF.
+    ///
+    /// Seeking discards the digest, since it can't cover a contiguous range
+    /// of the object anymore.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::io;
+    /// use opendal::readers::DigestKind;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(b"Hello, World!".to_vec()).await?;
+    ///
+    ///     let mut r = op.object("test").reader().with_digest(DigestKind::Crc32c);
+    ///     io::copy(&mut r, &mut io::sink()).await?;
+    ///     assert_eq!(r.digest().unwrap().to_string(), "4d551068");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_digest(mut self, kind: DigestKind) -> Self {
+        self.hasher = Some(Hasher::new(kind));
+        self.digest = None;
+        self
+    }
+
+    /// Resume from the last delivered offset if the stream fails in the
+    /// middle, at most `max_resumes` times in a row.
+    ///
+    /// Resuming requires the backend to return an etag while reading, the
+    /// etag of the resumed response must be the same, otherwise the read
+    /// fails with [`Kind::PreconditionFailed`] since the object has changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::AsyncReadExt;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(b"Hello, World!".to_vec()).await?;
+    ///
+    ///     let mut r = op.object("test").reader().resumable(3);
+    ///     let mut buf = Vec::new();
+    ///     r.read_to_end(&mut buf).await?;
+    ///     assert_eq!(buf, b"Hello, World!");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn resumable(mut self, max_resumes: usize) -> Self {
+        self.max_resumes = max_resumes;
+        self
+    }
+
+    /// Fail with [`Kind::RangeNotSatisfiable`] instead of returning EOF if
+    /// the range starts at or beyond the end of the object.
+    ///
+    /// The error will be returned without sending any request if the total
+    /// length of the object is known, for example, cached by the `Object`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::AsyncReadExt;
+    /// use opendal::error::Kind;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![0; 13]).await?;
+    ///
+    ///     let mut buf = Vec::new();
+    ///     let mut r = op.object("test").offset_reader(13);
+    ///     assert_eq!(r.read_to_end(&mut buf).await?, 0);
+    ///
+    ///     let mut r = op.object("test").offset_reader(13).strict_range();
+    ///     assert!(r.read_to_end(&mut buf).await.is_err());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn strict_range(mut self) -> Self {
+        self.strict_range = true;
+        self
+    }
+
+    /// Buffer at most `size` bytes read from the backend, `0` means
+    /// unbuffered. The buffer is 64 KiB by default.
+    ///
+    /// The buffer is filled by one read of the backend's reader, so chunks
+    /// are taken as the backend produces them instead of waiting for the
+    /// buffer to be full. Reads not smaller than the buffer bypass it, and
+    /// readers already serving reads from memory (see
+    /// [`ObjectReader::with_buffered`]) are never buffered again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::AsyncReadExt;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![0; 4096]).await?;
+    ///
+    ///     let mut r = op.object("test").reader().buffer(1024);
+    ///     let mut buf = [0; 16];
+    ///     r.read_exact(&mut buf).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn buffer(mut self, size: usize) -> Self {
+        self.buffer = size;
+        self
+    }
+
+    /// Returns the number of bytes that this reader could read from its
+    /// start, or `None` if not known yet.
+    ///
+    /// Unlike [`Reader::available`], no request will be sent. The length
+    /// is known once the response of the first poll arrives if the backend
+    /// returns it while reading, for ranged reads it's the length of the
+    /// range truncated by EOF.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::AsyncReadExt;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![0; 13]).await?;
+    ///
+    ///     let mut r = op.object("test").reader();
+    ///     assert_eq!(r.content_length(), None);
+    ///
+    ///     let mut buf = [0; 1];
+    ///     r.read_exact(&mut buf).await?;
+    ///     assert_eq!(r.content_length(), Some(13));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn content_length(&self) -> Option<u64> {
+        let total = self.total?;
+        let remaining = total.saturating_sub(self.offset.unwrap_or_default());
+        Some(self.size.map_or(remaining, |size| size.min(remaining)))
+    }
+
+    /// Returns the tags attached by layers to the latest response, like
+    /// whether it's served by a cache, see [`Provenance`].
+    ///
+    /// It's empty before the response arrives, read it after completion.
+    pub fn provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+
+    /// Returns the digest of all bytes read, or `None` if the reader hasn't
+    /// reached EOF.
+    pub fn digest(&self) -> Option<&Digest> {
+        self.digest.as_ref()
+    }
+
+    /// Read the version described by `meta` instead of the latest one.
+    ///
+    /// The size will be resolved by `meta`, so that seeking from the end
+    /// doesn't need to `stat` the latest version.
+    pub(crate) fn with_version(mut self, meta: &Metadata) -> Self {
+        self.version_id = meta.version_id().map(|v| v.to_string());
+        self.resolve_size(meta);
+        self
+    }
+
+    /// Allow reading paths ending with `/`, see [`ReadOptions::allow_trailing_slash`][field@crate::ops::ReadOptions::allow_trailing_slash].
+    pub(crate) fn with_trailing_slash_allowed(mut self, v: bool) -> Self {
+        self.allow_trailing_slash = v;
+        self
+    }
+
+    /// Decode the content by its encoding, see [`ReadOptions::decode_content`][crate::ops::ReadOptions::decode_content].
+    pub(crate) fn with_content_decoded(mut self, v: bool) -> Self {
+        self.decode_content = v;
+        self
+    }
+
+    /// Require all responses to carry `etag`, the read fails with
+    /// [`Kind::PreconditionFailed`] if the object has changed.
+    pub(crate) fn with_etag(mut self, etag: &str) -> Self {
+        self.etag = Some(etag.to_string());
+        self
+    }
+
+    /// Read the version `version_id` instead of the latest one.
+    pub(crate) fn with_version_id(mut self, version_id: &str) -> Self {
+        self.version_id = Some(version_id.to_string());
+        self
+    }
+
     fn current_offset(&self) -> u64 {
         self.offset.unwrap_or_default() + self.pos
     }
 
     fn current_size(&self) -> Option<u64> {
-        self.size.map(|v| v - self.pos)
+        self.size.map(|v| v.saturating_sub(self.pos))
+    }
+
+    fn current_op(&self) -> OpRead {
+        OpRead {
+            path: self.path.to_string(),
+            offset: Some(self.current_offset()),
+            size: self.current_size(),
+            version_id: self.version_id.clone(),
+        }
+    }
+
+    /// Build the future of the read request, reads on the root will be
+    /// rejected before reaching the backend.
+    ///
+    /// Empty ranges, including ranges starting at or beyond the known end
+    /// of the object, will be served by an empty reader without sending
+    /// any request. The end is only known from earlier responses or the
+    /// pinned version, cached metadata could be stale after the object
+    /// grew, so ranges beyond it are left to the backend.
+    fn send(&self) -> BoxFuture<'static, Result<ObjectReader>> {
+        let acc = self.acc.clone();
+        let op = self.current_op();
+        let offset = self.current_offset();
+        let beyond_end = self.total.is_some_and(|total| offset >= total);
+        let empty = self.current_size() == Some(0) || beyond_end;
+        // Only ranges requested by callers are checked, reading again after
+        // seeking to the end is still allowed.
+        let strict = self.strict_range && self.pos == 0 && offset > 0;
+        let allow_trailing_slash = self.allow_trailing_slash;
+        let decode_ranged = self.decode_content && (self.ranged || offset > 0);
+
+        Box::pin(async move {
+            if is_root(&op.path) {
+                return Err(Error::Object {
+                    kind: Kind::Unsupported,
+                    op: "read",
+                    path: op.path,
+                    source: anyhow!("read on the root is not allowed"),
+                });
+            }
+            if op.path.ends_with('/') && !allow_trailing_slash {
+                return Err(Error::Object {
+                    kind: Kind::Unsupported,
+                    op: "read",
+                    path: op.path,
+                    source: anyhow!("read on a dir is not allowed without allow_trailing_slash"),
+                });
+            }
+            if decode_ranged {
+                return Err(Error::Object {
+                    kind: Kind::Unsupported,
+                    op: "read",
+                    path: op.path,
+                    source: anyhow!(
+                        "ranged read can't decode content, ranges refer to the encoded content"
+                    ),
+                });
+            }
+
+            let unsatisfiable = || Error::Object {
+                kind: Kind::RangeNotSatisfiable,
+                op: "read",
+                path: op.path.clone(),
+                source: anyhow!("offset {} is beyond the end of object", offset),
+            };
+
+            if beyond_end && strict {
+                return Err(unsatisfiable());
+            }
+            if empty {
+                return Ok(ObjectReader::new(Box::new(futures::io::empty())));
+            }
+
+            match acc.read(&op).await {
+                Err(e) if e.kind() == Kind::RangeNotSatisfiable && !strict => {
+                    Ok(ObjectReader::new(Box::new(futures::io::empty())))
+                }
+                // Backends like fs read nothing instead of failing.
+                Ok(r)
+                    if strict
+                        && r.metadata().has(MetaField::ContentLength)
+                        && offset >= r.metadata().content_length() =>
+                {
+                    Err(unsatisfiable())
+                }
+                r => r,
+            }
+        })
+    }
+
+    /// Accept the metadata of a read response, responses of a resumed read
+    /// must carry the same etag as the first one.
+    fn accept(&mut self, meta: &Metadata) -> Result<()> {
+        match (&self.etag, meta.etag()) {
+            (Some(expected), Some(actual)) if expected != actual => {
+                return Err(Error::Object {
+                    kind: Kind::PreconditionFailed,
+                    op: "read",
+                    path: self.path.clone(),
+                    source: anyhow!(
+                        "object changed while resuming read, etag {expected} != {actual}"
+                    ),
+                });
+            }
+            (None, Some(actual)) => self.etag = Some(actual.to_string()),
+            _ => {}
+        }
+
+        self.resolve_size(meta);
+        Ok(())
+    }
+
+    /// Start reading the response, decode and wrap it in a buffer if
+    /// needed.
+    fn start_reading(&mut self, r: ObjectReader) -> Result<()> {
+        let buffered = r.is_buffered();
+        self.provenance = r.provenance().clone();
+        let (r, meta) = r.into_parts();
+        self.accept(&meta)?;
+        debug!(
+            "object {} read started: offset {}, provenance [{}]",
+            self.path, self.pos, self.provenance
+        );
+
+        let r = match self.content_decoder(&meta)? {
+            Some(algo) => {
+                self.decoding = true;
+                Box::new(DecompressReader::new(r, algo).expect("decoder must be enabled"))
+            }
+            None => r,
+        };
+        let r: BoxedAsyncReader = if self.buffer > 0 && !buffered {
+            Box::new(BufReader::with_capacity(self.buffer, r))
+        } else {
+            r
+        };
+        self.state = ReadState::Reading(r);
+        Ok(())
+    }
+
+    /// Returns the algorithm to decode the content with, `None` if the
+    /// content should be returned as is.
+    ///
+    /// Encodings without their feature enabled are rejected, returning
+    /// the encoded bytes silently would corrupt callers' data.
+    fn content_decoder(&self, meta: &Metadata) -> Result<Option<CompressAlgorithm>> {
+        if !self.decode_content {
+            return Ok(None);
+        }
+        let encoding = match meta.content_encoding().map(str::trim) {
+            None => return Ok(None),
+            Some(v) if v.is_empty() || v.eq_ignore_ascii_case("identity") => return Ok(None),
+            Some(v) => v,
+        };
+
+        let source = match CompressAlgorithm::from_content_encoding(encoding) {
+            Some(algo) if algo.is_enabled() => return Ok(Some(algo)),
+            Some(algo) => anyhow!(
+                "decoding content encoded by {:?} needs feature {}",
+                algo,
+                algo.feature()
+            ),
+            None => anyhow!("unknown content encoding {}", encoding),
+        };
+        Err(Error::Object {
+            kind: Kind::Unsupported,
+            op: "read",
+            path: self.path.clone(),
+            source,
+        })
+    }
+
+    /// Clamp the size with the object's total length carried by the read
+    /// response, so that ranges over-reading EOF report the real size.
+    fn resolve_size(&mut self, meta: &Metadata) {
+        if !meta.has(MetaField::ContentLength) {
+            return;
+        }
+
+        self.total = Some(meta.content_length());
+        let remaining = meta
+            .content_length()
+            .saturating_sub(self.offset.unwrap_or_default());
+        self.size = Some(match self.size {
+            Some(size) => size.min(remaining),
+            None => remaining,
+        });
+    }
+
+    /// Returns the number of bytes that this reader could read from its
+    /// start, ranges over-reading EOF will be truncated.
+    ///
+    /// The read request will be sent if it's not sent yet, and the total
+    /// length carried by the response will be used. We will fallback to
+    /// `stat` if the backend doesn't return it while reading.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::AsyncReadExt;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![0; 13]).await?;
+    ///
+    ///     let mut r = op.object("test").range_reader(10, 100);
+    ///     assert_eq!(r.available().await?, 3);
+    ///
+    ///     let mut buf = Vec::new();
+    ///     r.read_to_end(&mut buf).await?;
+    ///     assert_eq!(buf.len(), 3);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn available(&mut self) -> Result<u64> {
+        let r = match std::mem::replace(&mut self.state, ReadState::Idle) {
+            ReadState::Idle => Some(self.send().await?),
+            ReadState::Sending(future) => Some(future.await?),
+            ReadState::Seeking(future) => {
+                let meta = future.await?;
+                self.resolve_size(&meta);
+                None
+            }
+            ReadState::Reading(r) => {
+                self.state = ReadState::Reading(r);
+                None
+            }
+        };
+        if let Some(r) = r {
+            self.start_reading(r)?;
+        }
+
+        if self.size.is_none() {
+            let meta = self.acc.stat(&OpStat::new(&self.path)).await?;
+            self.resolve_size(&meta);
+        }
+
+        Ok(self.size.expect("size must be resolved"))
     }
 }
 
@@ -88,30 +723,53 @@ impl AsyncRead for Reader {
     ) -> Poll<std::io::Result<usize>> {
         match &mut self.state {
             ReadState::Idle => {
-                let acc = self.acc.clone();
-                let op = OpRead {
-                    path: self.path.to_string(),
-                    offset: Some(self.current_offset()),
-                    size: self.current_size(),
-                };
-
-                let future = async move { acc.read(&op).await };
-
-                self.state = ReadState::Sending(Box::pin(future));
+                self.state = ReadState::Sending(self.send());
                 self.poll_read(cx, buf)
             }
             ReadState::Sending(future) => match ready!(Pin::new(future).poll(cx)) {
                 Ok(r) => {
-                    self.state = ReadState::Reading(r);
+                    if let Err(e) = self.start_reading(r) {
+                        self.state = ReadState::Idle;
+                        return Poll::Ready(Err(io::Error::from(e)));
+                    }
                     self.poll_read(cx, buf)
                 }
-                Err(e) => Poll::Ready(Err(io::Error::from(e))),
+                Err(e) => {
+                    // Polling again will send the request again instead of
+                    // polling a finished future.
+                    self.state = ReadState::Idle;
+                    Poll::Ready(Err(io::Error::from(e)))
+                }
             },
             ReadState::Reading(r) => match ready!(Pin::new(r).poll_read(cx, buf)) {
                 Ok(n) => {
                     self.pos += n as u64;
+                    if n > 0 {
+                        self.resumes = 0;
+                        if let Some(hasher) = self.hasher.as_mut() {
+                            hasher.update(&buf[..n]);
+                        }
+                    } else if !buf.is_empty() {
+                        self.digest = self.hasher.take().map(|h| h.finalize());
+                    }
                     Poll::Ready(Ok(n))
                 }
+                // Only resume if we can make sure the object is not changed.
+                Err(e)
+                    if self.resumes < self.max_resumes && self.etag.is_some() && !self.decoding =>
+                {
+                    self.resumes += 1;
+                    warn!(
+                        "object {} read failed at offset {}, resuming ({}/{}), provenance [{}]: {e}",
+                        self.path,
+                        self.current_offset(),
+                        self.resumes,
+                        self.max_resumes,
+                        self.provenance
+                    );
+                    self.state = ReadState::Sending(self.send());
+                    self.poll_read(cx, buf)
+                }
                 Err(e) => Poll::Ready(Err(e)),
             },
             _ => unreachable!("read while seeking is invalid"),
@@ -127,13 +785,22 @@ impl AsyncSeek for Reader {
     ) -> Poll<std::io::Result<u64>> {
         if let ReadState::Seeking(future) = &mut self.state {
             match ready!(Pin::new(future).poll(cx)) {
-                Ok(meta) => {
-                    self.size = Some(meta.content_length() - self.offset.unwrap_or_default())
-                }
+                Ok(meta) => self.resolve_size(&meta),
                 Err(e) => return Poll::Ready(Err(io::Error::from(e))),
             }
         }
 
+        if !self.seekable {
+            return match pos {
+                SeekFrom::Current(0) => Poll::Ready(Ok(self.pos)),
+                SeekFrom::Start(off) if off == self.pos => Poll::Ready(Ok(self.pos)),
+                _ => Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    anyhow!("seeking is not supported on content not ranged from the object"),
+                ))),
+            };
+        }
+
         let cur = self.pos as i64;
         let cur = match pos {
             SeekFrom::Start(off) => off as i64,
@@ -156,8 +823,20 @@ impl AsyncSeek for Reader {
             }
         };
 
+        // Positions of decoded content can't be mapped to the object.
+        if self.decoding {
+            if cur == self.pos as i64 {
+                return Poll::Ready(Ok(self.pos));
+            }
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                anyhow!("seeking is not supported while decoding content"),
+            )));
+        }
         self.pos = cur as u64;
 
+        self.hasher = None;
+        self.digest = None;
         self.state = ReadState::Idle;
         Poll::Ready(Ok(self.pos))
     }
@@ -171,6 +850,8 @@ impl AsyncSeek for Reader {
 pub struct Writer {
     acc: Arc<dyn Accessor>,
     path: String,
+    opts: WriteOptions,
+    clock: Arc<dyn Clock>,
 }
 
 impl Writer {
@@ -178,24 +859,297 @@ impl Writer {
         Self {
             acc,
             path: path.to_string(),
+            opts: WriteOptions::default(),
+            clock: Arc::new(TokioClock),
         }
     }
 
-    pub async fn write_bytes(self, bs: Vec<u8>) -> Result<usize> {
-        let op = &OpWrite {
-            path: self.path.clone(),
-            size: bs.len() as u64,
+    /// Replace all options with `opts`.
+    pub(crate) fn with_options(mut self, opts: WriteOptions) -> Self {
+        self.opts = opts;
+        self
+    }
+
+    /// Wait for visibility on `clock` instead of tokio's time.
+    #[cfg(any(test, feature = "testing"))]
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Upload data in parts of `size` if the backend supports multipart
+    /// upload.
+    #[must_use]
+    pub fn part_size(mut self, size: u64) -> Self {
+        self.opts.part_size = Some(size);
+        self
+    }
+
+    /// Allow at most `n` parts in flight while uploading in multipart.
+    #[must_use]
+    pub fn parallelism(mut self, n: usize) -> Self {
+        self.opts.parallelism = Some(n);
+        self
+    }
+
+    /// Make sure the object is not observable at its final path until the
+    /// write finished.
+    ///
+    /// Without this option, backends make a partially written object visible
+    /// at different times:
+    ///
+    /// - fs: immediately after the write started.
+    /// - s3: only after the upload (or multipart complete) succeeded.
+    /// - memory: only after all data has been read into memory.
+    ///
+    /// With this option, fs will write into a temporary file and rename it
+    /// to the final path after all data written. The write will fail with
+    /// [`Kind::Unsupported`] if the backend can't guarantee it, check
+    /// [`AccessorMetadata::can_commit_visible`][crate::AccessorMetadata::can_commit_visible]
+    /// in advance.
+    #[must_use]
+    pub fn commit_visible(mut self, v: bool) -> Self {
+        self.opts.commit_visible = v;
+        self
+    }
+
+    /// Set the `Content-Type` of the object, like `application/json`.
+    ///
+    /// Only s3 stores it for now, other backends will ignore it.
+    #[must_use]
+    pub fn content_type(mut self, v: &str) -> Self {
+        self.opts.content_type = Some(v.to_string());
+        self
+    }
+
+    /// Set the `Content-Language` of the object, like `en-US`.
+    ///
+    /// Only s3 stores it for now, other backends will ignore it.
+    #[must_use]
+    pub fn content_language(mut self, v: &str) -> Self {
+        self.opts.content_language = Some(v.to_string());
+        self
+    }
+
+    /// Set the `Cache-Control` of the object, like `max-age=3600`, which
+    /// will be returned while serving it via presigned or public urls.
+    ///
+    /// Only s3 stores it for now, other backends will ignore it.
+    #[must_use]
+    pub fn cache_control(mut self, v: &str) -> Self {
+        self.opts.cache_control = Some(v.to_string());
+        self
+    }
+
+    /// Set the `Expires` of the object, after which caches should consider
+    /// it stale.
+    ///
+    /// It's only a hint for caches, the object won't be deleted. Only s3
+    /// stores it for now, other backends will ignore it.
+    #[must_use]
+    pub fn expires(mut self, t: SystemTime) -> Self {
+        self.opts.expires = Some(t);
+        self
+    }
+
+    /// Add a tag to the object, lifecycle rules of the bucket could match
+    /// it, like deleting objects tagged `temp=true` after some days.
+    ///
+    /// Only s3 stores it for now, other backends will ignore it.
+    #[must_use]
+    pub fn tag(mut self, key: &str, value: &str) -> Self {
+        self.opts.tags.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Add a user defined metadata to the object.
+    ///
+    /// Only s3 stores it for now, other backends will ignore it.
+    #[must_use]
+    pub fn user_metadata(mut self, key: &str, value: &str) -> Self {
+        self.opts
+            .user_metadata
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Set the canned ACL of the object, like `private` or `public-read`.
+    ///
+    /// Only s3 stores it for now, other backends will ignore it.
+    #[must_use]
+    pub fn acl(mut self, v: &str) -> Self {
+        self.opts.acl = Some(v.to_string());
+        self
+    }
+
+    /// Set the storage class of the object, like `STANDARD_IA` on s3.
+    ///
+    /// Only s3 stores it for now, other backends will ignore it.
+    #[must_use]
+    pub fn storage_class(mut self, v: &str) -> Self {
+        self.opts.storage_class = Some(v.to_string());
+        self
+    }
+
+    /// Hash the content in `kind` as it's written, see
+    /// [`WriteOptions::with_digest`][crate::ops::WriteOptions::with_digest].
+    ///
+    /// The digest is returned by [`Writer::finish_bytes`] and
+    /// [`Writer::finish_reader`].
+    #[must_use]
+    pub fn digest(mut self, kind: DigestKind) -> Self {
+        self.opts.digest = Some(kind);
+        self
+    }
+
+    fn op(&self, size: u64) -> Result<OpWrite> {
+        if self.opts.commit_visible && !self.acc.metadata().can_commit_visible() {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op: "write",
+                path: self.path.clone(),
+                source: anyhow!("commit visible is not supported by this backend"),
+            });
+        }
+
+        let mut op = OpWrite::new(&self.path, size);
+        op.options = self.opts.clone();
+        Ok(op)
+    }
+
+    /// Poll `stat` until the written object is visible, see
+    /// [`WriteOptions::await_visibility`].
+    async fn await_visibility(&self, size: u64) -> Result<()> {
+        let timeout = match self.opts.await_visibility {
+            Some(timeout) => timeout,
+            None => return Ok(()),
         };
-        let r = Box::new(futures::io::Cursor::new(bs));
 
-        self.acc.write(r, op).await
+        let clock = &self.clock;
+        let deadline = clock.now() + timeout;
+        let mut delays = Backoff::new()
+            .base(Duration::from_millis(50))
+            .max(Duration::from_secs(1))
+            .iter();
+        let op = OpStat::new(&self.path);
+        loop {
+            let reason = match self.acc.stat(&op).await {
+                Ok(meta)
+                    if !self.opts.await_length
+                        || !meta.has(MetaField::ContentLength)
+                        || meta.content_length() == size =>
+                {
+                    return Ok(());
+                }
+                Ok(meta) => format!("length is {} instead of {}", meta.content_length(), size),
+                Err(e) if e.kind() == Kind::ObjectNotExist => "object not exist".to_string(),
+                Err(e) => return Err(e),
+            };
+
+            let now = clock.now();
+            if now >= deadline {
+                return Err(Error::Object {
+                    kind: Kind::VisibilityTimeout,
+                    op: "write",
+                    path: self.path.clone(),
+                    source: anyhow!("not visible after {:?}: {}", timeout, reason),
+                });
+            }
+            let delay = delays.next().expect("backoff never ends");
+            clock.sleep(delay.min(deadline - now)).await;
+        }
     }
+
+    pub async fn write_bytes(self, bs: Vec<u8>) -> Result<usize> {
+        self.finish_bytes(bs).await.map(|v| v.size)
+    }
+
+    /// Write all data from `r` which must produce exactly `size` bytes.
+    ///
+    /// The write will be aborted with [`Kind::ContentLengthMismatch`] if `r`
+    /// ends early or produces more than `size`.
     pub async fn write_reader(self, r: BoxedAsyncReader, size: u64) -> Result<usize> {
-        let op = &OpWrite {
-            path: self.path.clone(),
-            size,
+        self.finish_reader(r, size).await.map(|v| v.size)
+    }
+
+    /// Same as [`Writer::write_bytes`], but returns the digest too if
+    /// [`Writer::digest`] is set.
+    ///
+    /// The digest is computed before writing and sent along with the
+    /// content, so that backends could verify it on the server side.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::readers::DigestKind;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     let w = op.object("test").writer().digest(DigestKind::Crc32c);
+    ///     let written = w.finish_bytes(b"Hello, World!".to_vec()).await?;
+    ///     assert_eq!(written.size, 13);
+    ///     assert_eq!(written.digest.unwrap().to_string(), "4d551068");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn finish_bytes(self, bs: Vec<u8>) -> Result<Written> {
+        let op = &mut self.op(bs.len() as u64)?;
+        let digest = self.opts.digest.map(|kind| Hasher::digest(kind, &bs));
+        op.checksum = digest.clone();
+        let r = Box::new(futures::io::Cursor::new(bs));
+
+        let size = self.acc.write(r, op).await?;
+        self.await_visibility(op.size).await?;
+        Ok(Written { size, digest })
+    }
+
+    /// Same as [`Writer::write_reader`], but returns the digest too if
+    /// [`Writer::digest`] is set.
+    ///
+    /// The digest is computed as `r` is consumed, and can't be verified by
+    /// backends on the server side.
+    pub async fn finish_reader(self, r: BoxedAsyncReader, size: u64) -> Result<Written> {
+        let op = &self.op(size)?;
+        let (r, check) = LengthCheckedReader::new(r, size);
+        let (r, handle): (BoxedAsyncReader, _) = match self.opts.digest {
+            Some(kind) => {
+                let (r, handle) = DigestReader::new(Box::new(r), kind);
+                (Box::new(r), Some(handle))
+            }
+            None => (Box::new(r), None),
         };
 
-        self.acc.write(r, op).await
+        let result = self.acc.write(r, op).await;
+        // Backends wrap the io error from reader into their own, use the
+        // mismatch recorded by the reader instead.
+        let size = match check.error("write", &self.path) {
+            Some(err) => Err(err),
+            None => result,
+        }?;
+        self.await_visibility(op.size).await?;
+        Ok(Written {
+            size,
+            // Backends could stop reading right after `size` bytes without
+            // reaching EOF, there is no digest then.
+            digest: handle.and_then(|v| v.digest()),
+        })
     }
 }
+
+/// Result of [`Writer::finish_bytes`] and [`Writer::finish_reader`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Written {
+    /// Bytes written.
+    pub size: usize,
+    /// Digest of the written content, only set if [`Writer::digest`] is
+    /// set.
+    pub digest: Option<Digest>,
+}
@@ -0,0 +1,172 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+/// Backoff computes the delays between attempts, which grow exponentially
+/// from `base` by `multiplier` until `max`.
+///
+/// With jitter enabled, every delay is picked uniformly from
+/// `[delay / 2, delay]`, so that clients failed at the same time won't
+/// retry at the same time. The randomness could be fixed by
+/// [`Backoff::seed`] to make tests deterministic.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use opendal::layers::Backoff;
+///
+/// let backoff = Backoff::new()
+///     .base(Duration::from_millis(100))
+///     .max(Duration::from_secs(1))
+///     .multiplier(2.0)
+///     .jitter(false);
+///
+/// let delays: Vec<_> = backoff.iter().take(5).collect();
+/// assert_eq!(delays[0], Duration::from_millis(100));
+/// assert_eq!(delays[4], Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    multiplier: f64,
+    jitter: bool,
+    seed: Option<u64>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: true,
+            seed: None,
+        }
+    }
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Backoff that always waits `delay` without jitter.
+    pub fn constant(delay: Duration) -> Self {
+        Self {
+            base: delay,
+            max: delay,
+            multiplier: 1.0,
+            jitter: false,
+            seed: None,
+        }
+    }
+
+    /// The first delay, default to 100ms.
+    #[must_use]
+    pub fn base(mut self, base: Duration) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Delays will not grow beyond this, default to 10s.
+    #[must_use]
+    pub fn max(mut self, max: Duration) -> Self {
+        self.max = max;
+        self
+    }
+
+    /// Every delay is `multiplier` times of the previous one, default
+    /// to 2.0.
+    ///
+    /// Multipliers less than 1.0 will be treated as 1.0.
+    #[must_use]
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Enable or disable jitter, enabled by default.
+    #[must_use]
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// Seed the randomness of jitter, a random seed will be used if not
+    /// set.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Returns the delays of attempts, the iterator never ends.
+    pub fn iter(&self) -> BackoffIter {
+        let seed = self
+            .seed
+            .unwrap_or_else(|| uuid::Uuid::new_v4().as_u128() as u64);
+
+        BackoffIter {
+            backoff: *self,
+            current: self.base.min(self.max),
+            // xorshift gets stuck at zero.
+            state: seed.max(1),
+        }
+    }
+}
+
+/// BackoffIter yields the delays of a [`Backoff`].
+#[derive(Debug, Clone)]
+pub struct BackoffIter {
+    backoff: Backoff,
+    current: Duration,
+    state: u64,
+}
+
+impl BackoffIter {
+    /// Returns the delay before the next attempt.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current =
+            Duration::try_from_secs_f64(self.current.as_secs_f64() * self.backoff.multiplier)
+                .unwrap_or(self.backoff.max)
+                .min(self.backoff.max);
+
+        if !self.backoff.jitter {
+            return delay;
+        }
+        let half = delay / 2;
+        half + (delay - half).mul_f64(self.next_f64())
+    }
+
+    /// Returns a random number in `[0, 1)` by xorshift64.
+    fn next_f64(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Iterator for BackoffIter {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        Some(self.next_delay())
+    }
+}
@@ -0,0 +1,415 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::AsyncReadExt;
+use metrics::increment_counter;
+
+use crate::clock::Clock;
+use crate::clock::TokioClock;
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Layer;
+use crate::MetaField;
+use crate::Metadata;
+use crate::ObjectReader;
+use crate::Provenance;
+
+/// InMemoryCacheLayer caches small hot objects (config files, index headers
+/// and so on) in process.
+///
+/// - Only reads of the latest whole object will be cached, ranged and
+///   versioned reads bypass it.
+/// - Entries are keyed by path and etag, objects without etag will not be
+///   cached. After `ttl`, an entry will be re-validated by a `stat` before
+///   being served.
+/// - Writes, appends and deletes through the same operator invalidate the entry, so
+///   stale data will never be served after them.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anyhow::Result;
+/// use opendal::layers::InMemoryCacheLayer;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let cache = InMemoryCacheLayer::new()
+///         .max_total_bytes(16 * 1024 * 1024)
+///         .max_entry_size(64 * 1024)
+///         .ttl(Duration::from_secs(30));
+///     let op = Operator::new(memory::Backend::build().finish().await?).layer(cache.clone());
+///
+///     println!("cache hits: {}", cache.hits());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InMemoryCacheLayer {
+    max_total_bytes: u64,
+    max_entry_size: u64,
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+
+    stats: Arc<CacheStats>,
+}
+
+#[derive(Debug, Default)]
+struct CacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Default for InMemoryCacheLayer {
+    fn default() -> Self {
+        Self {
+            max_total_bytes: 64 * 1024 * 1024,
+            max_entry_size: 1024 * 1024,
+            ttl: Duration::from_secs(60),
+            clock: Arc::new(TokioClock),
+            stats: Arc::default(),
+        }
+    }
+}
+
+impl InMemoryCacheLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Max bytes of all cached objects, default to 64 MiB.
+    #[must_use]
+    pub fn max_total_bytes(mut self, n: u64) -> Self {
+        self.max_total_bytes = n;
+        self
+    }
+
+    /// Objects larger than this will not be cached, default to 1 MiB.
+    #[must_use]
+    pub fn max_entry_size(mut self, n: u64) -> Self {
+        self.max_entry_size = n;
+        self
+    }
+
+    /// Entries older than this will be re-validated, default to 60s.
+    #[must_use]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Expire entries on `clock` instead of tokio's time.
+    #[cfg(any(test, feature = "testing"))]
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Number of reads served from cache.
+    pub fn hits(&self) -> u64 {
+        self.stats.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of whole object reads that are not served from cache.
+    pub fn misses(&self) -> u64 {
+        self.stats.misses.load(Ordering::Relaxed)
+    }
+}
+
+impl Layer for InMemoryCacheLayer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(CacheAccessor {
+            inner,
+            max_total_bytes: self.max_total_bytes,
+            max_entry_size: self.max_entry_size,
+            ttl: self.ttl,
+            clock: self.clock.clone(),
+            stats: self.stats.clone(),
+            state: Mutex::default(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    data: Bytes,
+    meta: Metadata,
+    validated_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    total_bytes: u64,
+    /// Bumped by every write and delete, reads started before that must
+    /// not populate the cache.
+    generation: u64,
+}
+
+impl CacheState {
+    fn remove(&mut self, path: &str) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.total_bytes -= entry.data.len() as u64;
+        }
+    }
+
+    fn insert(&mut self, path: &str, entry: CacheEntry, max_total_bytes: u64) {
+        self.remove(path);
+
+        // Evict the least recently validated entries until it fits.
+        let size = entry.data.len() as u64;
+        while self.total_bytes + size > max_total_bytes {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, v)| v.validated_at)
+                .map(|(k, _)| k.clone());
+            match oldest {
+                Some(k) => self.remove(&k),
+                None => return,
+            }
+        }
+
+        self.total_bytes += size;
+        self.entries.insert(path.to_string(), entry);
+    }
+}
+
+#[derive(Debug)]
+struct CacheAccessor {
+    inner: Arc<dyn Accessor>,
+
+    max_total_bytes: u64,
+    max_entry_size: u64,
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+
+    stats: Arc<CacheStats>,
+    state: Mutex<CacheState>,
+}
+
+impl CacheAccessor {
+    fn hit(&self, data: Bytes, meta: Metadata) -> ObjectReader {
+        self.stats.hits.fetch_add(1, Ordering::Relaxed);
+        increment_counter!("opendal_cache_hits");
+
+        let r: BoxedAsyncReader = Box::new(futures::io::Cursor::new(data));
+        ObjectReader::new(r)
+            .with_metadata(meta)
+            .with_tag(Provenance::SERVED_BY, "cache")
+    }
+
+    /// Return the cached entry if it's still valid.
+    async fn lookup(&self, path: &str) -> Result<Option<(Bytes, Metadata)>> {
+        let (data, meta, expired) = {
+            let state = self.state.lock().expect("lock poisoned");
+            match state.entries.get(path) {
+                Some(entry) => (
+                    entry.data.clone(),
+                    entry.meta.clone(),
+                    self.clock.now().duration_since(entry.validated_at) >= self.ttl,
+                ),
+                None => return Ok(None),
+            }
+        };
+        if !expired {
+            return Ok(Some((data, meta)));
+        }
+
+        // Entry expired, re-validate it by etag.
+        let current = match self.inner.stat(&OpStat::new(path)).await {
+            Ok(m) => Some(m),
+            Err(e) if e.kind() == Kind::ObjectNotExist => None,
+            Err(e) => return Err(e),
+        };
+
+        let valid = matches!(&current, Some(m) if m.etag().is_some() && m.etag() == meta.etag());
+
+        let mut state = self.state.lock().expect("lock poisoned");
+        match state.entries.get_mut(path) {
+            // The entry could have been invalidated while we are waiting for
+            // the stat.
+            Some(entry) if valid && entry.meta.etag() == meta.etag() => {
+                entry.validated_at = self.clock.now();
+                Ok(Some((data, meta)))
+            }
+            _ => {
+                state.remove(path);
+                Ok(None)
+            }
+        }
+    }
+
+    fn invalidate(&self, path: &str) {
+        let mut state = self.state.lock().expect("lock poisoned");
+        state.remove(path);
+        state.generation += 1;
+    }
+}
+
+#[async_trait]
+impl Accessor for CacheAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        // Ranged and versioned reads bypass the cache, entries are only
+        // validated against the latest version.
+        if args.offset.unwrap_or_default() != 0 || args.size.is_some() || args.version_id.is_some()
+        {
+            let mut or = self.inner.read(args).await?;
+            or.provenance_mut()
+                .set_if_absent(Provenance::SERVED_BY, "origin");
+            return Ok(or);
+        }
+
+        if let Some((data, meta)) = self.lookup(&args.path).await? {
+            return Ok(self.hit(data, meta));
+        }
+
+        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+        increment_counter!("opendal_cache_misses");
+
+        let generation = self.state.lock().expect("lock poisoned").generation;
+        let mut or = self.inner.read(args).await?;
+        or.provenance_mut()
+            .set_if_absent(Provenance::SERVED_BY, "origin");
+
+        let cacheable = or.metadata().etag().is_some()
+            && or.metadata().has(MetaField::ContentLength)
+            && or.metadata().content_length() <= self.max_entry_size
+            && or.metadata().content_length() <= self.max_total_bytes;
+        if !cacheable {
+            return Ok(or);
+        }
+
+        let provenance = or.provenance().clone();
+        let (mut r, meta) = or.into_parts();
+        let mut buf = Vec::with_capacity(meta.content_length() as usize);
+        r.read_to_end(&mut buf).await.map_err(|e| Error::Object {
+            kind: Kind::Unexpected,
+            op: "read",
+            path: args.path.clone(),
+            source: anyhow::Error::from(e),
+        })?;
+        let data = Bytes::from(buf);
+
+        {
+            let mut state = self.state.lock().expect("lock poisoned");
+            // Skip the insertion if the object has been changed since our
+            // read started.
+            if state.generation == generation {
+                let entry = CacheEntry {
+                    data: data.clone(),
+                    meta: meta.clone(),
+                    validated_at: self.clock.now(),
+                };
+                state.insert(&args.path, entry, self.max_total_bytes);
+            }
+        }
+
+        let r: BoxedAsyncReader = Box::new(futures::io::Cursor::new(data));
+        Ok(ObjectReader::new(r)
+            .with_metadata(meta)
+            .with_provenance(provenance))
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        let result = self.inner.write(r, args).await;
+        // The object could be partially overwritten even if write failed.
+        self.invalidate(&args.path);
+        result
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        let result = self.inner.append(r, args).await;
+        self.invalidate(&args.path);
+        result
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.inner.stat(args).await
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        let result = self.inner.delete(args).await;
+        self.invalidate(&args.path);
+        result
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        self.inner.list(args).await
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.inner.bucket_exists().await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.inner.select(args).await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        self.inner.presign(args).await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.inner.list_multipart_uploads(args).await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.inner.abort_multipart_upload(args).await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.inner.retention(args).await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        let result = self.inner.copy(args).await;
+        self.invalidate(&args.to);
+        result
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        let result = self.inner.create(args).await;
+        self.invalidate(&args.path);
+        result
+    }
+}
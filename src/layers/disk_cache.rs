@@ -0,0 +1,453 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use blocking::unblock;
+use blocking::Unblock;
+use futures::io;
+use futures::AsyncWriteExt;
+use log::warn;
+use metrics::increment_counter;
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Layer;
+use crate::MetaField;
+use crate::Metadata;
+use crate::ObjectReader;
+use crate::Provenance;
+
+/// DiskCacheLayer caches read objects as files under a local directory,
+/// which is useful for repeated reads of large remote objects.
+///
+/// - Only reads of the latest whole object will be cached, ranged and
+///   versioned reads bypass it.
+/// - Objects without etag or larger than `max_bytes` will not be cached.
+/// - Every hit will be validated by a `stat` first, the cached file is
+///   served only if the etag is still the same.
+/// - The least recently used files will be evicted once the total size
+///   exceeds `max_bytes`.
+/// - Writes, appends and deletes through the same operator invalidate the
+///   cached file.
+///
+/// The index of cached files lives in memory, files left by other processes
+/// will not be reused. Cached files are removed while the accessor dropped.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::DiskCacheLayer;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let cache = DiskCacheLayer::new("/tmp/opendal-cache", 1024 * 1024 * 1024);
+///     let op = Operator::new(memory::Backend::build().finish().await?).layer(cache.clone());
+///
+///     println!("cache hits: {}", cache.hits());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiskCacheLayer {
+    cache_dir: PathBuf,
+    max_bytes: u64,
+
+    stats: Arc<CacheStats>,
+}
+
+#[derive(Debug, Default)]
+struct CacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl DiskCacheLayer {
+    /// Cache objects under `cache_dir` with at most `max_bytes` in total.
+    ///
+    /// `cache_dir` will be created if not exist.
+    pub fn new(cache_dir: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            cache_dir: cache_dir.into(),
+            max_bytes,
+            stats: Arc::default(),
+        }
+    }
+
+    /// Number of reads served from cache.
+    pub fn hits(&self) -> u64 {
+        self.stats.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of whole object reads that are not served from cache.
+    pub fn misses(&self) -> u64 {
+        self.stats.misses.load(Ordering::Relaxed)
+    }
+}
+
+impl Layer for DiskCacheLayer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(DiskCacheAccessor {
+            inner,
+            cache_dir: self.cache_dir.clone(),
+            max_bytes: self.max_bytes,
+            stats: self.stats.clone(),
+            state: Mutex::default(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    file: PathBuf,
+    meta: Metadata,
+    /// Tick of the last access, used by LRU eviction.
+    accessed_at: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    total_bytes: u64,
+    tick: u64,
+    /// Bumped by every write and delete, reads started before that must
+    /// not populate the cache.
+    generation: u64,
+}
+
+impl CacheState {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Remove the entry from index, the returned file should be removed by caller.
+    fn remove(&mut self, path: &str) -> Option<PathBuf> {
+        let entry = self.entries.remove(path)?;
+        self.total_bytes -= entry.meta.content_length();
+        Some(entry.file)
+    }
+
+    /// Insert the entry and return the files to be removed.
+    fn insert(&mut self, path: &str, entry: CacheEntry, max_bytes: u64) -> Vec<PathBuf> {
+        let mut evicted: Vec<PathBuf> = self.remove(path).into_iter().collect();
+
+        // Evict the least recently used entries until it fits.
+        let size = entry.meta.content_length();
+        while self.total_bytes + size > max_bytes {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, v)| v.accessed_at)
+                .map(|(k, _)| k.clone());
+            match oldest {
+                Some(k) => evicted.extend(self.remove(&k)),
+                None => break,
+            }
+        }
+
+        self.total_bytes += size;
+        self.entries.insert(path.to_string(), entry);
+        evicted
+    }
+}
+
+#[derive(Debug)]
+struct DiskCacheAccessor {
+    inner: Arc<dyn Accessor>,
+
+    cache_dir: PathBuf,
+    max_bytes: u64,
+
+    stats: Arc<CacheStats>,
+    state: Mutex<CacheState>,
+}
+
+/// Remove cached files, failures are logged since they only waste disk.
+async fn remove_files(files: Vec<PathBuf>) {
+    if files.is_empty() {
+        return;
+    }
+    unblock(move || {
+        for f in files {
+            if let Err(e) = fs::remove_file(&f) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("cache file {:?} remove: {:?}", &f, e);
+                }
+            }
+        }
+    })
+    .await
+}
+
+impl DiskCacheAccessor {
+    /// Open the cached file if its etag is still the same as the object.
+    async fn lookup(&self, path: &str) -> Result<Option<ObjectReader>> {
+        let (file, meta) = {
+            let state = self.state.lock().expect("lock poisoned");
+            match state.entries.get(path) {
+                Some(entry) => (entry.file.clone(), entry.meta.clone()),
+                None => return Ok(None),
+            }
+        };
+
+        let current = match self.inner.stat(&OpStat::new(path)).await {
+            Ok(m) => Some(m),
+            Err(e) if e.kind() == Kind::ObjectNotExist => None,
+            Err(e) => return Err(e),
+        };
+        let valid = matches!(&current, Some(m) if m.etag().is_some() && m.etag() == meta.etag());
+
+        let opened = if valid {
+            let open_file = file.clone();
+            unblock(move || fs::File::open(open_file)).await.ok()
+        } else {
+            None
+        };
+
+        let stale = {
+            let mut state = self.state.lock().expect("lock poisoned");
+            match (opened, state.entries.get(path)) {
+                // The entry could have been replaced while we are waiting
+                // for the stat.
+                (Some(f), Some(entry)) if entry.file == file => {
+                    let tick = state.next_tick();
+                    if let Some(entry) = state.entries.get_mut(path) {
+                        entry.accessed_at = tick;
+                    }
+
+                    let r: BoxedAsyncReader = Box::new(Unblock::new(f));
+                    return Ok(Some(
+                        ObjectReader::new(r)
+                            .with_metadata(meta)
+                            .with_tag(Provenance::SERVED_BY, "cache"),
+                    ));
+                }
+                (_, Some(entry)) if entry.file == file => state.remove(path),
+                _ => None,
+            }
+        };
+        remove_files(stale.into_iter().collect()).await;
+        Ok(None)
+    }
+
+    /// Copy the object into a new cache file and serve the read from it.
+    async fn fill(&self, args: &OpRead, or: ObjectReader, generation: u64) -> Result<ObjectReader> {
+        let provenance = or.provenance().clone();
+        let (r, meta) = or.into_parts();
+        let to_err = |e: std::io::Error| Error::Object {
+            kind: Kind::Unexpected,
+            op: "read",
+            path: args.path.clone(),
+            source: anyhow::Error::from(e),
+        };
+
+        let dir = self.cache_dir.clone();
+        let file = self.cache_dir.join(format!("{}.cache", Uuid::new_v4()));
+        let create_file = file.clone();
+        let f = unblock(move || {
+            fs::create_dir_all(dir)?;
+            fs::File::create(create_file)
+        })
+        .await
+        .map_err(to_err)?;
+
+        let mut w = Unblock::new(f);
+        let copied = match io::copy(r, &mut w).await {
+            Ok(_) => w.close().await,
+            Err(e) => Err(e),
+        };
+        // Open before indexing, the file could be evicted by other reads
+        // right after that.
+        let opened = match copied {
+            Ok(()) => {
+                let open_file = file.clone();
+                unblock(move || fs::File::open(open_file)).await
+            }
+            Err(e) => Err(e),
+        };
+        let f = match opened {
+            Ok(f) => f,
+            Err(e) => {
+                remove_files(vec![file]).await;
+                return Err(to_err(e));
+            }
+        };
+
+        let evicted = {
+            let mut state = self.state.lock().expect("lock poisoned");
+            // Skip the insertion if the object has been changed since our
+            // read started.
+            if state.generation == generation {
+                let entry = CacheEntry {
+                    file,
+                    meta: meta.clone(),
+                    accessed_at: state.next_tick(),
+                };
+                state.insert(&args.path, entry, self.max_bytes)
+            } else {
+                vec![file]
+            }
+        };
+        remove_files(evicted).await;
+
+        let r: BoxedAsyncReader = Box::new(Unblock::new(f));
+        Ok(ObjectReader::new(r)
+            .with_metadata(meta)
+            .with_provenance(provenance))
+    }
+
+    async fn invalidate(&self, path: &str) {
+        let stale = {
+            let mut state = self.state.lock().expect("lock poisoned");
+            state.generation += 1;
+            state.remove(path)
+        };
+        remove_files(stale.into_iter().collect()).await;
+    }
+}
+
+impl Drop for DiskCacheAccessor {
+    fn drop(&mut self) {
+        let state = self.state.get_mut().expect("lock poisoned");
+        for (_, entry) in state.entries.drain() {
+            let _ = fs::remove_file(entry.file);
+        }
+    }
+}
+
+#[async_trait]
+impl Accessor for DiskCacheAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        // Ranged and versioned reads bypass the cache, entries are only
+        // validated against the latest version.
+        if args.offset.unwrap_or_default() != 0 || args.size.is_some() || args.version_id.is_some()
+        {
+            let mut or = self.inner.read(args).await?;
+            or.provenance_mut()
+                .set_if_absent(Provenance::SERVED_BY, "origin");
+            return Ok(or);
+        }
+
+        if let Some(r) = self.lookup(&args.path).await? {
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+            increment_counter!("opendal_disk_cache_hits");
+            return Ok(r);
+        }
+
+        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+        increment_counter!("opendal_disk_cache_misses");
+
+        let generation = self.state.lock().expect("lock poisoned").generation;
+        let mut or = self.inner.read(args).await?;
+        or.provenance_mut()
+            .set_if_absent(Provenance::SERVED_BY, "origin");
+
+        let cacheable = or.metadata().etag().is_some()
+            && or.metadata().has(MetaField::ContentLength)
+            && or.metadata().content_length() <= self.max_bytes;
+        if !cacheable {
+            return Ok(or);
+        }
+
+        self.fill(args, or, generation).await
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        let result = self.inner.write(r, args).await;
+        // The object could be partially overwritten even if write failed.
+        self.invalidate(&args.path).await;
+        result
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        let result = self.inner.append(r, args).await;
+        self.invalidate(&args.path).await;
+        result
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.inner.stat(args).await
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        let result = self.inner.delete(args).await;
+        self.invalidate(&args.path).await;
+        result
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        self.inner.list(args).await
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.inner.bucket_exists().await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.inner.select(args).await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        self.inner.presign(args).await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.inner.list_multipart_uploads(args).await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.inner.abort_multipart_upload(args).await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.inner.retention(args).await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        let result = self.inner.copy(args).await;
+        self.invalidate(&args.to).await;
+        result
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        let result = self.inner.create(args).await;
+        self.invalidate(&args.path).await;
+        result
+    }
+}
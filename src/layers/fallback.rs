@@ -0,0 +1,338 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::AsyncReadExt;
+use log::warn;
+use metrics::increment_counter;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Layer;
+use crate::Metadata;
+use crate::ObjectReader;
+use crate::Operator;
+use crate::Provenance;
+
+/// How [`FallbackLayer`] handles writes and deletes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WritePolicy {
+    /// Only send to the primary.
+    #[default]
+    PrimaryOnly,
+    /// Send to the primary and then the secondary, fails if any of them
+    /// failed.
+    ///
+    /// The data of writes and appends will be buffered in memory.
+    Mirror,
+}
+
+/// FallbackLayer falls back to a secondary operator if the primary failed.
+///
+/// - `read`, `stat` and `list` fall back to the secondary on errors of the
+///   configured kinds, which are [`Kind::Temporary`], [`Kind::Unexpected`]
+///   and [`Kind::ObjectNotExist`] by default.
+/// - Only the request that opens a reader or a list falls back, errors
+///   returned while consuming them will be returned as is.
+/// - If both failed, the returned error has the kind of the secondary one
+///   and carries both errors.
+/// - Writes and deletes are handled by [`WritePolicy`], other operations
+///   are only sent to the primary.
+///
+/// # Repair
+///
+/// With [`FallbackLayer::repair`] enabled, whole object reads served by
+/// the secondary will be buffered in memory and written back to the
+/// primary in a background tokio task. Ranged and versioned reads will not
+/// be repaired.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::FallbackLayer;
+/// use opendal::layers::WritePolicy;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let secondary = Operator::new(memory::Backend::build().finish().await?);
+///     let fallback = FallbackLayer::new(secondary)
+///         .write_policy(WritePolicy::Mirror)
+///         .repair(true);
+///     let op = Operator::new(memory::Backend::build().finish().await?).layer(fallback);
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FallbackLayer {
+    secondary: Arc<dyn Accessor>,
+    kinds: Vec<Kind>,
+    write_policy: WritePolicy,
+    repair: bool,
+}
+
+impl FallbackLayer {
+    pub fn new(secondary: Operator) -> Self {
+        Self {
+            secondary: secondary.inner(),
+            kinds: vec![Kind::Temporary, Kind::Unexpected, Kind::ObjectNotExist],
+            write_policy: WritePolicy::default(),
+            repair: false,
+        }
+    }
+
+    /// Error kinds of the primary that trigger the fallback.
+    #[must_use]
+    pub fn fallback_on(mut self, kinds: impl IntoIterator<Item = Kind>) -> Self {
+        self.kinds = kinds.into_iter().collect();
+        self
+    }
+
+    /// How to handle writes and deletes, default to
+    /// [`WritePolicy::PrimaryOnly`].
+    #[must_use]
+    pub fn write_policy(mut self, policy: WritePolicy) -> Self {
+        self.write_policy = policy;
+        self
+    }
+
+    /// Write objects read from the secondary back to the primary, default
+    /// to `false`.
+    #[must_use]
+    pub fn repair(mut self, v: bool) -> Self {
+        self.repair = v;
+        self
+    }
+}
+
+impl Layer for FallbackLayer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(FallbackAccessor {
+            primary: inner,
+            layer: self.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct FallbackAccessor {
+    primary: Arc<dyn Accessor>,
+    layer: FallbackLayer,
+}
+
+impl FallbackAccessor {
+    fn should_fallback(&self, err: &Error) -> bool {
+        self.layer.kinds.contains(&err.kind())
+    }
+
+    /// Combine the errors of both attempts.
+    fn both_failed(op: &'static str, path: &str, primary: Error, secondary: Error) -> Error {
+        Error::Object {
+            kind: secondary.kind(),
+            op,
+            path: path.to_string(),
+            source: anyhow!("primary: {}, secondary: {}", primary, secondary),
+        }
+    }
+
+    async fn read_all(path: &str, mut r: BoxedAsyncReader) -> Result<Bytes> {
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf).await.map_err(|e| Error::Object {
+            kind: Kind::Unexpected,
+            op: "read",
+            path: path.to_string(),
+            source: anyhow::Error::from(e),
+        })?;
+        Ok(Bytes::from(buf))
+    }
+
+    /// Read the object from the secondary and write it back to the
+    /// primary in background.
+    async fn repair(&self, args: &OpRead, or: ObjectReader) -> Result<ObjectReader> {
+        let provenance = or.provenance().clone();
+        let (r, meta) = or.into_parts();
+        let data = FallbackAccessor::read_all(&args.path, r).await?;
+
+        let primary = self.primary.clone();
+        let (path, bs) = (args.path.clone(), data.clone());
+        tokio::spawn(async move {
+            increment_counter!("opendal_fallback_repairs");
+            let op = OpWrite::new(&path, bs.len() as u64);
+            let r: BoxedAsyncReader = Box::new(futures::io::Cursor::new(bs));
+            if let Err(e) = primary.write(r, &op).await {
+                warn!("object {} repair primary: {:?}", &path, e);
+            }
+        });
+
+        let r: BoxedAsyncReader = Box::new(futures::io::Cursor::new(data));
+        Ok(ObjectReader::new(r)
+            .with_metadata(meta)
+            .with_provenance(provenance))
+    }
+}
+
+#[async_trait]
+impl Accessor for FallbackAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        let err = match self.primary.read(args).await {
+            Err(e) if self.should_fallback(&e) => e,
+            Ok(mut or) => {
+                or.provenance_mut()
+                    .set_if_absent(Provenance::SERVED_BY, "origin");
+                return Ok(or);
+            }
+            r => return r,
+        };
+        increment_counter!("opendal_fallback_requests");
+
+        let or = match self.layer.secondary.read(args).await {
+            // The secondary could be layered by a cache, but it's still
+            // not the origin.
+            Ok(or) => or.with_tag(Provenance::SERVED_BY, "secondary"),
+            Err(e) => return Err(FallbackAccessor::both_failed("read", &args.path, err, e)),
+        };
+        let whole = args.offset.unwrap_or_default() == 0
+            && args.size.is_none()
+            && args.version_id.is_none();
+        if self.layer.repair && whole {
+            return self.repair(args, or).await;
+        }
+        Ok(or)
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        match self.layer.write_policy {
+            WritePolicy::PrimaryOnly => self.primary.write(r, args).await,
+            WritePolicy::Mirror => {
+                let data = FallbackAccessor::read_all(&args.path, r).await?;
+                let n = self
+                    .primary
+                    .write(Box::new(futures::io::Cursor::new(data.clone())), args)
+                    .await?;
+                self.layer
+                    .secondary
+                    .write(Box::new(futures::io::Cursor::new(data)), args)
+                    .await?;
+                Ok(n)
+            }
+        }
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        match self.layer.write_policy {
+            WritePolicy::PrimaryOnly => self.primary.append(r, args).await,
+            WritePolicy::Mirror => {
+                let data = FallbackAccessor::read_all(&args.path, r).await?;
+                let n = self
+                    .primary
+                    .append(Box::new(futures::io::Cursor::new(data.clone())), args)
+                    .await?;
+                self.layer
+                    .secondary
+                    .append(Box::new(futures::io::Cursor::new(data)), args)
+                    .await?;
+                Ok(n)
+            }
+        }
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        let err = match self.primary.stat(args).await {
+            Err(e) if self.should_fallback(&e) => e,
+            r => return r,
+        };
+        increment_counter!("opendal_fallback_requests");
+
+        self.layer
+            .secondary
+            .stat(args)
+            .await
+            .map_err(|e| FallbackAccessor::both_failed("stat", &args.path, err, e))
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        self.primary.delete(args).await?;
+        if self.layer.write_policy == WritePolicy::Mirror {
+            self.layer.secondary.delete(args).await?;
+        }
+        Ok(())
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        let err = match self.primary.list(args).await {
+            Err(e) if self.should_fallback(&e) => e,
+            r => return r,
+        };
+        increment_counter!("opendal_fallback_requests");
+
+        self.layer
+            .secondary
+            .list(args)
+            .await
+            .map_err(|e| FallbackAccessor::both_failed("list", &args.path, err, e))
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.primary.bucket_exists().await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.primary.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.primary.select(args).await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        self.primary.presign(args).await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.primary.list_multipart_uploads(args).await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.primary.abort_multipart_upload(args).await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.primary.retention(args).await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        self.primary.copy(args).await
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        self.primary.create(args).await
+    }
+}
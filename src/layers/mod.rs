@@ -0,0 +1,52 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Layer related helper tools
+mod backoff;
+pub use backoff::Backoff;
+pub use backoff::BackoffIter;
+
+mod cache;
+pub use cache::InMemoryCacheLayer;
+
+mod disk_cache;
+pub use disk_cache::DiskCacheLayer;
+
+mod fallback;
+pub use fallback::FallbackLayer;
+pub use fallback::WritePolicy;
+
+mod path_map;
+pub use path_map::PathMapLayer;
+
+mod quota;
+pub use quota::QuotaLayer;
+
+mod record;
+#[cfg(any(test, feature = "testing"))]
+pub(crate) use record::decode_hex;
+#[cfg(any(test, feature = "testing"))]
+pub(crate) use record::Record;
+pub use record::RecordLayer;
+#[cfg(any(test, feature = "testing"))]
+pub(crate) use record::Response;
+
+mod retry;
+pub use retry::RetryLayer;
+
+mod write_defaults;
+pub use write_defaults::WriteDefaultsLayer;
+
+mod write_once;
+pub use write_once::WriteOnceLayer;
@@ -0,0 +1,247 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Layer;
+use crate::Metadata;
+use crate::Object;
+use crate::ObjectReader;
+
+type PathFn = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// PathMapLayer rewrites the paths of all operations before they reach the
+/// backend, like prepending an environment prefix or spreading keys across
+/// shards.
+///
+/// `map` turns the path used by callers into the path stored in backend,
+/// and `unmap` reverses it, so that metadata and listing results still show
+/// the original paths. The path to list is mapped as well, so `map` must
+/// keep the children of a dir under the mapped dir for listing to work.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::PathMapLayer;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let backend = memory::Backend::build().finish().await?;
+///     let op = Operator::new(backend.clone()).layer(PathMapLayer::new(
+///         |path| format!("staging/{}", path),
+///         |path| path.trim_start_matches("staging/").to_string(),
+///     ));
+///
+///     op.object("test").writer().write_bytes(b"Hello".to_vec()).await?;
+///     assert!(Operator::new(backend).object("staging/test").is_exist().await?);
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct PathMapLayer {
+    map: PathFn,
+    unmap: PathFn,
+}
+
+impl Debug for PathMapLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PathMapLayer")
+            .field("map", &"<fn>")
+            .field("unmap", &"<fn>")
+            .finish()
+    }
+}
+
+impl PathMapLayer {
+    pub fn new(
+        map: impl Fn(&str) -> String + Send + Sync + 'static,
+        unmap: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            map: Arc::new(map),
+            unmap: Arc::new(unmap),
+        }
+    }
+
+    fn map(&self, path: &str) -> String {
+        (self.map)(path)
+    }
+
+    /// Restore the path of metadata returned by backend.
+    fn unmap_metadata(&self, meta: &mut Metadata) {
+        if !meta.path().is_empty() {
+            let path = (self.unmap)(meta.path());
+            meta.set_path(&path);
+        }
+    }
+}
+
+impl Layer for PathMapLayer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(PathMapAccessor {
+            inner,
+            layer: self.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PathMapAccessor {
+    inner: Arc<dyn Accessor>,
+    layer: PathMapLayer,
+}
+
+#[async_trait]
+impl Accessor for PathMapAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+
+        let r = self.inner.read(&op).await?;
+        let buffered = r.is_buffered();
+        let provenance = r.provenance().clone();
+        let (r, mut meta) = r.into_parts();
+        self.layer.unmap_metadata(&mut meta);
+        let r = ObjectReader::new(r)
+            .with_metadata(meta)
+            .with_provenance(provenance);
+        Ok(if buffered { r.with_buffered() } else { r })
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+        self.inner.write(r, &op).await
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+        self.inner.append(r, &op).await
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+
+        let mut meta = self.inner.stat(&op).await?;
+        self.layer.unmap_metadata(&mut meta);
+        Ok(meta)
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+        self.inner.delete(&op).await
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+        op.start_after = args.start_after.as_deref().map(|v| self.layer.map(v));
+
+        // Objects must be bound to this accessor, so that following
+        // operations on them will be mapped too.
+        let acc: Arc<dyn Accessor> = Arc::new(self.clone());
+        let layer = self.layer.clone();
+        let obs = self.inner.list(&op).await?.map(move |r| {
+            r.map(|o| {
+                let mut meta = o.metadata_ref().clone();
+                layer.unmap_metadata(&mut meta);
+
+                let mut o = Object::new(acc.clone(), meta.path());
+                *o.metadata_mut() = meta;
+                o
+            })
+        });
+        Ok(Box::new(obs))
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.inner.bucket_exists().await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+        self.inner.select(&op).await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+        self.inner.presign(&op).await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        let mut op = args.clone();
+        op.prefix = self.layer.map(&args.prefix);
+
+        let uploads = self.inner.list_multipart_uploads(&op).await?;
+        Ok(uploads
+            .into_iter()
+            .map(|u| {
+                MultipartUpload::new(&(self.layer.unmap)(u.path()), u.upload_id(), u.initiated())
+            })
+            .collect())
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+        self.inner.abort_multipart_upload(&op).await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+        self.inner.retention(&op).await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        let mut op = args.clone();
+        op.from = self.layer.map(&args.from);
+        op.to = self.layer.map(&args.to);
+        self.inner.copy(&op).await
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        let mut op = args.clone();
+        op.path = self.layer.map(&args.path);
+        self.inner.create(&op).await
+    }
+}
@@ -0,0 +1,492 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::ready;
+use futures::AsyncRead;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignOperation;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Layer;
+use crate::Metadata;
+use crate::ObjectMode;
+use crate::ObjectReader;
+
+type UsageFn = Arc<dyn Fn(&str) -> BoxFuture<'static, Result<u64>> + Send + Sync>;
+
+/// QuotaLayer limits the bytes stored through an operator, in total and
+/// optionally under given prefixes.
+///
+/// Writes, appends and copies that would exceed any matching limit are
+/// rejected before being sent with [`Kind::QuotaExceeded`]. Streaming
+/// writes producing more bytes than the declared size are cut off with the
+/// same kind, so the declared size could not be used to sneak data in.
+/// Presigned writes bypass the layer and are rejected with
+/// [`Kind::ObjectPermissionDenied`].
+///
+/// # Usage
+///
+/// By default, the usage is counted from the writes and deletes observed
+/// through this layer, starting from zero. A `stat` will be sent before
+/// overwrites and deletes to learn the size of the old object.
+///
+/// Only deletes of single objects through this layer give bytes back.
+/// Objects removed in other ways are still counted: deleting a dir
+/// recursively in one call on backends that support it, deleting through
+/// another operator, or expiring by lifecycle rules.
+///
+/// With [`QuotaLayer::usage_fn`], the authoritative usage of the prefix
+/// (`""` for the total) is fetched before every write instead, and this
+/// layer only counts the bytes of writes in flight.
+///
+/// # Race
+///
+/// Bytes are reserved atomically before the write and released if it
+/// fails, so concurrent writes of different objects could never exceed the
+/// limit together. Concurrent overwrites of the same object all compare
+/// with the same old object, the usage could drift from the stored bytes
+/// then.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::QuotaLayer;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let quota = QuotaLayer::new(1024 * 1024).prefix_limit("tmp/", 1024);
+///     let op = Operator::new(memory::Backend::build().finish().await?).layer(quota.clone());
+///
+///     op.object("data").writer().write_bytes(vec![0; 4096]).await?;
+///     assert!(op.object("tmp/data").writer().write_bytes(vec![0; 4096]).await.is_err());
+///     assert_eq!(quota.usage(), 4096);
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct QuotaLayer {
+    /// The total quota goes first with an empty prefix.
+    quotas: Vec<Arc<Quota>>,
+    usage_fn: Option<UsageFn>,
+}
+
+#[derive(Debug)]
+struct Quota {
+    prefix: String,
+    limit: u64,
+    /// Usage fetched by `usage_fn` lastly.
+    fetched: AtomicU64,
+    /// Usage counted by this layer.
+    counted: AtomicU64,
+}
+
+impl Quota {
+    fn new(prefix: &str, limit: u64) -> Self {
+        Self {
+            prefix: prefix.trim_start_matches('/').to_string(),
+            limit,
+            fetched: AtomicU64::new(0),
+            counted: AtomicU64::new(0),
+        }
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        path.trim_start_matches('/').starts_with(&self.prefix)
+    }
+
+    fn usage(&self) -> u64 {
+        self.fetched.load(Ordering::SeqCst) + self.counted.load(Ordering::SeqCst)
+    }
+
+    /// Reserve `n` bytes, returns false if the limit will be exceeded.
+    fn reserve(&self, n: u64) -> bool {
+        self.counted
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |v| {
+                let fetched = self.fetched.load(Ordering::SeqCst);
+                v.checked_add(n)
+                    .filter(|total| fetched.saturating_add(*total) <= self.limit)
+            })
+            .is_ok()
+    }
+
+    fn release(&self, n: u64) {
+        let _ = self
+            .counted
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |v| {
+                Some(v.saturating_sub(n))
+            });
+    }
+}
+
+impl Debug for QuotaLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuotaLayer")
+            .field("quotas", &self.quotas)
+            .field("usage_fn", &self.usage_fn.as_ref().map(|_| "<fn>"))
+            .finish()
+    }
+}
+
+impl QuotaLayer {
+    /// Create a layer limiting the total bytes to `limit`.
+    pub fn new(limit: u64) -> Self {
+        Self {
+            quotas: vec![Arc::new(Quota::new("", limit))],
+            usage_fn: None,
+        }
+    }
+
+    /// Limit the bytes of objects under `prefix` to `limit` as well.
+    #[must_use]
+    pub fn prefix_limit(mut self, prefix: &str, limit: u64) -> Self {
+        self.quotas.push(Arc::new(Quota::new(prefix, limit)));
+        self
+    }
+
+    /// Fetch the authoritative usage of a prefix with `f` before every
+    /// write instead of counting it, the prefix is `""` for the total.
+    #[must_use]
+    pub fn usage_fn(
+        mut self,
+        f: impl Fn(&str) -> BoxFuture<'static, Result<u64>> + Send + Sync + 'static,
+    ) -> Self {
+        self.usage_fn = Some(Arc::new(f));
+        self
+    }
+
+    /// Total bytes used, including writes in flight.
+    pub fn usage(&self) -> u64 {
+        self.quotas[0].usage()
+    }
+
+    /// Bytes used under `prefix` including writes in flight, returns
+    /// `None` if `prefix` is not limited.
+    pub fn prefix_usage(&self, prefix: &str) -> Option<u64> {
+        let prefix = prefix.trim_start_matches('/');
+        self.quotas[1..]
+            .iter()
+            .find(|q| q.prefix == prefix)
+            .map(|q| q.usage())
+    }
+
+    /// Whether the usage is counted by this layer.
+    fn counting(&self) -> bool {
+        self.usage_fn.is_none()
+    }
+
+    /// Reserve `size` bytes from all quotas matching `path`.
+    async fn reserve(&self, op: &'static str, path: &str, size: u64) -> Result<Reservation> {
+        let quotas: Vec<_> = self.quotas.iter().filter(|q| q.matches(path)).collect();
+
+        if let Some(f) = &self.usage_fn {
+            for q in &quotas {
+                q.fetched.store(f(&q.prefix).await?, Ordering::SeqCst);
+            }
+        }
+
+        let mut r = Reservation {
+            quotas: Vec::with_capacity(quotas.len()),
+            size,
+            keep: false,
+        };
+        for q in quotas {
+            if !q.reserve(size) {
+                // Bytes reserved from other quotas are released by drop.
+                return Err(Error::Object {
+                    kind: Kind::QuotaExceeded,
+                    op,
+                    path: path.to_string(),
+                    source: anyhow!(
+                        "writing {} bytes exceeds quota of {} bytes under {:?}, used {}",
+                        size,
+                        q.limit,
+                        q.prefix,
+                        q.usage()
+                    ),
+                });
+            }
+            r.quotas.push(q.clone());
+        }
+        Ok(r)
+    }
+
+    /// Release the bytes of a removed or overwritten object under `path`.
+    fn free(&self, path: &str, size: u64) {
+        if self.counting() {
+            for q in self.quotas.iter().filter(|q| q.matches(path)) {
+                q.release(size)
+            }
+        }
+    }
+}
+
+impl Layer for QuotaLayer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(QuotaAccessor {
+            inner,
+            layer: self.clone(),
+        })
+    }
+}
+
+/// Reservation holds bytes reserved for a write, they are released on drop
+/// unless kept.
+struct Reservation {
+    quotas: Vec<Arc<Quota>>,
+    size: u64,
+    keep: bool,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if !self.keep {
+            for q in &self.quotas {
+                q.release(self.size)
+            }
+        }
+    }
+}
+
+/// QuotaReader fails the write once the reader produces more than the
+/// declared size, and records it in the shared flag since backends usually
+/// wrap the io error into their own.
+struct QuotaReader {
+    inner: BoxedAsyncReader,
+    size: u64,
+    read: u64,
+    exceeded: Arc<AtomicBool>,
+}
+
+impl AsyncRead for QuotaReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let n = ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        self.read += n as u64;
+
+        if self.read > self.size {
+            self.exceeded.store(true, Ordering::SeqCst);
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                anyhow!("reader produced more than the declared {} bytes", self.size),
+            )));
+        }
+        Poll::Ready(Ok(n))
+    }
+}
+
+#[derive(Debug)]
+struct QuotaAccessor {
+    inner: Arc<dyn Accessor>,
+    layer: QuotaLayer,
+}
+
+impl QuotaAccessor {
+    /// Size of the object to be overwritten or removed, only needed while
+    /// counting.
+    async fn old_size(&self, path: &str) -> Result<u64> {
+        if !self.layer.counting() {
+            return Ok(0);
+        }
+
+        match self.inner.stat(&OpStat::new(path)).await {
+            Ok(meta) if meta.mode() == ObjectMode::FILE => Ok(meta.content_length()),
+            Ok(_) => Ok(0),
+            Err(e) if e.kind() == Kind::ObjectNotExist => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Run the write of `r` replacing an object of `old` bytes.
+    ///
+    /// Only the growth is reserved, and the shrinkage is freed after the
+    /// write succeeded, so that overwrites near the limit are not rejected
+    /// for counting both objects at the same time.
+    async fn write_with<F>(
+        &self,
+        op: &'static str,
+        path: &str,
+        size: u64,
+        old: u64,
+        r: BoxedAsyncReader,
+        f: impl FnOnce(BoxedAsyncReader) -> F,
+    ) -> Result<usize>
+    where
+        F: std::future::Future<Output = Result<usize>>,
+    {
+        let mut reservation = self
+            .layer
+            .reserve(op, path, size.saturating_sub(old))
+            .await?;
+
+        let exceeded = Arc::new(AtomicBool::new(false));
+        let r = Box::new(QuotaReader {
+            inner: r,
+            size,
+            read: 0,
+            exceeded: exceeded.clone(),
+        });
+
+        let result = f(r).await;
+        if exceeded.load(Ordering::SeqCst) {
+            return Err(Error::Object {
+                kind: Kind::QuotaExceeded,
+                op,
+                path: path.to_string(),
+                source: anyhow!("reader produced more than the declared {} bytes", size),
+            });
+        }
+        let n = result?;
+
+        // The fetched usage includes the written bytes since now.
+        reservation.keep = self.layer.counting();
+        drop(reservation);
+        self.layer.free(path, old.saturating_sub(size));
+        Ok(n)
+    }
+}
+
+#[async_trait]
+impl Accessor for QuotaAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        self.inner.read(args).await
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        let old = self.old_size(&args.path).await?;
+        self.write_with("write", &args.path, args.size, old, r, |r| {
+            self.inner.write(r, args)
+        })
+        .await
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        self.write_with("append", &args.path, args.size, 0, r, |r| {
+            self.inner.append(r, args)
+        })
+        .await
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.inner.stat(args).await
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        let old = self.old_size(&args.path).await?;
+        self.inner.delete(args).await?;
+        self.layer.free(&args.path, old);
+        Ok(())
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        self.inner.list(args).await
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.inner.bucket_exists().await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.inner.select(args).await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        if matches!(args.op, PresignOperation::Write) {
+            return Err(Error::Object {
+                kind: Kind::ObjectPermissionDenied,
+                op: "presign",
+                path: args.path.clone(),
+                source: anyhow!("presigned writes bypass the quota"),
+            });
+        }
+        self.inner.presign(args).await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.inner.list_multipart_uploads(args).await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.inner.abort_multipart_upload(args).await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.inner.retention(args).await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        let size = self
+            .inner
+            .stat(&OpStat::new(&args.from))
+            .await?
+            .content_length();
+        let old = self.old_size(&args.to).await?;
+
+        let mut reservation = self
+            .layer
+            .reserve("copy", &args.to, size.saturating_sub(old))
+            .await?;
+        self.inner.copy(args).await?;
+        reservation.keep = self.layer.counting();
+        drop(reservation);
+        self.layer.free(&args.to, old.saturating_sub(size));
+        Ok(())
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        if args.mode == ObjectMode::DIR {
+            return self.inner.create(args).await;
+        }
+
+        // Creating an object truncates the existing one.
+        let old = self.old_size(&args.path).await?;
+        self.inner.create(args).await?;
+        self.layer.free(&args.path, old);
+        Ok(())
+    }
+}
@@ -0,0 +1,576 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use futures::AsyncReadExt;
+use futures::TryStreamExt;
+use log::warn;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::ops::RetentionMode;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Layer;
+use crate::MetaField;
+use crate::Metadata;
+use crate::ObjectMode;
+use crate::ObjectReader;
+
+/// RecordLayer records operations and their results into a file, so that
+/// they can be replayed offline by `testing::ReplayAccessor` (enabled by
+/// the `testing` feature).
+///
+/// Every record is a JSON line with the operation, the path (the prefix of
+/// `list_multipart_uploads` and the source of `copy`) and the result. All
+/// operations are recorded except `metadata`, which is not a request.
+///
+/// # Note
+///
+/// - Reads and selects are fully buffered in memory to capture their
+///   data, don't use it for large objects.
+/// - Listings are collected before returning, and only the metadata of
+///   entries is recorded.
+/// - Reads record the [`Provenance`][crate::Provenance] attached by the
+///   layers below, like whether they are served by a cache.
+/// - Failing to write the record file doesn't fail the operation, only a
+///   warning will be logged.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::RecordLayer;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let path = std::env::temp_dir().join(format!("opendal-{}.jsonl", uuid::Uuid::new_v4()));
+///     let op = Operator::new(memory::Backend::build().finish().await?)
+///         .layer(RecordLayer::new(&path)?);
+///
+///     op.object("test").writer().write_bytes(b"Hello, World!".to_vec()).await?;
+///     assert_eq!(std::fs::read_to_string(&path)?.lines().count(), 1);
+///
+///     std::fs::remove_file(&path)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordLayer {
+    file: Arc<Mutex<File>>,
+}
+
+impl RecordLayer {
+    /// Append records to the file at `path`, the file will be created if
+    /// not exist.
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+}
+
+impl Layer for RecordLayer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(RecordAccessor {
+            inner,
+            file: self.file.clone(),
+        })
+    }
+}
+
+/// Record is an operation along with its result.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Record {
+    pub op: String,
+    pub path: String,
+    pub result: std::result::Result<Response, RecordedError>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Response {
+    BucketExists(bool),
+    /// Data is encoded in hex.
+    Read {
+        data: String,
+        meta: RecordedMetadata,
+        /// Tags attached by the layers below, see [`Provenance`][crate::Provenance].
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        provenance: BTreeMap<String, String>,
+    },
+    Write(usize),
+    Append(usize),
+    Stat(RecordedMetadata),
+    Delete,
+    List(Vec<RecordedMetadata>),
+    /// Rows are encoded in hex.
+    Select(String),
+    Presign {
+        method: String,
+        uri: String,
+        headers: Vec<(String, String)>,
+    },
+    ListMultipartUploads(Vec<RecordedUpload>),
+    AbortMultipartUpload,
+    Retention {
+        mode: Option<String>,
+        retain_until: Option<Duration>,
+        legal_hold: bool,
+    },
+    Copy,
+    Create,
+}
+
+impl From<&PresignedRequest> for Response {
+    fn from(req: &PresignedRequest) -> Self {
+        Response::Presign {
+            method: req.method().to_string(),
+            uri: req.uri().to_string(),
+            headers: req
+                .headers()
+                .iter()
+                .map(|(k, v)| {
+                    (
+                        k.to_string(),
+                        String::from_utf8_lossy(v.as_bytes()).to_string(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&Retention> for Response {
+    fn from(r: &Retention) -> Self {
+        Response::Retention {
+            mode: r.mode().map(|v| {
+                match v {
+                    RetentionMode::Governance => "governance",
+                    RetentionMode::Compliance => "compliance",
+                }
+                .to_string()
+            }),
+            retain_until: r.retain_until().and_then(since_epoch),
+            legal_hold: r.legal_hold(),
+        }
+    }
+}
+
+/// A multipart upload, times are recorded as durations since the unix
+/// epoch.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct RecordedUpload {
+    path: String,
+    upload_id: String,
+    initiated: Option<Duration>,
+}
+
+impl From<&MultipartUpload> for RecordedUpload {
+    fn from(u: &MultipartUpload) -> Self {
+        Self {
+            path: u.path().to_string(),
+            upload_id: u.upload_id().to_string(),
+            initiated: u.initiated().and_then(since_epoch),
+        }
+    }
+}
+
+impl From<RecordedUpload> for MultipartUpload {
+    fn from(r: RecordedUpload) -> Self {
+        MultipartUpload::new(
+            &r.path,
+            &r.upload_id,
+            r.initiated.map(|v| SystemTime::UNIX_EPOCH + v),
+        )
+    }
+}
+
+fn since_epoch(t: SystemTime) -> Option<Duration> {
+    t.duration_since(SystemTime::UNIX_EPOCH).ok()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct RecordedError {
+    #[serde(with = "KindDef")]
+    pub kind: Kind,
+    pub message: String,
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl RecordedError {
+    pub(crate) fn into_error(self, op: &'static str, path: &str) -> Error {
+        Error::Object {
+            kind: self.kind,
+            op,
+            path: path.to_string(),
+            source: anyhow::anyhow!("{}", self.message),
+        }
+    }
+}
+
+impl From<&Error> for RecordedError {
+    fn from(e: &Error) -> Self {
+        Self {
+            kind: e.kind(),
+            message: e.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Kind")]
+enum KindDef {
+    BackendNotSupported,
+    BackendConfigurationInvalid,
+    ObjectNotExist,
+    ObjectPermissionDenied,
+    ObjectPathInvalid,
+    PreconditionFailed,
+    RangeNotSatisfiable,
+    ChecksumMismatch,
+    ContentLengthMismatch,
+    Unsupported,
+    VisibilityTimeout,
+    Temporary,
+    ResumeTokenInvalid,
+    QuotaExceeded,
+    Unexpected,
+}
+
+/// Metadata with only the fields filled by backend, times are recorded
+/// as durations since the unix epoch.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct RecordedMetadata {
+    path: String,
+    fully_loaded: bool,
+    mode: Option<String>,
+    content_length: Option<u64>,
+    etag: Option<String>,
+    last_modified: Option<Duration>,
+    version_id: Option<String>,
+    content_type: Option<String>,
+    content_encoding: Option<String>,
+    content_language: Option<String>,
+    cache_control: Option<String>,
+    expires: Option<Duration>,
+    owner_id: Option<String>,
+    owner_display_name: Option<String>,
+    storage_class: Option<String>,
+}
+
+impl From<&Metadata> for RecordedMetadata {
+    fn from(m: &Metadata) -> Self {
+        let to_string = |v: Option<&str>| v.map(|v| v.to_string());
+
+        Self {
+            path: m.path().to_string(),
+            fully_loaded: m.is_fully_loaded(),
+            mode: m.has(MetaField::Mode).then(|| {
+                match m.mode() {
+                    ObjectMode::FILE => "file",
+                    ObjectMode::DIR => "dir",
+                    ObjectMode::Unknown => "unknown",
+                }
+                .to_string()
+            }),
+            content_length: m.has(MetaField::ContentLength).then(|| m.content_length()),
+            etag: to_string(m.etag()),
+            last_modified: m.last_modified().and_then(since_epoch),
+            version_id: to_string(m.version_id()),
+            content_type: to_string(m.content_type()),
+            content_encoding: to_string(m.content_encoding()),
+            content_language: to_string(m.content_language()),
+            cache_control: to_string(m.cache_control()),
+            expires: m.expires().and_then(since_epoch),
+            owner_id: to_string(m.owner_id()),
+            owner_display_name: to_string(m.owner_display_name()),
+            storage_class: to_string(m.storage_class()),
+        }
+    }
+}
+
+impl From<RecordedMetadata> for Metadata {
+    fn from(r: RecordedMetadata) -> Self {
+        let mut m = Metadata::default();
+        m.set_path(&r.path);
+        if r.fully_loaded {
+            m.set_fully_loaded();
+        }
+        match r.mode.as_deref() {
+            Some("file") => m.set_mode(ObjectMode::FILE),
+            Some("dir") => m.set_mode(ObjectMode::DIR),
+            Some(_) => m.set_mode(ObjectMode::Unknown),
+            None => &mut m,
+        };
+        if let Some(v) = r.content_length {
+            m.set_content_length(v);
+        }
+        if let Some(v) = &r.etag {
+            m.set_etag(v);
+        }
+        if let Some(v) = r.last_modified {
+            m.set_last_modified(SystemTime::UNIX_EPOCH + v);
+        }
+        if let Some(v) = &r.version_id {
+            m.set_version_id(v);
+        }
+        if let Some(v) = &r.content_type {
+            m.set_content_type(v);
+        }
+        if let Some(v) = &r.content_encoding {
+            m.set_content_encoding(v);
+        }
+        if let Some(v) = &r.content_language {
+            m.set_content_language(v);
+        }
+        if let Some(v) = &r.cache_control {
+            m.set_cache_control(v);
+        }
+        if let Some(v) = r.expires {
+            m.set_expires(SystemTime::UNIX_EPOCH + v);
+        }
+        if let Some(v) = &r.owner_id {
+            m.set_owner_id(v);
+        }
+        if let Some(v) = &r.owner_display_name {
+            m.set_owner_display_name(v);
+        }
+        if let Some(v) = &r.storage_class {
+            m.set_storage_class(v);
+        }
+        m
+    }
+}
+
+pub(crate) fn encode_hex(bs: &[u8]) -> String {
+    let mut s = String::with_capacity(bs.len() * 2);
+    for b in bs {
+        let _ = write!(s, "{:02x}", b);
+    }
+    s
+}
+
+#[cfg(any(test, feature = "testing"))]
+pub(crate) fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    s.as_bytes()
+        .chunks(2)
+        .map(|c| match c {
+            [h, l] => Some(((*h as char).to_digit(16)? * 16 + (*l as char).to_digit(16)?) as u8),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct RecordAccessor {
+    inner: Arc<dyn Accessor>,
+    file: Arc<Mutex<File>>,
+}
+
+impl RecordAccessor {
+    fn record<T>(&self, op: &str, path: &str, result: &Result<T>, f: impl FnOnce(&T) -> Response) {
+        let record = Record {
+            op: op.to_string(),
+            path: path.to_string(),
+            result: result.as_ref().map(f).map_err(RecordedError::from),
+        };
+
+        let mut file = self.file.lock().expect("lock poisoned");
+        let res = serde_json::to_vec(&record)
+            .map_err(io::Error::from)
+            .and_then(|mut line| {
+                line.push(b'\n');
+                file.write_all(&line)
+            });
+        if let Err(e) = res {
+            warn!("object {} record {}: {:?}", path, op, e);
+        }
+    }
+}
+
+#[async_trait]
+impl Accessor for RecordAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        let result = match self.inner.read(args).await {
+            Ok(or) => {
+                let provenance = or.provenance().clone();
+                let (mut r, meta) = or.into_parts();
+                let mut buf = Vec::new();
+                r.read_to_end(&mut buf)
+                    .await
+                    .map(|_| (buf, meta, provenance))
+                    .map_err(|e| Error::Object {
+                        kind: Kind::Unexpected,
+                        op: "read",
+                        path: args.path.clone(),
+                        source: anyhow::Error::from(e),
+                    })
+            }
+            Err(e) => Err(e),
+        };
+        self.record("read", &args.path, &result, |(buf, meta, provenance)| {
+            Response::Read {
+                data: encode_hex(buf),
+                meta: meta.into(),
+                provenance: provenance
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            }
+        });
+
+        let (buf, meta, provenance) = result?;
+        Ok(ObjectReader::new(Box::new(futures::io::Cursor::new(buf)))
+            .with_metadata(meta)
+            .with_provenance(provenance))
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        let result = self.inner.write(r, args).await;
+        self.record("write", &args.path, &result, |n| Response::Write(*n));
+        result
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        let result = self.inner.append(r, args).await;
+        self.record("append", &args.path, &result, |n| Response::Append(*n));
+        result
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        let result = self.inner.stat(args).await;
+        self.record("stat", &args.path, &result, |m| Response::Stat(m.into()));
+        result
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        let result = self.inner.delete(args).await;
+        self.record("delete", &args.path, &result, |_| Response::Delete);
+        result
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        let result = match self.inner.list(args).await {
+            Ok(s) => s.try_collect::<Vec<_>>().await,
+            Err(e) => Err(e),
+        };
+        self.record("list", &args.path, &result, |obs| {
+            Response::List(obs.iter().map(|o| o.metadata_ref().into()).collect())
+        });
+
+        Ok(Box::new(futures::stream::iter(result?.into_iter().map(Ok))))
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        let result = self.inner.bucket_exists().await;
+        self.record("bucket_exists", "/", &result, |v| {
+            Response::BucketExists(*v)
+        });
+        result
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        let result = match self.inner.select(args).await {
+            Ok(mut r) => {
+                let mut buf = Vec::new();
+                r.read_to_end(&mut buf)
+                    .await
+                    .map(|_| buf)
+                    .map_err(|e| Error::Object {
+                        kind: Kind::Unexpected,
+                        op: "select",
+                        path: args.path.clone(),
+                        source: anyhow::Error::from(e),
+                    })
+            }
+            Err(e) => Err(e),
+        };
+        self.record("select", &args.path, &result, |buf| {
+            Response::Select(encode_hex(buf))
+        });
+
+        Ok(Box::new(futures::io::Cursor::new(result?)))
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        let result = self.inner.presign(args).await;
+        self.record("presign", &args.path, &result, |v| v.into());
+        result
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        let result = self.inner.list_multipart_uploads(args).await;
+        self.record("list_multipart_uploads", &args.prefix, &result, |v| {
+            Response::ListMultipartUploads(v.iter().map(RecordedUpload::from).collect())
+        });
+        result
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        let result = self.inner.abort_multipart_upload(args).await;
+        self.record("abort_multipart_upload", &args.path, &result, |_| {
+            Response::AbortMultipartUpload
+        });
+        result
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        let result = self.inner.retention(args).await;
+        self.record("retention", &args.path, &result, |v| v.into());
+        result
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        let result = self.inner.copy(args).await;
+        self.record("copy", &args.from, &result, |_| Response::Copy);
+        result
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        let result = self.inner.create(args).await;
+        self.record("create", &args.path, &result, |_| Response::Create);
+        result
+    }
+}
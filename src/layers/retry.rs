@@ -0,0 +1,352 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::AsyncReadExt;
+use log::warn;
+use metrics::increment_counter;
+
+use super::Backoff;
+use crate::clock::Clock;
+use crate::clock::TokioClock;
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::Operation;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Layer;
+use crate::Metadata;
+use crate::ObjectReader;
+
+/// RetryLayer retries operations failed with [`Kind::Temporary`] or
+/// [`Kind::Unexpected`].
+///
+/// - Only idempotent operations will be retried, see
+///   [`Operation::is_idempotent`]. `Append` will never be retried, since
+///   replaying a succeeded append duplicates the data.
+/// - Retries could be turned off per operation by [`RetryLayer::retry`].
+/// - `Write` is a full object upload, so it's safe to replay. The data has
+///   to be buffered in memory for that, writes larger than
+///   `max_write_buffer` will not be retried.
+/// - Conditional writes, like `if_not_exists`, will not be retried: the
+///   failed attempt may have succeeded on the server, and replaying it
+///   fails with [`Kind::PreconditionFailed`].
+/// - A source shorter than the declared size fails with
+///   [`Kind::ContentLengthMismatch`] before anything is written, it's
+///   never retried.
+/// - Only the request that opens a reader or a list is retried, errors
+///   returned while consuming them will be returned as is.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anyhow::Result;
+/// use opendal::layers::Backoff;
+/// use opendal::layers::RetryLayer;
+/// use opendal::ops::Operation;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let retry = RetryLayer::new()
+///         .max_retries(5)
+///         .backoff(Backoff::new().base(Duration::from_millis(50)))
+///         .retry(Operation::Write, false);
+///     let op = Operator::new(memory::Backend::build().finish().await?).layer(retry);
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryLayer {
+    max_retries: usize,
+    backoff: Backoff,
+    max_write_buffer: u64,
+    operations: HashSet<Operation>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for RetryLayer {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff: Backoff::constant(Duration::from_millis(100)),
+            max_write_buffer: 8 * 1024 * 1024,
+            operations: HashSet::from([
+                Operation::BucketExists,
+                Operation::Read,
+                Operation::Write,
+                Operation::Stat,
+                Operation::Delete,
+                Operation::List,
+                Operation::Select,
+                Operation::Presign,
+                Operation::ListMultipartUploads,
+                Operation::AbortMultipartUpload,
+                Operation::Retention,
+                Operation::Copy,
+                Operation::Create,
+            ]),
+            clock: Arc::new(TokioClock),
+        }
+    }
+}
+
+impl RetryLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Max retries of every operation, default to 3.
+    #[must_use]
+    pub fn max_retries(mut self, n: usize) -> Self {
+        self.max_retries = n;
+        self
+    }
+
+    /// Wait `delay` between two attempts, default to 100ms.
+    ///
+    /// This is a shortcut of [`Backoff::constant`], use
+    /// [`RetryLayer::backoff`] for exponential delays.
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.backoff = Backoff::constant(delay);
+        self
+    }
+
+    /// Compute the delays between attempts by `backoff`.
+    #[must_use]
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Writes larger than this will not be retried, default to 8 MiB.
+    #[must_use]
+    pub fn max_write_buffer(mut self, size: u64) -> Self {
+        self.max_write_buffer = size;
+        self
+    }
+
+    /// Enable or disable retries of `op`, all idempotent operations are
+    /// enabled by default.
+    ///
+    /// Enabling a non-idempotent operation takes no effect.
+    #[must_use]
+    pub fn retry(mut self, op: Operation, enabled: bool) -> Self {
+        if enabled && op.is_idempotent() {
+            self.operations.insert(op);
+        } else {
+            self.operations.remove(&op);
+        }
+        self
+    }
+
+    /// Wait between attempts on `clock` instead of tokio's time.
+    #[cfg(any(test, feature = "testing"))]
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+impl Layer for RetryLayer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(RetryAccessor {
+            inner,
+            layer: self.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct RetryAccessor {
+    inner: Arc<dyn Accessor>,
+    layer: RetryLayer,
+}
+
+impl RetryAccessor {
+    fn enabled(&self, op: Operation) -> bool {
+        self.layer.max_retries > 0 && self.layer.operations.contains(&op)
+    }
+
+    /// Call `f` until it succeeds, fails with an error that can't be
+    /// retried or runs out of retries.
+    async fn retry<T, F, Fut>(&self, op: Operation, path: &str, f: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if !self.enabled(op) {
+            return f().await;
+        }
+
+        let mut attempt = 0;
+        let mut delays = self.layer.backoff.iter();
+        loop {
+            match f().await {
+                Err(e)
+                    if attempt < self.layer.max_retries
+                        && matches!(e.kind(), Kind::Temporary | Kind::Unexpected) =>
+                {
+                    attempt += 1;
+                    warn!(
+                        "operation {:?} on {} failed, retry {}/{}: {:?}",
+                        op, path, attempt, self.layer.max_retries, e
+                    );
+                    increment_counter!("opendal_retry_attempts");
+                    self.layer.clock.sleep(delays.next_delay()).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Accessor for RetryAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        self.retry(Operation::Read, &args.path, || self.inner.read(args))
+            .await
+    }
+    async fn write(&self, mut r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        if !self.enabled(Operation::Write)
+            || args.size > self.layer.max_write_buffer
+            || args.has_precondition()
+        {
+            return self.inner.write(r, args).await;
+        }
+
+        // Buffer the data so that it could be replayed.
+        let mut buf = Vec::with_capacity(args.size as usize);
+        (&mut r)
+            .take(args.size)
+            .read_to_end(&mut buf)
+            .await
+            .map_err(|e| Error::Object {
+                kind: Kind::Unexpected,
+                op: "write",
+                path: args.path.clone(),
+                source: anyhow::Error::from(e),
+            })?;
+        if buf.len() as u64 != args.size {
+            return Err(Error::Object {
+                kind: Kind::ContentLengthMismatch,
+                op: "write",
+                path: args.path.clone(),
+                source: anyhow!(
+                    "source ended early: expected {} bytes, got {}",
+                    args.size,
+                    buf.len()
+                ),
+            });
+        }
+        let data = Bytes::from(buf);
+
+        self.retry(Operation::Write, &args.path, || {
+            let r: BoxedAsyncReader = Box::new(futures::io::Cursor::new(data.clone()));
+            self.inner.write(r, args)
+        })
+        .await
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        self.inner.append(r, args).await
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.retry(Operation::Stat, &args.path, || self.inner.stat(args))
+            .await
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        self.retry(Operation::Delete, &args.path, || self.inner.delete(args))
+            .await
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        self.retry(Operation::List, &args.path, || self.inner.list(args))
+            .await
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.retry(Operation::BucketExists, "/", || self.inner.bucket_exists())
+            .await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.retry(Operation::Select, &args.path, || self.inner.select(args))
+            .await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        self.retry(Operation::Presign, &args.path, || self.inner.presign(args))
+            .await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.retry(Operation::ListMultipartUploads, &args.prefix, || {
+            self.inner.list_multipart_uploads(args)
+        })
+        .await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.retry(Operation::AbortMultipartUpload, &args.path, || {
+            self.inner.abort_multipart_upload(args)
+        })
+        .await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.retry(Operation::Retention, &args.path, || {
+            self.inner.retention(args)
+        })
+        .await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        self.retry(Operation::Copy, &args.to, || self.inner.copy(args))
+            .await
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        self.retry(Operation::Create, &args.path, || self.inner.create(args))
+            .await
+    }
+}
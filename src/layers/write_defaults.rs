@@ -0,0 +1,220 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Layer;
+use crate::Metadata;
+use crate::ObjectReader;
+
+type ContentTypeFn = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// WriteDefaultsLayer fills the metadata of all writes that don't set it,
+/// values set by [`Writer`][crate::Writer] always take precedence.
+///
+/// Backends that can't store a field will ignore it as usual.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::WriteDefaultsLayer;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let op = Operator::new(memory::Backend::build().finish().await?).layer(
+///         WriteDefaultsLayer::new()
+///             .cache_control("max-age=3600")
+///             .content_type_fn(|path| match path.rsplit_once('.') {
+///                 Some((_, "json")) => Some("application/json".to_string()),
+///                 Some((_, "csv")) => Some("text/csv".to_string()),
+///                 _ => None,
+///             }),
+///     );
+///
+///     // Written with `Content-Type: application/json`.
+///     op.object("test.json").writer().write_bytes(b"{}".to_vec()).await?;
+///     // Written with `Content-Type: text/plain` instead.
+///     op.object("test.json")
+///         .writer()
+///         .content_type("text/plain")
+///         .write_bytes(b"{}".to_vec())
+///         .await?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct WriteDefaultsLayer {
+    content_type: Option<ContentTypeFn>,
+    content_language: Option<String>,
+    cache_control: Option<String>,
+}
+
+impl Debug for WriteDefaultsLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WriteDefaultsLayer")
+            .field("content_type", &self.content_type.as_ref().map(|_| "<fn>"))
+            .field("content_language", &self.content_language)
+            .field("cache_control", &self.cache_control)
+            .finish()
+    }
+}
+
+impl WriteDefaultsLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the default `Content-Type` of all objects.
+    #[must_use]
+    pub fn content_type(self, v: &str) -> Self {
+        let v = v.to_string();
+        self.content_type_fn(move |_| Some(v.clone()))
+    }
+
+    /// Decide the default `Content-Type` by the object's path, like by its
+    /// extension. Returning `None` leaves it unset.
+    #[must_use]
+    pub fn content_type_fn(
+        mut self,
+        f: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.content_type = Some(Arc::new(f));
+        self
+    }
+
+    /// Set the default `Content-Language` of all objects.
+    #[must_use]
+    pub fn content_language(mut self, v: &str) -> Self {
+        self.content_language = Some(v.to_string());
+        self
+    }
+
+    /// Set the default `Cache-Control` of all objects.
+    #[must_use]
+    pub fn cache_control(mut self, v: &str) -> Self {
+        self.cache_control = Some(v.to_string());
+        self
+    }
+
+    /// Fill the fields that are not set by `op`.
+    fn apply(&self, op: &mut OpWrite) {
+        if op.options.content_type.is_none() {
+            op.options.content_type = self.content_type.as_ref().and_then(|f| f(&op.path));
+        }
+        if op.options.content_language.is_none() {
+            op.options.content_language = self.content_language.clone();
+        }
+        if op.options.cache_control.is_none() {
+            op.options.cache_control = self.cache_control.clone();
+        }
+    }
+}
+
+impl Layer for WriteDefaultsLayer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(WriteDefaultsAccessor {
+            inner,
+            layer: self.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct WriteDefaultsAccessor {
+    inner: Arc<dyn Accessor>,
+    layer: WriteDefaultsLayer,
+}
+
+#[async_trait]
+impl Accessor for WriteDefaultsAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        self.inner.read(args).await
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        let mut op = args.clone();
+        self.layer.apply(&mut op);
+        self.inner.write(r, &op).await
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        self.inner.append(r, args).await
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.inner.stat(args).await
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        self.inner.delete(args).await
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        self.inner.list(args).await
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.inner.bucket_exists().await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.inner.select(args).await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        self.inner.presign(args).await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.inner.list_multipart_uploads(args).await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.inner.abort_multipart_upload(args).await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.inner.retention(args).await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        self.inner.copy(args).await
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        self.inner.create(args).await
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignOperation;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Layer;
+use crate::Metadata;
+use crate::ObjectMode;
+use crate::ObjectReader;
+
+/// WriteOnceLayer protects objects matching the given glob patterns from
+/// being changed once written.
+///
+/// - Writes to an existing protected object fail with
+///   [`Kind::PreconditionFailed`].
+/// - Deletes, appends and presigned writes of protected objects fail with
+///   [`Kind::ObjectPermissionDenied`].
+/// - Objects not matching any pattern are not affected.
+///
+/// Patterns are matched against the whole path without the leading `/`:
+/// `*` matches any characters except `/`, `**` matches any characters and
+/// `?` matches one character except `/`. So `audit/**` protects all objects
+/// under `audit/`.
+///
+/// # Race
+///
+/// If the backend supports [`AccessorMetadata::can_write_if_not_exists`]
+/// (like fs and memory), the check of writes and file creations is done by
+/// the backend atomically. Otherwise (like s3), a `stat` will be sent
+/// before them, and objects created by others in between will be
+/// overwritten.
+///
+/// No backend supports conditional server-side copy yet, so copies to
+/// protected paths are always checked by a `stat` and have the same race.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::WriteOnceLayer;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let op = Operator::new(memory::Backend::build().finish().await?)
+///         .layer(WriteOnceLayer::new(["audit/**", "*.lock"]));
+///
+///     // Writes are checked atomically by the backend.
+///     assert!(op.metadata().can_write_if_not_exists());
+///
+///     op.object("audit/1.log").writer().write_bytes(vec![0; 4]).await?;
+///     assert!(op.object("audit/1.log").delete().await.is_err());
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WriteOnceLayer {
+    patterns: Arc<Vec<Vec<char>>>,
+}
+
+impl WriteOnceLayer {
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            patterns: Arc::new(
+                patterns
+                    .into_iter()
+                    .map(|v| v.as_ref().trim_start_matches('/').chars().collect())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Check if the path is protected by any pattern.
+    pub fn is_protected(&self, path: &str) -> bool {
+        let path: Vec<char> = path.trim_start_matches('/').chars().collect();
+        self.patterns.iter().any(|p| glob_match(p, &path))
+    }
+}
+
+impl Layer for WriteOnceLayer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(WriteOnceAccessor {
+            inner,
+            layer: self.clone(),
+        })
+    }
+}
+
+fn glob_match(p: &[char], s: &[char]) -> bool {
+    match p {
+        [] => s.is_empty(),
+        ['*', '*', rest @ ..] => (0..=s.len()).any(|i| glob_match(rest, &s[i..])),
+        ['*', rest @ ..] => {
+            for i in 0..=s.len() {
+                if glob_match(rest, &s[i..]) {
+                    return true;
+                }
+                if i < s.len() && s[i] == '/' {
+                    return false;
+                }
+            }
+            false
+        }
+        ['?', rest @ ..] => matches!(s, [c, ..] if *c != '/') && glob_match(rest, &s[1..]),
+        [c, rest @ ..] => s.first() == Some(c) && glob_match(rest, &s[1..]),
+    }
+}
+
+#[derive(Debug)]
+struct WriteOnceAccessor {
+    inner: Arc<dyn Accessor>,
+    layer: WriteOnceLayer,
+}
+
+impl WriteOnceAccessor {
+    fn denied(op: &'static str, path: &str) -> Error {
+        Error::Object {
+            kind: Kind::ObjectPermissionDenied,
+            op,
+            path: path.to_string(),
+            source: anyhow!("object is write-once"),
+        }
+    }
+}
+
+#[async_trait]
+impl Accessor for WriteOnceAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        self.inner.read(args).await
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        if !self.layer.is_protected(&args.path) {
+            return self.inner.write(r, args).await;
+        }
+
+        if self.inner.metadata().can_write_if_not_exists() {
+            let mut op = args.clone();
+            op.options.if_not_exists = true;
+            return self.inner.write(r, &op).await;
+        }
+
+        // Objects created between the stat and the write will be
+        // overwritten.
+        match self.inner.stat(&OpStat::new(&args.path)).await {
+            Ok(_) => Err(Error::Object {
+                kind: Kind::PreconditionFailed,
+                op: "write",
+                path: args.path.clone(),
+                source: anyhow!("object is write-once and already exists"),
+            }),
+            Err(e) if e.kind() == Kind::ObjectNotExist => self.inner.write(r, args).await,
+            Err(e) => Err(e),
+        }
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        if self.layer.is_protected(&args.path) {
+            return Err(WriteOnceAccessor::denied("append", &args.path));
+        }
+        self.inner.append(r, args).await
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.inner.stat(args).await
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        if self.layer.is_protected(&args.path) {
+            return Err(WriteOnceAccessor::denied("delete", &args.path));
+        }
+        self.inner.delete(args).await
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        self.inner.list(args).await
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.inner.bucket_exists().await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.inner.select(args).await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        // Presigned writes and deletes bypass the check.
+        let modify = matches!(args.op, PresignOperation::Write | PresignOperation::Delete);
+        if modify && self.layer.is_protected(&args.path) {
+            return Err(WriteOnceAccessor::denied("presign", &args.path));
+        }
+        self.inner.presign(args).await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.inner.list_multipart_uploads(args).await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.inner.abort_multipart_upload(args).await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.inner.retention(args).await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        if !self.layer.is_protected(&args.to) {
+            return self.inner.copy(args).await;
+        }
+
+        // Objects created between the stat and the copy will be
+        // overwritten.
+        match self.inner.stat(&OpStat::new(&args.to)).await {
+            Ok(_) => Err(Error::Object {
+                kind: Kind::PreconditionFailed,
+                op: "copy",
+                path: args.to.clone(),
+                source: anyhow!("object is write-once and already exists"),
+            }),
+            Err(e) if e.kind() == Kind::ObjectNotExist => self.inner.copy(args).await,
+            Err(e) => Err(e),
+        }
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        // Dirs carry no data, creating them again changes nothing.
+        if args.mode == ObjectMode::DIR || !self.layer.is_protected(&args.path) {
+            return self.inner.create(args).await;
+        }
+
+        // Creating a file writes an empty object, check it along with the
+        // write like `write` does.
+        if self.inner.metadata().can_write_if_not_exists() {
+            let mut op = OpWrite::new(&args.path, 0);
+            op.options.if_not_exists = true;
+            let r = Box::new(futures::io::Cursor::new(Vec::new()));
+            self.inner.write(r, &op).await?;
+            return Ok(());
+        }
+
+        // Objects created between the stat and the create will be
+        // overwritten.
+        match self.inner.stat(&OpStat::new(&args.path)).await {
+            Ok(_) => Err(Error::Object {
+                kind: Kind::PreconditionFailed,
+                op: "create",
+                path: args.path.clone(),
+                source: anyhow!("object is write-once and already exists"),
+            }),
+            Err(e) if e.kind() == Kind::ObjectNotExist => self.inner.create(args).await,
+            Err(e) => Err(e),
+        }
+    }
+}
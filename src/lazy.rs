@@ -0,0 +1,146 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::lock::Mutex;
+use once_cell::sync::OnceCell;
+
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::Accessor;
+use crate::AccessorBuilder;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Metadata;
+use crate::ObjectReader;
+
+/// LazyAccessor holds the builder and constructs the accessor on the first
+/// operation.
+///
+/// If the construction failed, the error will be returned and the next
+/// operation will try again.
+pub(crate) struct LazyAccessor {
+    builder: Mutex<Box<dyn AccessorBuilder>>,
+    accessor: OnceCell<Arc<dyn Accessor>>,
+}
+
+impl LazyAccessor {
+    pub(crate) fn new(builder: impl AccessorBuilder) -> Self {
+        Self {
+            builder: Mutex::new(Box::new(builder)),
+            accessor: OnceCell::new(),
+        }
+    }
+
+    async fn get(&self) -> Result<Arc<dyn Accessor>> {
+        if let Some(acc) = self.accessor.get() {
+            return Ok(acc.clone());
+        }
+
+        let mut builder = self.builder.lock().await;
+        // Another operation could have finished the construction while we
+        // are waiting for the lock.
+        if let Some(acc) = self.accessor.get() {
+            return Ok(acc.clone());
+        }
+
+        let acc = builder.finish().await?;
+        Ok(self.accessor.get_or_init(|| acc).clone())
+    }
+}
+
+impl Debug for LazyAccessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyAccessor")
+            .field("accessor", &self.accessor.get())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Accessor for LazyAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        self.get().await?.read(args).await
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        self.get().await?.write(r, args).await
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        self.get().await?.append(r, args).await
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.get().await?.stat(args).await
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        self.get().await?.delete(args).await
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        self.get().await?.list(args).await
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.get().await?.bucket_exists().await
+    }
+    /// Metadata is unknown before the accessor constructed, the default
+    /// one will be returned in that case.
+    fn metadata(&self) -> AccessorMetadata {
+        self.accessor
+            .get()
+            .map(|acc| acc.metadata())
+            .unwrap_or_default()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.get().await?.select(args).await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        self.get().await?.presign(args).await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.get().await?.list_multipart_uploads(args).await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.get().await?.abort_multipart_upload(args).await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.get().await?.retention(args).await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        self.get().await?.copy(args).await
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        self.get().await?.create(args).await
+    }
+}
@@ -66,11 +66,24 @@
 //! ```
 mod accessor;
 pub use accessor::Accessor;
+pub use accessor::AccessorBuilder;
+pub use accessor::AccessorMetadata;
+pub use accessor::ReadConsistency;
 
 mod io;
 pub use io::BoxedAsyncReader;
+pub use io::ObjectReader;
 pub use io::Reader;
 pub use io::Writer;
+pub use io::Written;
+
+mod clock;
+#[cfg(any(test, feature = "testing"))]
+pub use clock::Clock;
+#[cfg(any(test, feature = "testing"))]
+pub use clock::TokioClock;
+
+mod lazy;
 
 mod layer;
 pub use layer::Layer;
@@ -78,21 +91,49 @@ pub use layer::Layer;
 mod operator;
 pub use operator::Operator;
 
+mod observe;
+pub use observe::Observations;
+pub use observe::Sample;
+
+mod path;
+
+mod provenance;
+pub use provenance::Provenance;
+
 mod object;
+pub use object::ErrorPolicy;
+pub use object::MetaField;
 pub use object::Metadata;
 pub use object::Object;
 pub use object::ObjectMode;
 pub use object::ObjectStream;
+pub use object::RecursiveSummary;
+pub use object::WriteResult;
 
 mod scheme;
+pub use scheme::register_scheme;
 pub use scheme::Scheme;
+pub use scheme::SchemeFactory;
+
+mod stats;
+pub use stats::OperatorStats;
+
+mod transfer;
 
 pub mod credential;
 pub mod error;
+#[cfg(any(test, feature = "fmt"))]
+pub mod fmt;
+pub mod health;
+pub mod layers;
 pub mod readers;
+pub mod writers;
 
 pub mod ops;
 pub mod services;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod tmp;
 
 #[cfg(test)]
 pub mod tests;
@@ -15,20 +15,58 @@ use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::future::Future;
+use std::path::Path;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+use std::time::SystemTime;
 
+use anyhow::anyhow;
 use futures::future::BoxFuture;
 use futures::ready;
+use futures::stream::FuturesUnordered;
+use futures::AsyncReadExt;
+use futures::StreamExt;
+use log::warn;
+use serde::Deserialize;
+use serde::Serialize;
 
+use crate::error::Error;
 use crate::error::Kind;
 use crate::error::Result;
+use crate::observe::Observer;
+use crate::ops::DeleteMode;
+use crate::ops::ListMode;
+use crate::ops::ListOptions;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
 use crate::ops::OpDelete;
 use crate::ops::OpList;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
 use crate::ops::OpStat;
+use crate::ops::PresignOperation;
+use crate::ops::PresignedRequest;
+use crate::ops::ReadOptions;
+use crate::ops::Retention;
+use crate::ops::SelectInput;
+use crate::ops::SelectOutput;
+use crate::ops::WriteOptions;
+use crate::readers::CompressAlgorithm;
+use crate::readers::DecompressReader;
+use crate::readers::ReadEvent;
+use crate::services::fs::error::parse_io_error;
+use crate::transfer;
+use crate::writers::JsonLinesWriter;
+use crate::writers::SpooledWriter;
+use crate::writers::WriteThenName;
 use crate::Accessor;
+use crate::BoxedAsyncReader;
+use crate::ObjectReader;
 use crate::Reader;
 use crate::Writer;
 
@@ -37,6 +75,11 @@ use crate::Writer;
 pub struct Object {
     acc: Arc<dyn Accessor>,
     meta: Metadata,
+    /// Metadata of the version that reads are pinned to, set by snapshot
+    /// listing.
+    pinned: Option<Metadata>,
+    /// Observations of the operator which creates this object.
+    observer: Option<Arc<Observer>>,
 }
 
 impl Object {
@@ -48,9 +91,39 @@ impl Object {
                 path: path.to_string(),
                 ..Default::default()
             },
+            pinned: None,
+            observer: None,
         }
     }
 
+    /// Use observations of `observer` to drive adaptive behaviors.
+    pub(crate) fn with_observer(mut self, observer: Arc<Observer>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Pin all following reads to the version in current metadata.
+    ///
+    /// Metadata will still be refreshed from the latest version, only reads
+    /// are pinned.
+    pub(crate) fn pin_version(&mut self) {
+        if self.meta.version_id().is_some() {
+            self.pinned = Some(self.meta.clone());
+        }
+    }
+
+    /// Returns the version id that reads are pinned to, see
+    /// [`ObjectStream::snapshot`].
+    pub fn pinned_version(&self) -> Option<&str> {
+        self.pinned.as_ref().and_then(|m| m.version_id())
+    }
+
+    /// Returns the path of this object, normalized if it's created by
+    /// [`Operator::try_object`][crate::Operator::try_object].
+    pub fn path(&self) -> &str {
+        self.meta.path()
+    }
+
     /// Create a new reader which can read the whole object.
     ///
     /// # Example
@@ -76,15 +149,180 @@ impl Object {
     /// }
     /// ```
     pub fn reader(&self) -> Reader {
-        Reader::new(self.acc.clone(), self.meta.path(), None, None)
+        self.reader_with(ReadOptions::new())
+    }
+
+    /// Create a new reader with given [`ReadOptions`].
+    ///
+    /// Other readers like [`Object::range_reader`] are shortcuts of it.
+    ///
+    /// An explicit `version_id` takes precedence over the version pinned by
+    /// [`ObjectStream::snapshot`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use futures::AsyncReadExt;
+    /// use opendal::ops::ReadOptions;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     let bs = "Hello, World!".as_bytes().to_vec();
+    ///     op.object("test").writer().write_bytes(bs).await?;
+    ///
+    ///     // Read within [7, 12) bytes.
+    ///     let mut r = op
+    ///         .object("test")
+    ///         .reader_with(ReadOptions::new().offset(7).size(5));
+    ///     let mut buf = String::new();
+    ///     r.read_to_string(&mut buf).await?;
+    ///     assert_eq!(buf, "World");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn reader_with(&self, opts: ReadOptions) -> Reader {
+        let mut r = Reader::new(self.acc.clone(), self.meta.path(), opts.offset, opts.size)
+            .with_trailing_slash_allowed(opts.allow_trailing_slash)
+            .with_content_decoded(opts.decode_content);
+        if let Some(size) = opts.buffer {
+            r = r.buffer(size);
+        }
+        if let Some(kind) = opts.digest {
+            r = r.with_digest(kind);
+        }
+        match (&opts.version_id, &self.pinned) {
+            (Some(version_id), _) => r.with_version_id(version_id),
+            (None, Some(meta)) => r.with_version(meta),
+            (None, None) => r,
+        }
+    }
+
+    /// Read the whole object into memory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(b"Hello".to_vec()).await?;
+    ///
+    ///     assert_eq!(op.object("test").read().await?, b"Hello");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn read(&self) -> Result<Vec<u8>> {
+        self.read_with(ReadOptions::new()).await
+    }
+
+    /// Read the object with given [`ReadOptions`] into memory, see
+    /// [`Object::reader_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::ops::ReadOptions;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(b"Hello, World!".to_vec()).await?;
+    ///
+    ///     let bs = op
+    ///         .object("test")
+    ///         .read_with(ReadOptions::new().offset(7).size(5))
+    ///         .await?;
+    ///     assert_eq!(bs, b"World");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn read_with(&self, opts: ReadOptions) -> Result<Vec<u8>> {
+        let mut bs = Vec::with_capacity(opts.size.unwrap_or_default() as usize);
+        self.reader_with(opts)
+            .read_to_end(&mut bs)
+            .await
+            .map_err(|e| parse_io_error(e, "read", self.meta.path()))?;
+        Ok(bs)
+    }
+
+    /// Send the read request immediately and cache the metadata learned
+    /// while reading.
+    ///
+    /// Errors like [`Kind::ObjectNotExist`] will be returned here instead of
+    /// the first read. Most backends return metadata along with the data, so
+    /// the following `metadata_cached()` could be free.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use futures::io;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     let bs = "Hello, World!".as_bytes().to_vec();
+    ///     op.object("test").writer().write_bytes(bs).await?;
+    ///
+    ///     let mut o = op.object("test");
+    ///     let mut r = o.reader_checked().await?;
+    ///     io::copy(&mut r, &mut io::sink()).await?;
+    ///     // No more requests will be sent.
+    ///     assert_eq!(o.metadata_cached().await?.content_length(), 13);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn reader_checked(&mut self) -> Result<BoxedAsyncReader> {
+        let op = &OpRead {
+            path: self.meta.path().to_string(),
+            offset: None,
+            size: None,
+            version_id: self.pinned_version().map(|v| v.to_string()),
+        };
+        self.check_not_root("read")?;
+        if op.path.ends_with('/') {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op: "read",
+                path: op.path.clone(),
+                source: anyhow!("read on a dir is not allowed, use reader_with instead"),
+            });
+        }
+
+        let (r, meta) = self.acc.read(op).await?.into_parts();
+        if meta.is_fully_loaded() && self.pinned.is_none() {
+            self.meta = meta;
+        }
+
+        Ok(r)
     }
 
     /// Create a new ranged reader which can only read data between [offset, offset+size).
     ///
     /// # Note
     ///
-    /// The input offset and size are not checked, callers could meet error
-    /// while reading.
+    /// Ranges over-reading EOF will be truncated, use [`Reader::available`]
+    /// to get the real size. Ranges starting at or beyond EOF will read
+    /// nothing, use [`Reader::strict_range`] to get an error instead.
     ///
     /// # Example
     ///
@@ -109,14 +347,15 @@ impl Object {
     /// }
     /// ```
     pub fn range_reader(&self, offset: u64, size: u64) -> Reader {
-        Reader::new(self.acc.clone(), self.meta.path(), Some(offset), Some(size))
+        self.reader_with(ReadOptions::new().offset(offset).size(size))
     }
 
     /// Create a new offset reader which can read data since offset.
     ///
     /// # Note
     ///
-    /// The input offset is not checked, callers could meet error while reading.
+    /// Offsets at or beyond EOF will read nothing, use [`Reader::strict_range`]
+    /// to get an error instead.
     ///
     /// # Example
     ///
@@ -141,7 +380,7 @@ impl Object {
     /// }
     /// ```
     pub fn offset_reader(&self, offset: u64) -> Reader {
-        Reader::new(self.acc.clone(), self.meta.path(), Some(offset), None)
+        self.reader_with(ReadOptions::new().offset(offset))
     }
 
     /// Create a new limited reader which can only read limited data.
@@ -173,17 +412,27 @@ impl Object {
     /// }
     /// ```
     pub fn limited_reader(&self, size: u64) -> Reader {
-        Reader::new(self.acc.clone(), self.meta.path(), None, Some(size))
+        self.reader_with(ReadOptions::new().size(size))
     }
 
-    /// Create a new writer which can write data into the object.
+    /// Create a new reader which decompresses the object by the extension
+    /// of its path, see [`CompressAlgorithm::from_path`].
+    ///
+    /// Objects with unknown extensions will be read as is.
+    ///
+    /// # Features
+    ///
+    /// Decompressing is enabled by features `compress-gzip` and
+    /// `compress-zstd`, objects of an algorithm without its feature will be
+    /// rejected with [`Kind::Unsupported`] instead of returning the
+    /// compressed bytes silently.
     ///
     /// # Example
     ///
     /// ```
     /// use opendal::services::memory;
     /// use anyhow::Result;
-    /// use futures::io;
+    /// use futures::AsyncReadExt;
     /// use opendal::Operator;
     ///
     /// #[tokio::main]
@@ -191,192 +440,1797 @@ impl Object {
     ///     let op = Operator::new(memory::Backend::build().finish().await?);
     ///
     ///     let bs = "Hello, World!".as_bytes().to_vec();
-    ///     op.object("test").writer().write_bytes(bs).await?;
+    ///     op.object("test.txt").writer().write_bytes(bs).await?;
+    ///
+    ///     let mut buf = Vec::new();
+    ///     op.object("test.txt").auto_reader()?.read_to_end(&mut buf).await?;
+    ///     assert_eq!(buf, b"Hello, World!");
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub fn writer(&self) -> Writer {
-        Writer::new(self.acc.clone(), self.meta.path())
+    pub fn auto_reader(&self) -> Result<BoxedAsyncReader> {
+        let algo = match CompressAlgorithm::from_path(self.meta.path()) {
+            None => return Ok(Box::new(self.reader())),
+            Some(algo) => algo,
+        };
+
+        match DecompressReader::new(Box::new(self.reader()), algo) {
+            Some(r) => Ok(Box::new(r)),
+            None => Err(Error::Object {
+                kind: Kind::Unsupported,
+                op: "read",
+                path: self.meta.path().to_string(),
+                source: anyhow!("decompressing {:?} needs feature {}", algo, algo.feature()),
+            }),
+        }
     }
 
-    /// Delete current object.
+    /// Sample `count` chunks of `chunk_size` bytes spread evenly across the
+    /// object, returns them concatenated.
+    ///
+    /// The first chunk starts at the beginning of the object and the last
+    /// one ends at the end, so only `count` small ranged reads are sent
+    /// instead of downloading the whole object. Objects not larger than
+    /// `count * chunk_size` will be read entirely instead.
     ///
     /// # Example
     ///
     /// ```
-    /// use opendal::services::memory;
     /// use anyhow::Result;
-    /// use futures::io;
+    /// use opendal::services::memory;
     /// use opendal::Operator;
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<()> {
     ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(b"0123456789".to_vec()).await?;
     ///
-    ///     let bs = "Hello, World!".as_bytes().to_vec();
-    ///     op.object("test").delete().await?;
+    ///     let bs = op.object("test").sample(3, 2).await?;
+    ///     assert_eq!(bs, b"014589");
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn delete(&self) -> Result<()> {
-        let op = &OpDelete::new(self.meta.path());
+    pub async fn sample(&self, count: usize, chunk_size: u64) -> Result<Vec<u8>> {
+        let meta = self.file_metadata("sample").await?;
 
-        self.acc.delete(op).await
+        let reads = sample_ranges(meta.content_length(), count, chunk_size)
+            .into_iter()
+            .map(|(offset, size)| async move {
+                let mut bs = Vec::with_capacity(size as usize);
+                self.range_reader(offset, size)
+                    .read_to_end(&mut bs)
+                    .await
+                    .map_err(|e| parse_io_error(e, "sample", self.meta.path()))?;
+                Ok::<_, Error>(bs)
+            });
+
+        Ok(futures::future::try_join_all(reads).await?.concat())
     }
 
-    /// Get current object's metadata.
+    /// Run a SQL `expression` on the object and read the matched rows.
+    ///
+    /// Only services support server side filtering (like s3 select) could
+    /// handle this operation, others will return an error with
+    /// [`Kind::Unsupported`].
+    ///
+    /// Rows are not a range of the object, so the returned reader can't
+    /// seek or resume from failures, but buffering and digests work as
+    /// usual.
     ///
     /// # Example
     ///
-    /// ```
-    /// use opendal::services::memory;
+    /// ```no_run
     /// use anyhow::Result;
-    /// use futures::io;
+    /// use futures::AsyncReadExt;
+    /// use opendal::ops::SelectInput;
+    /// use opendal::ops::SelectOutput;
     /// use opendal::Operator;
-    /// use opendal::error::Kind;
+    /// # use opendal::services::memory;
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<()> {
-    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    /// #   let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     let mut r = op
+    ///         .object("test.csv")
+    ///         .select(
+    ///             "SELECT * FROM S3Object s WHERE s.age > '18'",
+    ///             SelectInput::Csv { has_header: true },
+    ///             SelectOutput::Csv,
+    ///         )
+    ///         .await?;
     ///
-    ///     if let Err(e) =  op.object("test").metadata().await {
-    ///         if e.kind() == Kind::ObjectNotExist {
-    ///             println!("object not exist")
-    ///         }
-    ///     }
+    ///     let mut rows = String::new();
+    ///     r.read_to_string(&mut rows).await?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn metadata(&self) -> Result<Metadata> {
-        let op = &OpStat::new(self.meta.path());
+    pub async fn select(
+        &self,
+        expression: &str,
+        input: SelectInput,
+        output: SelectOutput,
+    ) -> Result<Reader> {
+        let op = &OpSelect::new(self.meta.path(), expression, input, output);
 
-        self.acc.stat(op).await
+        let r = self.acc.select(op).await?;
+        Reader::from_object_reader(self.acc.clone(), self.meta.path(), ObjectReader::new(r))
     }
 
-    /// Use local cached metadata if possible.
+    /// Generate a presigned request to read the object, which is valid for
+    /// `expire`.
     ///
     /// # Example
     ///
-    /// ```
-    /// use opendal::services::memory;
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
     /// use anyhow::Result;
-    /// use futures::io;
     /// use opendal::Operator;
-    /// use opendal::error::Kind;
+    /// # use opendal::services::memory;
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<()> {
-    ///     let op = Operator::new(memory::Backend::build().finish().await?);
-    ///     let mut o = op.object("test");
+    /// #   let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     let req = op
+    ///         .object("test")
+    ///         .presign_read(Duration::from_secs(3600))
+    ///         .await?;
+    ///     println!("{} {}", req.method(), req.uri());
     ///
-    ///     o.metadata_cached().await;
-    ///     // The second call to metadata_cached will have no cost.
-    ///     o.metadata_cached().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn presign_read(&self, expire: Duration) -> Result<PresignedRequest> {
+        self.presign_read_with(expire, None, None).await
+    }
+
+    /// Generate a presigned request to read the object, the response will
+    /// use the given `Content-Disposition` and `Content-Type` instead of
+    /// the stored ones.
+    ///
+    /// Backends that can't override response headers will return an error
+    /// with [`Kind::Unsupported`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use anyhow::Result;
+    /// use opendal::Operator;
+    /// # use opendal::services::memory;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    /// #   let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     let req = op
+    ///         .object("reports/2022.csv")
+    ///         .presign_read_with(
+    ///             Duration::from_secs(3600),
+    ///             Some("attachment; filename=\"report.csv\""),
+    ///             Some("text/csv"),
+    ///         )
+    ///         .await?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn metadata_cached(&mut self) -> Result<&Metadata> {
-        if self.meta.complete() {
-            return Ok(&self.meta);
-        }
+    pub async fn presign_read_with(
+        &self,
+        expire: Duration,
+        content_disposition: Option<&str>,
+        content_type: Option<&str>,
+    ) -> Result<PresignedRequest> {
+        let mut op = OpPresign::new(self.meta.path(), PresignOperation::Read, expire);
+        op.override_content_disposition = content_disposition.map(|v| v.to_string());
+        op.override_content_type = content_type.map(|v| v.to_string());
 
-        let op = &OpStat::new(self.meta.path());
-        self.meta = self.acc.stat(op).await?;
+        self.acc.presign(&op).await
+    }
 
-        Ok(&self.meta)
+    /// Generate a presigned request to write the object, which is valid for
+    /// `expire`.
+    pub async fn presign_write(&self, expire: Duration) -> Result<PresignedRequest> {
+        let op = OpPresign::new(self.meta.path(), PresignOperation::Write, expire);
+
+        self.acc.presign(&op).await
     }
 
-    pub(crate) fn metadata_mut(&mut self) -> &mut Metadata {
-        &mut self.meta
+    /// Generate a presigned request to fetch the metadata of the object,
+    /// which is valid for `expire`.
+    ///
+    /// Clients could check the existence of the object by the status of
+    /// the response without credentials, like `HEAD` on s3.
+    pub async fn presign_stat(&self, expire: Duration) -> Result<PresignedRequest> {
+        let op = OpPresign::new(self.meta.path(), PresignOperation::Stat, expire);
+
+        self.acc.presign(&op).await
     }
 
-    /// Check if this object exist or not.
+    /// Get the lock status of the object, including its retention and
+    /// legal hold.
+    ///
+    /// Only services support object lock (like s3) could handle this
+    /// operation, others will return an error with [`Kind::Unsupported`].
     ///
     /// # Example
     ///
+    /// ```no_run
+    /// use anyhow::Result;
+    /// use opendal::Operator;
+    /// # use opendal::services::memory;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    /// #   let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     let retention = op.object("report.csv").retention().await?;
+    ///     println!(
+    ///         "mode: {:?}, retain until: {:?}, legal hold: {}",
+    ///         retention.mode(),
+    ///         retention.retain_until(),
+    ///         retention.legal_hold()
+    ///     );
+    ///
+    ///     Ok(())
+    /// }
     /// ```
-    /// use opendal::services::memory;
+    pub async fn retention(&self) -> Result<Retention> {
+        self.check_not_root("retention")?;
+
+        self.acc
+            .retention(&OpRetention::new(self.meta.path()))
+            .await
+    }
+
+    /// Copy the object to `to` inside the backend, data will not be
+    /// transferred through the client. The object at `to` will be
+    /// overwritten if exists.
+    ///
+    /// Only services support server-side copy (like s3) could handle this
+    /// operation, others will return an error with [`Kind::Unsupported`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
     /// use anyhow::Result;
-    /// use futures::io;
     /// use opendal::Operator;
-    /// use opendal::error::Kind;
+    /// # use opendal::services::memory;
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<()> {
-    ///     let op = Operator::new(memory::Backend::build().finish().await?);
-    ///     let _ = op.object("test").is_exist().await?;
+    /// #   let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("report.csv").copy("backup/report.csv").await?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn is_exist(&self) -> Result<bool> {
-        let r = self.metadata().await;
-        match r {
-            Ok(_) => Ok(true),
-            Err(err) => match err.kind() {
-                Kind::ObjectNotExist => Ok(false),
-                _ => Err(err),
-            },
+    pub async fn copy(&self, to: &str) -> Result<()> {
+        for path in [self.meta.path(), to] {
+            if is_root(path) || path.ends_with('/') {
+                return Err(Error::Object {
+                    kind: Kind::Unsupported,
+                    op: "copy",
+                    path: path.to_string(),
+                    source: anyhow!("copy from or into a dir is not supported"),
+                });
+            }
         }
-    }
-}
 
-/// Metadata carries all object metadata.
-#[derive(Debug, Clone, Default)]
-pub struct Metadata {
-    complete: bool,
+        self.acc.copy(&OpCopy::new(self.meta.path(), to)).await
+    }
 
-    path: String,
-    mode: Option<ObjectMode>,
+    /// Copy current object to `to`, and all objects under it if it's a dir.
+    ///
+    /// A dir is copied into `to` which must be a dir too, so `dir/a/b`
+    /// will be copied to `{to}a/b`. Only files are copied, dirs are implied
+    /// by the paths of their files. Like [`Object::delete_recursive`], this
+    /// will not stop at the first failure, the result of every source path
+    /// is reported in the returned [`RecursiveSummary`].
+    ///
+    /// Like [`Object::copy`], only services support server-side copy could
+    /// handle this operation.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anyhow::Result;
+    /// use opendal::Operator;
+    /// # use opendal::services::memory;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    /// #   let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     let summary = op.object("dir/").copy_recursive("backup/dir/").await;
+    ///     for (path, err) in &summary.failed {
+    ///         println!("copy {} failed: {}", path, err);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn copy_recursive(&self, to: &str) -> RecursiveSummary {
+        let mut summary = RecursiveSummary::default();
+        let from = self.meta.path();
 
-    content_length: Option<u64>,
-}
+        if !is_root(from) && !from.ends_with('/') {
+            summary.record(from, self.copy(to).await);
+            return summary;
+        }
+        if !is_root(to) && !to.ends_with('/') {
+            let r = Err(Error::Object {
+                kind: Kind::Unsupported,
+                op: "copy",
+                path: to.to_string(),
+                source: anyhow!("copy a dir into a file is not supported"),
+            });
+            summary.record(from, r);
+            return summary;
+        }
+        let prefix = if is_root(from) { "" } else { from };
+        let to = if is_root(to) { "" } else { to };
 
-impl Metadata {
-    /// Returns object path that relative to corresponding backend's root.
-    pub fn path(&self) -> &str {
-        &self.path
-    }
+        let mut running: FuturesUnordered<BoxFuture<'static, (String, Result<()>)>> =
+            FuturesUnordered::new();
+        let mut stack = vec![(from.to_string(), ObjectStream::new(self.acc.clone(), from))];
+        while let Some((dir, obs)) = stack.last_mut() {
+            let entry = match obs.next().await {
+                Some(Ok(mut o)) => o
+                    .metadata_cached_for(&[MetaField::Mode])
+                    .await
+                    .map(|meta| Some((meta.path().to_string(), meta.mode()))),
+                Some(Err(e)) => Err(e),
+                None => Ok(None),
+            };
 
-    pub(crate) fn set_path(&mut self, path: &str) -> &mut Self {
-        self.path = path.to_string();
-        self
-    }
+            match entry {
+                // Some backends return the dir itself.
+                Ok(Some((p, _))) if &p == dir => {}
+                Ok(Some((p, ObjectMode::DIR))) => {
+                    // fs returns dirs without the trailing `/`.
+                    let p = if p.ends_with('/') {
+                        p
+                    } else {
+                        format!("{}/", p)
+                    };
+                    let obs = ObjectStream::new(self.acc.clone(), &p);
+                    stack.push((p, obs));
+                }
+                Ok(Some((p, _))) => {
+                    if running.len() >= COPY_CONCURRENCY {
+                        if let Some((p, r)) = running.next().await {
+                            summary.record(&p, r);
+                        }
+                    }
+                    let target = format!("{}{}", to, p.strip_prefix(prefix).unwrap_or(&p));
+                    let acc = self.acc.clone();
+                    running.push(Box::pin(async move {
+                        let r = acc.copy(&OpCopy::new(&p, &target)).await;
+                        (p, r)
+                    }));
+                }
+                Err(e) => {
+                    summary.record(dir, Err(e));
+                    stack.pop();
+                }
+                Ok(None) => {
+                    stack.pop();
+                }
+            }
+        }
+        while let Some((p, r)) = running.next().await {
+            summary.record(&p, r);
+        }
 
-    pub fn complete(&self) -> bool {
-        self.complete
+        summary
     }
 
-    pub(crate) fn set_complete(&mut self) -> &mut Self {
-        self.complete = true;
-        self
+    /// Create a new writer which can write data into the object.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use futures::io;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     let bs = "Hello, World!".as_bytes().to_vec();
+    ///     op.object("test").writer().write_bytes(bs).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn writer(&self) -> Writer {
+        Writer::new(self.acc.clone(), self.meta.path())
+    }
+
+    /// Create a new writer with given [`WriteOptions`].
+    ///
+    /// Options set on the returned writer later will override `opts`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use opendal::ops::WriteOptions;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     let opts = WriteOptions::new().if_not_exists(true);
+    ///     let bs = "Hello, World!".as_bytes().to_vec();
+    ///     op.object("test").writer_with(opts.clone()).write_bytes(bs).await?;
+    ///
+    ///     // The object exists now.
+    ///     let bs = "Hello, World!".as_bytes().to_vec();
+    ///     assert!(op.object("test").writer_with(opts).write_bytes(bs).await.is_err());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn writer_with(&self, opts: WriteOptions) -> Writer {
+        self.writer().with_options(opts)
+    }
+
+    /// Write `bs` into the object with given [`WriteOptions`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use opendal::error::Kind;
+    /// use opendal::ops::WriteOptions;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     let bs = "Hello, World!".as_bytes().to_vec();
+    ///     // memory can't store content type, reject it instead of ignoring.
+    ///     let opts = WriteOptions::new().content_type("text/plain").strict(true);
+    ///     let err = op.object("test").write_with(opts, bs).await.unwrap_err();
+    ///     assert_eq!(err.kind(), Kind::Unsupported);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn write_with(&self, opts: WriteOptions, bs: Vec<u8>) -> Result<usize> {
+        self.writer_with(opts).write_bytes(bs).await
+    }
+
+    /// Append `bs` to the end of current object, the object will be created
+    /// if not exist.
+    ///
+    /// Returns an error with [`Kind::Unsupported`] if the backend doesn't
+    /// support append.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     op.object("test").append(b"Hello, ".to_vec()).await?;
+    ///     op.object("test").append(b"World!".to_vec()).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn append(&self, bs: Vec<u8>) -> Result<usize> {
+        let op = &OpAppend::new(self.meta.path(), bs.len() as u64);
+        let r = Box::new(futures::io::Cursor::new(bs));
+
+        self.acc.append(r, op).await
+    }
+
+    /// Write `bs` only if it differs from the stored content, so that
+    /// no-op uploads won't waste bandwidth or touch last modified time.
+    ///
+    /// Content is compared by the md5 of `bs` and the etag of the object,
+    /// objects without a plain md5 etag (like s3 multipart uploads or fs)
+    /// and missing objects are always treated as changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    /// use opendal::WriteResult;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     let o = op.object("test");
+    ///
+    ///     let res = o.write_if_changed(b"Hello, World!".to_vec()).await?;
+    ///     assert_eq!(res, WriteResult::Written(13));
+    ///     let res = o.write_if_changed(b"Hello, World!".to_vec()).await?;
+    ///     assert_eq!(res, WriteResult::Unchanged);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn write_if_changed(&self, bs: Vec<u8>) -> Result<WriteResult> {
+        match self.metadata().await {
+            Ok(meta) => {
+                let local = format!("{:x}", md5::compute(&bs));
+                let unchanged = meta
+                    .etag()
+                    .map(|etag| etag.trim_matches('"').eq_ignore_ascii_case(&local))
+                    .unwrap_or_default();
+                if unchanged && meta.content_length() == bs.len() as u64 {
+                    return Ok(WriteResult::Unchanged);
+                }
+            }
+            Err(e) if e.kind() == Kind::ObjectNotExist => {}
+            Err(e) => return Err(e),
+        }
+
+        let n = self.writer().write_bytes(bs).await?;
+        Ok(WriteResult::Written(n))
+    }
+
+    /// Create a new [`JsonLinesWriter`] to write serialized records line by
+    /// line.
+    ///
+    /// Read [`JsonLinesWriter`] for more details.
+    pub fn json_lines_writer(&self) -> JsonLinesWriter {
+        JsonLinesWriter::new(self.acc.clone(), self.meta.path())
+    }
+
+    /// Create a new [`SpooledWriter`] to write data of unknown length.
+    ///
+    /// Read [`SpooledWriter`] for more details.
+    pub fn spooled_writer(&self) -> SpooledWriter {
+        SpooledWriter::new(self.acc.clone(), self.meta.path())
+    }
+
+    /// Create a new [`WriteThenName`] which uses current object as the
+    /// temporary object and names the final object by the data's digest.
+    ///
+    /// Read [`WriteThenName`] for more details.
+    pub fn write_then_name(&self) -> WriteThenName {
+        WriteThenName::new(self.acc.clone(), self.meta.path())
+    }
+
+    /// Download current object into the local file at `path`, returns the
+    /// bytes transferred.
+    ///
+    /// - Parent dirs of `path` will be created if not exist.
+    /// - Data will be written into a temp file and renamed to `path` after
+    ///   synced, so `path` will never contain partial data.
+    /// - Length and checksum will be verified against the metadata learned
+    ///   while reading if possible, [`Kind::ChecksumMismatch`] will be returned
+    ///   if they are diverged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![0; 16]).await?;
+    ///
+    ///     let n = op.object("test").fetch_to_path("/tmp/test").await?;
+    ///     assert_eq!(n, 16);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn fetch_to_path(&self, path: impl AsRef<Path>) -> Result<u64> {
+        self.fetch_to_path_with(path, |_| {}).await
+    }
+
+    /// Same as [`Object::fetch_to_path`], but `f` will be called with every
+    /// [`ReadEvent`] of the object reader, which could be used to report the
+    /// progress.
+    pub async fn fetch_to_path_with<F>(&self, path: impl AsRef<Path>, f: F) -> Result<u64>
+    where
+        F: FnMut(ReadEvent) + Send + Unpin,
+    {
+        transfer::fetch_to_path(
+            self.acc.clone(),
+            self.meta.path(),
+            self.pinned_version().map(|v| v.to_string()),
+            path.as_ref(),
+            f,
+        )
+        .await
+    }
+
+    /// Download current object into `w` by fetching ranges of `part_size`
+    /// concurrently, at most `concurrency` of them in flight. Returns the
+    /// bytes written.
+    ///
+    /// This could be much faster than a single reader for huge objects,
+    /// since one stream is usually limited by the bandwidth of a single
+    /// connection.
+    ///
+    /// - Parts are written into `w` in order, so at most
+    ///   `concurrency * part_size` bytes will be buffered in memory.
+    /// - All parts are read from the object learned by `stat` (or the pinned
+    ///   version), [`Kind::PreconditionFailed`] will be returned if it has
+    ///   changed in the middle.
+    /// - Every part resumes from where it failed for a few times, see
+    ///   [`Reader::resumable`].
+    /// - [`Kind::ContentLengthMismatch`] will be returned if any part or the
+    ///   total length is different from the object's length.
+    ///
+    /// Use [`Object::fetch_to_path_concurrent`] to download into a local
+    /// file, which writes parts at their offsets without buffering them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![1; 4096]).await?;
+    ///
+    ///     let mut buf = Vec::new();
+    ///     let n = op
+    ///         .object("test")
+    ///         .fetch_range_concurrent(&mut buf, 1024, 4)
+    ///         .await?;
+    ///     assert_eq!(n, 4096);
+    ///     assert_eq!(buf, vec![1; 4096]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn fetch_range_concurrent<W>(
+        &self,
+        mut w: W,
+        part_size: u64,
+        concurrency: usize,
+    ) -> Result<u64>
+    where
+        W: futures::AsyncWrite + Unpin,
+    {
+        let (parts, total) = self
+            .concurrent_parts("fetch_range_concurrent", part_size, concurrency)
+            .await?;
+        transfer::fetch_parts_to_writer(self.meta.path(), parts, total, concurrency, &mut w).await
+    }
+
+    /// Same as [`Object::fetch_range_concurrent`], but the number of parts
+    /// read ahead adapts between 1 and `max_concurrency`.
+    ///
+    /// The depth starts from 1 and is adjusted after every part is written
+    /// into `w`: it's the median latency of reads observed by the operator
+    /// (see [`Operator::observations`][crate::Operator::observations])
+    /// divided by the time `w` took to drain the part, so that fast
+    /// consumers fetch ahead while slow ones don't buffer parts they can't
+    /// drain in time.
+    ///
+    /// Objects not created by an operator, or with observations disabled,
+    /// read ahead `max_concurrency` parts like
+    /// [`Object::fetch_range_concurrent`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![1; 4096]).await?;
+    ///
+    ///     let mut buf = Vec::new();
+    ///     let n = op
+    ///         .object("test")
+    ///         .fetch_range_adaptive(&mut buf, 1024, 4)
+    ///         .await?;
+    ///     assert_eq!(n, 4096);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn fetch_range_adaptive<W>(
+        &self,
+        mut w: W,
+        part_size: u64,
+        max_concurrency: usize,
+    ) -> Result<u64>
+    where
+        W: futures::AsyncWrite + Unpin,
+    {
+        let (parts, total) = self
+            .concurrent_parts("fetch_range_adaptive", part_size, max_concurrency)
+            .await?;
+        let observer = self.observer.clone().filter(|v| v.is_enabled());
+        transfer::fetch_parts_to_writer_adaptive(
+            self.meta.path(),
+            parts,
+            total,
+            max_concurrency,
+            observer,
+            &mut w,
+        )
+        .await
+    }
+
+    /// Same as [`Object::fetch_range_concurrent`], but parts are written
+    /// into the local file at `path` at their offsets.
+    ///
+    /// Like [`Object::fetch_to_path`], data will be written into a temp file
+    /// and renamed to `path` after synced and verified.
+    pub async fn fetch_to_path_concurrent(
+        &self,
+        path: impl AsRef<Path>,
+        part_size: u64,
+        concurrency: usize,
+    ) -> Result<u64> {
+        let (parts, total) = self
+            .concurrent_parts("fetch_to_path_concurrent", part_size, concurrency)
+            .await?;
+        transfer::fetch_parts_to_path(self.meta.path(), parts, total, concurrency, path.as_ref())
+            .await
+    }
+
+    /// Fetch the metadata of the file to read, which is the pinned version
+    /// if any, dirs and the root will be rejected.
+    async fn file_metadata(&self, op: &'static str) -> Result<Metadata> {
+        self.check_not_root(op)?;
+
+        let meta = match &self.pinned {
+            Some(meta) => meta.clone(),
+            None => self.stat().await?,
+        };
+        if meta.mode() != ObjectMode::FILE {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op,
+                path: self.meta.path().to_string(),
+                source: anyhow!("{} on a dir is not allowed", op),
+            });
+        }
+
+        Ok(meta)
+    }
+
+    /// Split current object into parts of `part_size` for concurrent
+    /// fetching, returns them along with the total length.
+    async fn concurrent_parts(
+        &self,
+        op: &'static str,
+        part_size: u64,
+        concurrency: usize,
+    ) -> Result<(Vec<transfer::Part>, u64)> {
+        if part_size == 0 || concurrency == 0 {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op,
+                path: self.meta.path().to_string(),
+                source: anyhow!("part size and concurrency must be positive"),
+            });
+        }
+        let meta = self.file_metadata(op).await?;
+
+        let total = meta.content_length();
+        // Parts are pinned by etag, and also by version if possible.
+        let version_id = if self.pinned.is_none() && self.acc.metadata().can_read_version() {
+            meta.version_id()
+        } else {
+            None
+        };
+        let parts = transfer::split_parts(total, part_size)
+            .into_iter()
+            .map(|(offset, size)| {
+                let mut opts = ReadOptions::new().offset(offset).size(size);
+                if let Some(version_id) = version_id {
+                    opts = opts.version_id(version_id);
+                }
+                let mut reader = self.reader_with(opts).resumable(transfer::PART_RESUMES);
+                if let Some(etag) = meta.etag() {
+                    reader = reader.with_etag(etag);
+                }
+                transfer::Part {
+                    offset,
+                    size,
+                    reader,
+                }
+            })
+            .collect();
+
+        Ok((parts, total))
+    }
+
+    /// Upload the local file at `path` into current object, returns the
+    /// bytes transferred.
+    pub async fn upload_from_path(&self, path: impl AsRef<Path>) -> Result<u64> {
+        self.upload_from_path_with(path, |_| {}).await
+    }
+
+    /// Same as [`Object::upload_from_path`], but `f` will be called with
+    /// every [`ReadEvent`] of the local file reader, which could be used to
+    /// report the progress.
+    pub async fn upload_from_path_with<F>(&self, path: impl AsRef<Path>, f: F) -> Result<u64>
+    where
+        F: FnMut(ReadEvent) + Send + Unpin + 'static,
+    {
+        transfer::upload_from_path(self.writer(), self.meta.path(), path.as_ref(), f).await
+    }
+
+    /// Delete current object.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use futures::io;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     let bs = "Hello, World!".as_bytes().to_vec();
+    ///     op.object("test").delete().await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn delete(&self) -> Result<()> {
+        self.check_not_root("delete")?;
+        let op = &OpDelete::new(self.meta.path());
+
+        self.acc.delete(op).await
+    }
+
+    /// Delete current object only if its etag still equals to `etag`.
+    ///
+    /// Returns an error with [`Kind::PreconditionFailed`] and leaves the
+    /// object intact if the object has been changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     let o = op.object("test");
+    ///
+    ///     o.writer().write_bytes("Hello, World!".as_bytes().to_vec()).await?;
+    ///     let meta = o.metadata().await?;
+    ///     o.delete_if_match(meta.etag().expect("etag must exist")).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn delete_if_match(&self, etag: &str) -> Result<()> {
+        self.check_not_root("delete")?;
+        let mut op = OpDelete::new(self.meta.path());
+        op.if_match = Some(etag.to_string());
+
+        self.acc.delete(&op).await
+    }
+
+    /// Delete current object only if it has not been modified since `t`.
+    ///
+    /// Returns an error with [`Kind::PreconditionFailed`] and leaves the
+    /// object intact if the object has been modified after `t`.
+    pub async fn delete_if_unmodified_since(&self, t: SystemTime) -> Result<()> {
+        self.check_not_root("delete")?;
+        let mut op = OpDelete::new(self.meta.path());
+        op.if_unmodified_since = Some(t);
+
+        self.acc.delete(&op).await
+    }
+
+    /// Remove all versions of current object including delete markers, so
+    /// that the key is truly gone on versioned s3 buckets instead of being
+    /// hidden by a delete marker.
+    ///
+    /// Returns an error with [`Kind::Unsupported`] if the backend doesn't
+    /// support it, see [`AccessorMetadata::can_delete_all_versions`].
+    ///
+    /// Versions are deleted in batches, if some of them failed to be
+    /// deleted, the returned error reports their version ids.
+    ///
+    /// [`AccessorMetadata::can_delete_all_versions`]: crate::AccessorMetadata::can_delete_all_versions
+    pub async fn delete_all_versions(&self) -> Result<()> {
+        self.check_not_root("delete")?;
+        if !self.acc.metadata().can_delete_all_versions() {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op: "delete",
+                path: self.meta.path().to_string(),
+                source: anyhow!("backend doesn't support deleting all versions"),
+            });
+        }
+        let mut op = OpDelete::new(self.meta.path());
+        op.mode = DeleteMode::AllVersions;
+
+        self.acc.delete(&op).await
+    }
+
+    /// Delete current object, and all objects under it if it's a dir.
+    ///
+    /// Unlike [`Object::delete`], this will not stop at the first failure.
+    /// All files are deleted before their parent dirs, and the result of
+    /// every path is reported in the returned [`RecursiveSummary`], so
+    /// cleanup jobs could resume from the failed ones. Dirs that failed to
+    /// be listed are reported as failed too.
+    ///
+    /// The root itself will not be deleted, only the objects under it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("dir/a").writer().write_bytes(vec![0; 4]).await?;
+    ///
+    ///     let summary = op.object("dir/").delete_recursive().await;
+    ///     for (path, err) in &summary.failed {
+    ///         println!("delete {} failed: {}", path, err);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn delete_recursive(&self) -> RecursiveSummary {
+        let mut summary = RecursiveSummary::default();
+        self.delete_recursive_with(|path, r| summary.record(path, r))
+            .await;
+        summary
+    }
+
+    /// Like [`Object::delete_recursive`], but report the result of every
+    /// path to `f` instead of collecting them.
+    ///
+    /// The tree is walked depth first while deleting: files are deleted
+    /// as soon as they are listed with at most 16 requests in flight,
+    /// and every dir is deleted right after its children. Only one
+    /// listing per level is kept open, so memory is bounded by the depth
+    /// of the tree instead of the number of objects.
+    ///
+    /// Objects that have been removed by others count as deleted. If a
+    /// dir still has children when we delete it (new objects written
+    /// while walking), it will be listed again for at most 3 times.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("dir/a").writer().write_bytes(vec![0; 4]).await?;
+    ///
+    ///     let mut deleted = 0;
+    ///     op.object("dir/")
+    ///         .delete_recursive_with(|path, r| match r {
+    ///             Ok(()) => deleted += 1,
+    ///             Err(err) => println!("delete {} failed: {}", path, err),
+    ///         })
+    ///         .await;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn delete_recursive_with<F>(&self, mut f: F)
+    where
+        F: FnMut(&str, Result<()>),
+    {
+        let path = self.meta.path();
+
+        if !path.is_empty() && !path.ends_with('/') {
+            let r = self.acc.delete(&OpDelete::new(path)).await;
+            f(path, ignore_not_exist(r));
+            return;
+        }
+
+        let mut running: FuturesUnordered<BoxFuture<'static, (String, Result<()>)>> =
+            FuturesUnordered::new();
+        let mut stack = vec![DeleteFrame::new(self.acc.clone(), path)];
+        while let Some(frame) = stack.last_mut() {
+            let entry = match frame.obs.next().await {
+                Some(Ok(mut o)) => o
+                    .metadata_cached_for(&[MetaField::Mode])
+                    .await
+                    .map(|meta| Some((meta.path().to_string(), meta.mode()))),
+                Some(Err(e)) => Err(e),
+                None => Ok(None),
+            };
+
+            match entry {
+                // Some backends return the dir itself.
+                Ok(Some((p, _))) if p == frame.path => {}
+                Ok(Some((p, ObjectMode::DIR))) => {
+                    // fs returns dirs without the trailing `/`.
+                    let p = if p.ends_with('/') {
+                        p
+                    } else {
+                        format!("{}/", p)
+                    };
+                    stack.push(DeleteFrame::new(self.acc.clone(), &p));
+                }
+                Ok(Some((p, _))) => {
+                    if running.len() >= DELETE_CONCURRENCY {
+                        if let Some((p, r)) = running.next().await {
+                            frame.failed |= r.is_err();
+                            f(&p, r);
+                        }
+                    }
+                    let acc = self.acc.clone();
+                    running.push(Box::pin(async move {
+                        let r = acc.delete(&OpDelete::new(&p)).await;
+                        (p, ignore_not_exist(r))
+                    }));
+                }
+                // Dir has been removed by others.
+                Err(e) if e.kind() == Kind::ObjectNotExist => {
+                    stack.pop();
+                }
+                Err(e) => {
+                    f(&frame.path, Err(e));
+                    stack.pop();
+                }
+                // All children have been listed, wait for them before
+                // deleting the dir.
+                Ok(None) => {
+                    while let Some((p, r)) = running.next().await {
+                        frame.failed |= r.is_err();
+                        f(&p, r);
+                    }
+                    if is_root(&frame.path) {
+                        stack.pop();
+                        continue;
+                    }
+
+                    let r = self.acc.delete(&OpDelete::new(&frame.path)).await;
+                    match ignore_not_exist(r) {
+                        // Children have been added while we are walking, walk
+                        // again unless they are the ones we failed to delete.
+                        Err(e)
+                            if !frame.failed
+                                && e.kind() != Kind::ObjectPermissionDenied
+                                && frame.retries < DELETE_DIR_RETRIES =>
+                        {
+                            warn!("object {} delete: {:?}, list again", &frame.path, e);
+                            frame.retries += 1;
+                            frame.obs = ObjectStream::new(self.acc.clone(), &frame.path);
+                        }
+                        r => {
+                            f(&frame.path, r);
+                            stack.pop();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// List the objects under current object, which must be a dir.
+    ///
+    /// The path is listed in [`ListMode::Dir`], so `abc` lists the children
+    /// of `abc/` but never `abcdef`. Use [`Object::list_with`] to list by
+    /// prefix instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::TryStreamExt;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("abc/d").writer().write_bytes(vec![0; 4]).await?;
+    ///     op.object("abcdef").writer().write_bytes(vec![0; 4]).await?;
+    ///
+    ///     let obs: Vec<_> = op.object("abc").list().try_collect().await?;
+    ///     assert_eq!(obs.len(), 1);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn list(&self) -> ObjectStream {
+        self.list_with(ListOptions::new())
+    }
+
+    /// List the objects under current object with given [`ListOptions`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::TryStreamExt;
+    /// use opendal::ops::ListOptions;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("dir/a").writer().write_bytes(vec![0; 4]).await?;
+    ///     op.object("dir/b").writer().write_bytes(vec![]).await?;
+    ///
+    ///     let obs: Vec<_> = op
+    ///         .object("dir/")
+    ///         .list_with(ListOptions::new().min_size(1))
+    ///         .try_collect()
+    ///         .await?;
+    ///     assert_eq!(obs.len(), 1);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn list_with(&self, opts: ListOptions) -> ObjectStream {
+        ObjectStream::with_options(self.acc.clone(), self.meta.path(), &opts)
+    }
+
+    /// Get current object's metadata.
+    ///
+    /// The root (`""` or `"/"`) always returns a dir metadata without
+    /// content length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use futures::io;
+    /// use opendal::Operator;
+    /// use opendal::error::Kind;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     if let Err(e) =  op.object("test").metadata().await {
+    ///         if e.kind() == Kind::ObjectNotExist {
+    ///             println!("object not exist")
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn metadata(&self) -> Result<Metadata> {
+        self.stat().await
+    }
+
+    /// The root is a synthetic dir, operations like `read` and `delete` on
+    /// it will be rejected before reaching the backend.
+    fn check_not_root(&self, op: &'static str) -> Result<()> {
+        if is_root(self.meta.path()) {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op,
+                path: self.meta.path().to_string(),
+                source: anyhow!("{} on the root is not allowed", op),
+            });
+        }
+        Ok(())
+    }
+
+    /// Stat the object, the root will get a synthetic dir metadata without
+    /// reaching the backend.
+    async fn stat(&self) -> Result<Metadata> {
+        if is_root(self.meta.path()) {
+            let mut meta = Metadata::default();
+            meta.set_path("/")
+                .set_mode(ObjectMode::DIR)
+                .set_fully_loaded();
+            return Ok(meta);
+        }
+
+        let op = &OpStat::new(self.meta.path());
+        self.acc.stat(op).await
+    }
+
+    /// Use local cached metadata if possible.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use futures::io;
+    /// use opendal::Operator;
+    /// use opendal::error::Kind;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     let mut o = op.object("test");
+    ///
+    ///     o.metadata_cached().await;
+    ///     // The second call to metadata_cached will have no cost.
+    ///     o.metadata_cached().await;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn metadata_cached(&mut self) -> Result<&Metadata> {
+        if self.meta.is_fully_loaded() {
+            return Ok(&self.meta);
+        }
+
+        self.meta = self.stat().await?;
+
+        Ok(&self.meta)
+    }
+
+    /// Use local cached metadata if all required fields are present.
+    ///
+    /// Objects returned by list could carry partial metadata. We will only
+    /// send a `stat` request when some of the `fields` are missing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use opendal::MetaField;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![0; 16]).await?;
+    ///
+    ///     let mut o = op.object("test");
+    ///     let meta = o.metadata_cached_for(&[MetaField::ContentLength]).await?;
+    ///     assert_eq!(meta.content_length(), 16);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn metadata_cached_for(&mut self, fields: &[MetaField]) -> Result<&Metadata> {
+        if self.meta.is_fully_loaded() || fields.iter().all(|f| self.meta.has(*f)) {
+            return Ok(&self.meta);
+        }
+
+        self.meta = self.stat().await?;
+
+        Ok(&self.meta)
+    }
+
+    pub(crate) fn metadata_ref(&self) -> &Metadata {
+        &self.meta
+    }
+
+    pub(crate) fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.meta
+    }
+
+    /// Check if this object exist or not.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::memory;
+    /// use anyhow::Result;
+    /// use futures::io;
+    /// use opendal::Operator;
+    /// use opendal::error::Kind;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     let _ = op.object("test").is_exist().await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn is_exist(&self) -> Result<bool> {
+        // The root exists as long as the backend is reachable.
+        if is_root(self.meta.path()) {
+            return match self.acc.bucket_exists().await {
+                Err(err) if err.kind() == Kind::Unsupported => Ok(true),
+                r => r,
+            };
+        }
+
+        let r = self.metadata().await;
+        match r {
+            Ok(_) => Ok(true),
+            Err(err) => match err.kind() {
+                Kind::ObjectNotExist => Ok(false),
+                _ => Err(err),
+            },
+        }
+    }
+
+    /// Check if this object is a dir.
+    ///
+    /// Cached mode (like the one returned by list) will be used if present,
+    /// otherwise a `stat` will be sent. Returns `false` if the object does
+    /// not exist, same as [`Object::is_exist`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("dir/test").writer().write_bytes(vec![0; 16]).await?;
+    ///
+    ///     assert!(op.object("dir/").is_dir().await?);
+    ///     assert!(!op.object("dir/test").is_dir().await?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn is_dir(&self) -> Result<bool> {
+        Ok(self.mode().await? == Some(ObjectMode::DIR))
+    }
+
+    /// Check if this object is a file.
+    ///
+    /// Cached mode (like the one returned by list) will be used if present,
+    /// otherwise a `stat` will be sent. Returns `false` if the object does
+    /// not exist, same as [`Object::is_exist`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![0; 16]).await?;
+    ///
+    ///     assert!(op.object("test").is_file().await?);
+    ///     assert!(!op.object("not_exist").is_file().await?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn is_file(&self) -> Result<bool> {
+        Ok(self.mode().await? == Some(ObjectMode::FILE))
+    }
+
+    /// Returns the mode of this object, or `None` if it does not exist.
+    async fn mode(&self) -> Result<Option<ObjectMode>> {
+        if self.meta.is_fully_loaded() || self.meta.has(MetaField::Mode) {
+            return Ok(Some(self.meta.mode()));
+        }
+
+        match self.stat().await {
+            Ok(meta) => Ok(Some(meta.mode())),
+            Err(err) if err.kind() == Kind::ObjectNotExist => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Max delete requests in flight of [`Object::delete_recursive`].
+const DELETE_CONCURRENCY: usize = 16;
+
+/// Max copy requests in flight of [`Object::copy_recursive`].
+const COPY_CONCURRENCY: usize = 16;
+
+/// Max times [`Object::delete_recursive`] lists a dir again if it's not
+/// empty after all its children have been deleted.
+const DELETE_DIR_RETRIES: usize = 3;
+
+/// A dir being walked by [`Object::delete_recursive_with`].
+struct DeleteFrame {
+    path: String,
+    obs: ObjectStream,
+    /// Some children of this dir failed to be deleted.
+    failed: bool,
+    retries: usize,
+}
+
+impl DeleteFrame {
+    fn new(acc: Arc<dyn Accessor>, path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            obs: ObjectStream::new(acc, path),
+            failed: false,
+            retries: 0,
+        }
+    }
+}
+
+/// Objects that have been deleted by others are treated as deleted.
+fn ignore_not_exist(r: Result<()>) -> Result<()> {
+    match r {
+        Err(e) if e.kind() == Kind::ObjectNotExist => Ok(()),
+        r => r,
+    }
+}
+
+/// Result of [`Object::write_if_changed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteResult {
+    /// Content has been written with the given size.
+    Written(usize),
+    /// Content is the same as stored, nothing has been written.
+    Unchanged,
+}
+
+/// Result of a recursive operation like [`Object::delete_recursive`] and
+/// [`Object::copy_recursive`], which keeps going after individual failures.
+#[derive(Debug, Default)]
+pub struct RecursiveSummary {
+    /// Paths that succeeded, in the order they are processed.
+    pub succeeded: Vec<String>,
+    /// Paths that failed along with their errors.
+    pub failed: Vec<(String, Error)>,
+}
+
+impl RecursiveSummary {
+    /// Check if all paths succeeded.
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    fn record(&mut self, path: &str, r: Result<()>) {
+        match r {
+            Ok(()) => self.succeeded.push(path.to_string()),
+            Err(e) => self.failed.push((path.to_string(), e)),
+        }
+    }
+}
+
+/// Returns `true` if `path` points to the root of the backend, like `""`
+/// or `"/"`.
+pub(crate) fn is_root(path: &str) -> bool {
+    path.chars().all(|c| c == '/')
+}
+
+/// Split `total` bytes into at most `count` ranges of `chunk_size`, evenly
+/// spaced from the beginning to the end.
+///
+/// Returns the whole range if it's not larger than `count * chunk_size`.
+fn sample_ranges(total: u64, count: usize, chunk_size: u64) -> Vec<(u64, u64)> {
+    if total == 0 || count == 0 || chunk_size == 0 {
+        return Vec::new();
+    }
+    let count = count as u64;
+    if total <= count.saturating_mul(chunk_size) {
+        return vec![(0, total)];
+    }
+    if count == 1 {
+        return vec![(0, chunk_size)];
+    }
+
+    let span = total - chunk_size;
+    (0..count)
+        .map(|i| {
+            (
+                (span as u128 * i as u128 / (count - 1) as u128) as u64,
+                chunk_size,
+            )
+        })
+        .collect()
+}
+
+/// MetaField is the field of [`Metadata`] that could be missing.
+///
+/// Backends only fill the fields they know. For example, objects returned
+/// by s3 list don't carry the fields that only `HeadObject` returns. Use
+/// [`Metadata::has`] to check whether a field is trustworthy.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum MetaField {
+    Mode,
+    ContentLength,
+    ETag,
+    LastModified,
+    VersionId,
+    ContentType,
+    ContentEncoding,
+    ContentLanguage,
+    CacheControl,
+    Expires,
+    OwnerId,
+    OwnerDisplayName,
+    StorageClass,
+}
+
+/// Metadata carries all object metadata.
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    fully_loaded: bool,
+
+    path: String,
+    mode: Option<ObjectMode>,
+
+    content_length: Option<u64>,
+    etag: Option<String>,
+    last_modified: Option<SystemTime>,
+    version_id: Option<String>,
+    content_type: Option<String>,
+    content_encoding: Option<String>,
+    content_language: Option<String>,
+    cache_control: Option<String>,
+    expires: Option<SystemTime>,
+    owner_id: Option<String>,
+    owner_display_name: Option<String>,
+    storage_class: Option<String>,
+}
+
+impl Metadata {
+    /// Returns object path that relative to corresponding backend's root.
+    pub fn path(&self) -> &str {
+        &self.path
     }
 
+    pub(crate) fn set_path(&mut self, path: &str) -> &mut Self {
+        self.path = path.to_string();
+        self
+    }
+
+    /// Returns `true` if this metadata has been loaded via `stat`, that
+    /// means all fields that the backend could provide are filled.
+    ///
+    /// Fields that the backend doesn't support could still be missing,
+    /// use [`Metadata::has`] to check them.
+    pub fn is_fully_loaded(&self) -> bool {
+        self.fully_loaded
+    }
+
+    /// Returns `true` if this metadata has been loaded via `stat`.
+    #[deprecated(since = "0.2.2", note = "use `Metadata::is_fully_loaded` instead")]
+    pub fn complete(&self) -> bool {
+        self.is_fully_loaded()
+    }
+
+    pub(crate) fn set_fully_loaded(&mut self) -> &mut Self {
+        self.fully_loaded = true;
+        self
+    }
+
+    /// Returns `true` if the given field has been filled by backend.
+    pub fn has(&self, field: MetaField) -> bool {
+        match field {
+            MetaField::Mode => self.mode.is_some(),
+            MetaField::ContentLength => self.content_length.is_some(),
+            MetaField::ETag => self.etag.is_some(),
+            MetaField::LastModified => self.last_modified.is_some(),
+            MetaField::VersionId => self.version_id.is_some(),
+            MetaField::ContentType => self.content_type.is_some(),
+            MetaField::ContentEncoding => self.content_encoding.is_some(),
+            MetaField::ContentLanguage => self.content_language.is_some(),
+            MetaField::CacheControl => self.cache_control.is_some(),
+            MetaField::Expires => self.expires.is_some(),
+            MetaField::OwnerId => self.owner_id.is_some(),
+            MetaField::OwnerDisplayName => self.owner_display_name.is_some(),
+            MetaField::StorageClass => self.storage_class.is_some(),
+        }
+    }
+
+    /// Returns the mode of object.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the mode is not filled, use
+    /// [`Metadata::try_mode`] for partially-populated metadata like listing
+    /// results. Enable the `lenient-metadata` feature to return the default
+    /// value instead.
     pub fn mode(&self) -> ObjectMode {
-        debug_assert!(self.mode.is_some(), "mode must exist");
+        debug_assert!(
+            cfg!(feature = "lenient-metadata") || self.mode.is_some(),
+            "mode must exist"
+        );
 
         self.mode.unwrap_or_default()
     }
 
+    /// Returns the mode of object, or `None` if it's not filled.
+    pub fn try_mode(&self) -> Option<ObjectMode> {
+        self.mode
+    }
+
     pub(crate) fn set_mode(&mut self, mode: ObjectMode) -> &mut Self {
         self.mode = Some(mode);
         self
     }
 
+    /// Returns the content length of object.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the content length is not filled, use
+    /// [`Metadata::try_content_length`] for partially-populated metadata
+    /// like listing results. Enable the `lenient-metadata` feature to return
+    /// `0` instead.
     pub fn content_length(&self) -> u64 {
-        debug_assert!(self.content_length.is_some(), "content length must exist");
+        debug_assert!(
+            cfg!(feature = "lenient-metadata") || self.content_length.is_some(),
+            "content length must exist"
+        );
 
         self.content_length.unwrap_or_default()
     }
 
+    /// Returns the content length of object, or `None` if it's not filled.
+    pub fn try_content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
     pub(crate) fn set_content_length(&mut self, content_length: u64) -> &mut Self {
         self.content_length = Some(content_length);
         self
     }
+
+    /// Returns the etag of this object if the backend provides one.
+    ///
+    /// The etag is opaque, callers should only compare it with other etags
+    /// returned by the same backend.
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    pub(crate) fn set_etag(&mut self, etag: &str) -> &mut Self {
+        self.etag = Some(etag.to_string());
+        self
+    }
+
+    /// Returns the last modified time of this object if the backend provides one.
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        self.last_modified
+    }
+
+    pub(crate) fn set_last_modified(&mut self, last_modified: SystemTime) -> &mut Self {
+        self.last_modified = Some(last_modified);
+        self
+    }
+
+    /// Returns the version id of this object if the backend provides one.
+    ///
+    /// Like etag, the version id is opaque and only comparable with others
+    /// returned by the same backend.
+    pub fn version_id(&self) -> Option<&str> {
+        self.version_id.as_deref()
+    }
+
+    pub(crate) fn set_version_id(&mut self, version_id: &str) -> &mut Self {
+        self.version_id = Some(version_id.to_string());
+        self
+    }
+
+    /// Returns the `Content-Type` of this object if it's set while writing
+    /// and the backend could store it.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    pub(crate) fn set_content_type(&mut self, content_type: &str) -> &mut Self {
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+
+    /// Returns the `Content-Encoding` of this object, like `gzip`.
+    ///
+    /// The content is returned as stored unless it's read with
+    /// [`ReadOptions::decode_content`], so `content_length` is the size of
+    /// the encoded content.
+    pub fn content_encoding(&self) -> Option<&str> {
+        self.content_encoding.as_deref()
+    }
+
+    pub(crate) fn set_content_encoding(&mut self, content_encoding: &str) -> &mut Self {
+        self.content_encoding = Some(content_encoding.to_string());
+        self
+    }
+
+    /// Returns the `Content-Language` of this object if it's set while
+    /// writing and the backend could store it.
+    pub fn content_language(&self) -> Option<&str> {
+        self.content_language.as_deref()
+    }
+
+    pub(crate) fn set_content_language(&mut self, content_language: &str) -> &mut Self {
+        self.content_language = Some(content_language.to_string());
+        self
+    }
+
+    /// Returns the `Cache-Control` of this object if it's set while writing
+    /// and the backend could store it.
+    pub fn cache_control(&self) -> Option<&str> {
+        self.cache_control.as_deref()
+    }
+
+    pub(crate) fn set_cache_control(&mut self, cache_control: &str) -> &mut Self {
+        self.cache_control = Some(cache_control.to_string());
+        self
+    }
+
+    /// Returns the `Expires` of this object if it's set while writing and
+    /// the backend could store it.
+    pub fn expires(&self) -> Option<SystemTime> {
+        self.expires
+    }
+
+    pub(crate) fn set_expires(&mut self, expires: SystemTime) -> &mut Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    /// Returns the id of the object's owner.
+    ///
+    /// s3 only returns it in listing with [`ObjectStream::fetch_owner`].
+    pub fn owner_id(&self) -> Option<&str> {
+        self.owner_id.as_deref()
+    }
+
+    pub(crate) fn set_owner_id(&mut self, owner_id: &str) -> &mut Self {
+        self.owner_id = Some(owner_id.to_string());
+        self
+    }
+
+    /// Returns the display name of the object's owner.
+    ///
+    /// Not all s3 regions return it, see [`Metadata::owner_id`] too.
+    pub fn owner_display_name(&self) -> Option<&str> {
+        self.owner_display_name.as_deref()
+    }
+
+    pub(crate) fn set_owner_display_name(&mut self, owner_display_name: &str) -> &mut Self {
+        self.owner_display_name = Some(owner_display_name.to_string());
+        self
+    }
+
+    /// Returns the storage class of the object, like `STANDARD_IA` in s3.
+    ///
+    /// s3 doesn't return it in `stat` for `STANDARD` objects.
+    pub fn storage_class(&self) -> Option<&str> {
+        self.storage_class.as_deref()
+    }
+
+    pub(crate) fn set_storage_class(&mut self, storage_class: &str) -> &mut Self {
+        self.storage_class = Some(storage_class.to_string());
+        self
+    }
 }
 
 /// ObjectMode represents the corresponding object's mode.
@@ -411,14 +2265,70 @@ pub type BoxedObjectStream = Box<dyn futures::Stream<Item = Result<Object>> + Un
 /// Handler for listing object under a dir.
 pub struct ObjectStream {
     acc: Arc<dyn Accessor>,
-    path: String,
+    op: OpList,
+    /// Token passed by [`ObjectStream::resume_from`], it will be validated
+    /// when listing starts.
+    resume: Option<String>,
+    /// Path of the last returned object.
+    last: Option<String>,
     state: State,
 }
 
+/// Payload of the token returned by [`ObjectStream::resume_token`].
+#[derive(Serialize, Deserialize)]
+struct ResumeToken {
+    /// Scheme of the backend which returned it, empty if unknown.
+    scheme: String,
+    /// The path being listed.
+    path: String,
+    /// Path of the last returned object.
+    after: String,
+}
+
+impl ResumeToken {
+    fn scheme_of(acc: &Arc<dyn Accessor>) -> String {
+        acc.metadata()
+            .scheme()
+            .map(|v| v.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Decode `token` and make sure it's returned by listing `path` on `acc`.
+    fn parse(acc: &Arc<dyn Accessor>, path: &str, token: &str) -> Result<Self> {
+        let invalid = |source| Error::Object {
+            kind: Kind::ResumeTokenInvalid,
+            op: "list",
+            path: path.to_string(),
+            source,
+        };
+
+        let t: ResumeToken =
+            serde_json::from_str(token).map_err(|e| invalid(anyhow!("malformed token: {}", e)))?;
+        let scheme = Self::scheme_of(acc);
+        if t.scheme != scheme {
+            return Err(invalid(anyhow!(
+                "token is returned by {} backend, but listing on {}",
+                t.scheme,
+                scheme
+            )));
+        }
+        if t.path != path {
+            return Err(invalid(anyhow!("token is returned by listing {}", t.path)));
+        }
+        Ok(t)
+    }
+}
+
 enum State {
     Idle,
     Sending(BoxFuture<'static, Result<BoxedObjectStream>>),
     Listing(BoxedObjectStream),
+    /// Entry doesn't carry the metadata that predicates require, we are
+    /// waiting for its `stat`.
+    Filtering(
+        BoxedObjectStream,
+        BoxFuture<'static, Result<Option<Object>>>,
+    ),
 }
 
 impl ObjectStream {
@@ -426,34 +2336,385 @@ impl ObjectStream {
     pub fn new(acc: Arc<dyn Accessor>, path: &str) -> Self {
         Self {
             acc,
-            path: path.to_string(),
+            op: OpList::new(path),
+            resume: None,
+            last: None,
+            state: State::Idle,
+        }
+    }
+
+    /// Creates a new object stream with given [`ListOptions`].
+    pub fn with_options(acc: Arc<dyn Accessor>, path: &str, opts: &ListOptions) -> Self {
+        Self {
+            acc,
+            op: OpList::with_options(path, opts),
+            resume: None,
+            last: None,
             state: State::Idle,
         }
     }
+
+    /// Only list files that modified after `t`.
+    ///
+    /// Dirs will always be listed.
+    ///
+    /// # Note
+    ///
+    /// Entries are filtered with the metadata returned by list. For entries
+    /// without it, a `stat` will be sent for each of them, which is costly.
+    #[must_use]
+    pub fn modified_after(mut self, t: SystemTime) -> Self {
+        self.op.modified_after = Some(t);
+        self
+    }
+
+    /// Only list files whose size is not less than `size`.
+    ///
+    /// Dirs will always be listed, see also [`ObjectStream::modified_after`].
+    #[must_use]
+    pub fn min_size(mut self, size: u64) -> Self {
+        self.op.min_size = Some(size);
+        self
+    }
+
+    /// Only list files whose size is not larger than `size`.
+    ///
+    /// Dirs will always be listed, see also [`ObjectStream::modified_after`].
+    #[must_use]
+    pub fn max_size(mut self, size: u64) -> Self {
+        self.op.max_size = Some(size);
+        self
+    }
+
+    /// Resume listing after `path`, which is returned by the previous
+    /// listing.
+    ///
+    /// This makes it possible to continue a listing across requests without
+    /// holding the stream.
+    #[must_use]
+    pub fn start_after(mut self, path: &str) -> Self {
+        self.op.start_after = Some(path.to_string());
+        self
+    }
+
+    /// Resume listing from `token`, which is returned by
+    /// [`ObjectStream::resume_token`] of a previous listing on the same path.
+    ///
+    /// Unlike [`ObjectStream::start_after`], the token is validated before
+    /// listing: tokens returned by another kind of backend or path will be
+    /// rejected with [`Kind::ResumeTokenInvalid`].
+    ///
+    /// # Note
+    ///
+    /// Resumption is best-effort if objects under the path are created or
+    /// deleted in between: created ones may or may not be listed, and fs
+    /// requires the last returned object to still exist.
+    #[must_use]
+    pub fn resume_from(mut self, token: &str) -> Self {
+        self.resume = Some(token.to_string());
+        self
+    }
+
+    /// Returns an opaque token to resume this listing after the last
+    /// returned object with [`ObjectStream::resume_from`].
+    ///
+    /// Returns `None` if no object has been returned yet, or the token
+    /// passed by `resume_from` if resumed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::StreamExt;
+    /// use futures::TryStreamExt;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("dir/a").writer().write_bytes(vec![0; 4]).await?;
+    ///     op.object("dir/b").writer().write_bytes(vec![0; 4]).await?;
+    ///
+    ///     let mut obs = op.objects("dir/");
+    ///     obs.next().await.expect("must have entry")?;
+    ///     let token = obs.resume_token().expect("must have token");
+    ///     drop(obs);
+    ///
+    ///     let obs: Vec<_> = op.objects("dir/").resume_from(&token).try_collect().await?;
+    ///     assert_eq!(obs.len(), 1);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn resume_token(&self) -> Option<String> {
+        match &self.last {
+            Some(after) => Some(
+                serde_json::to_string(&ResumeToken {
+                    scheme: ResumeToken::scheme_of(&self.acc),
+                    path: self.op.path.clone(),
+                    after: after.clone(),
+                })
+                .expect("serialize resume token must succeed"),
+            ),
+            None => self.resume.clone(),
+        }
+    }
+
+    /// List in snapshot mode: only the versions that are the latest at
+    /// listing time will be listed, and all reads of the listed objects
+    /// will be pinned to them.
+    ///
+    /// Objects overwritten or deleted after listing will still be read as
+    /// they were, which is required by consistent backups.
+    ///
+    /// # Note
+    ///
+    /// - Only backends with [`AccessorMetadata::can_read_version`][crate::AccessorMetadata::can_read_version] support
+    ///   it, others will return an error with [`Kind::Unsupported`].
+    /// - Only reads are pinned, `metadata()` still returns the latest one.
+    ///
+    /// # TODO
+    ///
+    /// The cross-operator sync utility should use snapshot listing when the
+    /// source supports versions. It will get that flag when it's added.
+    #[must_use]
+    pub fn snapshot(mut self) -> Self {
+        self.op.snapshot = true;
+        self
+    }
+
+    /// Match the path in `mode`, default to [`ListMode::Dir`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::TryStreamExt;
+    /// use opendal::ops::ListMode;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("abc/d").writer().write_bytes(vec![0; 4]).await?;
+    ///     op.object("abcdef").writer().write_bytes(vec![0; 4]).await?;
+    ///
+    ///     let obs: Vec<_> = op.objects("abc").try_collect().await?;
+    ///     assert_eq!(obs.len(), 1);
+    ///     let obs: Vec<_> = op.objects("abc").mode(ListMode::Prefix).try_collect().await?;
+    ///     assert_eq!(obs.len(), 2);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn mode(mut self, mode: ListMode) -> Self {
+        self.op.mode = mode;
+        self
+    }
+
+    /// Fill [`Metadata::owner_id`] and [`Metadata::owner_display_name`]
+    /// of listed objects.
+    ///
+    /// s3 doesn't return the owner in listing unless requested. Backends
+    /// without owners ignore it and leave them `None`.
+    #[must_use]
+    pub fn fetch_owner(mut self) -> Self {
+        self.op.fetch_owner = true;
+        self
+    }
+
+    /// Call `f` on every listed object, with at most `limit` of them running
+    /// at the same time.
+    ///
+    /// Stops at the first error of listing or `f`, and returns it. Calls
+    /// still running will be dropped. Use
+    /// [`ObjectStream::try_for_each_concurrent`] to collect all failures
+    /// instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     op.objects("dir/")
+    ///         .for_each_concurrent(16, |o| async move { o.delete().await })
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn for_each_concurrent<F, Fut>(self, limit: usize, f: F) -> Result<()>
+    where
+        F: FnMut(Object) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        self.try_for_each_concurrent(limit, ErrorPolicy::Abort, f)
+            .await
+            .map_err(|mut errs| errs.remove(0))
+    }
+
+    /// Call `f` on every listed object, with at most `limit` of them running
+    /// at the same time, and handle failures by `policy`.
+    ///
+    /// - The next entry is only pulled from the listing when a slot is
+    ///   free, so pages will not be fetched ahead of processing.
+    /// - A failed listing can't make progress, so it always stops pulling
+    ///   new entries. With [`ErrorPolicy::Continue`], calls already running
+    ///   will still be waited.
+    ///
+    /// Returns all failures in the order they happened, there will be only
+    /// one with [`ErrorPolicy::Abort`].
+    pub async fn try_for_each_concurrent<F, Fut>(
+        mut self,
+        limit: usize,
+        policy: ErrorPolicy,
+        mut f: F,
+    ) -> std::result::Result<(), Vec<Error>>
+    where
+        F: FnMut(Object) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let limit = limit.max(1);
+        let mut running = FuturesUnordered::new();
+        let mut listing = true;
+        let mut errors = vec![];
+
+        futures::future::poll_fn(|cx| loop {
+            while listing && running.len() < limit {
+                match self.poll_next_unpin(cx) {
+                    Poll::Ready(Some(Ok(o))) => running.push(f(o)),
+                    Poll::Ready(Some(Err(e))) => {
+                        listing = false;
+                        errors.push(e);
+                    }
+                    Poll::Ready(None) => listing = false,
+                    Poll::Pending => break,
+                }
+            }
+            if policy == ErrorPolicy::Abort && !errors.is_empty() {
+                return Poll::Ready(());
+            }
+
+            match running.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(()))) => {}
+                Poll::Ready(Some(Err(e))) => errors.push(e),
+                Poll::Ready(None) if !listing => return Poll::Ready(()),
+                // Nothing is running, or all of them are pending.
+                Poll::Ready(None) | Poll::Pending => return Poll::Pending,
+            }
+        })
+        .await;
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// How [`ObjectStream::try_for_each_concurrent`] handles failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Stop at the first failure and drop all running calls.
+    #[default]
+    Abort,
+    /// Keep going, and return all failures at the end.
+    Continue,
 }
 
 impl futures::Stream for ObjectStream {
     type Item = Result<Object>;
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match &mut self.state {
-            State::Idle => {
-                let acc = self.acc.clone();
-                let op = OpList::new(&self.path);
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
 
-                let future = async move { acc.list(&op).await };
+        loop {
+            match &mut this.state {
+                State::Idle => {
+                    let acc = this.acc.clone();
+                    let mut op = this.op.clone();
+                    let resume = this.resume.clone();
 
-                self.state = State::Sending(Box::pin(future));
-                self.poll_next(cx)
-            }
-            State::Sending(future) => match ready!(Pin::new(future).poll(cx)) {
-                Ok(obs) => {
-                    self.state = State::Listing(obs);
-                    self.poll_next(cx)
+                    let future = async move {
+                        if let Some(token) = resume {
+                            op.start_after =
+                                Some(ResumeToken::parse(&acc, &op.path, &token)?.after);
+                        }
+                        if op.snapshot && !acc.metadata().can_read_version() {
+                            return Err(Error::Object {
+                                kind: Kind::Unsupported,
+                                op: "list",
+                                path: op.path,
+                                source: anyhow!("backend doesn't support snapshot listing"),
+                            });
+                        }
+                        acc.list(&op).await
+                    };
+
+                    this.state = State::Sending(Box::pin(future));
                 }
-                Err(e) => Poll::Ready(Some(Err(e))),
-            },
-            State::Listing(obs) => Pin::new(obs).poll_next(cx),
+                State::Sending(future) => match ready!(Pin::new(future).poll(cx)) {
+                    Ok(obs) => this.state = State::Listing(obs),
+                    Err(e) => return Poll::Ready(Some(Err(e))),
+                },
+                State::Listing(obs) => {
+                    let mut o = match ready!(Pin::new(obs).poll_next(cx)) {
+                        Some(Ok(o)) => o,
+                        v => return Poll::Ready(v),
+                    };
+                    if this.op.snapshot {
+                        o.pin_version();
+                    }
+                    if !this.op.has_predicates() {
+                        this.last = Some(o.path().to_string());
+                        return Poll::Ready(Some(Ok(o)));
+                    }
+
+                    let fields = this.op.required_fields();
+                    if o.meta.is_fully_loaded() || fields.iter().all(|f| o.meta.has(*f)) {
+                        if this.op.matches(&o.meta) {
+                            this.last = Some(o.path().to_string());
+                            return Poll::Ready(Some(Ok(o)));
+                        }
+                        continue;
+                    }
+
+                    // Fallback to stat this entry.
+                    let op = this.op.clone();
+                    let future = async move {
+                        let mut o = o;
+                        let matched = op.matches(o.metadata_cached_for(&fields).await?);
+                        Ok(if matched { Some(o) } else { None })
+                    };
+                    match std::mem::replace(&mut this.state, State::Idle) {
+                        State::Listing(obs) => this.state = State::Filtering(obs, Box::pin(future)),
+                        _ => unreachable!("state must be listing"),
+                    }
+                }
+                State::Filtering(_, future) => {
+                    let result = ready!(future.as_mut().poll(cx));
+                    match std::mem::replace(&mut this.state, State::Idle) {
+                        State::Filtering(obs, _) => this.state = State::Listing(obs),
+                        _ => unreachable!("state must be filtering"),
+                    }
+                    match result {
+                        Ok(Some(o)) => {
+                            this.last = Some(o.path().to_string());
+                            return Poll::Ready(Some(Ok(o)));
+                        }
+                        Ok(None) => continue,
+                        Err(e) => return Poll::Ready(Some(Err(e))),
+                    }
+                }
+            }
         }
     }
 }
@@ -0,0 +1,216 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Samples of recent requests, which drive adaptive behaviors like the
+//! read-ahead depth of [`Object::fetch_range_adaptive`][crate::Object::fetch_range_adaptive].
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::atomic::fence;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use crate::ops::Operation;
+use crate::stats::OPERATIONS;
+
+/// The clock of samples, tests use tokio's to work with paused time.
+///
+/// The clock of std is much cheaper than tokio's with `test-util` (enabled
+/// by our dev-dependencies), which is not negligible for memory reads.
+#[cfg(not(test))]
+pub(crate) use std::time::Instant;
+#[cfg(test)]
+pub(crate) use tokio::time::Instant;
+
+/// Number of recent samples kept by [`Observer`].
+const CAPACITY: usize = 1024;
+
+/// A slot of the ring buffer, `seq` is `0` while it's being written.
+#[derive(Default)]
+struct Slot {
+    seq: AtomicU64,
+    op: AtomicU64,
+    bytes: AtomicU64,
+    nanos: AtomicU64,
+}
+
+/// Observer keeps samples of recent requests in a lock-free ring buffer,
+/// shared by an [`Operator`][crate::Operator] and its clones.
+///
+/// Writers claim slots by a counter and publish them by a sequence, so
+/// readers skip the slots being written. Samples are best-effort: a slot
+/// overwritten by two writers at the same time may mix their fields.
+pub(crate) struct Observer {
+    enabled: AtomicBool,
+    next: AtomicU64,
+    slots: Box<[Slot]>,
+}
+
+impl Default for Observer {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            next: AtomicU64::new(0),
+            slots: (0..CAPACITY).map(|_| Slot::default()).collect(),
+        }
+    }
+}
+
+impl Debug for Observer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Observer")
+            .field("enabled", &self.is_enabled())
+            .field("recorded", &self.next.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl Observer {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_enabled(&self, v: bool) {
+        self.enabled.store(v, Ordering::Relaxed)
+    }
+
+    /// Returns the time to start measuring, or `None` if disabled.
+    pub(crate) fn start(&self) -> Option<Instant> {
+        self.is_enabled().then(Instant::now)
+    }
+
+    /// Record a sample of `op` which transferred `bytes` in `duration`.
+    pub(crate) fn record(&self, op: Operation, bytes: u64, duration: Duration) {
+        if !self.is_enabled() {
+            return;
+        }
+        let idx = OPERATIONS
+            .iter()
+            .position(|v| *v == op)
+            .expect("operation must be observed");
+
+        let ticket = self.next.fetch_add(1, Ordering::Relaxed);
+        let slot = &self.slots[(ticket % CAPACITY as u64) as usize];
+        slot.seq.store(0, Ordering::Relaxed);
+        fence(Ordering::Release);
+        slot.op.store(idx as u64, Ordering::Relaxed);
+        slot.bytes.store(bytes, Ordering::Relaxed);
+        slot.nanos
+            .store(duration.as_nanos() as u64, Ordering::Relaxed);
+        slot.seq.store(ticket + 1, Ordering::Release);
+    }
+
+    /// Record a sample of `op` started at `start`, if it's measured.
+    pub(crate) fn record_since(&self, op: Operation, bytes: u64, start: Option<Instant>) {
+        if let Some(start) = start {
+            self.record(op, bytes, start.elapsed());
+        }
+    }
+
+    /// Collect published samples, the oldest first.
+    pub(crate) fn snapshot(&self) -> Observations {
+        let mut samples: Vec<(u64, Sample)> = Vec::with_capacity(CAPACITY);
+        for slot in self.slots.iter() {
+            let seq = slot.seq.load(Ordering::Acquire);
+            if seq == 0 {
+                continue;
+            }
+            let op = slot.op.load(Ordering::Relaxed);
+            let bytes = slot.bytes.load(Ordering::Relaxed);
+            let nanos = slot.nanos.load(Ordering::Relaxed);
+            fence(Ordering::Acquire);
+            if slot.seq.load(Ordering::Relaxed) != seq {
+                continue;
+            }
+
+            samples.push((
+                seq,
+                Sample {
+                    op: OPERATIONS[op as usize],
+                    bytes,
+                    duration: Duration::from_nanos(nanos),
+                },
+            ));
+        }
+        samples.sort_by_key(|(seq, _)| *seq);
+
+        Observations {
+            samples: samples.into_iter().map(|(_, v)| v).collect(),
+        }
+    }
+}
+
+/// A sample of a request sent to the backend.
+///
+/// Reads are measured from sending the request to reaching EOF (or being
+/// dropped), so `duration` covers the transfer of `bytes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub op: Operation,
+    pub bytes: u64,
+    pub duration: Duration,
+}
+
+/// Recent samples of requests sent by an [`Operator`][crate::Operator],
+/// returned by [`Operator::observations`][crate::Operator::observations].
+///
+/// At most 1024 samples of all operations are kept.
+#[derive(Debug, Clone, Default)]
+pub struct Observations {
+    samples: Vec<Sample>,
+}
+
+impl Observations {
+    /// Returns all samples, the oldest first.
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// Returns the `p`th percentile (in `[0, 1]`) of the latency of `op`,
+    /// or `None` if there is no sample of it.
+    pub fn latency(&self, op: Operation, p: f64) -> Option<Duration> {
+        let mut values: Vec<_> = self
+            .samples
+            .iter()
+            .filter(|v| v.op == op)
+            .map(|v| v.duration)
+            .collect();
+        values.sort();
+        percentile(&values, p).copied()
+    }
+
+    /// Returns the `p`th percentile (in `[0, 1]`) of the throughput of `op`
+    /// in bytes per second, or `None` if there is no sample of it which
+    /// transferred data.
+    pub fn throughput(&self, op: Operation, p: f64) -> Option<f64> {
+        let mut values: Vec<_> = self
+            .samples
+            .iter()
+            .filter(|v| v.op == op && v.bytes > 0 && !v.duration.is_zero())
+            .map(|v| v.bytes as f64 / v.duration.as_secs_f64())
+            .collect();
+        values.sort_by(|a, b| a.total_cmp(b));
+        percentile(&values, p).copied()
+    }
+}
+
+/// Returns the `p`th percentile of sorted `values` by nearest rank.
+fn percentile<T>(values: &[T], p: f64) -> Option<&T> {
+    if values.is_empty() {
+        return None;
+    }
+    let rank = (p.clamp(0.0, 1.0) * values.len() as f64).ceil() as usize;
+    values.get(rank.saturating_sub(1))
+}
@@ -12,19 +12,111 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
+use anyhow::anyhow;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::lazy::LazyAccessor;
+use crate::observe::Observations;
+use crate::observe::Observer;
+use crate::ops::ListOptions;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpCreate;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::PresignOperation;
+use crate::ops::PresignedRequest;
+use crate::path::validate_path;
+use crate::path::PathCheckAccessor;
+use crate::scheme::build_accessor;
+use crate::stats::Stats;
+use crate::stats::StatsAccessor;
+use crate::writers::PartitionedWriter;
 use crate::Accessor;
+use crate::AccessorBuilder;
+use crate::AccessorMetadata;
 use crate::Layer;
 use crate::Object;
+use crate::ObjectMode;
 use crate::ObjectStream;
+use crate::OperatorStats;
 
 /// User-facing APIs for object and object streams.
+///
+/// # Thread Safety
+///
+/// `Operator` is cheap to clone, clones share the same backend and stats.
+/// It's guaranteed to be `Send + Sync`, so that it could be shared across
+/// tasks and threads directly. So are [`Object`] and [`Writer`], while
+/// [`Reader`] and [`ObjectStream`] are `Send`. The guarantees are checked
+/// at compile time.
+///
+/// [`Accessor`] requires `Send + Sync`, layers must keep their state in
+/// atomics or locks instead of `Cell` or `RefCell`.
+///
+/// [`Writer`]: crate::Writer
+/// [`Reader`]: crate::Reader
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use futures::AsyncReadExt;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let op = Operator::new(memory::Backend::build().finish().await?);
+///     op.object("test")
+///         .writer()
+///         .write_bytes(b"Hello, World!".to_vec())
+///         .await?;
+///
+///     let mut tasks = Vec::new();
+///     for _ in 0..100 {
+///         let op = op.clone();
+///         tasks.push(tokio::spawn(async move {
+///             let mut buf = Vec::new();
+///             op.object("test").reader().read_to_end(&mut buf).await?;
+///             assert_eq!(buf, b"Hello, World!");
+///             Ok::<_, std::io::Error>(())
+///         }));
+///     }
+///     for task in tasks {
+///         task.await??;
+///     }
+///
+///     Ok(())
+/// }
+/// ```
 #[derive(Clone)]
 pub struct Operator {
     accessor: Arc<dyn Accessor>,
+    stats: Arc<Stats>,
+    observer: Arc<Observer>,
 }
 
+// Make sure the thread safety guarantees documented above are not broken
+// by accident, like a new field or layer using `Rc` or `RefCell`.
+const _: () = {
+    const fn assert_send_sync_clone<T: Send + Sync + Clone>() {}
+    const fn assert_send_sync<T: Send + Sync>() {}
+    const fn assert_send<T: Send>() {}
+
+    assert_send_sync_clone::<Operator>();
+    assert_send_sync_clone::<Object>();
+    assert_send_sync::<crate::Writer>();
+    assert_send::<crate::Reader>();
+    assert_send::<ObjectStream>();
+};
+
 impl Operator {
     /// Create a new operator.
     ///
@@ -64,7 +156,122 @@ impl Operator {
     /// }
     /// ```
     pub fn new(accessor: Arc<dyn Accessor>) -> Self {
-        Self { accessor }
+        let stats = Arc::new(Stats::default());
+        let observer = Arc::new(Observer::default());
+        Self {
+            accessor: Arc::new(PathCheckAccessor::new(Arc::new(StatsAccessor::new(
+                accessor,
+                stats.clone(),
+                observer.clone(),
+            )))),
+            stats,
+            observer,
+        }
+    }
+
+    /// Create a new operator which defers the backend construction until
+    /// the first operation.
+    ///
+    /// The constructed backend will be cached and reused by later operations.
+    /// Errors happened during construction will be returned by the first
+    /// operation instead.
+    ///
+    /// # Note
+    ///
+    /// [`Operator::metadata`] returns the default metadata until the backend
+    /// constructed, so the path limits of the backend will not be checked
+    /// by the first operation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opendal::services::fs;
+    /// use opendal::Operator;
+    ///
+    /// let mut builder = fs::Backend::build();
+    /// builder.root("/tmp");
+    ///
+    /// // No async context is required here.
+    /// let op = Operator::lazy(builder);
+    /// ```
+    pub fn lazy(builder: impl AccessorBuilder) -> Self {
+        Self::new(Arc::new(LazyAccessor::new(builder)))
+    }
+
+    /// Create a new operator of `scheme` from `config`, schemes registered
+    /// by [`register_scheme`][crate::register_scheme] are supported too.
+    ///
+    /// Returns an error with [`Kind::BackendNotSupported`] if the scheme is
+    /// unknown.
+    ///
+    /// [`Kind::BackendNotSupported`]: crate::error::Kind::BackendNotSupported
+    pub async fn from_map(scheme: &str, config: HashMap<String, String>) -> Result<Self> {
+        Ok(Self::new(build_accessor(scheme, config).await?))
+    }
+
+    /// Create a new operator from uri like `s3://bucket/root?endpoint=..`.
+    ///
+    /// The host is passed as `host` (used as `bucket` by s3), the path as
+    /// `root` and every query pair as is to [`Operator::from_map`]. Query
+    /// values are not percent decoded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::from_uri("fs:///tmp").await?;
+    ///     let op = Operator::from_uri("memory://").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn from_uri(uri: &str) -> Result<Self> {
+        let (scheme, rest) = uri.split_once("://").ok_or_else(|| Error::Backend {
+            kind: Kind::BackendConfigurationInvalid,
+            context: HashMap::from([("uri".to_string(), uri.to_string())]),
+            source: anyhow!("uri must be in the form of `scheme://host/path`"),
+        })?;
+        let (location, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let (host, path) = location.split_once('/').unwrap_or((location, ""));
+
+        let mut config = HashMap::new();
+        if !host.is_empty() {
+            config.insert("host".to_string(), host.to_string());
+        }
+        if !path.is_empty() {
+            config.insert("root".to_string(), format!("/{}", path));
+        }
+        for (k, v) in query
+            .split('&')
+            .filter(|kv| !kv.is_empty())
+            .map(|kv| kv.split_once('=').unwrap_or((kv, "")))
+        {
+            config.insert(k.to_string(), v.to_string());
+        }
+
+        Self::from_map(scheme, config).await
+    }
+
+    /// Create a new operator of `scheme` from environment variables like
+    /// `OPENDAL_S3_BUCKET`.
+    ///
+    /// Every variable prefixed by `OPENDAL_{SCHEME}_` is passed to
+    /// [`Operator::from_map`] with the rest of its name in lowercase, for
+    /// example `OPENDAL_S3_ACCESS_KEY_ID` is passed as `access_key_id`.
+    pub async fn from_env(scheme: &str) -> Result<Self> {
+        let prefix = format!("OPENDAL_{}_", scheme.to_uppercase());
+        let config = std::env::vars()
+            .filter_map(|(k, v)| {
+                k.strip_prefix(&prefix)
+                    .map(|k| (k.to_lowercase(), v.to_string()))
+            })
+            .collect();
+
+        Self::from_map(scheme, config).await
     }
 
     /// Create a new layer.
@@ -72,10 +279,252 @@ impl Operator {
     pub fn layer(self, layer: impl Layer) -> Self {
         Operator {
             accessor: layer.layer(self.accessor.clone()),
+            stats: self.stats,
+            observer: self.observer,
+        }
+    }
+
+    /// Check if the configured bucket exists and is accessible.
+    ///
+    /// - s3: send `HeadBucket` to the bucket.
+    /// - fs: check if the root dir exists.
+    /// - memory: always exists.
+    ///
+    /// Returns an error with [`Kind::ObjectPermissionDenied`] instead of
+    /// `false` if we don't have the permission to access it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     assert!(op.bucket_exists().await?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`Kind::ObjectPermissionDenied`]: crate::error::Kind::ObjectPermissionDenied
+    pub async fn bucket_exists(&self) -> Result<bool> {
+        self.accessor.bucket_exists().await
+    }
+
+    /// List multipart uploads under `prefix` that have been created but not
+    /// completed or aborted.
+    ///
+    /// Failed uploads could leave such uploads behind, and their parts are
+    /// charged until aborted. Maintenance jobs could abort them via
+    /// [`Operator::abort_multipart_upload`].
+    ///
+    /// Returns an error with [`Kind::Unsupported`] if the backend doesn't
+    /// support multipart upload.
+    ///
+    /// [`Kind::Unsupported`]: crate::error::Kind::Unsupported
+    pub async fn list_multipart_uploads(&self, prefix: &str) -> Result<Vec<MultipartUpload>> {
+        self.accessor
+            .list_multipart_uploads(&OpListMultipartUploads::new(prefix))
+            .await
+    }
+
+    /// Abort the multipart upload `upload_id` of `path`, all uploaded parts
+    /// will be freed.
+    ///
+    /// Returns an error with [`Kind::ObjectNotExist`] if the upload doesn't
+    /// exist, or [`Kind::Unsupported`] if the backend doesn't support
+    /// multipart upload.
+    ///
+    /// [`Kind::ObjectNotExist`]: crate::error::Kind::ObjectNotExist
+    /// [`Kind::Unsupported`]: crate::error::Kind::Unsupported
+    pub async fn abort_multipart_upload(&self, path: &str, upload_id: &str) -> Result<()> {
+        self.accessor
+            .abort_multipart_upload(&OpAbortMultipartUpload::new(path, upload_id))
+            .await
+    }
+
+    /// Create the dir at `path` along with all its missing parents, like
+    /// [`std::fs::create_dir_all`].
+    ///
+    /// `path` is treated as a dir whether or not it ends with `/`. Dirs are
+    /// real on fs and will be created recursively. Flat backends like s3
+    /// and memory get an empty marker object (like `a/`, `a/b/`) for every
+    /// level, so that `stat` and `list` behave the same as fs afterwards.
+    ///
+    /// Creating existing dirs is fine, while a file in the way fails the
+    /// creation on fs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::TryStreamExt;
+    /// use opendal::services::memory;
+    /// use opendal::ObjectMode;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.create_dir_all("a/b/c/").await?;
+    ///
+    ///     let meta = op.object("a/b/").metadata().await?;
+    ///     assert_eq!(meta.mode(), ObjectMode::DIR);
+    ///
+    ///     let obs: Vec<_> = op.objects("a/").try_collect().await?;
+    ///     assert_eq!(obs[0].path(), "a/b/");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn create_dir_all(&self, path: &str) -> Result<()> {
+        let p = validate_path("create", path, &self.metadata())?;
+        if p == "/" {
+            return Ok(());
         }
+
+        // Create from the top, so that a failure never leaves a dir
+        // without its parents.
+        let mut dir = String::with_capacity(p.len() + 1);
+        for seg in p.split('/').filter(|v| !v.is_empty()) {
+            dir.push_str(seg);
+            dir.push('/');
+            self.accessor
+                .create(&OpCreate::new(&dir, ObjectMode::DIR))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Generate a presigned request to perform `op` on `path`, which is
+    /// valid for `expire`.
+    ///
+    /// Clients could send the request directly without credentials, the
+    /// request must be sent with the returned method and headers.
+    ///
+    /// Returns an error with [`Kind::Unsupported`] if the backend can't
+    /// presign `op`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use anyhow::Result;
+    /// use opendal::ops::PresignOperation;
+    /// use opendal::Operator;
+    /// # use opendal::services::memory;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    /// #   let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     let req = op
+    ///         .presign("test", PresignOperation::Delete, Duration::from_secs(3600))
+    ///         .await?;
+    ///     println!("{} {}", req.method(), req.uri());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`Kind::Unsupported`]: crate::error::Kind::Unsupported
+    pub async fn presign(
+        &self,
+        path: &str,
+        op: PresignOperation,
+        expire: Duration,
+    ) -> Result<PresignedRequest> {
+        self.accessor
+            .presign(&OpPresign::new(path, op, expire))
+            .await
+    }
+
+    /// Get a snapshot of the traffic sent to the backend by this operator
+    /// and its clones.
+    ///
+    /// The counters are always on, read [`OperatorStats`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::ops::Operation;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![0; 16]).await?;
+    ///
+    ///     let stats = op.stats();
+    ///     assert_eq!(stats.bytes_written(), 16);
+    ///     assert_eq!(stats.requests(Operation::Write), 1);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn stats(&self) -> OperatorStats {
+        self.stats.snapshot()
+    }
+
+    /// Reset all counters of [`Operator::stats`] to zero.
+    ///
+    /// Counters are reset one by one, operations running concurrently could
+    /// be partially counted.
+    pub fn reset_stats(&self) {
+        self.stats.reset()
+    }
+
+    /// Returns samples of recent requests sent to the backend, along with
+    /// their percentiles.
+    ///
+    /// Samples are recorded beside [`Operator::stats`] and used by adaptive
+    /// behaviors like [`Object::fetch_range_adaptive`], read [`Observations`]
+    /// for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::ops::Operation;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///     op.object("test").writer().write_bytes(vec![0; 16]).await?;
+    ///
+    ///     let obs = op.observations();
+    ///     assert_eq!(obs.samples()[0].bytes, 16);
+    ///     println!("p99 of write: {:?}", obs.latency(Operation::Write, 0.99));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn observations(&self) -> Observations {
+        self.observer.snapshot()
+    }
+
+    /// Enable or disable recording [`Operator::observations`], enabled by
+    /// default.
+    ///
+    /// Clones share the same setting. Adaptive behaviors fall back to their
+    /// defaults without observations.
+    pub fn set_observations_enabled(&self, v: bool) {
+        self.observer.set_enabled(v)
     }
 
-    fn inner(&self) -> Arc<dyn Accessor> {
+    /// Get the metadata of the underlying accessor.
+    pub fn metadata(&self) -> AccessorMetadata {
+        self.accessor.metadata()
+    }
+
+    pub(crate) fn inner(&self) -> Arc<dyn Accessor> {
         self.accessor.clone()
     }
 
@@ -128,7 +577,45 @@ impl Operator {
     /// }
     /// ```
     pub fn object(&self, path: &str) -> Object {
-        Object::new(self.inner(), path)
+        Object::new(self.inner(), path).with_observer(self.observer.clone())
+    }
+
+    /// Create a new object handle with the path normalized and validated.
+    ///
+    /// Unlike [`Operator::object`], invalid paths are rejected here with
+    /// [`Kind::ObjectPathInvalid`][crate::error::Kind::ObjectPathInvalid]
+    /// instead of failing later inside the backend call.
+    ///
+    /// - Empty segments are removed, so `//dir//file` becomes `dir/file`.
+    /// - Paths containing control characters (including NUL) or relative
+    ///   segments (`.` and `..`) are rejected.
+    /// - Paths exceeding the limits of
+    ///   [`AccessorMetadata`][crate::AccessorMetadata] are rejected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use opendal::error::Kind;
+    /// use opendal::services::memory;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let op = Operator::new(memory::Backend::build().finish().await?);
+    ///
+    ///     let o = op.try_object("/dir//test_file")?;
+    ///     assert_eq!(o.path(), "dir/test_file");
+    ///
+    ///     let err = op.try_object("dir/../test_file").unwrap_err();
+    ///     assert_eq!(err.kind(), Kind::ObjectPathInvalid);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn try_object(&self, path: &str) -> Result<Object> {
+        let p = validate_path("object", path, &self.metadata())?;
+        Ok(Object::new(self.inner(), &p).with_observer(self.observer.clone()))
     }
 
     /// Create a new object stream handle to list objects.
@@ -181,4 +668,25 @@ impl Operator {
     pub fn objects(&self, path: &str) -> ObjectStream {
         ObjectStream::new(self.inner(), path)
     }
+
+    /// Create a new [`ObjectStream`] which lists `path` with given
+    /// [`ListOptions`], see [`Object::list_with`][crate::Object::list_with].
+    pub fn objects_with(&self, path: &str, opts: ListOptions) -> ObjectStream {
+        ObjectStream::with_options(self.inner(), path, &opts)
+    }
+
+    /// Create a new [`PartitionedWriter`] which splits a stream into parts
+    /// at paths generated by `template`, like `logs/part-{n}`.
+    ///
+    /// Read [`PartitionedWriter`] for more details.
+    pub fn partitioned_writer(&self, template: &str) -> Result<PartitionedWriter> {
+        PartitionedWriter::new(self.inner(), template)
+    }
+
+    /// Create a new object stream handle with the path normalized and
+    /// validated, see [`Operator::try_object`] for the rules.
+    pub fn try_objects(&self, path: &str) -> Result<ObjectStream> {
+        let p = validate_path("list", path, &self.metadata())?;
+        Ok(ObjectStream::new(self.inner(), &p))
+    }
 }
@@ -14,11 +14,183 @@
 
 //! Operations used by [`Accessor`][crate::Accessor]
 
+use std::collections::BTreeMap;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+use anyhow::Result;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::readers::Digest;
+use crate::readers::DigestKind;
+use crate::MetaField;
+use crate::Metadata;
+use crate::ObjectMode;
+
+/// All operations that an [`Accessor`][crate::Accessor] could serve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    BucketExists,
+    Read,
+    Write,
+    Append,
+    Stat,
+    Delete,
+    List,
+    Select,
+    Presign,
+    ListMultipartUploads,
+    AbortMultipartUpload,
+    Retention,
+    Copy,
+    Create,
+}
+
+impl Operation {
+    /// Returns `true` if replaying a succeeded operation has no extra
+    /// effect.
+    ///
+    /// `Write` uploads the whole object, so replaying it with the same data
+    /// leaves the same object. `Append` will duplicate the data instead.
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, Operation::Append)
+    }
+}
+
+/// Options for reading an object, see [`Object::reader_with`][crate::Object::reader_with].
+///
+/// All options are optional, unset options keep the default behavior of
+/// [`Object::reader`][crate::Object::reader]. New options could be added
+/// in the future, so it can only be built via [`ReadOptions::new`].
+///
+/// # Example
+///
+/// ```
+/// use opendal::ops::ReadOptions;
+///
+/// let opts = ReadOptions::new().offset(4).size(8);
+/// assert_eq!(opts.offset, Some(4));
+/// assert_eq!(opts.size, Some(8));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReadOptions {
+    pub offset: Option<u64>,
+    pub size: Option<u64>,
+    pub version_id: Option<String>,
+    /// Allow reading paths ending with `/`.
+    ///
+    /// Paths ending with `/` are treated as dirs and reading them will be
+    /// rejected with [`Kind::Unsupported`]. But services like s3 allow
+    /// keys like `abc/` to carry data (some tools create them), enable
+    /// this to read their data.
+    pub allow_trailing_slash: bool,
+    /// Buffer size of the reader, see [`Reader::buffer`][crate::Reader::buffer].
+    pub buffer: Option<usize>,
+    /// Decode the content by its `Content-Encoding`, see
+    /// [`ReadOptions::decode_content`].
+    pub decode_content: bool,
+    /// Hash the content as it's read, see [`ReadOptions::with_digest`].
+    pub digest: Option<DigestKind>,
+}
+
+impl ReadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start reading at `offset`.
+    #[must_use]
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Read at most `size` bytes.
+    #[must_use]
+    pub fn size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Read this version of the object instead of the latest one, see
+    /// [`OpRead::version_id`].
+    #[must_use]
+    pub fn version_id(mut self, version_id: &str) -> Self {
+        self.version_id = Some(version_id.to_string());
+        self
+    }
+
+    /// Allow reading paths ending with `/`, see [`ReadOptions::allow_trailing_slash`][field@ReadOptions::allow_trailing_slash].
+    #[must_use]
+    pub fn allow_trailing_slash(mut self) -> Self {
+        self.allow_trailing_slash = true;
+        self
+    }
+
+    /// Buffer at most `size` bytes read from the backend, `0` means
+    /// unbuffered.
+    #[must_use]
+    pub fn buffer(mut self, size: usize) -> Self {
+        self.buffer = Some(size);
+        self
+    }
+
+    /// Decode the content by its [`Metadata::content_encoding`][crate::Metadata::content_encoding]
+    /// (like `gzip` set by static site uploaders), instead of returning the
+    /// encoded bytes.
+    ///
+    /// - Objects without encoding or with `identity` are read as is.
+    /// - Ranges refer to the encoded content, so ranged reads and seeking
+    ///   will be rejected with [`Kind::Unsupported`][crate::error::Kind::Unsupported],
+    ///   and failed reads will not be resumed.
+    ///
+    /// # Features
+    ///
+    /// Every encoding is decoded only if its feature is enabled:
+    /// `compress-gzip` for `gzip`, `compress-deflate` for `deflate`,
+    /// `compress-brotli` for `br` and `compress-zstd` for `zstd`. Objects
+    /// of other encodings will be rejected with [`Kind::Unsupported`][crate::error::Kind::Unsupported]
+    /// instead of returning the encoded bytes silently.
+    #[must_use]
+    pub fn decode_content(mut self, v: bool) -> Self {
+        self.decode_content = v;
+        self
+    }
+
+    /// Hash the content in `kind` as it's read, the digest will be
+    /// available via [`Reader::digest`][crate::Reader::digest] after EOF.
+    #[must_use]
+    pub fn with_digest(mut self, kind: DigestKind) -> Self {
+        self.digest = Some(kind);
+        self
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct OpRead {
     pub path: String,
     pub offset: Option<u64>,
     pub size: Option<u64>,
+    /// Read this version of the object instead of the latest one.
+    ///
+    /// Only backends with [`AccessorMetadata::can_read_version`][crate::AccessorMetadata::can_read_version]
+    /// accept it, others will return an error with
+    /// [`Kind::Unsupported`][crate::error::Kind::Unsupported].
+    pub version_id: Option<String>,
+}
+
+impl OpRead {
+    pub fn new(path: &str, opts: &ReadOptions) -> Self {
+        Self {
+            path: path.to_string(),
+            offset: opts.offset,
+            size: opts.size,
+            version_id: opts.version_id.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -34,44 +206,888 @@ impl OpStat {
     }
 }
 
+/// Options for writing an object, see [`Object::writer_with`][crate::Object::writer_with].
+///
+/// All options are optional. Options that the backend can't honor will be
+/// ignored, or rejected with [`Kind::Unsupported`] if [`WriteOptions::strict`]
+/// is enabled. New options could be added in the future, so it can only be
+/// built via [`WriteOptions::new`].
+///
+/// # Example
+///
+/// ```
+/// use opendal::ops::WriteOptions;
+///
+/// let opts = WriteOptions::new()
+///     .content_type("application/json")
+///     .cache_control("no-cache");
+/// assert_eq!(opts.content_type.as_deref(), Some("application/json"));
+/// assert!(!opts.strict);
+/// ```
+///
+/// # TODO
+///
+/// Content digests are covered by [`WriteOptions::with_digest`], and
+/// `if_not_exists` is the only conditional write for now. `If-Match` on
+/// s3 needs sdk support.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WriteOptions {
+    /// Upload data in parts of this size if the backend supports multipart
+    /// upload and `size` is larger than it.
+    ///
+    /// Backends reject part sizes they can't upload with, like s3 which
+    /// requires at least 5 MiB per part and at most 10000 parts, with
+    /// [`Kind::Unsupported`].
+    pub part_size: Option<u64>,
+    /// Max parts in flight while uploading in multipart, default to 1.
+    ///
+    /// Every part in flight holds its own buffer, so memory usage is
+    /// bounded by `parallelism * part_size`.
+    pub parallelism: Option<usize>,
+    /// Don't make the object observable at its final path until the write
+    /// finished.
+    pub commit_visible: bool,
+    /// `Content-Type` of the object.
+    pub content_type: Option<String>,
+    /// `Content-Language` of the object.
+    pub content_language: Option<String>,
+    /// `Cache-Control` of the object.
+    pub cache_control: Option<String>,
+    /// `Expires` of the object, after which caches should consider it
+    /// stale.
+    pub expires: Option<SystemTime>,
+    /// Tags of the object as key value pairs.
+    ///
+    /// Unlike `expires`, tags could be matched by the lifecycle rules of
+    /// the bucket. For example, tag temporary uploads with `temp=true`, and
+    /// a rule filtered by that tag will delete them after some days.
+    pub tags: Vec<(String, String)>,
+    /// User defined metadata of the object, s3 stores them as
+    /// `x-amz-meta-{key}`.
+    pub user_metadata: BTreeMap<String, String>,
+    /// Canned ACL of the object, like `private` or `public-read`.
+    pub acl: Option<String>,
+    /// Storage class of the object, like `STANDARD_IA` on s3.
+    pub storage_class: Option<String>,
+    /// Only write if the object does not exist, fails with
+    /// [`Kind::PreconditionFailed`] otherwise.
+    ///
+    /// Only backends with [`AccessorMetadata::can_write_if_not_exists`][crate::AccessorMetadata::can_write_if_not_exists]
+    /// support it, others will fail with [`Kind::Unsupported`] no matter
+    /// whether `strict` is enabled.
+    pub if_not_exists: bool,
+    /// Reject options that the backend can't honor instead of ignoring
+    /// them.
+    pub strict: bool,
+    /// After the write succeeded, poll `stat` until the object is visible
+    /// or this timeout elapsed, see [`WriteOptions::await_visibility`].
+    pub await_visibility: Option<Duration>,
+    /// Also wait until the visible object has the written length, only
+    /// takes effect along with `await_visibility`.
+    pub await_length: bool,
+    /// Hash the content as it's written, see [`WriteOptions::with_digest`].
+    pub digest: Option<DigestKind>,
+}
+
+impl WriteOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn part_size(mut self, size: u64) -> Self {
+        self.part_size = Some(size);
+        self
+    }
+
+    #[must_use]
+    pub fn parallelism(mut self, n: usize) -> Self {
+        self.parallelism = Some(n);
+        self
+    }
+
+    #[must_use]
+    pub fn commit_visible(mut self, v: bool) -> Self {
+        self.commit_visible = v;
+        self
+    }
+
+    #[must_use]
+    pub fn content_type(mut self, v: &str) -> Self {
+        self.content_type = Some(v.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn content_language(mut self, v: &str) -> Self {
+        self.content_language = Some(v.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn cache_control(mut self, v: &str) -> Self {
+        self.cache_control = Some(v.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn expires(mut self, t: SystemTime) -> Self {
+        self.expires = Some(t);
+        self
+    }
+
+    /// Add a tag to the object, see [`WriteOptions::tags`][field@WriteOptions::tags].
+    #[must_use]
+    pub fn tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    #[must_use]
+    pub fn user_metadata(mut self, key: &str, value: &str) -> Self {
+        self.user_metadata
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn acl(mut self, v: &str) -> Self {
+        self.acl = Some(v.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn storage_class(mut self, v: &str) -> Self {
+        self.storage_class = Some(v.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn if_not_exists(mut self, v: bool) -> Self {
+        self.if_not_exists = v;
+        self
+    }
+
+    #[must_use]
+    pub fn strict(mut self, v: bool) -> Self {
+        self.strict = v;
+        self
+    }
+
+    /// Wait until the written object is visible to `stat` before returning.
+    ///
+    /// Some S3-compatible gateways are eventually consistent: a `stat`
+    /// right after a successful write could still return
+    /// [`Kind::ObjectNotExist`]. With this option, `stat` will be polled
+    /// with a short backoff after the write succeeded, and the write fails
+    /// with [`Kind::VisibilityTimeout`] if the object is still invisible
+    /// after `timeout`.
+    ///
+    /// This is unnecessary on strongly consistent backends like fs, memory
+    /// and AWS S3, which cost an extra `stat` for nothing. Nothing will be
+    /// sent if it's not set.
+    #[must_use]
+    pub fn await_visibility(mut self, timeout: Duration) -> Self {
+        self.await_visibility = Some(timeout);
+        self
+    }
+
+    /// Also wait until the visible object has the written length, which
+    /// covers overwrites that are served stale.
+    #[must_use]
+    pub fn await_length(mut self, v: bool) -> Self {
+        self.await_length = v;
+        self
+    }
+
+    /// Hash the content in `kind` as it's written, the digest will be
+    /// returned in [`Written::digest`][crate::Written::digest].
+    ///
+    /// Content written in one request from bytes is hashed before sending,
+    /// and backends could send the digest for the server to verify, like s3
+    /// which sends CRC32C and SHA-256 in `x-amz-checksum-*`. Content from
+    /// readers or uploaded in multipart is hashed as it flows instead.
+    #[must_use]
+    pub fn with_digest(mut self, kind: DigestKind) -> Self {
+        self.digest = Some(kind);
+        self
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct OpWrite {
     pub path: String,
     pub size: u64,
+    pub options: WriteOptions,
+    /// Digest of the whole content computed before writing, backends
+    /// could send it for the server to verify.
+    pub checksum: Option<Digest>,
+}
+
+impl OpWrite {
+    pub fn new(path: &str, size: u64) -> Self {
+        Self {
+            path: path.to_string(),
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if the write is conditional, like `if_not_exists`.
+    ///
+    /// Such writes are not idempotent: replaying a succeeded one fails its
+    /// precondition.
+    pub(crate) fn has_precondition(&self) -> bool {
+        self.options.if_not_exists
+    }
+
+    /// Fail with [`Kind::Unsupported`] if options not listed in `supported`
+    /// are set while `strict` is enabled.
+    ///
+    /// `if_not_exists` is not covered, backends must always reject it if
+    /// they can't honor it.
+    pub(crate) fn check_supported(&self, supported: &[&str]) -> crate::error::Result<()> {
+        let o = &self.options;
+        if !o.strict {
+            return Ok(());
+        }
+
+        let unsupported: Vec<_> = [
+            ("part_size", o.part_size.is_some()),
+            ("parallelism", o.parallelism.is_some()),
+            ("commit_visible", o.commit_visible),
+            ("content_type", o.content_type.is_some()),
+            ("content_language", o.content_language.is_some()),
+            ("cache_control", o.cache_control.is_some()),
+            ("expires", o.expires.is_some()),
+            ("tags", !o.tags.is_empty()),
+            ("user_metadata", !o.user_metadata.is_empty()),
+            ("acl", o.acl.is_some()),
+            ("storage_class", o.storage_class.is_some()),
+        ]
+        .into_iter()
+        .filter(|(name, set)| *set && !supported.contains(name))
+        .map(|(name, _)| name)
+        .collect();
+        if unsupported.is_empty() {
+            return Ok(());
+        }
+
+        Err(Error::Object {
+            kind: Kind::Unsupported,
+            op: "write",
+            path: self.path.clone(),
+            source: anyhow!("write options {:?} are not supported", unsupported),
+        })
+    }
+}
+
+/// Args for `append` operation.
+///
+/// Append is not idempotent: replaying a succeeded append duplicates the
+/// data, so it must never be retried blindly.
+#[derive(Debug, Clone, Default)]
+pub struct OpAppend {
+    pub path: String,
+    pub size: u64,
+}
+
+impl OpAppend {
+    pub fn new(path: &str, size: u64) -> Self {
+        Self {
+            path: path.to_string(),
+            size,
+        }
+    }
+}
+
+/// What will be removed by [`OpDelete`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeleteMode {
+    /// Delete the object as the backend does by default, versioned s3
+    /// buckets keep old versions and add a delete marker.
+    #[default]
+    Default,
+    /// Remove all versions of the object including delete markers, so
+    /// that the key is truly gone.
+    ///
+    /// Only backends with [`AccessorMetadata::can_delete_all_versions`][crate::AccessorMetadata::can_delete_all_versions]
+    /// support it.
+    AllVersions,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct OpDelete {
     pub path: String,
+    /// Only delete the object if its current etag equals to this one.
+    pub if_match: Option<String>,
+    /// Only delete the object if it has not been modified since this time.
+    pub if_unmodified_since: Option<SystemTime>,
+    pub mode: DeleteMode,
 }
 
 impl OpDelete {
     pub fn new(path: &str) -> Self {
         Self {
             path: path.to_string(),
+            ..Default::default()
         }
     }
+
+    /// Returns `true` if this delete carries any precondition.
+    pub fn is_conditional(&self) -> bool {
+        self.if_match.is_some() || self.if_unmodified_since.is_some()
+    }
+
+    /// Check the preconditions against the object's current etag and
+    /// last modified time.
+    ///
+    /// Returns `false` if any precondition is not satisfied. A precondition
+    /// is treated as unsatisfied if the backend can't provide the value to
+    /// compare with.
+    pub fn check_precondition(
+        &self,
+        etag: Option<&str>,
+        last_modified: Option<SystemTime>,
+    ) -> bool {
+        if let Some(expected) = &self.if_match {
+            if etag != Some(expected.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(since) = self.if_unmodified_since {
+            match last_modified {
+                Some(t) if t <= since => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
 }
 
+/// How the path of [`OpList`] is matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListMode {
+    /// List the dir at the path like `ls`.
+    ///
+    /// Both `abc` and `abc/` list the children of dir `abc/`, and
+    /// children of sub dirs are folded into dir entries. If `abc` is a
+    /// file, only the file itself will be listed. Keys like `abcdef`
+    /// never match.
+    #[default]
+    Dir,
+    /// List all keys that start with the path as a string, `abc` matches
+    /// `abc/d/e` and `abcdef`. Sub dirs are not folded.
+    ///
+    /// Only backends with flat namespace (like s3 and memory) support it,
+    /// others will return an error with
+    /// [`Kind::Unsupported`][crate::error::Kind::Unsupported].
+    Prefix,
+}
+
+/// Options for listing objects, see [`Object::list_with`][crate::Object::list_with].
+///
+/// All options are optional. New options could be added in the future, so
+/// it can only be built via [`ListOptions::new`].
+///
+/// # Example
+///
+/// ```
+/// use opendal::ops::ListOptions;
+///
+/// let opts = ListOptions::new().min_size(1).fetch_owner(true);
+/// assert_eq!(opts.min_size, Some(1));
+/// assert!(opts.fetch_owner);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ListOptions {
+    /// See [`OpList::mode`].
+    pub mode: ListMode,
+    /// See [`OpList::modified_after`].
+    pub modified_after: Option<SystemTime>,
+    /// See [`OpList::min_size`].
+    pub min_size: Option<u64>,
+    /// See [`OpList::max_size`].
+    pub max_size: Option<u64>,
+    /// See [`OpList::start_after`].
+    pub start_after: Option<String>,
+    /// See [`ObjectStream::snapshot`][crate::ObjectStream::snapshot].
+    pub snapshot: bool,
+    /// See [`ObjectStream::fetch_owner`][crate::ObjectStream::fetch_owner].
+    pub fetch_owner: bool,
+}
+
+impl ListOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match the path in `mode`, default to [`ListMode::Dir`].
+    #[must_use]
+    pub fn mode(mut self, mode: ListMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    #[must_use]
+    pub fn modified_after(mut self, t: SystemTime) -> Self {
+        self.modified_after = Some(t);
+        self
+    }
+
+    #[must_use]
+    pub fn min_size(mut self, size: u64) -> Self {
+        self.min_size = Some(size);
+        self
+    }
+
+    #[must_use]
+    pub fn max_size(mut self, size: u64) -> Self {
+        self.max_size = Some(size);
+        self
+    }
+
+    #[must_use]
+    pub fn start_after(mut self, path: &str) -> Self {
+        self.start_after = Some(path.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn snapshot(mut self, v: bool) -> Self {
+        self.snapshot = v;
+        self
+    }
+
+    #[must_use]
+    pub fn fetch_owner(mut self, v: bool) -> Self {
+        self.fetch_owner = v;
+        self
+    }
+}
+
+/// Args for `list` operation.
+///
+/// The predicates only apply to files, dirs will always be returned so that
+/// callers could walk into them. Backends could apply the predicates at the
+/// source, [`ObjectStream`][crate::ObjectStream] will always check them with
+/// the metadata of every entry.
 #[derive(Debug, Clone, Default)]
 pub struct OpList {
     pub path: String,
+    /// How `path` is matched, default to [`ListMode::Dir`].
+    pub mode: ListMode,
+    /// Only list files that modified after this time.
+    pub modified_after: Option<SystemTime>,
+    /// Only list files whose size is not less than this.
+    pub min_size: Option<u64>,
+    /// Only list files whose size is not larger than this.
+    pub max_size: Option<u64>,
+    /// Resume listing after this path, which is returned by the previous
+    /// listing.
+    ///
+    /// Backends which list in lexicographical order (like s3 and memory)
+    /// start from the first path after it. Others (like fs) skip entries
+    /// until this path has been met, so it must still exist.
+    pub start_after: Option<String>,
+    /// List the versions that are the latest at listing time, and carry
+    /// their version ids in the metadata.
+    ///
+    /// Only backends with [`AccessorMetadata::can_read_version`][crate::AccessorMetadata::can_read_version]
+    /// support it.
+    pub snapshot: bool,
+    /// Fill the owner of listed objects, which is not returned by s3
+    /// unless requested.
+    pub fetch_owner: bool,
 }
 
 impl OpList {
     pub fn new(path: &str) -> Self {
         Self {
             path: path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_options(path: &str, opts: &ListOptions) -> Self {
+        Self {
+            path: path.to_string(),
+            mode: opts.mode,
+            modified_after: opts.modified_after,
+            min_size: opts.min_size,
+            max_size: opts.max_size,
+            start_after: opts.start_after.clone(),
+            snapshot: opts.snapshot,
+            fetch_owner: opts.fetch_owner,
+        }
+    }
+
+    /// Check if any predicate has been set.
+    pub fn has_predicates(&self) -> bool {
+        self.modified_after.is_some() || self.min_size.is_some() || self.max_size.is_some()
+    }
+
+    /// Metadata fields that required to check the predicates.
+    pub fn required_fields(&self) -> Vec<MetaField> {
+        let mut fields = vec![MetaField::Mode];
+        if self.modified_after.is_some() {
+            fields.push(MetaField::LastModified);
+        }
+        if self.min_size.is_some() || self.max_size.is_some() {
+            fields.push(MetaField::ContentLength);
+        }
+        fields
+    }
+
+    /// Check if the entry matches all predicates.
+    ///
+    /// Files without the required fields never match.
+    pub fn matches(&self, meta: &Metadata) -> bool {
+        if !meta.has(MetaField::Mode) {
+            return false;
+        }
+        if meta.mode() != ObjectMode::FILE {
+            return true;
+        }
+
+        if let Some(t) = self.modified_after {
+            match meta.last_modified() {
+                Some(v) if v > t => {}
+                _ => return false,
+            }
+        }
+        if self.min_size.is_some() || self.max_size.is_some() {
+            if !meta.has(MetaField::ContentLength) {
+                return false;
+            }
+            let size = meta.content_length();
+            if self.min_size.is_some_and(|v| size < v) || self.max_size.is_some_and(|v| size > v) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Format of the object that [`OpSelect`] queries on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectInput {
+    /// CSV, use the first line as header if `has_header` is true.
+    Csv {
+        has_header: bool,
+    },
+    /// JSON document.
+    Json,
+    /// JSON lines, every line is a JSON document.
+    JsonLines,
+    Parquet,
+}
+
+/// Format of the result rows returned by [`OpSelect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectOutput {
+    Csv,
+    Json,
+}
+
+/// Run a SQL expression on the object and only return the matched rows.
+#[derive(Debug, Clone)]
+pub struct OpSelect {
+    pub path: String,
+    pub expression: String,
+    pub input: SelectInput,
+    pub output: SelectOutput,
+}
+
+impl OpSelect {
+    pub fn new(path: &str, expression: &str, input: SelectInput, output: SelectOutput) -> Self {
+        Self {
+            path: path.to_string(),
+            expression: expression.to_string(),
+            input,
+            output,
+        }
+    }
+}
+
+/// Operation that a presigned request will perform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresignOperation {
+    Read,
+    Write,
+    /// Fetch the metadata of the object, like `HEAD` on s3.
+    Stat,
+    Delete,
+}
+
+/// Args for `presign` operation.
+///
+/// Response header overrides only apply to [`PresignOperation::Read`].
+/// Backends that support presign but not the overrides must reject them
+/// with [`Kind::Unsupported`][crate::error::Kind::Unsupported] instead of
+/// silently generating a request without them.
+#[derive(Debug, Clone)]
+pub struct OpPresign {
+    pub path: String,
+    pub op: PresignOperation,
+    /// How long the presigned request will be valid for.
+    pub expire: Duration,
+    /// Override the `Content-Disposition` header of the response.
+    pub override_content_disposition: Option<String>,
+    /// Override the `Content-Type` header of the response.
+    pub override_content_type: Option<String>,
+}
+
+impl OpPresign {
+    pub fn new(path: &str, op: PresignOperation, expire: Duration) -> Self {
+        Self {
+            path: path.to_string(),
+            op,
+            expire,
+            override_content_disposition: None,
+            override_content_type: None,
+        }
+    }
+
+    /// Check if any response header override has been set.
+    pub fn has_overrides(&self) -> bool {
+        self.override_content_disposition.is_some() || self.override_content_type.is_some()
+    }
+}
+
+/// Args for `list_multipart_uploads` operation.
+#[derive(Debug, Clone, Default)]
+pub struct OpListMultipartUploads {
+    /// Only list uploads whose path starts with it.
+    pub prefix: String,
+}
+
+impl OpListMultipartUploads {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+        }
+    }
+}
+
+/// Args for `abort_multipart_upload` operation.
+#[derive(Debug, Clone, Default)]
+pub struct OpAbortMultipartUpload {
+    pub path: String,
+    pub upload_id: String,
+}
+
+impl OpAbortMultipartUpload {
+    pub fn new(path: &str, upload_id: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            upload_id: upload_id.to_string(),
+        }
+    }
+}
+
+/// Args for `copy` operation.
+#[derive(Debug, Clone, Default)]
+pub struct OpCopy {
+    /// Path of the source object.
+    pub from: String,
+    /// Path of the target object, it will be overwritten if exists.
+    pub to: String,
+}
+
+impl OpCopy {
+    pub fn new(from: &str, to: &str) -> Self {
+        Self {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+}
+
+/// Args for `create` operation.
+#[derive(Debug, Clone)]
+pub struct OpCreate {
+    pub path: String,
+    /// Create an empty file, or a dir if [`ObjectMode::DIR`] whose path
+    /// must end with `/`.
+    pub mode: ObjectMode,
+}
+
+impl OpCreate {
+    pub fn new(path: &str, mode: ObjectMode) -> Self {
+        Self {
+            path: path.to_string(),
+            mode,
+        }
+    }
+}
+
+/// Args for `retention` operation.
+#[derive(Debug, Clone, Default)]
+pub struct OpRetention {
+    pub path: String,
+}
+
+impl OpRetention {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+        }
+    }
+}
+
+/// Mode of an object's retention, see [`Retention`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionMode {
+    /// Users with special permissions could still delete the object or
+    /// shorten the retention.
+    Governance,
+    /// No one could delete the object until the retention expires.
+    Compliance,
+}
+
+/// Lock status of an object, returned by
+/// [`Object::retention`][crate::Object::retention].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Retention {
+    mode: Option<RetentionMode>,
+    retain_until: Option<SystemTime>,
+    legal_hold: bool,
+}
+
+impl Retention {
+    pub fn new(
+        mode: Option<RetentionMode>,
+        retain_until: Option<SystemTime>,
+        legal_hold: bool,
+    ) -> Self {
+        Self {
+            mode,
+            retain_until,
+            legal_hold,
+        }
+    }
+
+    /// Mode of the retention, `None` if the object has no retention.
+    pub fn mode(&self) -> Option<RetentionMode> {
+        self.mode
+    }
+
+    /// The object can't be deleted or overwritten before this time.
+    pub fn retain_until(&self) -> Option<SystemTime> {
+        self.retain_until
+    }
+
+    /// Check if the object is under legal hold, which prevents deletion
+    /// until it's removed regardless of the retention.
+    pub fn legal_hold(&self) -> bool {
+        self.legal_hold
+    }
+}
+
+/// A multipart upload that has been created but not completed or aborted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipartUpload {
+    path: String,
+    upload_id: String,
+    initiated: Option<SystemTime>,
+}
+
+impl MultipartUpload {
+    pub fn new(path: &str, upload_id: &str, initiated: Option<SystemTime>) -> Self {
+        Self {
+            path: path.to_string(),
+            upload_id: upload_id.to_string(),
+            initiated,
         }
     }
+
+    /// Path of the object that this upload will create.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn upload_id(&self) -> &str {
+        &self.upload_id
+    }
+
+    /// Time when this upload was created, could be used to only clean up
+    /// uploads that are old enough.
+    pub fn initiated(&self) -> Option<SystemTime> {
+        self.initiated
+    }
+}
+
+/// A signed request that could be sent by anyone without credentials.
+#[derive(Debug, Clone)]
+pub struct PresignedRequest {
+    method: http::Method,
+    uri: http::Uri,
+    headers: http::HeaderMap,
+}
+
+impl PresignedRequest {
+    pub fn new(method: http::Method, uri: http::Uri, headers: http::HeaderMap) -> Self {
+        Self {
+            method,
+            uri,
+            headers,
+        }
+    }
+
+    /// HTTP method of this request.
+    pub fn method(&self) -> &http::Method {
+        &self.method
+    }
+
+    /// URI of this request, including the signature.
+    pub fn uri(&self) -> &http::Uri {
+        &self.uri
+    }
+
+    /// Headers that must be sent along with this request.
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+}
+
+/// Convert into a request without body, which could be sent by http
+/// clients like hyper or reqwest directly.
+impl From<PresignedRequest> for http::Request<()> {
+    fn from(req: PresignedRequest) -> Self {
+        let mut r = http::Request::new(());
+        *r.method_mut() = req.method;
+        *r.uri_mut() = req.uri;
+        *r.headers_mut() = req.headers;
+        r
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct HeaderRange(Option<u64>, Option<u64>);
 
 impl HeaderRange {
-    pub fn new(offset: Option<u64>, size: Option<u64>) -> Self {
-        HeaderRange(offset, size)
+    /// Create a new range, ranges that can't be expressed by the `Range`
+    /// header will be rejected:
+    ///
+    /// - Neither offset nor size is set, callers should read the whole
+    ///   object without range instead.
+    /// - Size is `0`, there is nothing to read and backends will respond
+    ///   `416 Range Not Satisfiable`.
+    /// - The end of the range overflows.
+    pub fn new(offset: Option<u64>, size: Option<u64>) -> Result<Self> {
+        match (offset, size) {
+            (None, None) => Err(anyhow!("range without offset and size")),
+            (_, Some(0)) => Err(anyhow!("range with zero size")),
+            (Some(offset), Some(size)) if offset.checked_add(size).is_none() => {
+                Err(anyhow!("range {}+{} overflows", offset, size))
+            }
+            _ => Ok(HeaderRange(offset, size)),
+        }
     }
 }
 
@@ -85,7 +1101,7 @@ impl ToString for HeaderRange {
             (Some(offset), None) => format!("bytes={}-", offset),
             (None, Some(size)) => format!("bytes=0-{}", size - 1),
             (Some(offset), Some(size)) => format!("bytes={}-{}", offset, offset + size - 1),
-            _ => panic!("invalid range"),
+            _ => unreachable!("invalid range must be rejected while constructing"),
         }
     }
 }
@@ -0,0 +1,179 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Metadata;
+use crate::ObjectReader;
+
+/// Normalize the path by removing empty segments, the trailing `/` of dirs
+/// is kept. The root is normalized to `/`.
+pub(crate) fn normalize_path(path: &str) -> String {
+    let has_trailing = path.ends_with('/');
+
+    let mut p = path
+        .split('/')
+        .filter(|v| !v.is_empty())
+        .collect::<Vec<&str>>()
+        .join("/");
+
+    if p.is_empty() {
+        return "/".to_string();
+    }
+    if has_trailing {
+        p.push('/')
+    }
+
+    p
+}
+
+/// Normalize and validate the path eagerly, used by
+/// [`Operator::try_object`][crate::Operator::try_object].
+///
+/// Besides the limits declared in [`AccessorMetadata`], paths containing
+/// NUL, control characters or relative segments (`.` and `..`) are
+/// rejected since backends resolve them differently.
+pub(crate) fn validate_path(
+    op: &'static str,
+    path: &str,
+    meta: &AccessorMetadata,
+) -> Result<String> {
+    let err = |source| Error::Object {
+        kind: Kind::ObjectPathInvalid,
+        op,
+        path: path.to_string(),
+        source,
+    };
+
+    if let Some(c) = path.chars().find(|c| c.is_control()) {
+        return Err(err(anyhow!("path contains control character {:?}", c)));
+    }
+    if let Some(seg) = path.split('/').find(|v| *v == "." || *v == "..") {
+        return Err(err(anyhow!("path contains relative segment {:?}", seg)));
+    }
+
+    let p = normalize_path(path);
+    meta.check_path(op, &p)?;
+    Ok(p)
+}
+
+/// PathCheckAccessor rejects paths that violate the limits declared in
+/// [`AccessorMetadata`] before they reach the backend, so that users get
+/// a clear error instead of a cryptic one from the service.
+#[derive(Debug)]
+pub(crate) struct PathCheckAccessor {
+    inner: Arc<dyn Accessor>,
+}
+
+impl PathCheckAccessor {
+    pub(crate) fn new(inner: Arc<dyn Accessor>) -> Self {
+        Self { inner }
+    }
+
+    fn check(&self, op: &'static str, path: &str) -> Result<()> {
+        self.inner.metadata().check_path(op, path)
+    }
+}
+
+#[async_trait]
+impl Accessor for PathCheckAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        self.check("read", &args.path)?;
+        self.inner.read(args).await
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        self.check("write", &args.path)?;
+        self.inner.write(r, args).await
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        self.check("append", &args.path)?;
+        self.inner.append(r, args).await
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.check("stat", &args.path)?;
+        self.inner.stat(args).await
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        self.check("delete", &args.path)?;
+        self.inner.delete(args).await
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        self.check("list", &args.path)?;
+        self.inner.list(args).await
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.inner.bucket_exists().await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.check("select", &args.path)?;
+        self.inner.select(args).await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        self.check("presign", &args.path)?;
+        self.inner.presign(args).await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.check("list_multipart_uploads", &args.prefix)?;
+        self.inner.list_multipart_uploads(args).await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.check("abort_multipart_upload", &args.path)?;
+        self.inner.abort_multipart_upload(args).await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.check("retention", &args.path)?;
+        self.inner.retention(args).await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        self.check("copy", &args.from)?;
+        self.check("copy", &args.to)?;
+        self.inner.copy(args).await
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        self.check("create", &args.path)?;
+        self.inner.create(args).await
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Tags describing how a result was served, like whether a read came
+//! from a cache layer.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+/// Provenance is the key/value context attached to a result by layers,
+/// like the context of [`Error::Backend`][crate::error::Error::Backend].
+///
+/// Layers record how they served the result, so that users could debug
+/// why a read is stale or slow without knowing the layers in between.
+/// Layers not listed here could attach their own keys.
+///
+/// | Key | Values | Set by |
+/// | --- | ------ | ------ |
+/// | `served_by` | `cache`, `origin`, `secondary` | cache and fallback layers |
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use futures::AsyncReadExt;
+/// use opendal::layers::InMemoryCacheLayer;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+/// use opendal::Provenance;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let op = Operator::new(memory::Backend::build().finish().await?)
+///         .layer(InMemoryCacheLayer::new());
+///     op.object("test").writer().write_bytes(b"Hello".to_vec()).await?;
+///
+///     for expected in ["origin", "cache"] {
+///         let mut r = op.object("test").reader();
+///         r.read_to_end(&mut Vec::new()).await?;
+///         assert_eq!(r.provenance().get(Provenance::SERVED_BY), Some(expected));
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Provenance {
+    tags: BTreeMap<String, String>,
+}
+
+impl Provenance {
+    /// Which layer served the data: `cache`, `origin` or `secondary`.
+    pub const SERVED_BY: &'static str = "served_by";
+
+    /// Returns the value of `key` if attached.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.tags.get(key).map(|v| v.as_str())
+    }
+
+    /// Attach `key` with `value`, the old value will be replaced.
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        self.tags.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Attach `key` with `value` only if `key` is not attached yet, so
+    /// that tags set by inner layers, which are closer to the data, win.
+    pub fn set_if_absent(&mut self, key: &str, value: &str) -> &mut Self {
+        self.tags
+            .entry(key.to_string())
+            .or_insert_with(|| value.to_string());
+        self
+    }
+
+    /// Iterate all tags in the order of keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tags.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+}
+
+impl FromIterator<(String, String)> for Provenance {
+    fn from_iter<T: IntoIterator<Item = (String, String)>>(iter: T) -> Self {
+        Self {
+            tags: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Formatted as `keyA=valueA, keyB=valueB` to be included in logs.
+impl Display for Provenance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (k, v)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{k}={v}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::io::SeekFrom;
+
+use futures::AsyncRead;
+use futures::AsyncReadExt;
+use futures::AsyncSeek;
+use futures::AsyncSeekExt;
+use tokio::runtime::Handle;
+
+/// BlockingReader is used to convert an async reader (like [`Reader`][crate::Reader])
+/// into `std::io::Read` and `std::io::Seek` for sync callers.
+///
+/// Every operation will be driven by `block_on` on the given runtime handle.
+///
+/// # Deadlock
+///
+/// Never use `BlockingReader` inside the runtime's own threads (for example,
+/// inside an async task spawned on the same runtime). `block_on` will block
+/// the worker thread, and the IO it waits for may never be polled. Use it in
+/// a dedicated thread like `tokio::task::spawn_blocking` instead.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Read;
+///
+/// use anyhow::Result;
+/// use opendal::readers::BlockingReader;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// fn main() -> Result<()> {
+///     let rt = tokio::runtime::Runtime::new()?;
+///     let op = rt.block_on(async {
+///         let op = Operator::new(memory::Backend::build().finish().await?);
+///         op.object("test").writer().write_bytes(vec![0; 16]).await?;
+///         Ok::<_, anyhow::Error>(op)
+///     })?;
+///
+///     let mut r = BlockingReader::new(rt.handle().clone(), op.object("test").reader());
+///     let mut buf = Vec::new();
+///     r.read_to_end(&mut buf)?;
+///
+///     Ok(())
+/// }
+/// ```
+pub struct BlockingReader<R> {
+    handle: Handle,
+    r: R,
+}
+
+impl<R> BlockingReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    pub fn new(handle: Handle, r: R) -> Self {
+        Self { handle, r }
+    }
+}
+
+impl<R> io::Read for BlockingReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.handle.block_on(self.r.read(buf))
+    }
+}
+
+impl<R> io::Seek for BlockingReader<R>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.handle.block_on(self.r.seek(pos))
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+
+use anyhow::anyhow;
+use futures::ready;
+use futures::AsyncRead;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::BoxedAsyncReader;
+
+/// LengthCheckedReader makes sure the reader produces exactly the declared
+/// size of bytes.
+///
+/// The read fails if the inner reader ends before the declared size or
+/// produces more than it. Backends usually wrap the io error into their
+/// own, so the mismatch is also recorded in the returned [`LengthCheck`]
+/// which could be used to build a [`Kind::ContentLengthMismatch`] error
+/// after the reader has been consumed.
+pub struct LengthCheckedReader {
+    inner: BoxedAsyncReader,
+    expected: u64,
+    actual: u64,
+    check: LengthCheck,
+}
+
+impl LengthCheckedReader {
+    pub fn new(r: BoxedAsyncReader, expected: u64) -> (Self, LengthCheck) {
+        let check = LengthCheck::default();
+        let r = LengthCheckedReader {
+            inner: r,
+            expected,
+            actual: 0,
+            check: check.clone(),
+        };
+        (r, check)
+    }
+
+    fn mismatch(&mut self, kind: io::ErrorKind) -> io::Error {
+        let mut state = self.check.0.lock().expect("lock poisoned");
+        *state = Some((self.expected, self.actual));
+
+        io::Error::new(kind, mismatch_error(self.expected, self.actual))
+    }
+}
+
+impl AsyncRead for LengthCheckedReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let n = ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        self.actual += n as u64;
+
+        if n == 0 && self.actual < self.expected {
+            return Poll::Ready(Err(self.mismatch(io::ErrorKind::UnexpectedEof)));
+        }
+        if self.actual > self.expected {
+            return Poll::Ready(Err(self.mismatch(io::ErrorKind::InvalidData)));
+        }
+        Poll::Ready(Ok(n))
+    }
+}
+
+/// LengthCheck records the mismatch detected by [`LengthCheckedReader`].
+#[derive(Debug, Clone, Default)]
+pub struct LengthCheck(Arc<Mutex<Option<(u64, u64)>>>);
+
+impl LengthCheck {
+    /// Returns an error with [`Kind::ContentLengthMismatch`] if a mismatch
+    /// has been detected.
+    pub fn error(&self, op: &'static str, path: &str) -> Option<Error> {
+        let state = self.0.lock().expect("lock poisoned");
+        state.map(|(expected, actual)| Error::Object {
+            kind: Kind::ContentLengthMismatch,
+            op,
+            path: path.to_string(),
+            source: mismatch_error(expected, actual),
+        })
+    }
+}
+
+fn mismatch_error(expected: u64, actual: u64) -> anyhow::Error {
+    if actual < expected {
+        anyhow!(
+            "source ended early: expected {} bytes, got {}",
+            expected,
+            actual
+        )
+    } else {
+        anyhow!(
+            "source is longer than declared: expected {} bytes, got at least {}",
+            expected,
+            actual
+        )
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use futures::AsyncRead;
+
+use crate::BoxedAsyncReader;
+
+/// Compression algorithms that could be detected from the object's path
+/// or its `Content-Encoding`.
+///
+/// Used by [`Object::auto_reader`][crate::Object::auto_reader] and
+/// [`ReadOptions::decode_content`][crate::ops::ReadOptions::decode_content].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompressAlgorithm {
+    /// [gzip](https://www.rfc-editor.org/rfc/rfc1952), extensions `.gz` and `.gzip`.
+    Gzip,
+    /// [zstd](https://www.rfc-editor.org/rfc/rfc8878), extensions `.zst` and `.zstd`.
+    Zstd,
+    /// [deflate](https://www.rfc-editor.org/rfc/rfc1950), only used as
+    /// `Content-Encoding`.
+    Deflate,
+    /// [brotli](https://www.rfc-editor.org/rfc/rfc7932), only used as
+    /// `Content-Encoding: br`.
+    Brotli,
+}
+
+impl CompressAlgorithm {
+    /// Detect the algorithm by the extension of `path`, case-insensitive.
+    ///
+    /// Returns `None` for unknown extensions and directories.
+    pub fn from_path(path: &str) -> Option<Self> {
+        if path.ends_with('/') {
+            return None;
+        }
+        let name = path.rsplit('/').next().unwrap_or(path);
+        let (stem, ext) = name.rsplit_once('.')?;
+        // Hidden files like `.gz` don't have an extension.
+        if stem.is_empty() {
+            return None;
+        }
+
+        match ext.to_ascii_lowercase().as_str() {
+            "gz" | "gzip" => Some(CompressAlgorithm::Gzip),
+            "zst" | "zstd" => Some(CompressAlgorithm::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The cargo feature enabling decompression of this algorithm.
+    pub fn feature(&self) -> &'static str {
+        match self {
+            CompressAlgorithm::Gzip => "compress-gzip",
+            CompressAlgorithm::Zstd => "compress-zstd",
+            CompressAlgorithm::Deflate => "compress-deflate",
+            CompressAlgorithm::Brotli => "compress-brotli",
+        }
+    }
+
+    /// Check if decompression of this algorithm is enabled by its
+    /// [`feature`][CompressAlgorithm::feature].
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            CompressAlgorithm::Gzip => cfg!(feature = "compress-gzip"),
+            CompressAlgorithm::Zstd => cfg!(feature = "compress-zstd"),
+            CompressAlgorithm::Deflate => cfg!(feature = "compress-deflate"),
+            CompressAlgorithm::Brotli => cfg!(feature = "compress-brotli"),
+        }
+    }
+
+    /// Detect the algorithm by the `Content-Encoding` of an object,
+    /// case-insensitive.
+    ///
+    /// Returns `None` for unknown encodings, callers should check
+    /// `identity` before calling it.
+    pub fn from_content_encoding(encoding: &str) -> Option<Self> {
+        match encoding.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(CompressAlgorithm::Gzip),
+            "deflate" => Some(CompressAlgorithm::Deflate),
+            "br" => Some(CompressAlgorithm::Brotli),
+            "zstd" => Some(CompressAlgorithm::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// DecompressReader returns the decompressed content of the inner reader.
+///
+/// Gzip streams with multiple members (like concatenated `.gz` files) are
+/// decompressed as a whole. Corrupted content fails the read with
+/// [`io::ErrorKind::InvalidData`].
+pub struct DecompressReader {
+    inner: BoxedAsyncReader,
+}
+
+impl DecompressReader {
+    /// Create a reader decompressing `r` by `algo`, returns `None` if the
+    /// feature of `algo` is not enabled.
+    pub fn new(r: BoxedAsyncReader, algo: CompressAlgorithm) -> Option<Self> {
+        #[allow(unreachable_patterns)]
+        let inner: Option<BoxedAsyncReader> = match algo {
+            #[cfg(feature = "compress-gzip")]
+            CompressAlgorithm::Gzip => {
+                let mut r = async_compression::futures::bufread::GzipDecoder::new(buffered(r));
+                r.multiple_members(true);
+                Some(Box::new(r))
+            }
+            #[cfg(feature = "compress-zstd")]
+            CompressAlgorithm::Zstd => {
+                let mut r = async_compression::futures::bufread::ZstdDecoder::new(buffered(r));
+                r.multiple_members(true);
+                Some(Box::new(r))
+            }
+            // `deflate` of `Content-Encoding` is zlib actually.
+            #[cfg(feature = "compress-deflate")]
+            CompressAlgorithm::Deflate => Some(Box::new(
+                async_compression::futures::bufread::ZlibDecoder::new(buffered(r)),
+            )),
+            #[cfg(feature = "compress-brotli")]
+            CompressAlgorithm::Brotli => Some(Box::new(
+                async_compression::futures::bufread::BrotliDecoder::new(buffered(r)),
+            )),
+            _ => {
+                drop(r);
+                None
+            }
+        };
+        inner.map(|inner| Self { inner })
+    }
+}
+
+#[cfg(any(
+    feature = "compress-gzip",
+    feature = "compress-zstd",
+    feature = "compress-deflate",
+    feature = "compress-brotli"
+))]
+fn buffered(r: BoxedAsyncReader) -> futures::io::BufReader<BoxedAsyncReader> {
+    futures::io::BufReader::new(r)
+}
+
+impl AsyncRead for DecompressReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+
+use futures::ready;
+use futures::AsyncRead;
+use sha2::Digest as _;
+use sha2::Sha256;
+#[cfg(feature = "digest-xxh3")]
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::BoxedAsyncReader;
+
+/// Algorithms supported by [`DigestReader`] and [`Reader::with_digest`][crate::Reader::with_digest].
+///
+/// # Features
+///
+/// `Xxh3` is only available with the `digest-xxh3` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DigestKind {
+    /// CRC-32C (Castagnoli), the same as `x-amz-checksum-crc32c` of s3.
+    Crc32c,
+    /// XXH3 64 bits, much faster than the others but not supported by s3.
+    #[cfg(feature = "digest-xxh3")]
+    Xxh3,
+    /// SHA-256, the same as `x-amz-checksum-sha256` of s3.
+    Sha256,
+}
+
+/// Digest of all bytes that passed through a reader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digest {
+    kind: DigestKind,
+    bytes: Vec<u8>,
+}
+
+impl Digest {
+    pub fn kind(&self) -> DigestKind {
+        self.kind
+    }
+
+    /// Returns the digest in big-endian bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Format the digest in lowercase hex.
+impl Display for Digest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for b in &self.bytes {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// Hasher computes the digest incrementally.
+pub(crate) enum Hasher {
+    Crc32c(u32),
+    #[cfg(feature = "digest-xxh3")]
+    Xxh3(Box<Xxh3>),
+    Sha256(Box<Sha256>),
+}
+
+impl Hasher {
+    pub(crate) fn new(kind: DigestKind) -> Self {
+        match kind {
+            DigestKind::Crc32c => Hasher::Crc32c(!0),
+            #[cfg(feature = "digest-xxh3")]
+            DigestKind::Xxh3 => Hasher::Xxh3(Box::new(Xxh3::new())),
+            DigestKind::Sha256 => Hasher::Sha256(Box::new(Sha256::new())),
+        }
+    }
+
+    pub(crate) fn update(&mut self, bs: &[u8]) {
+        match self {
+            Hasher::Crc32c(crc) => *crc = crc32c_update(*crc, bs),
+            #[cfg(feature = "digest-xxh3")]
+            Hasher::Xxh3(h) => h.update(bs),
+            Hasher::Sha256(h) => h.update(bs),
+        }
+    }
+
+    /// Compute the digest of `bs` in one go.
+    pub(crate) fn digest(kind: DigestKind, bs: &[u8]) -> Digest {
+        let mut h = Hasher::new(kind);
+        h.update(bs);
+        h.finalize()
+    }
+
+    pub(crate) fn finalize(self) -> Digest {
+        match self {
+            Hasher::Crc32c(crc) => Digest {
+                kind: DigestKind::Crc32c,
+                bytes: (!crc).to_be_bytes().to_vec(),
+            },
+            #[cfg(feature = "digest-xxh3")]
+            Hasher::Xxh3(h) => Digest {
+                kind: DigestKind::Xxh3,
+                bytes: h.digest().to_be_bytes().to_vec(),
+            },
+            Hasher::Sha256(h) => Digest {
+                kind: DigestKind::Sha256,
+                bytes: h.finalize().to_vec(),
+            },
+        }
+    }
+}
+
+/// Table of the reflected Castagnoli polynomial `0x82F63B78`.
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82F6_3B78
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32c_update(mut crc: u32, bs: &[u8]) -> u32 {
+    for b in bs {
+        crc = CRC32C_TABLE[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+/// DigestReader hashes bytes as they flow through, so that callers could
+/// learn the digest of what they have written or read without a second
+/// pass over the data.
+///
+/// The digest will be available in the returned [`DigestHandle`] after the
+/// reader reached EOF.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use futures::io::Cursor;
+/// use opendal::readers::DigestKind;
+/// use opendal::readers::DigestReader;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let op = Operator::new(memory::Backend::build().finish().await?);
+///
+///     let r = Box::new(Cursor::new(b"Hello, World!".to_vec()));
+///     let (r, digest) = DigestReader::new(r, DigestKind::Crc32c);
+///     op.object("test").writer().write_reader(Box::new(r), 13).await?;
+///     assert_eq!(digest.digest().unwrap().to_string(), "4d551068");
+///
+///     Ok(())
+/// }
+/// ```
+pub struct DigestReader {
+    inner: BoxedAsyncReader,
+    hasher: Option<Hasher>,
+    handle: DigestHandle,
+}
+
+impl DigestReader {
+    pub fn new(r: BoxedAsyncReader, kind: DigestKind) -> (Self, DigestHandle) {
+        let handle = DigestHandle::default();
+        let r = DigestReader {
+            inner: r,
+            hasher: Some(Hasher::new(kind)),
+            handle: handle.clone(),
+        };
+        (r, handle)
+    }
+}
+
+impl AsyncRead for DigestReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let n = ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+
+        if n > 0 {
+            if let Some(hasher) = self.hasher.as_mut() {
+                hasher.update(&buf[..n]);
+            }
+        } else if !buf.is_empty() {
+            if let Some(hasher) = self.hasher.take() {
+                *self.handle.0.lock().expect("lock poisoned") = Some(hasher.finalize());
+            }
+        }
+        Poll::Ready(Ok(n))
+    }
+}
+
+/// DigestHandle holds the digest computed by [`DigestReader`].
+#[derive(Debug, Clone, Default)]
+pub struct DigestHandle(Arc<Mutex<Option<Digest>>>);
+
+impl DigestHandle {
+    /// Returns the digest, or `None` if the reader hasn't reached EOF.
+    pub fn digest(&self) -> Option<Digest> {
+        self.0.lock().expect("lock poisoned").clone()
+    }
+}
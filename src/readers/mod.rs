@@ -22,3 +22,21 @@ pub use stream::ReaderStream;
 mod observer;
 pub use observer::ObserveReader;
 pub use observer::ReadEvent;
+
+mod checked;
+pub use checked::LengthCheck;
+pub use checked::LengthCheckedReader;
+
+mod digest;
+pub use digest::Digest;
+pub use digest::DigestHandle;
+pub use digest::DigestKind;
+pub use digest::DigestReader;
+pub(crate) use digest::Hasher;
+
+mod blocking;
+pub use blocking::BlockingReader;
+
+mod compress;
+pub use compress::CompressAlgorithm;
+pub use compress::DecompressReader;
@@ -11,30 +11,56 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::RwLock;
 
 use anyhow::anyhow;
+use futures::future::BoxFuture;
+use once_cell::sync::Lazy;
 
 use super::error::Error;
+use crate::credential::Credential;
 use crate::error::Kind;
+use crate::error::Result;
+use crate::services::fs;
+use crate::services::memory;
+use crate::services::s3;
+use crate::Accessor;
 
 /// Backends that OpenDAL supports
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Scheme {
     // TODO: Although we don't have azblob support for now, but we need to add it for compatibility. We will implement azblob support as soon as possible.
     Azblob,
     Fs,
+    Memory,
     S3,
 }
 
+impl Display for Scheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scheme::Azblob => write!(f, "azblob"),
+            Scheme::Fs => write!(f, "fs"),
+            Scheme::Memory => write!(f, "memory"),
+            Scheme::S3 => write!(f, "s3"),
+        }
+    }
+}
+
 impl FromStr for Scheme {
     type Err = Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         let s = s.to_lowercase();
         match s.as_str() {
             "azblob" => Ok(Scheme::Azblob),
             "fs" => Ok(Scheme::Fs),
+            "memory" => Ok(Scheme::Memory),
             "s3" => Ok(Scheme::S3),
 
             // TODO: it's used for compatibility with dal1, should be removed in the future
@@ -49,3 +75,125 @@ impl FromStr for Scheme {
         }
     }
 }
+
+/// SchemeFactory builds the accessor of a custom scheme from its config,
+/// registered by [`register_scheme`].
+pub type SchemeFactory = Arc<
+    dyn Fn(HashMap<String, String>) -> BoxFuture<'static, Result<Arc<dyn Accessor>>> + Send + Sync,
+>;
+
+static FACTORIES: Lazy<RwLock<HashMap<String, SchemeFactory>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Register `factory` for the custom `scheme` in current process, so that
+/// [`Operator::from_uri`][crate::Operator::from_uri] and
+/// [`Operator::from_env`][crate::Operator::from_env] could build it like
+/// the built-in ones.
+///
+/// Schemes are case insensitive. Returns an error with
+/// [`Kind::BackendConfigurationInvalid`] if `scheme` is built-in or has
+/// been registered.
+///
+/// # Example
+///
+/// ```
+/// use futures::FutureExt;
+/// use opendal::register_scheme;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// register_scheme("acme", |_| async { memory::Backend::build().finish().await }.boxed())?;
+///
+/// let op = Operator::from_uri("acme://cluster-1/root").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn register_scheme<F>(scheme: &str, factory: F) -> Result<()>
+where
+    F: Fn(HashMap<String, String>) -> BoxFuture<'static, Result<Arc<dyn Accessor>>>
+        + Send
+        + Sync
+        + 'static,
+{
+    let scheme = scheme.to_lowercase();
+    let err = |msg: &str| Error::Backend {
+        kind: Kind::BackendConfigurationInvalid,
+        context: HashMap::from([("scheme".to_string(), scheme.clone())]),
+        source: anyhow!("{}", msg),
+    };
+    if Scheme::from_str(&scheme).is_ok() {
+        return Err(err("built-in scheme can't be overridden"));
+    }
+
+    let mut factories = FACTORIES.write().expect("lock poisoned");
+    if factories.contains_key(&scheme) {
+        return Err(err("scheme has been registered"));
+    }
+    factories.insert(scheme.clone(), Arc::new(factory));
+    Ok(())
+}
+
+/// Build the accessor of `scheme` from `config`, built-in schemes take:
+///
+/// - fs: `root`
+/// - s3: `bucket` (or `host`), `root`, `endpoint`, `region`,
+///   `endpoint_suffix`, `access_key_id`, `secret_access_key`
+/// - memory: nothing
+pub(crate) async fn build_accessor(
+    scheme: &str,
+    config: HashMap<String, String>,
+) -> Result<Arc<dyn Accessor>> {
+    let get = |k: &str| config.get(k).map(String::as_str);
+
+    match Scheme::from_str(scheme) {
+        Ok(Scheme::Fs) => {
+            let mut builder = fs::Backend::build();
+            if let Some(v) = get("root") {
+                builder.root(v);
+            }
+            builder.finish().await
+        }
+        Ok(Scheme::S3) => {
+            let mut builder = s3::Backend::build();
+            if let Some(v) = get("bucket").or_else(|| get("host")) {
+                builder.bucket(v);
+            }
+            if let Some(v) = get("root") {
+                builder.root(v);
+            }
+            if let Some(v) = get("endpoint") {
+                builder.endpoint(v);
+            }
+            if let Some(v) = get("region") {
+                builder.region(v);
+            }
+            if let Some(v) = get("endpoint_suffix") {
+                builder.endpoint_suffix(v);
+            }
+            builder.credential(Credential::hmac(
+                get("access_key_id").unwrap_or_default(),
+                get("secret_access_key").unwrap_or_default(),
+            ));
+            builder.finish().await
+        }
+        Ok(Scheme::Memory) => memory::Backend::build().finish().await,
+        Ok(Scheme::Azblob) => Err(Error::Backend {
+            kind: Kind::BackendNotSupported,
+            context: HashMap::from([("scheme".to_string(), scheme.to_string())]),
+            source: anyhow!("{} is not supported", scheme),
+        }),
+        Err(e) => {
+            let factory = FACTORIES
+                .read()
+                .expect("lock poisoned")
+                .get(&scheme.to_lowercase())
+                .cloned();
+            match factory {
+                Some(f) => f(config).await,
+                None => Err(e),
+            }
+        }
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::ops::Bound;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::ListMode;
+use crate::ops::OpAppend;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpRead;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::Accessor;
+use crate::AccessorBuilder;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Metadata;
+use crate::Object;
+use crate::ObjectMode;
+use crate::ObjectReader;
+
+#[derive(Default)]
+pub struct Builder {
+    entries: Vec<(String, Bytes)>,
+}
+
+impl Builder {
+    /// Add an object at `path`, later entries override earlier ones with
+    /// the same path.
+    ///
+    /// `&'static [u8]` and `Bytes` will be served without copying.
+    pub fn entry(&mut self, path: &str, data: impl Into<Bytes>) -> &mut Self {
+        self.entries.push((path.to_string(), data.into()));
+        self
+    }
+
+    /// Add all objects from the iterator.
+    pub fn entries(&mut self, entries: impl IntoIterator<Item = (String, Bytes)>) -> &mut Self {
+        self.entries.extend(entries);
+        self
+    }
+
+    /// Add all objects from a static table, like the one built by
+    /// `include_bytes!`.
+    pub fn static_entries(
+        &mut self,
+        entries: &'static [(&'static str, &'static [u8])],
+    ) -> &mut Self {
+        self.entries.extend(
+            entries
+                .iter()
+                .map(|(path, data)| (path.to_string(), Bytes::from_static(data))),
+        );
+        self
+    }
+
+    pub async fn finish(&mut self) -> Result<Arc<dyn Accessor>> {
+        let mut blobs = BTreeMap::new();
+        for (path, data) in self.entries.drain(..) {
+            let key = Backend::normalize_path(&path);
+            if key.is_empty() || key.ends_with('/') {
+                return Err(Error::Backend {
+                    kind: Kind::BackendConfigurationInvalid,
+                    context: HashMap::from([
+                        ("scheme".to_string(), "data".to_string()),
+                        ("path".to_string(), path),
+                    ]),
+                    source: anyhow!("entry path must be a file"),
+                });
+            }
+            blobs.insert(key, Blob::new(data));
+        }
+
+        Ok(Arc::new(Backend {
+            blobs: Arc::new(blobs),
+        }))
+    }
+}
+
+#[async_trait]
+impl AccessorBuilder for Builder {
+    async fn finish(&mut self) -> Result<Arc<dyn Accessor>> {
+        Builder::finish(self).await
+    }
+}
+
+/// Backend is used to serve `Accessor` support from embedded bytes.
+///
+/// # Note
+///
+/// The backend is read-only, `write`, `append` and `delete` will fail with
+/// [`Kind::Unsupported`]. Dirs are not stored but synthesized from the
+/// paths of files.
+#[derive(Debug, Clone)]
+pub struct Backend {
+    blobs: Arc<BTreeMap<String, Blob>>,
+}
+
+#[derive(Debug)]
+struct Blob {
+    data: Bytes,
+    etag: String,
+}
+
+impl Blob {
+    fn new(data: Bytes) -> Self {
+        Self {
+            etag: format!("\"{:x}\"", md5::compute(&data)),
+            data,
+        }
+    }
+
+    fn metadata(&self, path: &str) -> Metadata {
+        let mut meta = Metadata::default();
+        meta.set_path(path)
+            .set_mode(ObjectMode::FILE)
+            .set_content_length(self.data.len() as u64)
+            .set_etag(&self.etag)
+            .set_fully_loaded();
+        meta
+    }
+}
+
+impl Backend {
+    pub fn build() -> Builder {
+        Builder::default()
+    }
+
+    /// normalize_path removes all internal `//` inside path, the root will
+    /// be normalized into an empty string.
+    fn normalize_path(path: &str) -> String {
+        let mut p = path
+            .split('/')
+            .filter(|v| !v.is_empty())
+            .collect::<Vec<&str>>()
+            .join("/");
+
+        if path.ends_with('/') && !p.is_empty() {
+            p.push('/')
+        }
+
+        p
+    }
+
+    fn dir_metadata(path: &str) -> Metadata {
+        let mut meta = Metadata::default();
+        meta.set_path(path)
+            .set_mode(ObjectMode::DIR)
+            .set_content_length(0)
+            .set_fully_loaded();
+        meta
+    }
+
+    /// Check if any file exists under the dir.
+    fn dir_exists(&self, dir: &str) -> bool {
+        self.blobs
+            .range::<str, _>((Bound::Included(dir), Bound::Unbounded))
+            .next()
+            .is_some_and(|(k, _)| k.starts_with(dir))
+    }
+
+    fn read_only(op: &'static str, path: &str) -> Error {
+        Error::Object {
+            kind: Kind::Unsupported,
+            op,
+            path: path.to_string(),
+            source: anyhow!("data backend is read-only"),
+        }
+    }
+}
+
+#[async_trait]
+impl Accessor for Backend {
+    async fn bucket_exists(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn metadata(&self) -> AccessorMetadata {
+        AccessorMetadata::default()
+    }
+
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        let path = Backend::normalize_path(&args.path);
+        if args.version_id.is_some() {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op: "read",
+                path,
+                source: anyhow!("read by version id is not supported"),
+            });
+        }
+
+        let blob = self.blobs.get(&path).ok_or_else(|| Error::Object {
+            kind: Kind::ObjectNotExist,
+            op: "read",
+            path: path.to_string(),
+            source: anyhow!("key not exists in data"),
+        })?;
+
+        let mut data = blob.data.clone();
+        if let Some(offset) = args.offset {
+            // Like s3, reading from the start of an empty object is fine.
+            if offset > 0 && offset >= data.len() as u64 {
+                return Err(Error::Object {
+                    kind: Kind::RangeNotSatisfiable,
+                    op: "read",
+                    path: path.to_string(),
+                    source: anyhow!("offset out of bound {} >= {}", offset, data.len()),
+                });
+            }
+            data = data.slice(offset as usize..);
+        }
+
+        // Like other backends, ranges over-reading EOF will be truncated.
+        if let Some(size) = args.size {
+            data = data.slice(..(size as usize).min(data.len()));
+        }
+
+        let r: BoxedAsyncReader = Box::new(futures::io::Cursor::new(data));
+        Ok(ObjectReader::new(r).with_metadata(blob.metadata(&path)))
+    }
+    async fn write(&self, _: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        Err(Backend::read_only("write", &args.path))
+    }
+    async fn append(&self, _: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        Err(Backend::read_only("append", &args.path))
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        let path = Backend::normalize_path(&args.path);
+
+        if path.is_empty() || (path.ends_with('/') && self.dir_exists(&path)) {
+            return Ok(Backend::dir_metadata(&path));
+        }
+
+        let blob = self.blobs.get(&path).ok_or_else(|| Error::Object {
+            kind: Kind::ObjectNotExist,
+            op: "stat",
+            path: path.to_string(),
+            source: anyhow!("key not exists in data"),
+        })?;
+
+        Ok(blob.metadata(&path))
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        Err(Backend::read_only("delete", &args.path))
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        Err(Backend::read_only("create", &args.path))
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        let mut path = Backend::normalize_path(&args.path);
+        let start_after = args.start_after.as_deref().map(Backend::normalize_path);
+        let acc: Arc<dyn Accessor> = Arc::new(self.clone());
+
+        let mut entries = Vec::new();
+        if args.mode == ListMode::Prefix {
+            for (key, blob) in self
+                .blobs
+                .range::<str, _>((Bound::Included(path.as_str()), Bound::Unbounded))
+                .take_while(|(k, _)| k.starts_with(&path))
+            {
+                entries.push((key.clone(), blob.metadata(key)));
+            }
+        } else if let Some(blob) = self.blobs.get(&path) {
+            // Listing a file returns a stream that contains the file only.
+            entries.push((path.clone(), blob.metadata(&path)));
+        } else {
+            // `abc` lists the dir `abc/` instead of keys like `abcdef`.
+            if !path.is_empty() && !path.ends_with('/') {
+                path.push('/');
+            }
+
+            for (key, blob) in self
+                .blobs
+                .range::<str, _>((Bound::Included(path.as_str()), Bound::Unbounded))
+                .take_while(|(k, _)| k.starts_with(&path))
+            {
+                // Files under the sub dirs are folded into a dir entry, keys
+                // are sorted so they are adjacent.
+                let entry = match key[path.len()..].find('/') {
+                    Some(idx) => {
+                        let dir = &key[..path.len() + idx + 1];
+                        if entries.last().is_some_and(|(k, _)| k == dir) {
+                            continue;
+                        }
+                        (dir.to_string(), Backend::dir_metadata(dir))
+                    }
+                    None => (key.clone(), blob.metadata(key)),
+                };
+                entries.push(entry);
+            }
+        }
+
+        let objects = entries
+            .into_iter()
+            .filter(move |(k, _)| start_after.as_ref().is_none_or(|v| k > v))
+            .map(move |(k, meta)| {
+                let mut o = Object::new(acc.clone(), &k);
+                *o.metadata_mut() = meta;
+                Ok(o)
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Box::new(stream::iter(objects)))
+    }
+}
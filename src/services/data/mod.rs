@@ -0,0 +1,49 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-only backend serving objects from embedded bytes.
+//!
+//! Objects are served from the provided [`Bytes`][bytes::Bytes] without
+//! copying, so data from `include_bytes!` could be used directly. It's
+//! useful for tests, examples and default configs shipped inside the
+//! binary.
+//!
+//! # Example
+//!
+//! ```
+//! use anyhow::Result;
+//! use futures::AsyncReadExt;
+//! use opendal::services::data;
+//! use opendal::Operator;
+//!
+//! static FILES: &[(&str, &[u8])] = &[("config.toml", b"debug = true\n")];
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let op = Operator::new(data::Backend::build().static_entries(FILES).finish().await?);
+//!
+//!     let mut buf = String::new();
+//!     op.object("config.toml")
+//!         .reader()
+//!         .read_to_string(&mut buf)
+//!         .await?;
+//!     assert_eq!(buf, "debug = true\n");
+//!
+//!     Ok(())
+//! }
+//! ```
+
+mod backend;
+pub use backend::Backend;
+pub use backend::Builder;
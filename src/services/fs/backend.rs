@@ -13,24 +13,30 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::fmt::Debug;
 use std::fs;
 use std::io::SeekFrom;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::UNIX_EPOCH;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
 use blocking::unblock;
 use blocking::Unblock;
 use futures::io;
+use futures::stream;
 use futures::AsyncReadExt;
 use futures::AsyncSeekExt;
 use futures::AsyncWriteExt;
 use log::error;
 use log::info;
+use log::warn;
 use metrics::increment_counter;
+use uuid::Uuid;
 
 use super::error::parse_io_error;
+use super::error::parse_write_error;
 use super::object_stream::Readdir;
 use crate::error::Error;
 use crate::error::Kind;
@@ -38,19 +44,35 @@ use crate::error::Result;
 use crate::object::BoxedObjectStream;
 use crate::object::Metadata;
 use crate::object::ObjectMode;
+use crate::ops::ListMode;
+use crate::ops::OpAppend;
+use crate::ops::OpCreate;
 use crate::ops::OpDelete;
 use crate::ops::OpList;
 use crate::ops::OpRead;
 use crate::ops::OpStat;
 use crate::ops::OpWrite;
 use crate::Accessor;
+use crate::AccessorBuilder;
+use crate::AccessorMetadata;
 use crate::BoxedAsyncReader;
+use crate::Object;
+use crate::ObjectReader;
+use crate::ReadConsistency;
+use crate::Scheme;
 
 #[derive(Default, Debug)]
 pub struct Builder {
     root: Option<String>,
+    io_block_size: Option<usize>,
+    /// Moved into the backend and dropped along with its last clone.
+    pub(crate) guard: Option<Arc<dyn Debug + Send + Sync>>,
 }
 
+/// Default size of the buffer used by reads and writes, same as the one
+/// used by `blocking::Unblock`.
+const DEFAULT_IO_BLOCK_SIZE: usize = 8 * 1024;
+
 impl Builder {
     pub fn root(&mut self, root: &str) -> &mut Self {
         self.root = Some(root.to_string());
@@ -58,6 +80,17 @@ impl Builder {
         self
     }
 
+    /// Set the size of the buffer used by reads and writes, default to
+    /// 8 KiB.
+    ///
+    /// Larger blocks improve the throughput of large sequential IO,
+    /// especially on network file systems.
+    pub fn io_block_size(&mut self, size: usize) -> &mut Self {
+        self.io_block_size = Some(size);
+
+        self
+    }
+
     pub async fn finish(&mut self) -> Result<Arc<dyn Accessor>> {
         info!("backend build started: {:?}", &self);
 
@@ -68,27 +101,74 @@ impl Builder {
                 if !v.starts_with('/') {
                     return Err(Error::Backend {
                         kind: Kind::BackendConfigurationInvalid,
-                        context: HashMap::from([("root".to_string(), v.clone())]),
-                        source: anyhow!("Root must start with /"),
+                        context: HashMap::from([
+                            ("scheme".to_string(), Scheme::Fs.to_string()),
+                            ("root".to_string(), v.clone()),
+                        ]),
+                        source: anyhow!("root must be absolute for fs"),
                     });
                 }
                 v.to_string()
             }
         };
+        let context = HashMap::from([
+            ("scheme".to_string(), Scheme::Fs.to_string()),
+            ("root".to_string(), root.clone()),
+        ]);
+
+        let io_block_size = self.io_block_size.unwrap_or(DEFAULT_IO_BLOCK_SIZE);
+        if io_block_size == 0 {
+            return Err(Error::Backend {
+                kind: Kind::BackendConfigurationInvalid,
+                context,
+                source: anyhow!("io block size must be greater than 0"),
+            });
+        }
 
         // If root dir is not exist, we must create it.
         let metadata_root = root.clone();
-        if let Err(e) = unblock(|| fs::metadata(metadata_root)).await {
-            if e.kind() == std::io::ErrorKind::NotFound {
+        match unblock(|| fs::metadata(metadata_root)).await {
+            Ok(meta) => {
+                if !meta.is_dir() {
+                    return Err(Error::Backend {
+                        kind: Kind::BackendConfigurationInvalid,
+                        context,
+                        source: anyhow!("root exists but is not a directory"),
+                    });
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                 let dir_root = root.clone();
                 unblock(|| fs::create_dir_all(dir_root))
                     .await
-                    .map_err(|e| parse_io_error(e, "build", &root))?;
+                    .map_err(|e| Error::Backend {
+                        kind: Kind::BackendConfigurationInvalid,
+                        context: context.clone(),
+                        source: anyhow!("create root failed: {}", e),
+                    })?;
+            }
+            Err(e) => {
+                return Err(Error::Backend {
+                    kind: Kind::BackendConfigurationInvalid,
+                    context,
+                    source: anyhow!("stat root failed: {}", e),
+                });
             }
         }
 
         info!("backend build finished: {:?}", &self);
-        Ok(Arc::new(Backend { root }))
+        Ok(Arc::new(Backend {
+            root,
+            io_block_size,
+            _guard: self.guard.take(),
+        }))
+    }
+}
+
+#[async_trait]
+impl AccessorBuilder for Builder {
+    async fn finish(&mut self) -> Result<Arc<dyn Accessor>> {
+        Builder::finish(self).await
     }
 }
 
@@ -100,11 +180,31 @@ impl Builder {
 /// for better async performance under tokio. All `std::File` will be wrapped
 /// by `Unblock` to gain async support. IO will happen at the separate dedicated
 /// thread pool, so we will not block the tokio runtime.
+///
+/// Writes go to a temp file next to the object which is renamed into place
+/// once finished. Readers keep the file they opened, so they never observe
+/// a partial write ([`ReadConsistency::Committed`]).
 #[derive(Debug, Clone)]
 pub struct Backend {
     root: String,
+    io_block_size: usize,
+    /// Dropped along with the last clone of the backend, including the
+    /// ones held by listed objects. Used by tempfs to clean up the root.
+    _guard: Option<Arc<dyn Debug + Send + Sync>>,
 }
 
+/// Max length of a file name, which is `NAME_MAX` of most file systems.
+const MAX_SEGMENT_LEN: usize = 255;
+
+/// Max length of a full path, which is `PATH_MAX` (including the trailing
+/// nul) of the OS.
+#[cfg(target_os = "linux")]
+const MAX_PATH_LEN: usize = 4095;
+#[cfg(windows)]
+const MAX_PATH_LEN: usize = 259;
+#[cfg(not(any(target_os = "linux", windows)))]
+const MAX_PATH_LEN: usize = 1023;
+
 impl Backend {
     pub fn build() -> Builder {
         Builder::default()
@@ -124,11 +224,113 @@ impl Backend {
             .to_string_lossy()
             .to_string()
     }
+
+    /// Copy all data from the reader into a new file at `path`, which must
+    /// not exist.
+    async fn write_file(&self, r: BoxedAsyncReader, path: &str) -> Result<u64> {
+        let capture_path = path.to_string();
+        let f = unblock(move || {
+            fs::OpenOptions::new()
+                .create_new(true)
+                .write(true)
+                .open(capture_path)
+        })
+        .await
+        .map_err(|e| {
+            let e = parse_write_error(e, path);
+            error!("object {} open: {:?}", path, e);
+            e
+        })?;
+
+        let mut f = Unblock::with_capacity(self.io_block_size, f);
+
+        // TODO: we should respect the input size.
+        let r = io::BufReader::with_capacity(self.io_block_size, r);
+        let s = io::copy_buf(r, &mut f).await.map_err(|e| {
+            let e = parse_io_error(e, "write", path);
+            error!("object {} copy: {:?}", path, e);
+            e
+        })?;
+
+        // `std::fs::File`'s errors detected on closing are ignored by
+        // the implementation of Drop.
+        // So we need to call `flush` to make sure all data have been flushed
+        // to fs successfully.
+        f.flush().await.map_err(|e| {
+            let e = parse_io_error(e, "write", path);
+            error!("object {} flush: {:?}", path, e);
+            e
+        })?;
+
+        Ok(s)
+    }
+
+    /// Build object metadata from fs metadata.
+    pub(crate) fn build_metadata(path: &str, meta: &fs::Metadata) -> Metadata {
+        let mut m = Metadata::default();
+        m.set_path(path);
+        if meta.is_dir() {
+            m.set_mode(ObjectMode::DIR);
+        } else {
+            // TODO: we should handle LINK or other types here.
+            m.set_mode(ObjectMode::FILE);
+        }
+        m.set_content_length(meta.len());
+        if let Ok(t) = meta.modified() {
+            m.set_last_modified(t);
+        }
+        if let Some(etag) = Backend::build_etag(meta) {
+            m.set_etag(&etag);
+        }
+        m.set_fully_loaded();
+        m
+    }
+
+    /// fs doesn't have etag, we build a weak one from the modified time
+    /// and the length of the file, just like nginx does.
+    pub(crate) fn build_etag(meta: &fs::Metadata) -> Option<String> {
+        let modified = meta.modified().ok()?;
+        let d = modified.duration_since(UNIX_EPOCH).ok()?;
+
+        Some(format!(
+            "\"{:x}.{:x}-{:x}\"",
+            d.as_secs(),
+            d.subsec_nanos(),
+            meta.len()
+        ))
+    }
 }
 
 #[async_trait]
 impl Accessor for Backend {
-    async fn read(&self, args: &OpRead) -> Result<BoxedAsyncReader> {
+    async fn bucket_exists(&self) -> Result<bool> {
+        increment_counter!("opendal_fs_bucket_exists_requests");
+
+        let capture_root = self.root.clone();
+        match unblock(|| fs::metadata(capture_root)).await {
+            Ok(meta) => Ok(meta.is_dir()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => {
+                let e = parse_io_error(e, "bucket_exists", &self.root);
+                error!("backend root {} bucket_exists: {:?}", &self.root, e);
+                Err(e)
+            }
+        }
+    }
+
+    fn metadata(&self) -> AccessorMetadata {
+        let mut m = AccessorMetadata::default();
+        m.set_scheme(Scheme::Fs)
+            .set_commit_visible(true)
+            .set_read_consistency(ReadConsistency::Committed)
+            .set_write_if_not_exists(true)
+            .set_max_segment_len(MAX_SEGMENT_LEN)
+            // Paths will be joined with the root by a separator.
+            .set_max_path_len(MAX_PATH_LEN.saturating_sub(self.root.len() + 1));
+        m
+    }
+
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
         increment_counter!("opendal_fs_read_requests");
 
         let path = self.get_abs_path(&args.path);
@@ -136,17 +338,30 @@ impl Accessor for Backend {
             "object {} read start: offset {:?}, size {:?}",
             &path, args.offset, args.size
         );
+        if args.version_id.is_some() {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op: "read",
+                path,
+                source: anyhow!("read by version id is not supported"),
+            });
+        }
 
         let open_path = path.clone();
-        let f = unblock(|| fs::OpenOptions::new().read(true).open(open_path))
-            .await
-            .map_err(|e| {
-                let e = parse_io_error(e, "read", &path);
-                error!("object {} open: {:?}", &path, e);
-                e
-            })?;
+        let (f, meta) = unblock(|| {
+            let f = fs::OpenOptions::new().read(true).open(open_path)?;
+            // Metadata of an opened file is cheap, take it to save a stat.
+            let meta = f.metadata()?;
+            Ok::<_, std::io::Error>((f, meta))
+        })
+        .await
+        .map_err(|e| {
+            let e = parse_io_error(e, "read", &path);
+            error!("object {} open: {:?}", &path, e);
+            e
+        })?;
 
-        let mut f = Unblock::new(f);
+        let mut f = Unblock::with_capacity(self.io_block_size, f);
 
         if let Some(offset) = args.offset {
             f.seek(SeekFrom::Start(offset)).await.map_err(|e| {
@@ -165,12 +380,22 @@ impl Accessor for Backend {
             "object {} reader created: offset {:?}, size {:?}",
             &path, args.offset, args.size
         );
-        Ok(r)
+        Ok(ObjectReader::new(r).with_metadata(Backend::build_metadata(&args.path, &meta)))
     }
 
-    async fn write(&self, mut r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
         increment_counter!("opendal_fs_write_requests");
 
+        args.check_supported(&["commit_visible"])?;
+        // Dirs can't carry data on fs, don't write into a file silently.
+        if args.path.ends_with('/') {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op: "write",
+                path: args.path.clone(),
+                source: anyhow!("writing data into a dir path is not supported"),
+            });
+        }
         let path = self.get_abs_path(&args.path);
         info!("object {} write start: size {}", &path, args.size);
 
@@ -199,40 +424,97 @@ impl Accessor for Backend {
                 e
             })?;
 
+        // Always write into a temp file and rename it after finished, so that
+        // the object will not be observable at its final path before that,
+        // and readers which have opened the old file keep reading it as a
+        // whole. This is required by both `commit_visible` and
+        // `ReadConsistency::Committed`.
+        let tmp_path = format!("{}.opendal.{}", &path, Uuid::new_v4());
+
+        let s = match self.write_file(r, &tmp_path).await {
+            Ok(s) => s,
+            Err(e) => {
+                let capture_path = tmp_path.clone();
+                if let Err(err) = unblock(|| fs::remove_file(capture_path)).await {
+                    warn!("object {} remove temp file {}: {:?}", &path, &tmp_path, err);
+                }
+                return Err(e);
+            }
+        };
+
+        let (from, to) = (tmp_path.clone(), path.clone());
+        let if_not_exists = args.options.if_not_exists;
+        unblock(move || {
+            let res = if if_not_exists {
+                // Linking fails if the target exists while renaming
+                // replaces it.
+                fs::hard_link(&from, to)
+            } else {
+                fs::rename(&from, to)
+            };
+            if res.is_err() || if_not_exists {
+                let _ = fs::remove_file(&from);
+            }
+            res
+        })
+        .await
+        .map_err(|e| {
+            let e = parse_write_error(e, &path);
+            error!("object {} commit from {}: {:?}", &path, &tmp_path, e);
+            e
+        })?;
+
+        info!("object {} write finished: size {:?}", &path, args.size);
+        Ok(s as usize)
+    }
+
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        increment_counter!("opendal_fs_append_requests");
+
+        let path = self.get_abs_path(&args.path);
+        info!("object {} append start: size {}", &path, args.size);
+
+        let parent = PathBuf::from(&path)
+            .parent()
+            .ok_or_else(|| anyhow!("malformed path: {:?}", &path))?
+            .to_path_buf();
+        let capture_parent = parent.clone();
+        unblock(|| fs::create_dir_all(capture_parent))
+            .await
+            .map_err(|e| {
+                let e = parse_io_error(e, "append", &parent.to_string_lossy());
+                error!("object {} create_dir_all for parent: {:?}", &path, e);
+                e
+            })?;
+
         let capture_path = path.clone();
         let f = unblock(|| {
             fs::OpenOptions::new()
                 .create(true)
-                .write(true)
+                .append(true)
                 .open(capture_path)
         })
         .await
         .map_err(|e| {
-            let e = parse_io_error(e, "write", &path);
+            let e = parse_io_error(e, "append", &path);
             error!("object {} open: {:?}", &path, e);
             e
         })?;
 
-        let mut f = Unblock::new(f);
-
-        // TODO: we should respect the input size.
-        let s = io::copy(&mut r, &mut f).await.map_err(|e| {
-            let e = parse_io_error(e, "write", &path);
+        let mut f = Unblock::with_capacity(self.io_block_size, f);
+        let r = io::BufReader::with_capacity(self.io_block_size, r);
+        let s = io::copy_buf(r, &mut f).await.map_err(|e| {
+            let e = parse_io_error(e, "append", &path);
             error!("object {} copy: {:?}", &path, e);
             e
         })?;
-
-        // `std::fs::File`'s errors detected on closing are ignored by
-        // the implementation of Drop.
-        // So we need to call `flush` to make sure all data have been flushed
-        // to fs successfully.
         f.flush().await.map_err(|e| {
-            let e = parse_io_error(e, "write", &path);
+            let e = parse_io_error(e, "append", &path);
             error!("object {} flush: {:?}", &path, e);
             e
         })?;
 
-        info!("object {} write finished: size {:?}", &path, args.size);
+        info!("object {} append finished: size {:?}", &path, args.size);
         Ok(s as usize)
     }
 
@@ -249,16 +531,7 @@ impl Accessor for Backend {
             e
         })?;
 
-        let mut m = Metadata::default();
-        m.set_path(&args.path);
-        if meta.is_dir() {
-            m.set_mode(ObjectMode::DIR);
-        } else {
-            // TODO: we should handle LINK or other types here.
-            m.set_mode(ObjectMode::FILE);
-        }
-        m.set_content_length(meta.len() as u64);
-        m.set_complete();
+        let m = Backend::build_metadata(&args.path, &meta);
 
         info!("object {} stat finished", &path);
         Ok(m)
@@ -287,6 +560,19 @@ impl Accessor for Backend {
         // Safety: Err branch has been checked, it's OK to unwrap.
         let meta = meta.ok().unwrap();
 
+        // fs doesn't support conditional delete natively, there is a race
+        // window between the precondition check and the removal.
+        if args.is_conditional()
+            && !args.check_precondition(Backend::build_etag(&meta).as_deref(), meta.modified().ok())
+        {
+            return Err(Error::Object {
+                kind: Kind::PreconditionFailed,
+                op: "delete",
+                path: path.to_string(),
+                source: anyhow!("object has been modified"),
+            });
+        }
+
         let f = if meta.is_dir() {
             let capture_path = path.clone();
             unblock(|| fs::remove_dir(capture_path)).await
@@ -301,12 +587,67 @@ impl Accessor for Backend {
         Ok(())
     }
 
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        increment_counter!("opendal_fs_create_requests");
+
+        if args.mode != ObjectMode::DIR {
+            let r = Box::new(futures::io::Cursor::new(Vec::new()));
+            self.write(r, &OpWrite::new(&args.path, 0)).await?;
+            return Ok(());
+        }
+
+        let path = self.get_abs_path(&args.path);
+        info!("object {} create start", &path);
+
+        // Dirs are real on fs, create all missing parents like `mkdir -p`.
+        let capture_path = path.clone();
+        unblock(|| fs::create_dir_all(capture_path))
+            .await
+            .map_err(|e| {
+                let e = parse_io_error(e, "create", &path);
+                error!("object {} create_dir_all: {:?}", &path, e);
+                e
+            })?;
+
+        info!("object {} create finished", &path);
+        Ok(())
+    }
+
     async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
         increment_counter!("opendal_fs_list_requests");
 
         let path = self.get_abs_path(&args.path);
         info!("object {} list start", &path);
 
+        if args.mode == ListMode::Prefix {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op: "list",
+                path,
+                source: anyhow!("fs doesn't support listing by prefix"),
+            });
+        }
+
+        // Listing a file returns a stream that contains the file only.
+        let capture_path = path.clone();
+        let meta = unblock(|| fs::metadata(capture_path)).await.map_err(|e| {
+            let e = parse_io_error(e, "list", &path);
+            error!("object {} list: {:?}", &path, e);
+            e
+        })?;
+        if !meta.is_dir() {
+            if args.start_after.is_some() {
+                info!("object {} list finished: object is a file", &path);
+                return Ok(Box::new(stream::empty()));
+            }
+
+            let mut o = Object::new(Arc::new(self.clone()), &args.path);
+            *o.metadata_mut() = Backend::build_metadata(&args.path, &meta);
+
+            info!("object {} list finished: object is a file", &path);
+            return Ok(Box::new(stream::iter(vec![Ok(o)])));
+        }
+
         let open_path = path.clone();
         let f = fs::read_dir(open_path).map_err(|e| {
             let e = parse_io_error(e, "read", &path);
@@ -314,7 +655,8 @@ impl Accessor for Backend {
             e
         })?;
 
-        let rd = Readdir::new(Arc::new(self.clone()), &self.root, &args.path, f);
+        let rd = Readdir::new(Arc::new(self.clone()), &self.root, &args.path, f)
+            .start_after(args.start_after.as_deref());
 
         Ok(Box::new(rd))
     }
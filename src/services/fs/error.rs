@@ -24,7 +24,8 @@ pub fn parse_io_error(err: std::io::Error, op: &'static str, path: &str) -> Erro
     use std::io::ErrorKind;
 
     match err.kind() {
-        ErrorKind::NotFound => Error::Object {
+        // `a/b` doesn't exist if `a` is a file.
+        ErrorKind::NotFound | ErrorKind::NotADirectory => Error::Object {
             kind: Kind::ObjectNotExist,
             op,
             path: path.to_string(),
@@ -44,3 +45,17 @@ pub fn parse_io_error(err: std::io::Error, op: &'static str, path: &str) -> Erro
         },
     }
 }
+
+/// Parse errors of write, existing files rejected by `create_new` or
+/// `hard_link` will be returned as [`Kind::PreconditionFailed`].
+pub fn parse_write_error(err: std::io::Error, path: &str) -> Error {
+    match err.kind() {
+        std::io::ErrorKind::AlreadyExists => Error::Object {
+            kind: Kind::PreconditionFailed,
+            op: "write",
+            path: path.to_string(),
+            source: anyhow::Error::from(err),
+        },
+        _ => parse_io_error(err, "write", path),
+    }
+}
@@ -51,5 +51,5 @@ mod backend;
 pub use backend::Backend;
 pub use backend::Builder;
 
-mod error;
+pub(crate) mod error;
 mod object_stream;
@@ -23,6 +23,7 @@ use log::debug;
 use log::error;
 
 use super::error::parse_io_error;
+use super::Backend;
 use crate::error::Error;
 use crate::error::Kind;
 use crate::error::Result;
@@ -30,10 +31,15 @@ use crate::object::ObjectMode;
 use crate::Accessor;
 use crate::Object;
 
+/// Max entries read ahead from `read_dir`.
+const READDIR_BUFFER: usize = 256;
+
 pub struct Readdir {
     acc: Arc<dyn Accessor>,
     root: String,
     path: String,
+    /// Skip entries until this path has been met.
+    start_after: Option<String>,
 
     rd: Unblock<std::fs::ReadDir>,
 }
@@ -44,9 +50,21 @@ impl Readdir {
             acc,
             root: root.to_string(),
             path: path.to_string(),
-            rd: Unblock::new(rd),
+            start_after: None,
+            // `Unblock` buffers 8192 entries by default, which is allocated
+            // upfront for every dir being listed.
+            rd: Unblock::with_capacity(READDIR_BUFFER, rd),
         }
     }
+
+    /// Skip entries until `path` has been met.
+    ///
+    /// `read_dir` doesn't return entries in order, but the order is stable
+    /// for unchanged dirs, so listing could be resumed in this way.
+    pub fn start_after(mut self, path: Option<&str>) -> Self {
+        self.start_after = path.map(|v| v.to_string());
+        self
+    }
 }
 
 impl futures::Stream for Readdir {
@@ -63,17 +81,6 @@ impl futures::Stream for Readdir {
                 Poll::Ready(Some(Err(parse_io_error(e, "list", &self.path))))
             }
             Some(Ok(de)) => {
-                // NOTE: metadata is syscall.
-                let de_meta = de.metadata().map_err(|e| {
-                    let e = parse_io_error(e, "list", &de.path().to_string_lossy());
-                    error!("object {:?} metadata: {:?}", &de.path(), e);
-                    e
-                });
-                if let Err(e) = de_meta {
-                    return Poll::Ready(Some(Err(e)));
-                }
-                let de_meta = de_meta.unwrap();
-
                 let de_path = de.path();
                 let de_path = de_path.strip_prefix(&self.root).map_err(|e| {
                     let e = Error::Object {
@@ -85,19 +92,43 @@ impl futures::Stream for Readdir {
                     error!("object {:?} path strip_prefix: {:?}", &de.path(), e);
                     e
                 })?;
-                let path = de_path.to_string_lossy();
+                let mut path = de_path.to_string_lossy().to_string();
+
+                if let Some(start_after) = &self.start_after {
+                    // Dirs are returned with the trailing `/`.
+                    if path == start_after.trim_end_matches('/') {
+                        self.start_after = None;
+                    }
+                    return self.poll_next(cx);
+                }
 
+                // NOTE: metadata is syscall.
+                let de_meta = de.metadata().map_err(|e| {
+                    let e = parse_io_error(e, "list", &de.path().to_string_lossy());
+                    error!("object {:?} metadata: {:?}", &de.path(), e);
+                    e
+                })?;
+
+                // Dirs end with `/` like other services.
+                if de_meta.is_dir() {
+                    path.push('/');
+                }
                 let mut o = Object::new(self.acc.clone(), &path);
 
                 let meta = o.metadata_mut();
-                meta.set_complete();
                 if de_meta.is_dir() {
                     meta.set_mode(ObjectMode::DIR);
                 } else {
                     meta.set_mode(ObjectMode::FILE);
                 }
                 meta.set_content_length(de_meta.len());
-                meta.set_complete();
+                if let Ok(t) = de_meta.modified() {
+                    meta.set_last_modified(t);
+                }
+                if let Some(etag) = Backend::build_etag(&de_meta) {
+                    meta.set_etag(&etag);
+                }
+                meta.set_fully_loaded();
 
                 debug!(
                     "object {} got entry, path: {}, mode: {}",
@@ -0,0 +1,143 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::ops::Bound;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::TryStreamExt;
+
+use super::backend::BytesStream;
+use crate::error::Result;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Metadata;
+use crate::ObjectMode;
+
+/// Value is the data stored in kv services along with its metadata.
+#[derive(Debug, Clone)]
+pub(crate) struct Value {
+    pub data: Bytes,
+    pub etag: String,
+    pub last_modified: SystemTime,
+    /// Version of the value, exposed as `version_id` if the service
+    /// tracks it.
+    pub version: Option<u64>,
+}
+
+impl Value {
+    /// Build a value modified now, the etag is the quoted md5 of `data`.
+    pub fn new(data: Bytes) -> Self {
+        Self {
+            etag: format!("\"{:x}\"", md5::compute(&data)),
+            last_modified: SystemTime::now(),
+            version: None,
+            data,
+        }
+    }
+
+    pub fn with_version(mut self, version: u64) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    pub fn metadata(&self, path: &str) -> Metadata {
+        let mut meta = Metadata::default();
+        // Keys like `abc/` are dirs even if they carry data.
+        let mode = if path.ends_with('/') {
+            ObjectMode::DIR
+        } else {
+            ObjectMode::FILE
+        };
+        meta.set_path(path)
+            .set_mode(mode)
+            .set_content_length(self.data.len() as u64)
+            .set_etag(&self.etag)
+            .set_last_modified(self.last_modified);
+        if let Some(version) = self.version {
+            meta.set_version_id(&version.to_string());
+        }
+        meta.set_fully_loaded();
+        meta
+    }
+}
+
+/// Adapter is the interface of flat key-value services, every type
+/// implements it will be an [`Accessor`][crate::Accessor].
+///
+/// Keys passed in are always normalized paths.
+#[async_trait]
+pub(crate) trait Adapter: Debug + Clone + Send + Sync + 'static {
+    /// Capabilities of the service, commit visible and write if not
+    /// exists are always supported on top of the adapter.
+    ///
+    /// The read consistency is left to the adapter: only adapters whose
+    /// [`Adapter::reader`] streams a snapshot of the value could report
+    /// [`ReadConsistency::Committed`][crate::ReadConsistency::Committed].
+    fn accessor_metadata(&self) -> AccessorMetadata {
+        AccessorMetadata::default()
+    }
+
+    /// Build the reader of `data` which has been sliced to the range to
+    /// read, the default implementation returns it in a single chunk.
+    fn reader(&self, data: Bytes) -> BoxedAsyncReader {
+        Box::new(BytesStream(data).into_async_read())
+    }
+
+    /// Get the value of `key`, returns `None` if not exists.
+    async fn get(&self, key: &str) -> Result<Option<Value>>;
+
+    /// Store `data` at `key`, replacing the old value.
+    ///
+    /// With `if_not_exists`, returns `false` without storing if `key`
+    /// exists. The check must be atomic with the store.
+    async fn set(&self, key: &str, data: Bytes, if_not_exists: bool) -> Result<bool>;
+
+    /// Append `data` to the value of `key`, creating it if not exists.
+    ///
+    /// The default implementation is not atomic, services should override
+    /// it if they could do better.
+    async fn append(&self, key: &str, data: &[u8]) -> Result<()> {
+        let mut bs = match self.get(key).await? {
+            Some(v) => v.data.to_vec(),
+            None => Vec::with_capacity(data.len()),
+        };
+        bs.extend_from_slice(data);
+        self.set(key, Bytes::from(bs), false).await?;
+        Ok(())
+    }
+
+    /// Delete `key` if `cond` returns `true` on its value, returns `false`
+    /// if rejected by `cond`. Deleting a not existing key succeeds.
+    ///
+    /// The check must be atomic with the deletion.
+    async fn delete(
+        &self,
+        key: &str,
+        cond: &(dyn for<'v> Fn(&'v Value) -> bool + Send + Sync),
+    ) -> Result<bool>;
+
+    /// Returns metadata of at most `limit` entries starting from `start`
+    /// in key order, the path of each metadata is its key.
+    ///
+    /// Data is not needed here, services should avoid copying it.
+    async fn scan(&self, start: Bound<String>, limit: usize) -> Result<Vec<Metadata>>;
+
+    /// Check whether `key` exists.
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.get(key).await?.is_some())
+    }
+}
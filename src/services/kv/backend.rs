@@ -0,0 +1,383 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+use std::ops::Bound;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::io;
+use futures::StreamExt;
+
+use super::Adapter;
+use super::Value;
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::ops::ListMode;
+use crate::ops::OpAppend;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpRead;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Metadata;
+use crate::Object;
+use crate::ObjectMode;
+use crate::ObjectReader;
+
+/// Entries fetched by one `scan` while listing.
+const SCAN_BATCH: usize = 256;
+
+// normalize_path removes all internal `//` inside path.
+fn normalize_path(path: &str) -> String {
+    let has_trailing = path.ends_with('/');
+
+    let mut p = path
+        .split('/')
+        .filter(|v| !v.is_empty())
+        .collect::<Vec<&str>>()
+        .join("/");
+
+    if has_trailing && !p.eq("/") {
+        p.push('/')
+    }
+
+    p
+}
+
+#[async_trait]
+impl<S: Adapter> Accessor for S {
+    async fn bucket_exists(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn metadata(&self) -> AccessorMetadata {
+        let mut m = self.accessor_metadata();
+        // Values are replaced as a whole, but the read consistency depends
+        // on how adapters get and stream values, they report it by
+        // themselves.
+        m.set_commit_visible(true).set_write_if_not_exists(true);
+        m
+    }
+
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        let path = normalize_path(&args.path);
+        // Old versions are not kept.
+        if args.version_id.is_some() {
+            return Err(Error::Object {
+                kind: Kind::Unsupported,
+                op: "read",
+                path,
+                source: anyhow!("read by version id is not supported"),
+            });
+        }
+
+        let value = self.get(&path).await?.ok_or_else(|| Error::Object {
+            kind: Kind::ObjectNotExist,
+            op: "read",
+            path: path.to_string(),
+            source: anyhow!("key not exists"),
+        })?;
+
+        let meta = value.metadata(&path);
+        let mut data = value.data;
+        if let Some(offset) = args.offset {
+            // Like s3, reading from the start of an empty object is fine.
+            if offset > 0 && offset >= data.len() as u64 {
+                return Err(Error::Object {
+                    kind: Kind::RangeNotSatisfiable,
+                    op: "read",
+                    path: path.to_string(),
+                    source: anyhow!("offset out of bound {} >= {}", offset, data.len()),
+                });
+            }
+            data = data.slice(offset as usize..data.len());
+        };
+
+        // Like other backends, ranges over-reading EOF will be truncated.
+        if let Some(size) = args.size {
+            data = data.slice(0..(size as usize).min(data.len()));
+        };
+
+        Ok(ObjectReader::new(self.reader(data))
+            .with_metadata(meta)
+            .with_buffered())
+    }
+    async fn write(&self, mut r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        // Data will be visible only after all of it has been read.
+        args.check_supported(&["commit_visible"])?;
+        let path = normalize_path(&args.path);
+
+        let bs = vec![0; args.size as usize];
+        let mut cursor = io::Cursor::new(bs);
+        let n = io::copy(&mut r, &mut cursor)
+            .await
+            .map_err(|e| Error::Object {
+                kind: Kind::Unexpected,
+                op: "write",
+                path: path.clone(),
+                source: anyhow::Error::from(e),
+            })?;
+        if n < args.size {
+            return Err(Error::Object {
+                kind: Kind::Unexpected,
+                op: "write",
+                path: path.clone(),
+                source: anyhow!("write short  {} M {}", n, args.size),
+            });
+        }
+
+        let data = Bytes::from(cursor.into_inner());
+        if !self.set(&path, data, args.options.if_not_exists).await? {
+            return Err(Error::Object {
+                kind: Kind::PreconditionFailed,
+                op: "write",
+                path,
+                source: anyhow!("object already exists"),
+            });
+        }
+
+        Ok(n as usize)
+    }
+    async fn append(&self, mut r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        let path = normalize_path(&args.path);
+
+        let mut bs = Vec::with_capacity(args.size as usize);
+        let n = io::copy(&mut r, &mut bs).await.map_err(|e| Error::Object {
+            kind: Kind::Unexpected,
+            op: "append",
+            path: path.clone(),
+            source: anyhow::Error::from(e),
+        })?;
+        if n < args.size {
+            return Err(Error::Object {
+                kind: Kind::Unexpected,
+                op: "append",
+                path: path.clone(),
+                source: anyhow!("append short {} M {}", n, args.size),
+            });
+        }
+
+        Adapter::append(self, &path, &bs).await?;
+
+        Ok(n as usize)
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        let path = normalize_path(&args.path);
+        let value = self.get(&path).await?;
+
+        if path.ends_with('/') {
+            // Dir markers with data expose their real length.
+            if let Some(value) = value {
+                return Ok(value.metadata(&path));
+            }
+
+            let mut meta = Metadata::default();
+            meta.set_path(&path)
+                .set_mode(ObjectMode::DIR)
+                .set_content_length(0)
+                .set_fully_loaded();
+
+            return Ok(meta);
+        }
+
+        let value = value.ok_or_else(|| Error::Object {
+            kind: Kind::ObjectNotExist,
+            op: "stat",
+            path: path.to_string(),
+            source: anyhow!("key not exists"),
+        })?;
+
+        Ok(value.metadata(&path))
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        let path = normalize_path(&args.path);
+
+        let cond = |v: &Value| args.check_precondition(Some(&v.etag), Some(v.last_modified));
+        if !Adapter::delete(self, &path, &cond).await? {
+            return Err(Error::Object {
+                kind: Kind::PreconditionFailed,
+                op: "delete",
+                path: path.to_string(),
+                source: anyhow!("object has been modified"),
+            });
+        }
+
+        Ok(())
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        let mut path = normalize_path(&args.path);
+
+        // Listing a file returns a stream that contains the file only, while
+        // `abc/` is always a dir even if its marker exists.
+        let exact =
+            args.mode == ListMode::Dir && !path.ends_with('/') && self.exists(&path).await?;
+        // `abc` lists the dir `abc/` instead of keys like `abcdef`.
+        if args.mode == ListMode::Dir && !exact && !path.is_empty() && !path.ends_with('/') {
+            path.push('/');
+        }
+        let cursor = match &args.start_after {
+            Some(v) if normalize_path(v) >= path => Bound::Excluded(normalize_path(v)),
+            _ => Bound::Included(path.clone()),
+        };
+
+        let state = EntryState {
+            adapter: self.clone(),
+            fold: args.mode == ListMode::Dir && !exact,
+            start_after: args.start_after.as_deref().map(normalize_path),
+            prefix: path,
+            cursor,
+            exact,
+            folded: None,
+            entries: VecDeque::new(),
+            done: false,
+        };
+        Ok(Box::new(
+            futures::stream::try_unfold(state, EntryState::next).boxed(),
+        ))
+    }
+}
+
+pub(super) struct BytesStream(pub(super) Bytes);
+
+impl futures::Stream for BytesStream {
+    type Item = std::result::Result<bytes::Bytes, std::io::Error>;
+
+    // Always poll the entire stream.
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let size = self.0.len();
+        match self.0.len() {
+            0 => Poll::Ready(None),
+            _ => Poll::Ready(Some(Ok(self.0.split_to(size)))),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+/// EntryState walks keys under the prefix in order.
+///
+/// Only a small batch of entries after the cursor is held, so listing a
+/// huge prefix doesn't need to copy all keys, and keys written or deleted
+/// during listing could be observed by later batches.
+struct EntryState<S> {
+    adapter: S,
+    prefix: String,
+    cursor: Bound<String>,
+    /// Only returns the key equals to prefix.
+    exact: bool,
+    /// List like a dir: the marker of the listed dir (like `abc/` while
+    /// listing `abc/`) is skipped, and keys in sub dirs are folded into
+    /// dir entries like fs.
+    fold: bool,
+    /// Dirs not after it have been returned by the previous listing.
+    start_after: Option<String>,
+    /// The sub dir returned last, keys under it are skipped.
+    folded: Option<String>,
+    entries: VecDeque<Metadata>,
+    /// No more entries after the buffered ones.
+    done: bool,
+}
+
+impl<S: Adapter> EntryState<S> {
+    async fn next(mut self) -> Result<Option<(Object, Self)>> {
+        loop {
+            if let Some(mut meta) = self.entries.pop_front() {
+                let matched = if self.exact {
+                    meta.path() == self.prefix
+                } else {
+                    meta.path().starts_with(&self.prefix)
+                };
+                // Keys are in order, no more matches after a mismatch.
+                if !matched {
+                    return Ok(None);
+                }
+                if self.fold {
+                    match self.fold_entry(meta) {
+                        Some(v) => meta = v,
+                        None => continue,
+                    }
+                }
+
+                let mut o = Object::new(Arc::new(self.adapter.clone()), meta.path());
+                *o.metadata_mut() = meta;
+                return Ok(Some((o, self)));
+            }
+            if self.done {
+                return Ok(None);
+            }
+
+            // Jump over the rest of the folded dir, keys under `abc/` are
+            // all before `abc0`.
+            if let (Some(dir), Bound::Excluded(last)) = (&self.folded, &self.cursor) {
+                if last.starts_with(dir.as_str()) {
+                    self.cursor = Bound::Included(format!("{}0", &dir[..dir.len() - 1]));
+                }
+            }
+            let entries = self.adapter.scan(self.cursor.clone(), SCAN_BATCH).await?;
+            self.done = entries.len() < SCAN_BATCH;
+            if let Some(meta) = entries.last() {
+                self.cursor = Bound::Excluded(meta.path().to_string());
+            }
+            self.entries = entries.into();
+        }
+    }
+
+    /// Returns the entry to list for `meta` in dir mode, or `None` if it
+    /// should be skipped.
+    fn fold_entry(&mut self, meta: Metadata) -> Option<Metadata> {
+        if meta.path() == self.prefix {
+            return None;
+        }
+        if let Some(dir) = &self.folded {
+            if meta.path().starts_with(dir.as_str()) {
+                return None;
+            }
+        }
+
+        let rest = &meta.path()[self.prefix.len()..];
+        let idx = match rest.find('/') {
+            Some(idx) => idx,
+            None => return Some(meta),
+        };
+        let dir = format!("{}{}", self.prefix, &rest[..=idx]);
+        self.folded = Some(dir.clone());
+        if matches!(&self.start_after, Some(v) if &dir <= v) {
+            return None;
+        }
+
+        // Markers with data keep their real length.
+        if meta.path() == dir {
+            return Some(meta);
+        }
+        let mut meta = Metadata::default();
+        meta.set_path(&dir)
+            .set_mode(ObjectMode::DIR)
+            .set_content_length(0)
+            .set_fully_loaded();
+        Some(meta)
+    }
+}
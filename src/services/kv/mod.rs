@@ -0,0 +1,31 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Shared backend of flat key-value services.
+//!
+//! Services like memory only need to implement [`Adapter`], all of
+//! [`Accessor`][crate::Accessor] will be served on top of it:
+//!
+//! - Keys are normalized paths, dirs are not stored unless they are
+//!   written explicitly as keys ending with `/`.
+//! - Values are written as a whole after all data has been read, so
+//!   writes are always commit visible.
+//! - Listing walks keys under the prefix in order via [`Adapter::scan`] in
+//!   small batches, so a huge prefix never needs to be held in memory.
+
+mod adapter;
+pub(crate) use adapter::Adapter;
+pub(crate) use adapter::Value;
+
+mod backend;
@@ -12,46 +12,114 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
-use std::pin::Pin;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::task::Context;
-use std::task::Poll;
+use std::time::Duration;
 
-use anyhow::anyhow;
 use async_trait::async_trait;
 use bytes::Bytes;
-use futures::io;
-use futures::TryStreamExt;
 
-use crate::error::Error;
-use crate::error::Kind;
+use super::latency::Latency;
 use crate::error::Result;
-use crate::object::BoxedObjectStream;
-use crate::ops::OpDelete;
-use crate::ops::OpList;
-use crate::ops::OpRead;
-use crate::ops::OpStat;
-use crate::ops::OpWrite;
+use crate::services::kv::Adapter;
+use crate::services::kv::Value;
 use crate::Accessor;
+use crate::AccessorBuilder;
+use crate::AccessorMetadata;
 use crate::BoxedAsyncReader;
 use crate::Metadata;
-use crate::Object;
-use crate::ObjectMode;
+use crate::ReadConsistency;
+use crate::Scheme;
 
 #[derive(Default)]
-pub struct Builder {}
+pub struct Builder {
+    max_path_len: Option<usize>,
+    latency: Duration,
+    jitter: Duration,
+    throughput: Option<u64>,
+    seed: Option<u64>,
+}
 
 impl Builder {
+    /// Reject paths longer than `n` bytes, unlimited by default.
+    ///
+    /// Useful to emulate the limits of other services in tests.
+    pub fn max_path_len(&mut self, n: usize) -> &mut Self {
+        self.max_path_len = Some(n);
+        self
+    }
+
+    /// Wait `d` in every operation to simulate the round trip of remote
+    /// services, zero by default.
+    pub fn latency(&mut self, d: Duration) -> &mut Self {
+        self.latency = d;
+        self
+    }
+
+    /// Wait an extra random delay picked uniformly from `[0, d)` in every
+    /// operation, zero by default.
+    pub fn jitter(&mut self, d: Duration) -> &mut Self {
+        self.jitter = d;
+        self
+    }
+
+    /// Transfer data at `bytes_per_sec`, unlimited by default.
+    ///
+    /// Reads wait while the reader is polled chunk by chunk, so slow
+    /// consumers and prefetching behave like reading from network.
+    pub fn throughput(&mut self, bytes_per_sec: u64) -> &mut Self {
+        self.throughput = Some(bytes_per_sec);
+        self
+    }
+
+    /// Seed the randomness of jitter to make delays deterministic, a
+    /// random seed will be used if not set.
+    pub fn seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = Some(seed);
+        self
+    }
+
     pub async fn finish(&mut self) -> Result<Arc<dyn Accessor>> {
-        Ok(Arc::new(Backend::default()))
+        Ok(Arc::new(Backend {
+            max_path_len: self.max_path_len,
+            latency: Latency::new(self.latency, self.jitter, self.throughput, self.seed),
+            ..Backend::default()
+        }))
+    }
+}
+
+#[async_trait]
+impl AccessorBuilder for Builder {
+    async fn finish(&mut self) -> Result<Arc<dyn Accessor>> {
+        Builder::finish(self).await
     }
 }
 
+/// Backend is used to serve `Accessor` support in memory.
+///
+/// # Note
+///
+/// Every write or delete bumps a generation number which is monotonically
+/// increasing across the backend, so it's also monotonically increasing
+/// per key. Files expose it as `version_id`, tests could use it to assert
+/// the order of writes without relying on timestamps.
+///
+/// Data is stored as immutable [`Bytes`] and replaced as a whole after the
+/// write finished. Readers hold the snapshot at the time they opened, so
+/// they always see either the complete old content or the complete new
+/// content, which is reported as [`ReadConsistency::Committed`].
+///
+/// [`ReadConsistency::Committed`]: crate::ReadConsistency::Committed
 #[derive(Debug, Clone, Default)]
 pub struct Backend {
-    inner: Arc<Mutex<HashMap<String, bytes::Bytes>>>,
+    inner: Arc<Mutex<BTreeMap<String, Value>>>,
+    generation: Arc<AtomicU64>,
+    max_path_len: Option<usize>,
+    latency: Latency,
 }
 
 impl Backend {
@@ -59,205 +127,91 @@ impl Backend {
         Builder::default()
     }
 
-    // normalize_path removes all internal `//` inside path.
-    pub(crate) fn normalize_path(path: &str) -> String {
-        let has_trailing = path.ends_with('/');
-
-        let mut p = path
-            .split('/')
-            .filter(|v| !v.is_empty())
-            .collect::<Vec<&str>>()
-            .join("/");
-
-        if has_trailing && !p.eq("/") {
-            p.push('/')
-        }
-
-        p
+    /// Bump the generation, must be called with the lock of `inner` held
+    /// so that generations follow the order of modifications.
+    fn next_generation(&self) -> u64 {
+        self.generation.fetch_add(1, Ordering::SeqCst) + 1
     }
 }
 
 #[async_trait]
-impl Accessor for Backend {
-    async fn read(&self, args: &OpRead) -> Result<BoxedAsyncReader> {
-        let path = Backend::normalize_path(&args.path);
-
-        let map = self.inner.lock().expect("lock poisoned");
-
-        let data = map.get(&path).ok_or_else(|| Error::Object {
-            kind: Kind::ObjectNotExist,
-            op: "read",
-            path: path.to_string(),
-            source: anyhow!("key not exists in map"),
-        })?;
-
-        let mut data = data.clone();
-        if let Some(offset) = args.offset {
-            if offset >= data.len() as u64 {
-                return Err(Error::Object {
-                    kind: Kind::Unexpected,
-                    op: "read",
-                    path: path.to_string(),
-                    source: anyhow!("offset out of bound {} >= {}", offset, data.len()),
-                });
-            }
-            data = data.slice(offset as usize..data.len());
-        };
-
-        if let Some(size) = args.size {
-            if size > data.len() as u64 {
-                return Err(Error::Object {
-                    kind: Kind::Unexpected,
-                    op: "read",
-                    path: path.to_string(),
-                    source: anyhow!("size out of bound {} > {}", size, data.len()),
-                });
-            }
-            data = data.slice(0..size as usize);
-        };
-
-        let r: BoxedAsyncReader = Box::new(BytesStream(data).into_async_read());
-        Ok(r)
-    }
-    async fn write(&self, mut r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
-        let path = Backend::normalize_path(&args.path);
-
-        let bs = vec![0; args.size as usize];
-        let mut cursor = io::Cursor::new(bs);
-        let n = io::copy(&mut r, &mut cursor)
-            .await
-            .map_err(|e| Error::Object {
-                kind: Kind::Unexpected,
-                op: "write",
-                path: path.clone(),
-                source: anyhow::Error::from(e),
-            })?;
-        if n < args.size {
-            return Err(Error::Object {
-                kind: Kind::Unexpected,
-                op: "write",
-                path: path.clone(),
-                source: anyhow!("write short  {} M {}", n, args.size),
-            });
+impl Adapter for Backend {
+    fn accessor_metadata(&self) -> AccessorMetadata {
+        let mut m = AccessorMetadata::default();
+        m.set_scheme(Scheme::Memory)
+            .set_read_consistency(ReadConsistency::Committed);
+        if let Some(n) = self.max_path_len {
+            m.set_max_path_len(n);
         }
-
-        let mut map = self.inner.lock().expect("lock poisoned");
-        map.insert(path.to_string(), Bytes::from(cursor.into_inner()));
-
-        Ok(n as usize)
+        m
     }
-    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
-        let path = Backend::normalize_path(&args.path);
-
-        if path.ends_with('/') {
-            let mut meta = Metadata::default();
-            meta.set_path(&path)
-                .set_mode(ObjectMode::DIR)
-                .set_content_length(0)
-                .set_complete();
 
-            return Ok(meta);
-        }
+    fn reader(&self, data: Bytes) -> BoxedAsyncReader {
+        self.latency.reader(data)
+    }
 
+    async fn get(&self, key: &str) -> Result<Option<Value>> {
+        self.latency.wait(0).await;
         let map = self.inner.lock().expect("lock poisoned");
-
-        let data = map.get(&path).ok_or_else(|| Error::Object {
-            kind: Kind::ObjectNotExist,
-            op: "stat",
-            path: path.to_string(),
-            source: anyhow!("key not exists in map"),
-        })?;
-
-        let mut meta = Metadata::default();
-        meta.set_path(&path)
-            .set_mode(ObjectMode::FILE)
-            .set_content_length(data.len() as u64)
-            .set_complete();
-
-        Ok(meta)
+        Ok(map.get(key).cloned())
     }
-    async fn delete(&self, args: &OpDelete) -> Result<()> {
-        let path = Backend::normalize_path(&args.path);
 
+    async fn set(&self, key: &str, data: Bytes, if_not_exists: bool) -> Result<bool> {
+        self.latency.wait(data.len()).await;
         let mut map = self.inner.lock().expect("lock poisoned");
-        map.remove(&path);
-
-        Ok(())
+        // Checked with the lock held, so that no write could happen between
+        // the check and the insertion.
+        if if_not_exists && map.contains_key(key) {
+            return Ok(false);
+        }
+        let generation = self.next_generation();
+        map.insert(key.to_string(), Value::new(data).with_version(generation));
+        Ok(true)
     }
-    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
-        let path = Backend::normalize_path(&args.path);
 
-        let map = self.inner.lock().expect("lock poisoned");
-
-        let paths = map
-            .iter()
-            .map(|(k, _)| k.clone())
-            .filter(|k| k.starts_with(&path))
-            .collect::<Vec<String>>();
-
-        Ok(Box::new(EntryStream {
-            backend: self.clone(),
-            paths,
-            idx: 0,
-        }))
+    async fn append(&self, key: &str, data: &[u8]) -> Result<()> {
+        self.latency.wait(data.len()).await;
+        let mut map = self.inner.lock().expect("lock poisoned");
+        let mut bs = map.get(key).map(|v| v.data.to_vec()).unwrap_or_default();
+        bs.extend_from_slice(data);
+        let generation = self.next_generation();
+        map.insert(
+            key.to_string(),
+            Value::new(Bytes::from(bs)).with_version(generation),
+        );
+        Ok(())
     }
-}
-
-struct BytesStream(Bytes);
 
-impl futures::Stream for BytesStream {
-    type Item = std::result::Result<bytes::Bytes, std::io::Error>;
-
-    // Always poll the entire stream.
-    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let size = self.0.len();
-        match self.0.len() {
-            0 => Poll::Ready(None),
-            _ => Poll::Ready(Some(Ok(self.0.split_to(size)))),
+    async fn delete(
+        &self,
+        key: &str,
+        cond: &(dyn for<'v> Fn(&'v Value) -> bool + Send + Sync),
+    ) -> Result<bool> {
+        self.latency.wait(0).await;
+        let mut map = self.inner.lock().expect("lock poisoned");
+        match map.get(key) {
+            None => return Ok(true),
+            Some(v) if !cond(v) => return Ok(false),
+            Some(_) => {}
         }
+        map.remove(key);
+        self.next_generation();
+        Ok(true)
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.0.len(), Some(self.0.len()))
+    async fn scan(&self, start: Bound<String>, limit: usize) -> Result<Vec<Metadata>> {
+        self.latency.wait(0).await;
+        let map = self.inner.lock().expect("lock poisoned");
+        Ok(map
+            .range((start, Bound::Unbounded))
+            .take(limit)
+            .map(|(k, v)| v.metadata(k))
+            .collect())
     }
-}
-
-struct EntryStream {
-    backend: Backend,
-    paths: Vec<String>,
-    idx: usize,
-}
-
-impl futures::Stream for EntryStream {
-    type Item = Result<Object>;
-
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        if self.idx >= self.paths.len() {
-            return Poll::Ready(None);
-        }
-
-        let idx = self.idx;
-        self.idx += 1;
-
-        let path = self.paths.get(idx).expect("path must valid");
-
-        let backend = self.backend.clone();
-        let map = backend.inner.lock().expect("lock poisoned");
 
-        let data = map.get(path);
-        // If the path is not get, we can skip it safely.
-        if data.is_none() {
-            return self.poll_next(cx);
-        }
-        let bs = data.expect("object must exist");
-
-        let mut o = Object::new(Arc::new(self.backend.clone()), path);
-        let meta = o.metadata_mut();
-        meta.set_path(path)
-            .set_mode(ObjectMode::FILE)
-            .set_content_length(bs.len() as u64)
-            .set_complete();
-
-        Poll::Ready(Some(Ok(o)))
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.latency.wait(0).await;
+        let map = self.inner.lock().expect("lock poisoned");
+        Ok(map.contains_key(key))
     }
 }
@@ -0,0 +1,120 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use futures::TryStreamExt;
+
+use crate::BoxedAsyncReader;
+
+/// Reads are served in chunks of this size, each chunk waits for its own
+/// share of the throughput.
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Latency simulates the time spent by remote services.
+///
+/// Every operation waits `base` plus a random delay picked uniformly from
+/// `[0, jitter)`, and transferring data waits `len / bytes_per_sec`.
+/// Zero by default so nothing will be waited.
+#[derive(Debug, Clone)]
+pub(crate) struct Latency {
+    pub base: Duration,
+    pub jitter: Duration,
+    pub bytes_per_sec: Option<u64>,
+    /// State of xorshift64, shared by clones so that a seeded backend
+    /// produces the same sequence of delays.
+    state: Arc<Mutex<u64>>,
+}
+
+impl Default for Latency {
+    fn default() -> Self {
+        Self {
+            base: Duration::ZERO,
+            jitter: Duration::ZERO,
+            bytes_per_sec: None,
+            state: Arc::new(Mutex::new(1)),
+        }
+    }
+}
+
+impl Latency {
+    pub fn new(
+        base: Duration,
+        jitter: Duration,
+        bytes_per_sec: Option<u64>,
+        seed: Option<u64>,
+    ) -> Self {
+        let seed = seed.unwrap_or_else(|| uuid::Uuid::new_v4().as_u128() as u64);
+        Self {
+            base,
+            jitter,
+            bytes_per_sec,
+            // xorshift gets stuck at zero.
+            state: Arc::new(Mutex::new(seed.max(1))),
+        }
+    }
+
+    /// Wait for an operation transferring `size` bytes.
+    pub async fn wait(&self, size: usize) {
+        let dur = self.base + self.jitter() + self.transfer(size);
+        if !dur.is_zero() {
+            tokio::time::sleep(dur).await
+        }
+    }
+
+    /// Build a reader of `data` which waits for the throughput while
+    /// being polled instead of up front.
+    pub fn reader(&self, data: Bytes) -> BoxedAsyncReader {
+        let latency = self.clone();
+        let s = futures::stream::unfold(data, move |mut data| {
+            let latency = latency.clone();
+            async move {
+                if data.is_empty() {
+                    return None;
+                }
+                let chunk = data.split_to(READ_CHUNK.min(data.len()));
+                let dur = latency.transfer(chunk.len());
+                if !dur.is_zero() {
+                    tokio::time::sleep(dur).await
+                }
+                Some((Ok::<_, std::io::Error>(chunk), data))
+            }
+        });
+        Box::new(s.boxed().into_async_read())
+    }
+
+    fn transfer(&self, size: usize) -> Duration {
+        match self.bytes_per_sec {
+            Some(n) if n > 0 => Duration::from_secs_f64(size as f64 / n as f64),
+            _ => Duration::ZERO,
+        }
+    }
+
+    fn jitter(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return Duration::ZERO;
+        }
+
+        let mut state = self.state.lock().expect("lock poisoned");
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        let f = (*state >> 11) as f64 / (1u64 << 53) as f64;
+        self.jitter.mul_f64(f)
+    }
+}
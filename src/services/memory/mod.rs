@@ -15,5 +15,6 @@
 //! In memory backend support.
 
 mod backend;
+mod latency;
 pub use backend::Backend;
 pub use backend::Builder;
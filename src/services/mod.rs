@@ -19,6 +19,9 @@
 //! - Builder: responsible for building the service backend.
 //! - Backend: the service backend which implements the [`Accessor`][crate::Accessor] trait.
 
+pub mod data;
 pub mod fs;
+pub(crate) mod kv;
 pub mod memory;
 pub mod s3;
+pub mod tempfs;
@@ -0,0 +1,56 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// MultipartChecksum records the MD5 of every part in a multipart upload
+/// and computes the composite ETag that S3 reports for the completed object.
+///
+/// The composite ETag is not the MD5 of the whole content, but the MD5 of
+/// the concatenated part MD5s followed by `-` and the number of parts, like
+/// `"72d27afac8e2fbd3662861b9d607a02c-2"`.
+///
+/// # Note
+///
+/// Objects encrypted by SSE-KMS or SSE-C don't have MD5 based ETags, so the
+/// composite ETag can't be compared with theirs.
+#[derive(Debug, Clone, Default)]
+pub struct MultipartChecksum {
+    digests: Vec<[u8; 16]>,
+}
+
+impl MultipartChecksum {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the content of next part.
+    ///
+    /// Parts must be pushed in the order of their part numbers.
+    pub fn push(&mut self, part: &[u8]) {
+        self.digests.push(md5::compute(part).0);
+    }
+
+    /// Return the number of recorded parts.
+    pub fn parts(&self) -> usize {
+        self.digests.len()
+    }
+
+    /// Compute the composite ETag with quotes, just like S3 returns.
+    pub fn etag(&self) -> String {
+        format!(
+            "\"{:x}-{}\"",
+            md5::compute(self.digests.concat()),
+            self.digests.len()
+        )
+    }
+}
@@ -12,12 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use aws_sdk_s3::error::AbortMultipartUploadError;
+use aws_sdk_s3::error::AbortMultipartUploadErrorKind;
 use aws_sdk_s3::error::GetObjectError;
 use aws_sdk_s3::error::GetObjectErrorKind;
+use aws_sdk_s3::error::GetObjectLegalHoldError;
+use aws_sdk_s3::error::GetObjectRetentionError;
+use aws_sdk_s3::error::HeadBucketError;
+use aws_sdk_s3::error::HeadBucketErrorKind;
 use aws_sdk_s3::error::HeadObjectError;
 use aws_sdk_s3::error::HeadObjectErrorKind;
+use std::fmt;
+
+use aws_smithy_http::operation;
 use aws_smithy_http::result::SdkError;
+use http::StatusCode;
 
+use super::partition::Partition;
+use crate::error::DeleteMarker;
 use crate::error::Error;
 use crate::error::Kind;
 
@@ -26,20 +38,32 @@ pub fn parse_get_object_error(
     op: &'static str,
     path: &str,
 ) -> Error {
-    if let SdkError::ServiceError { err, .. } = err {
-        match err.kind {
-            GetObjectErrorKind::NoSuchKey(_) => Error::Object {
+    if let SdkError::ServiceError { err, raw } = err {
+        if let Some(marker) = parse_delete_marker(&raw) {
+            return Error::Object {
                 kind: Kind::ObjectNotExist,
                 op,
                 path: path.to_string(),
-                source: anyhow::Error::from(err),
-            },
-            _ => Error::Object {
-                kind: Kind::Unexpected,
-                op,
-                path: path.to_string(),
-                source: anyhow::Error::from(err),
-            },
+                source: anyhow::Error::from(err).context(marker),
+            };
+        }
+
+        let kind = match err.kind {
+            GetObjectErrorKind::NoSuchKey(_) => Kind::ObjectNotExist,
+            // `NoSuchVersion` and 404 responses without body are missing
+            // objects too.
+            _ if raw.http().status() == StatusCode::NOT_FOUND => Kind::ObjectNotExist,
+            _ if raw.http().status() == StatusCode::FORBIDDEN => Kind::ObjectPermissionDenied,
+            _ if raw.http().status() == StatusCode::RANGE_NOT_SATISFIABLE => {
+                Kind::RangeNotSatisfiable
+            }
+            _ => Kind::Unexpected,
+        };
+        Error::Object {
+            kind,
+            op,
+            path: path.to_string(),
+            source: with_signature_mismatch(anyhow::Error::from(err), &raw),
         }
     } else {
         Error::Object {
@@ -56,20 +80,38 @@ pub fn parse_head_object_error(
     op: &'static str,
     path: &str,
 ) -> Error {
-    if let SdkError::ServiceError { err, .. } = err {
-        match err.kind {
-            HeadObjectErrorKind::NotFound(_) => Error::Object {
-                kind: Kind::ObjectNotExist,
+    if let SdkError::ServiceError { err, raw } = err {
+        if let Some(wrong) = parse_wrong_region(err.code(), err.message(), &raw) {
+            return Error::Object {
+                kind: Kind::BackendConfigurationInvalid,
                 op,
                 path: path.to_string(),
-                source: anyhow::Error::from(err),
-            },
-            _ => Error::Object {
-                kind: Kind::Unexpected,
+                source: anyhow::Error::from(err).context(wrong),
+            };
+        }
+
+        // Heading a delete marker returns 404, or 405 if it's addressed by
+        // version id.
+        if let Some(marker) = parse_delete_marker(&raw) {
+            return Error::Object {
+                kind: Kind::ObjectNotExist,
                 op,
                 path: path.to_string(),
-                source: anyhow::Error::from(err),
-            },
+                source: anyhow::Error::from(err).context(marker),
+            };
+        }
+
+        let kind = match err.kind {
+            HeadObjectErrorKind::NotFound(_) => Kind::ObjectNotExist,
+            // HeadObject doesn't have response body, so 403 is unhandled.
+            _ if raw.http().status() == StatusCode::FORBIDDEN => Kind::ObjectPermissionDenied,
+            _ => Kind::Unexpected,
+        };
+        Error::Object {
+            kind,
+            op,
+            path: path.to_string(),
+            source: anyhow::Error::from(err),
         }
     } else {
         Error::Object {
@@ -81,16 +123,322 @@ pub fn parse_head_object_error(
     }
 }
 
+pub fn parse_head_bucket_error(
+    err: SdkError<HeadBucketError>,
+    op: &'static str,
+    path: &str,
+) -> Error {
+    if let SdkError::ServiceError { err, raw } = err {
+        let kind = match err.kind {
+            HeadBucketErrorKind::NotFound(_) => Kind::ObjectNotExist,
+            // HeadBucket doesn't have response body, so 403 is unhandled.
+            _ if raw.http().status() == StatusCode::FORBIDDEN => Kind::ObjectPermissionDenied,
+            _ => Kind::Unexpected,
+        };
+        Error::Object {
+            kind,
+            op,
+            path: path.to_string(),
+            source: anyhow::Error::from(err),
+        }
+    } else {
+        Error::Object {
+            kind: Kind::Unexpected,
+            op,
+            path: path.to_string(),
+            source: anyhow::Error::from(err),
+        }
+    }
+}
+
+pub fn parse_abort_multipart_upload_error(
+    err: SdkError<AbortMultipartUploadError>,
+    op: &'static str,
+    path: &str,
+) -> Error {
+    if let SdkError::ServiceError { err, raw } = err {
+        let kind = match err.kind {
+            AbortMultipartUploadErrorKind::NoSuchUpload(_) => Kind::ObjectNotExist,
+            _ if raw.http().status() == StatusCode::FORBIDDEN => Kind::ObjectPermissionDenied,
+            _ => Kind::Unexpected,
+        };
+        Error::Object {
+            kind,
+            op,
+            path: path.to_string(),
+            source: with_signature_mismatch(anyhow::Error::from(err), &raw),
+        }
+    } else {
+        Error::Object {
+            kind: Kind::Unexpected,
+            op,
+            path: path.to_string(),
+            source: anyhow::Error::from(err),
+        }
+    }
+}
+
+pub fn parse_get_object_retention_error(
+    err: SdkError<GetObjectRetentionError>,
+    op: &'static str,
+    path: &str,
+) -> Error {
+    if let SdkError::ServiceError { err, raw } = err {
+        let kind = parse_object_lock_error_kind(err.code(), err.message(), raw.http().status());
+        Error::Object {
+            kind,
+            op,
+            path: path.to_string(),
+            source: with_signature_mismatch(anyhow::Error::from(err), &raw),
+        }
+    } else {
+        Error::Object {
+            kind: Kind::Unexpected,
+            op,
+            path: path.to_string(),
+            source: anyhow::Error::from(err),
+        }
+    }
+}
+
+pub fn parse_get_object_legal_hold_error(
+    err: SdkError<GetObjectLegalHoldError>,
+    op: &'static str,
+    path: &str,
+) -> Error {
+    if let SdkError::ServiceError { err, raw } = err {
+        let kind = parse_object_lock_error_kind(err.code(), err.message(), raw.http().status());
+        Error::Object {
+            kind,
+            op,
+            path: path.to_string(),
+            source: with_signature_mismatch(anyhow::Error::from(err), &raw),
+        }
+    } else {
+        Error::Object {
+            kind: Kind::Unexpected,
+            op,
+            path: path.to_string(),
+            source: anyhow::Error::from(err),
+        }
+    }
+}
+
+/// The bucket lives in another region than the one requests are sent to.
+///
+/// It's attached to the source of errors so that the expected region can
+/// be used to redirect requests.
+#[derive(Debug, Clone)]
+pub struct WrongRegion {
+    pub expected: Option<String>,
+}
+
+impl fmt::Display for WrongRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.expected {
+            Some(region) => write!(
+                f,
+                "bucket is in region {}, please check the region or endpoint of the backend",
+                region
+            ),
+            None => write!(
+                f,
+                "bucket is in another region, please check the region or endpoint of the backend"
+            ),
+        }
+    }
+}
+
+/// Detect requests sent to the wrong region of the bucket.
+///
+/// s3 will return `301 PermanentRedirect` or `400 AuthorizationHeaderMalformed`
+/// in this case. The expected region is parsed from the `x-amz-bucket-region`
+/// header, the `<Region>` of the response body or the error message like
+/// `the region 'us-east-1' is wrong; expecting 'eu-west-1'`.
+pub fn parse_wrong_region(
+    code: Option<&str>,
+    message: Option<&str>,
+    raw: &operation::Response,
+) -> Option<WrongRegion> {
+    let status = raw.http().status();
+    match code {
+        Some("PermanentRedirect") => {}
+        Some("AuthorizationHeaderMalformed") if status == StatusCode::BAD_REQUEST => {}
+        // HeadObject doesn't have response body, so only status is returned.
+        _ if status == StatusCode::MOVED_PERMANENTLY => {}
+        _ => return None,
+    }
+
+    let from_header = raw
+        .http()
+        .headers()
+        .get("x-amz-bucket-region")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let from_body = raw
+        .http()
+        .body()
+        .bytes()
+        .and_then(|v| std::str::from_utf8(v).ok())
+        .and_then(|v| between(v, "<Region>", "</Region>"));
+    let from_message = message.and_then(|v| between(v, "expecting '", "'"));
+
+    Some(WrongRegion {
+        expected: from_header
+            .or(from_body)
+            .or(from_message)
+            .filter(|v| !v.is_empty()),
+    })
+}
+
+fn between(s: &str, start: &str, end: &str) -> Option<String> {
+    let (_, rest) = s.split_once(start)?;
+    let (v, _) = rest.split_once(end)?;
+    Some(v.trim().to_string())
+}
+
+/// Object lock apis return all errors as unhandled, so we have to check the
+/// error code instead.
+///
+/// Buckets created without object lock enabled will be parsed into
+/// [`Kind::Unsupported`].
+fn parse_object_lock_error_kind(
+    code: Option<&str>,
+    message: Option<&str>,
+    status: StatusCode,
+) -> Kind {
+    match code {
+        Some("NoSuchKey") | Some("NoSuchVersion") => Kind::ObjectNotExist,
+        Some("InvalidRequest")
+            if message
+                .map(|v| v.to_lowercase().contains("object lock configuration"))
+                .unwrap_or(false) =>
+        {
+            Kind::Unsupported
+        }
+        _ if status == StatusCode::NOT_FOUND => Kind::ObjectNotExist,
+        _ if status == StatusCode::FORBIDDEN => Kind::ObjectPermissionDenied,
+        _ => Kind::Unexpected,
+    }
+}
+
 // parse_unexpect_error is used to parse SdkError into unexpected.
+//
+// `403 Forbidden` (including the mismatch of expected bucket owner) will be
+// parsed into permission denied.
 pub fn parse_unexpect_error<E: 'static + Send + Sync + std::error::Error>(
     err: SdkError<E>,
     op: &'static str,
     path: &str,
 ) -> Error {
+    let (kind, mismatch) = match &err {
+        SdkError::ServiceError { raw, .. } => (
+            if raw.http().status() == StatusCode::FORBIDDEN {
+                Kind::ObjectPermissionDenied
+            } else {
+                Kind::Unexpected
+            },
+            parse_signature_mismatch(raw),
+        ),
+        _ => (Kind::Unexpected, None),
+    };
+
+    let source = anyhow::Error::from(err);
     Error::Object {
-        kind: Kind::Unexpected,
+        kind,
         op,
         path: path.to_string(),
-        source: anyhow::Error::from(err),
+        source: match mismatch {
+            Some(v) => source.context(v),
+            None => source,
+        },
+    }
+}
+
+/// The request signature or credential is rejected by s3.
+///
+/// The most common cause on AWS is mixing partitions, for example, using
+/// credentials of `aws` against buckets in `aws-cn`. It's attached to the
+/// source of errors to hint the partition of the signing region.
+#[derive(Debug, Clone)]
+pub struct SignatureMismatch {
+    pub code: String,
+    /// Region parsed from the credential scope of `StringToSign`.
+    pub region: Option<String>,
+}
+
+impl fmt::Display for SignatureMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.region {
+            Some(region) => {
+                let partition = Partition::from_region(region);
+                write!(
+                    f,
+                    "request signed for region {} is rejected with {}, please check that the credential and endpoint belong to partition {}",
+                    region, self.code, partition
+                )
+            }
+            None => write!(
+                f,
+                "request is rejected with {}, please check that the credential, region and endpoint belong to the same partition (like aws, aws-cn or aws-us-gov)",
+                self.code
+            ),
+        }
+    }
+}
+
+/// Parse signature errors from the response body.
+///
+/// HEAD responses don't have body, so they are never parsed.
+fn parse_signature_mismatch(raw: &operation::Response) -> Option<SignatureMismatch> {
+    let body = raw
+        .http()
+        .body()
+        .bytes()
+        .and_then(|v| std::str::from_utf8(v).ok())?;
+    let code = between(body, "<Code>", "</Code>")?;
+    if !matches!(
+        code.as_str(),
+        "SignatureDoesNotMatch" | "InvalidAccessKeyId" | "InvalidToken" | "InvalidClientTokenId"
+    ) {
+        return None;
+    }
+
+    // Credential scope is the third line of `StringToSign` like
+    // `20220101/cn-north-1/s3/aws4_request`.
+    let region = between(body, "<StringToSign>", "</StringToSign>").and_then(|v| {
+        v.lines()
+            .nth(2)
+            .and_then(|scope| scope.split('/').nth(1))
+            .map(|v| v.to_string())
+    });
+
+    Some(SignatureMismatch { code, region })
+}
+
+fn with_signature_mismatch(source: anyhow::Error, raw: &operation::Response) -> anyhow::Error {
+    match parse_signature_mismatch(raw) {
+        Some(v) => source.context(v),
+        None => source,
     }
 }
+
+/// Parse the `x-amz-delete-marker` header of error responses.
+fn parse_delete_marker(raw: &operation::Response) -> Option<DeleteMarker> {
+    let headers = raw.http().headers();
+    let is_marker = headers
+        .get("x-amz-delete-marker")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or_default();
+    if !is_marker {
+        return None;
+    }
+
+    Some(DeleteMarker {
+        version_id: headers
+            .get("x-amz-version-id")
+            .and_then(|v| v.to_str().ok())
+            .map(String::from),
+    })
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::fmt::Debug;
+use std::sync::Arc;
 
 use aws_endpoint::AwsEndpointStage;
 use aws_http::recursion_detection::RecursionDetectionStage;
@@ -25,7 +26,10 @@ use tower::layer::util::Stack;
 use tower::ServiceBuilder;
 
 use super::credentials::CredentialsStage;
+use super::failover::EndpointPool;
+use super::failover::FailoverStage;
 use super::signer::SigningStage;
+use super::virtual_host::VirtualHostStage;
 
 type DefaultMiddlewareStack = Stack<
     MapRequestLayer<RecursionDetectionStage>,
@@ -35,7 +39,13 @@ type DefaultMiddlewareStack = Stack<
             AsyncMapRequestLayer<CredentialsStage>,
             Stack<
                 MapRequestLayer<UserAgentStage>,
-                Stack<MapRequestLayer<AwsEndpointStage>, Identity>,
+                Stack<
+                    MapRequestLayer<VirtualHostStage>,
+                    Stack<
+                        MapRequestLayer<FailoverStage>,
+                        Stack<MapRequestLayer<AwsEndpointStage>, Identity>,
+                    >,
+                >,
             >,
         >,
     >,
@@ -47,32 +57,58 @@ type DefaultMiddlewareStack = Stack<
 /// 1. Load credentials asynchronously into the property bag
 /// 2. Sign the request with SigV4
 /// 3. Resolve an Endpoint for the request
-/// 4. Add a user agent to the request
+/// 4. Pick an endpoint from the pool if configured
+/// 5. Rewrite the request into virtual-hosted style if enabled
+/// 6. Add a user agent to the request
 #[derive(Debug, Default, Clone)]
 #[non_exhaustive]
-pub struct DefaultMiddleware;
+pub struct DefaultMiddleware {
+    virtual_host: bool,
+    endpoint_pool: Option<Arc<EndpointPool>>,
+}
 
 impl DefaultMiddleware {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Send requests in virtual-hosted style instead of path-style.
+    pub fn with_virtual_host(mut self, enabled: bool) -> Self {
+        self.virtual_host = enabled;
+        self
+    }
+
+    /// Spread requests across the endpoints in pool.
+    pub fn with_endpoint_pool(mut self, pool: Option<Arc<EndpointPool>>) -> Self {
+        self.endpoint_pool = pool;
+        self
     }
 }
 
 // define the middleware stack in a non-generic location to reduce code bloat.
-fn base() -> ServiceBuilder<DefaultMiddlewareStack> {
+fn base(
+    virtual_host: bool,
+    endpoint_pool: Option<Arc<EndpointPool>>,
+) -> ServiceBuilder<DefaultMiddlewareStack> {
     let credential_provider = AsyncMapRequestLayer::for_mapper(CredentialsStage::new());
     let signer = MapRequestLayer::for_mapper(SigningStage::new(SigV4Signer::new()));
     let endpoint_resolver = MapRequestLayer::for_mapper(AwsEndpointStage);
+    let failover = MapRequestLayer::for_mapper(FailoverStage::new(endpoint_pool));
+    let virtual_host = MapRequestLayer::for_mapper(VirtualHostStage::new(virtual_host));
     let user_agent = MapRequestLayer::for_mapper(UserAgentStage::new());
     let recursion_detection = MapRequestLayer::for_mapper(RecursionDetectionStage::new());
     // These layers can be considered as occurring in order, that is:
     // 1. Resolve an endpoint
-    // 2. Add a user agent
-    // 3. Acquire credentials
-    // 4. Sign with credentials
-    // (5. Dispatch over the wire)
+    // 2. Pick an endpoint from the pool
+    // 3. Rewrite into virtual-hosted style
+    // 4. Add a user agent
+    // 5. Acquire credentials
+    // 6. Sign with credentials
+    // (7. Dispatch over the wire)
     ServiceBuilder::new()
         .layer(endpoint_resolver)
+        .layer(failover)
+        .layer(virtual_host)
         .layer(user_agent)
         .layer(credential_provider)
         .layer(signer)
@@ -83,6 +119,6 @@ impl<S> tower::Layer<S> for DefaultMiddleware {
     type Service = <DefaultMiddlewareStack as tower::Layer<S>>::Service;
 
     fn layer(&self, inner: S) -> Self::Service {
-        base().service(inner)
+        base(self.virtual_host, self.endpoint_pool.clone()).service(inner)
     }
 }
@@ -0,0 +1,239 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spread requests across several endpoints of the same S3-compatible
+//! cluster, and fail over when one of them is unreachable.
+//!
+//! - `FailoverStage` picks an endpoint for every attempt and rewrites the
+//!   request uri, it must be applied before signing.
+//! - `FailoverConnector` marks the endpoint unhealthy on connection errors,
+//!   and records the endpoint in the error.
+//!
+//! The sdk retries an attempt through the whole middleware stack, so a
+//! retried request will land on another endpoint.
+
+use std::error::Error;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use aws_smithy_http::body::SdkBody;
+use aws_smithy_http::middleware::MapRequest;
+use aws_smithy_http::operation::Request;
+use aws_smithy_http::result::ConnectorError;
+use futures::future::BoxFuture;
+use http::Uri;
+use log::warn;
+
+use super::super::backend::redact_endpoint;
+
+/// Unhealthy endpoints will not be picked until the cooldown passed.
+const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// EndpointPool picks endpoints in round-robin, and skips the unhealthy
+/// ones if possible.
+#[derive(Debug)]
+pub struct EndpointPool {
+    /// `scheme://authority` of all endpoints.
+    endpoints: Vec<String>,
+    next: AtomicUsize,
+    unhealthy_until: Mutex<Vec<Option<Instant>>>,
+}
+
+impl EndpointPool {
+    /// All endpoints must be valid uris like `http://127.0.0.1:9000`, only
+    /// their scheme and authority will be used.
+    pub fn new(endpoints: &[String]) -> Self {
+        let endpoints: Vec<String> = endpoints
+            .iter()
+            .map(|v| match Uri::try_from(v.as_str()) {
+                Ok(uri) => match (uri.scheme_str(), uri.authority()) {
+                    (Some(scheme), Some(authority)) => format!("{}://{}", scheme, authority),
+                    _ => v.to_string(),
+                },
+                Err(_) => v.to_string(),
+            })
+            .collect();
+        let unhealthy_until = Mutex::new(vec![None; endpoints.len()]);
+
+        Self {
+            endpoints,
+            next: AtomicUsize::new(0),
+            unhealthy_until,
+        }
+    }
+
+    /// Pick the next healthy endpoint, fallback to the next one if all of
+    /// them are unhealthy.
+    pub fn pick(&self) -> &str {
+        let n = self.endpoints.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+
+        let mut unhealthy_until = self.unhealthy_until.lock().expect("lock poisoned");
+        let now = Instant::now();
+        for i in 0..n {
+            let idx = (start + i) % n;
+            match unhealthy_until[idx] {
+                Some(t) if t > now => continue,
+                Some(_) => unhealthy_until[idx] = None,
+                None => {}
+            }
+            return &self.endpoints[idx];
+        }
+
+        &self.endpoints[start % n]
+    }
+
+    fn set_unhealthy_until(&self, endpoint: &str, until: Option<Instant>) {
+        if let Some(idx) = self.endpoints.iter().position(|v| v == endpoint) {
+            self.unhealthy_until.lock().expect("lock poisoned")[idx] = until;
+        }
+    }
+
+    pub fn mark_healthy(&self, endpoint: &str) {
+        self.set_unhealthy_until(endpoint, None)
+    }
+
+    pub fn mark_unhealthy(&self, endpoint: &str) {
+        warn!("endpoint {} is unhealthy", redact_endpoint(endpoint));
+        self.set_unhealthy_until(endpoint, Some(Instant::now() + UNHEALTHY_COOLDOWN))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FailoverStage {
+    pool: Option<Arc<EndpointPool>>,
+}
+
+impl FailoverStage {
+    pub fn new(pool: Option<Arc<EndpointPool>>) -> Self {
+        Self { pool }
+    }
+}
+
+impl MapRequest for FailoverStage {
+    type Error = http::Error;
+
+    fn apply(&self, req: Request) -> Result<Request, Self::Error> {
+        let pool = match &self.pool {
+            Some(pool) => pool,
+            None => return Ok(req),
+        };
+
+        req.augment(|mut req, _| {
+            let endpoint = pool.pick();
+            let path_and_query = req
+                .uri()
+                .path_and_query()
+                .map(|v| v.as_str())
+                .unwrap_or("/");
+            *req.uri_mut() = Uri::try_from(format!("{}{}", endpoint, path_and_query))?;
+            Ok(req)
+        })
+    }
+}
+
+/// FailoverConnector reports the health of endpoints to the pool.
+#[derive(Clone, Debug)]
+pub struct FailoverConnector<C> {
+    inner: C,
+    pool: Option<Arc<EndpointPool>>,
+}
+
+impl<C> FailoverConnector<C> {
+    pub fn new(inner: C, pool: Option<Arc<EndpointPool>>) -> Self {
+        Self { inner, pool }
+    }
+}
+
+impl<C> tower::Service<http::Request<SdkBody>> for FailoverConnector<C>
+where
+    C: tower::Service<
+        http::Request<SdkBody>,
+        Response = http::Response<SdkBody>,
+        Error = ConnectorError,
+    >,
+    C::Future: Send + 'static,
+{
+    type Response = http::Response<SdkBody>;
+    type Error = ConnectorError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<SdkBody>) -> Self::Future {
+        let pool = self.pool.clone();
+        let endpoint = match (req.uri().scheme_str(), req.uri().authority()) {
+            (Some(scheme), Some(authority)) => format!("{}://{}", scheme, authority),
+            _ => String::new(),
+        };
+        let fut = self.inner.call(req);
+
+        Box::pin(async move {
+            let pool = match pool {
+                Some(pool) => pool,
+                None => return fut.await,
+            };
+
+            match fut.await {
+                Ok(resp) => {
+                    pool.mark_healthy(&endpoint);
+                    Ok(resp)
+                }
+                Err(err) if err.is_io() || err.is_timeout() => {
+                    pool.mark_unhealthy(&endpoint);
+                    Err(ConnectorError::io(Box::new(EndpointError {
+                        endpoint,
+                        source: err,
+                    })))
+                }
+                Err(err) => Err(err),
+            }
+        })
+    }
+}
+
+/// EndpointError carries the endpoint that failed to connect.
+#[derive(Debug)]
+struct EndpointError {
+    endpoint: String,
+    source: ConnectorError,
+}
+
+impl Display for EndpointError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "endpoint {} failed: {}",
+            redact_endpoint(&self.endpoint),
+            self.source
+        )
+    }
+}
+
+impl Error for EndpointError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
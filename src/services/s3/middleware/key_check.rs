@@ -0,0 +1,142 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Gateways or proxies in front of s3 could be misconfigured and serve
+//! another object. `KeyCheckConnector` compares the `Content-Location` of
+//! `GET` and `HEAD` responses with the requested path, and fails the
+//! request if they are diverged.
+//!
+//! S3 itself doesn't return `Content-Location`, responses without it will
+//! pass the check.
+
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::task::Context;
+use std::task::Poll;
+
+use aws_smithy_http::body::SdkBody;
+use aws_smithy_http::result::ConnectorError;
+use futures::future::BoxFuture;
+use http::header::CONTENT_LOCATION;
+use http::Method;
+
+#[derive(Clone, Debug)]
+pub struct KeyCheckConnector<C> {
+    inner: C,
+    enabled: bool,
+}
+
+impl<C> KeyCheckConnector<C> {
+    pub fn new(inner: C, enabled: bool) -> Self {
+        Self { inner, enabled }
+    }
+}
+
+impl<C> tower::Service<http::Request<SdkBody>> for KeyCheckConnector<C>
+where
+    C: tower::Service<
+        http::Request<SdkBody>,
+        Response = http::Response<SdkBody>,
+        Error = ConnectorError,
+    >,
+    C::Future: Send + 'static,
+{
+    type Response = http::Response<SdkBody>;
+    type Error = ConnectorError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<SdkBody>) -> Self::Future {
+        let expected = if self.enabled && matches!(*req.method(), Method::GET | Method::HEAD) {
+            Some(req.uri().path().to_string())
+        } else {
+            None
+        };
+        let fut = self.inner.call(req);
+
+        Box::pin(async move {
+            let resp = fut.await?;
+            let expected = match expected {
+                Some(v) if resp.status().is_success() => v,
+                _ => return Ok(resp),
+            };
+
+            let actual = match resp.headers().get(CONTENT_LOCATION) {
+                Some(v) => String::from_utf8_lossy(v.as_bytes()).to_string(),
+                None => return Ok(resp),
+            };
+            if percent_decode(location_path(&actual)) == percent_decode(&expected) {
+                return Ok(resp);
+            }
+
+            Err(ConnectorError::other(
+                Box::new(KeyMismatchError { expected, actual }),
+                None,
+            ))
+        })
+    }
+}
+
+/// Returns the path of `Content-Location` which could be an absolute uri
+/// or a path.
+fn location_path(location: &str) -> &str {
+    let path = match location.split_once("://") {
+        Some((_, rest)) => rest.find('/').map(|idx| &rest[idx..]).unwrap_or("/"),
+        None => location,
+    };
+    path.split(['?', '#']).next().unwrap_or_default()
+}
+
+/// Decode `%XX` in path, invalid sequences will be kept as is.
+fn percent_decode(path: &str) -> Vec<u8> {
+    let bs = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bs.len());
+
+    let mut i = 0;
+    while i < bs.len() {
+        if bs[i] == b'%' && i + 2 < bs.len() {
+            let hex = std::str::from_utf8(&bs[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(b) = u8::from_str_radix(hex, 16) {
+                decoded.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bs[i]);
+        i += 1;
+    }
+    decoded
+}
+
+#[derive(Debug)]
+struct KeyMismatchError {
+    expected: String,
+    actual: String,
+}
+
+impl Display for KeyMismatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "response is for {} instead of the requested {}",
+            self.actual, self.expected
+        )
+    }
+}
+
+impl Error for KeyMismatchError {}
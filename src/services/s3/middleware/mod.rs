@@ -14,6 +14,14 @@
 
 mod credentials;
 mod default;
+mod failover;
+mod key_check;
 mod signer;
+mod virtual_host;
 
 pub use default::DefaultMiddleware;
+pub use failover::EndpointPool;
+pub use failover::FailoverConnector;
+pub use key_check::KeyCheckConnector;
+#[cfg(test)]
+pub use virtual_host::virtual_host_uri;
@@ -0,0 +1,83 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The sdk always sends path-style requests like
+//! `https://s3.amazonaws.com/bucket/key`, but some endpoints (Transfer
+//! Acceleration for example) only support virtual-hosted style requests like
+//! `https://bucket.s3-accelerate.amazonaws.com/key`.
+//!
+//! `VirtualHostStage` rewrites the request uri after the endpoint resolved,
+//! it must be applied before signing.
+
+use aws_smithy_http::middleware::MapRequest;
+use aws_smithy_http::operation::Request;
+use http::Uri;
+
+#[derive(Clone, Debug, Default)]
+pub struct VirtualHostStage {
+    enabled: bool,
+}
+
+impl VirtualHostStage {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+impl MapRequest for VirtualHostStage {
+    type Error = http::Error;
+
+    fn apply(&self, req: Request) -> Result<Request, Self::Error> {
+        if !self.enabled {
+            return Ok(req);
+        }
+
+        req.augment(|mut req, _| {
+            *req.uri_mut() = virtual_host_uri(req.uri())?;
+            Ok(req)
+        })
+    }
+}
+
+/// Move the bucket in path into host.
+///
+/// `https://s3.amazonaws.com/bucket/key?x=y` will be rewritten into
+/// `https://bucket.s3.amazonaws.com/key?x=y`.
+pub fn virtual_host_uri(uri: &Uri) -> Result<Uri, http::Error> {
+    let (authority, path_and_query) = match (uri.authority(), uri.path_and_query()) {
+        (Some(authority), Some(pq)) => (authority.as_str(), pq.as_str()),
+        _ => return Ok(uri.clone()),
+    };
+
+    let path_and_query = path_and_query.trim_start_matches('/');
+    let (bucket, rest) = match path_and_query.find(['/', '?']) {
+        Some(idx) => path_and_query.split_at(idx),
+        None => (path_and_query, ""),
+    };
+    if bucket.is_empty() {
+        return Ok(uri.clone());
+    }
+
+    let rest = if rest.starts_with('/') {
+        rest.to_string()
+    } else {
+        format!("/{}", rest)
+    };
+
+    let mut builder = Uri::builder().authority(format!("{}.{}", bucket, authority).as_str());
+    if let Some(scheme) = uri.scheme() {
+        builder = builder.scheme(scheme.clone());
+    }
+    builder.path_and_query(rest.as_str()).build()
+}
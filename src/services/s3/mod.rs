@@ -67,9 +67,20 @@
 //! ```
 
 mod backend;
+#[cfg(test)]
+pub(crate) use backend::copy_ranges;
 pub use backend::Backend;
 pub use backend::Builder;
 
+mod checksum;
+pub use checksum::MultipartChecksum;
+
 mod error;
+#[cfg(test)]
+pub(crate) use error::parse_head_object_error;
 mod middleware;
+#[cfg(test)]
+pub(crate) use middleware::virtual_host_uri;
+mod object_lock;
 mod object_stream;
+mod partition;
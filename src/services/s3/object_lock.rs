@@ -0,0 +1,103 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! s3 returns `Retention` and `LegalHold` as the root of object lock
+//! responses, but the sdk expects `ObjectLockRetention` and
+//! `ObjectLockLegalHold` and fails to parse them. We parse the raw body
+//! into the sdk's models instead.
+
+use std::convert::TryFrom;
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+use aws_sdk_s3::model::ObjectLockLegalHold;
+use aws_sdk_s3::model::ObjectLockLegalHoldStatus;
+use aws_sdk_s3::model::ObjectLockRetention;
+use aws_sdk_s3::model::ObjectLockRetentionMode;
+use aws_smithy_types::date_time::Format;
+use aws_smithy_types::DateTime;
+use aws_smithy_xml::decode::try_data;
+use aws_smithy_xml::decode::Document;
+
+use crate::ops::RetentionMode;
+
+/// Parse the body of `GetObjectRetention`.
+pub fn parse_retention(body: &[u8]) -> anyhow::Result<ObjectLockRetention> {
+    let mut doc = Document::try_from(body)?;
+    let mut root = doc.root_element()?;
+    if !root.start_el().matches("Retention") {
+        return Err(anyhow!("invalid root: {:?}", root.start_el()));
+    }
+
+    let mut builder = ObjectLockRetention::builder();
+    while let Some(mut tag) = root.next_tag() {
+        if tag.start_el().matches("Mode") {
+            builder = builder.mode(ObjectLockRetentionMode::from(try_data(&mut tag)?.as_ref()));
+        } else if tag.start_el().matches("RetainUntilDate") {
+            builder = builder.retain_until_date(DateTime::from_str(
+                try_data(&mut tag)?.as_ref(),
+                Format::DateTime,
+            )?);
+        }
+    }
+    Ok(builder.build())
+}
+
+/// Parse the body of `GetObjectLegalHold`.
+pub fn parse_legal_hold(body: &[u8]) -> anyhow::Result<ObjectLockLegalHold> {
+    let mut doc = Document::try_from(body)?;
+    let mut root = doc.root_element()?;
+    if !root.start_el().matches("LegalHold") {
+        return Err(anyhow!("invalid root: {:?}", root.start_el()));
+    }
+
+    let mut builder = ObjectLockLegalHold::builder();
+    while let Some(mut tag) = root.next_tag() {
+        if tag.start_el().matches("Status") {
+            builder = builder.status(ObjectLockLegalHoldStatus::from(
+                try_data(&mut tag)?.as_ref(),
+            ));
+        }
+    }
+    Ok(builder.build())
+}
+
+/// Convert the retention of sdk, `None` means the object has no retention.
+pub fn to_retention(
+    v: Option<ObjectLockRetention>,
+) -> anyhow::Result<(Option<RetentionMode>, Option<SystemTime>)> {
+    let v = match v {
+        None => return Ok((None, None)),
+        Some(v) => v,
+    };
+
+    let mode = match v.mode {
+        None => None,
+        Some(ObjectLockRetentionMode::Governance) => Some(RetentionMode::Governance),
+        Some(ObjectLockRetentionMode::Compliance) => Some(RetentionMode::Compliance),
+        Some(mode) => return Err(anyhow!("unknown retention mode: {}", mode.as_str())),
+    };
+    let retain_until = v
+        .retain_until_date
+        .and_then(|v| SystemTime::try_from(v).ok());
+    Ok((mode, retain_until))
+}
+
+/// Check if the legal hold of sdk is on.
+pub fn to_legal_hold(v: Option<ObjectLockLegalHold>) -> bool {
+    matches!(
+        v.and_then(|v| v.status),
+        Some(ObjectLockLegalHoldStatus::On)
+    )
+}
@@ -12,13 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::SystemTime;
 
 use aws_sdk_s3;
+use aws_sdk_s3::model::Owner;
+use aws_sdk_s3::output::ListObjectVersionsOutput;
 use aws_sdk_s3::output::ListObjectsV2Output;
 use futures::future::BoxFuture;
 use futures::ready;
@@ -27,6 +31,7 @@ use log::debug;
 use super::error::parse_unexpect_error;
 use super::Backend;
 use crate::error::Result;
+use crate::Metadata;
 use crate::Object;
 use crate::ObjectMode;
 
@@ -34,6 +39,15 @@ pub struct S3ObjectStream {
     backend: Backend,
     bucket: String,
     path: String,
+    /// Absolute path to start listing after, s3 will ignore it once the
+    /// continuation token is set.
+    start_after: Option<String>,
+    /// List all keys under the prefix without delimiter.
+    flat: bool,
+    fetch_owner: bool,
+    /// Common prefixes that have been yielded, a prefix whose children
+    /// straddle page boundaries could be returned in several pages.
+    seen_prefixes: HashSet<String>,
 
     token: String,
     done: bool,
@@ -69,11 +83,22 @@ enum State {
 }
 
 impl S3ObjectStream {
-    pub fn new(backend: Backend, bucket: String, path: String) -> Self {
+    pub fn new(
+        backend: Backend,
+        bucket: String,
+        path: String,
+        start_after: Option<String>,
+        flat: bool,
+        fetch_owner: bool,
+    ) -> Self {
         Self {
             backend,
             bucket,
             path,
+            start_after,
+            flat,
+            fetch_owner,
+            seen_prefixes: HashSet::new(),
 
             token: "".to_string(),
             done: false,
@@ -88,54 +113,69 @@ impl futures::Stream for S3ObjectStream {
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let backend = self.backend.clone();
 
-        match &mut self.state {
+        let this = &mut *self;
+        match &mut this.state {
             State::Idle => {
-                let client = self.backend.inner();
-                let bucket = self.bucket.clone();
-                let path = self.path.clone();
-                let token = self.token.clone();
+                let client = this.backend.inner();
+                let owner = this.backend.expected_bucket_owner();
+                let bucket = this.bucket.clone();
+                let path = this.path.clone();
+                let token = this.token.clone();
+                let start_after = this.start_after.clone();
+                let flat = this.flat;
+                let fetch_owner = this.fetch_owner;
                 let fut = async move {
                     let mut req = client
                         .list_objects_v2()
                         .bucket(bucket)
-                        .prefix(&path)
-                        .delimiter("/");
+                        .set_expected_bucket_owner(owner)
+                        .set_start_after(start_after)
+                        .prefix(&path);
+                    if !flat {
+                        req = req.delimiter("/");
+                    }
                     if !token.is_empty() {
                         req = req.continuation_token(token);
                     }
+                    if fetch_owner {
+                        req = req.fetch_owner(true);
+                    }
                     req.send()
                         .await
                         .map_err(|e| parse_unexpect_error(e, "list", &path))
                 };
-                self.state = State::Sending(Box::pin(fut));
+                this.state = State::Sending(Box::pin(fut));
                 self.poll_next(cx)
             }
             State::Sending(fut) => {
                 let output = ready!(Pin::new(fut).poll(cx))?;
 
-                self.done = !output.is_truncated;
-                self.token = output.continuation_token.clone().unwrap_or_default();
-                self.state = State::Listing((output, 0, 0));
+                this.done = !output.is_truncated;
+                this.token = output.next_continuation_token.clone().unwrap_or_default();
+                this.state = State::Listing((output, 0, 0));
                 self.poll_next(cx)
             }
             State::Listing((output, common_prefixes_idx, objects_idx)) => {
                 if let Some(prefixes) = &output.common_prefixes {
-                    if *common_prefixes_idx < prefixes.len() {
+                    while *common_prefixes_idx < prefixes.len() {
                         *common_prefixes_idx += 1;
-                        let prefix = &prefixes[*common_prefixes_idx - 1].prefix();
+                        let prefix = prefixes[*common_prefixes_idx - 1]
+                            .prefix()
+                            .expect("prefix should not be None");
+                        if !this.seen_prefixes.insert(prefix.to_string()) {
+                            continue;
+                        }
 
-                        let mut o = Object::new(
-                            Arc::new(backend.clone()),
-                            &backend.get_rel_path(prefix.expect("prefix should not be None")),
-                        );
+                        let mut o =
+                            Object::new(Arc::new(backend.clone()), &backend.get_rel_path(prefix));
                         let meta = o.metadata_mut();
                         meta.set_mode(ObjectMode::DIR)
                             .set_content_length(0)
-                            .set_complete();
+                            .set_fully_loaded();
 
                         debug!(
                             "object {} got entry, path: {}, mode: {}",
-                            &self.path,
+                            &this.path,
                             meta.path(),
                             meta.mode()
                         );
@@ -147,17 +187,36 @@ impl futures::Stream for S3ObjectStream {
                         *objects_idx += 1;
                         let object = &objects[*objects_idx - 1];
 
-                        let mut o = Object::new(
-                            Arc::new(backend.clone()),
-                            &backend.get_rel_path(object.key().expect("key should not be None")),
-                        );
+                        let key = object.key().expect("key should not be None");
+                        // Skip the marker of the dir being listed, dirs
+                        // don't contain themselves.
+                        if !this.flat && key == this.path {
+                            return self.poll_next(cx);
+                        }
+                        let mut o =
+                            Object::new(Arc::new(backend.clone()), &backend.get_rel_path(key));
                         let meta = o.metadata_mut();
-                        meta.set_mode(ObjectMode::FILE)
+                        meta.set_mode(key_mode(key))
                             .set_content_length(object.size as u64);
+                        if let Some(etag) = object.e_tag() {
+                            meta.set_etag(etag);
+                        }
+                        if let Some(t) = object
+                            .last_modified()
+                            .and_then(|v| SystemTime::try_from(*v).ok())
+                        {
+                            meta.set_last_modified(t);
+                        }
+                        if let Some(v) = object.storage_class() {
+                            meta.set_storage_class(v.as_str());
+                        }
+                        if let Some(owner) = object.owner() {
+                            set_owner(meta, owner);
+                        }
 
                         debug!(
                             "object {} got entry, path: {}, mode: {}",
-                            &self.path,
+                            &this.path,
                             meta.path(),
                             meta.mode()
                         );
@@ -165,14 +224,202 @@ impl futures::Stream for S3ObjectStream {
                     }
                 }
 
-                if self.done {
-                    debug!("object {} list done", &self.path);
+                if this.done {
+                    debug!("object {} list done", &this.path);
+                    return Poll::Ready(None);
+                }
+
+                this.state = State::Idle;
+                self.poll_next(cx)
+            }
+        }
+    }
+}
+
+/// S3VersionStream lists the latest versions via `ListObjectVersions`, used
+/// by snapshot listing.
+///
+/// Keys whose latest version is a delete marker will be skipped.
+pub struct S3VersionStream {
+    backend: Backend,
+    bucket: String,
+    path: String,
+    /// List all keys under the prefix without delimiter.
+    flat: bool,
+    seen_prefixes: HashSet<String>,
+
+    key_marker: Option<String>,
+    version_id_marker: Option<String>,
+    done: bool,
+    state: VersionState,
+}
+
+enum VersionState {
+    Idle,
+    Sending(BoxFuture<'static, Result<ListObjectVersionsOutput>>),
+    Listing(Box<(ListObjectVersionsOutput, usize, usize)>),
+}
+
+impl S3VersionStream {
+    pub fn new(
+        backend: Backend,
+        bucket: String,
+        path: String,
+        start_after: Option<String>,
+        flat: bool,
+    ) -> Self {
+        Self {
+            backend,
+            bucket,
+            path,
+            flat,
+            seen_prefixes: HashSet::new(),
+
+            key_marker: start_after,
+            version_id_marker: None,
+            done: false,
+            state: VersionState::Idle,
+        }
+    }
+}
+
+impl futures::Stream for S3VersionStream {
+    type Item = Result<Object>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let backend = self.backend.clone();
+
+        let this = &mut *self;
+        match &mut this.state {
+            VersionState::Idle => {
+                let client = this.backend.inner();
+                let owner = this.backend.expected_bucket_owner();
+                let bucket = this.bucket.clone();
+                let path = this.path.clone();
+                let key_marker = this.key_marker.clone();
+                let version_id_marker = this.version_id_marker.clone();
+                let flat = this.flat;
+                let fut = async move {
+                    let mut req = client
+                        .list_object_versions()
+                        .bucket(bucket)
+                        .set_expected_bucket_owner(owner)
+                        .set_key_marker(key_marker)
+                        .set_version_id_marker(version_id_marker)
+                        .prefix(&path);
+                    if !flat {
+                        req = req.delimiter("/");
+                    }
+                    req.send()
+                        .await
+                        .map_err(|e| parse_unexpect_error(e, "list", &path))
+                };
+                this.state = VersionState::Sending(Box::pin(fut));
+                self.poll_next(cx)
+            }
+            VersionState::Sending(fut) => {
+                let output = ready!(Pin::new(fut).poll(cx))?;
+
+                this.done = !output.is_truncated;
+                this.key_marker = output.next_key_marker.clone();
+                this.version_id_marker = output.next_version_id_marker.clone();
+                this.state = VersionState::Listing(Box::new((output, 0, 0)));
+                self.poll_next(cx)
+            }
+            VersionState::Listing(listing) => {
+                let (output, common_prefixes_idx, versions_idx) = listing.as_mut();
+                if let Some(prefixes) = &output.common_prefixes {
+                    while *common_prefixes_idx < prefixes.len() {
+                        *common_prefixes_idx += 1;
+                        let prefix = prefixes[*common_prefixes_idx - 1]
+                            .prefix()
+                            .expect("prefix should not be None");
+                        if !this.seen_prefixes.insert(prefix.to_string()) {
+                            continue;
+                        }
+
+                        let mut o =
+                            Object::new(Arc::new(backend.clone()), &backend.get_rel_path(prefix));
+                        o.metadata_mut()
+                            .set_mode(ObjectMode::DIR)
+                            .set_content_length(0)
+                            .set_fully_loaded();
+                        return Poll::Ready(Some(Ok(o)));
+                    }
+                }
+                if let Some(versions) = &output.versions {
+                    while *versions_idx < versions.len() {
+                        *versions_idx += 1;
+                        let version = &versions[*versions_idx - 1];
+                        if !version.is_latest {
+                            continue;
+                        }
+
+                        let key = version.key().expect("key should not be None");
+                        if !this.flat && key == this.path {
+                            continue;
+                        }
+                        let mut o =
+                            Object::new(Arc::new(backend.clone()), &backend.get_rel_path(key));
+                        let meta = o.metadata_mut();
+                        meta.set_mode(key_mode(key))
+                            .set_content_length(version.size as u64);
+                        if let Some(etag) = version.e_tag() {
+                            meta.set_etag(etag);
+                        }
+                        if let Some(v) = version.version_id() {
+                            meta.set_version_id(v);
+                        }
+                        if let Some(t) = version
+                            .last_modified()
+                            .and_then(|v| SystemTime::try_from(*v).ok())
+                        {
+                            meta.set_last_modified(t);
+                        }
+                        if let Some(v) = version.storage_class() {
+                            meta.set_storage_class(v.as_str());
+                        }
+                        if let Some(owner) = version.owner() {
+                            set_owner(meta, owner);
+                        }
+
+                        debug!(
+                            "object {} got version, path: {}, version: {:?}",
+                            &this.path,
+                            meta.path(),
+                            meta.version_id()
+                        );
+                        return Poll::Ready(Some(Ok(o)));
+                    }
+                }
+
+                if this.done {
+                    debug!("object {} list versions done", &this.path);
                     return Poll::Ready(None);
                 }
 
-                self.state = State::Idle;
+                this.state = VersionState::Idle;
                 self.poll_next(cx)
             }
         }
     }
 }
+
+fn set_owner(meta: &mut Metadata, owner: &Owner) {
+    if let Some(v) = owner.id() {
+        meta.set_owner_id(v);
+    }
+    if let Some(v) = owner.display_name() {
+        meta.set_owner_display_name(v);
+    }
+}
+
+/// Keys like `abc/` are dirs even if they carry data, their real size is
+/// still exposed by `content_length`.
+fn key_mode(key: &str) -> ObjectMode {
+    if key.ends_with('/') {
+        ObjectMode::DIR
+    } else {
+        ObjectMode::FILE
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::fmt;
+
+/// AWS partitions, regions in different partitions are isolated: they
+/// have their own endpoints, accounts and credentials.
+///
+/// ref: <https://docs.aws.amazon.com/general/latest/gr/aws-arns-and-namespaces.html>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Partition {
+    Aws,
+    AwsCn,
+    AwsUsGov,
+    AwsIso,
+    AwsIsoB,
+}
+
+impl Partition {
+    pub(crate) const ALL: [Partition; 5] = [
+        Partition::Aws,
+        Partition::AwsCn,
+        Partition::AwsUsGov,
+        Partition::AwsIso,
+        Partition::AwsIsoB,
+    ];
+
+    /// Detect the partition by the prefix of region like `cn-north-1`.
+    pub(crate) fn from_region(region: &str) -> Self {
+        if region.starts_with("cn-") {
+            Partition::AwsCn
+        } else if region.starts_with("us-gov-") {
+            Partition::AwsUsGov
+        } else if region.starts_with("us-isob-") {
+            Partition::AwsIsoB
+        } else if region.starts_with("us-iso-") {
+            Partition::AwsIso
+        } else {
+            Partition::Aws
+        }
+    }
+
+    /// Detect the partition by the host of AWS endpoints.
+    ///
+    /// Returns `None` if the host is not an AWS endpoint.
+    pub(crate) fn from_host(host: &str) -> Option<Self> {
+        let partition = Partition::ALL
+            .into_iter()
+            .find(|p| host.ends_with(&format!(".{}", p.endpoint_suffix())))?;
+
+        // GovCloud shares the suffix with the standard partition.
+        match partition {
+            Partition::Aws if host.contains("us-gov-") => Some(Partition::AwsUsGov),
+            p => Some(p),
+        }
+    }
+
+    /// Returns true if `suffix` is the endpoint suffix of any partition.
+    pub(crate) fn is_known_suffix(suffix: &str) -> bool {
+        Partition::ALL.iter().any(|p| p.endpoint_suffix() == suffix)
+    }
+
+    pub(crate) fn endpoint_suffix(&self) -> &'static str {
+        match self {
+            Partition::Aws | Partition::AwsUsGov => "amazonaws.com",
+            Partition::AwsCn => "amazonaws.com.cn",
+            Partition::AwsIso => "c2s.ic.gov",
+            Partition::AwsIsoB => "sc2s.sgov.gov",
+        }
+    }
+
+    /// Region used to sign requests if the bucket region is unknown.
+    pub(crate) fn default_region(&self) -> &'static str {
+        match self {
+            Partition::Aws => "us-east-1",
+            Partition::AwsCn => "cn-north-1",
+            Partition::AwsUsGov => "us-gov-west-1",
+            Partition::AwsIso => "us-iso-east-1",
+            Partition::AwsIsoB => "us-isob-east-1",
+        }
+    }
+}
+
+impl fmt::Display for Partition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Partition::Aws => write!(f, "aws"),
+            Partition::AwsCn => write!(f, "aws-cn"),
+            Partition::AwsUsGov => write!(f, "aws-us-gov"),
+            Partition::AwsIso => write!(f, "aws-iso"),
+            Partition::AwsIsoB => write!(f, "aws-iso-b"),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use blocking::unblock;
+use log::info;
+use log::warn;
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::services::fs;
+use crate::Accessor;
+use crate::AccessorBuilder;
+
+/// Builder for tempfs, which is a [`fs::Backend`] rooted at a new temp
+/// dir.
+#[derive(Default, Debug)]
+pub struct Builder {
+    parent: Option<String>,
+    prefix: Option<String>,
+}
+
+impl Builder {
+    /// Create the temp dir under `parent`, default to the system temp dir.
+    ///
+    /// NOTE: the parent must be absolute path.
+    pub fn parent(&mut self, parent: &str) -> &mut Self {
+        self.parent = Some(parent.to_string());
+
+        self
+    }
+
+    /// Prefix of the temp dir's name, default to `opendal-`.
+    pub fn prefix(&mut self, prefix: &str) -> &mut Self {
+        self.prefix = Some(prefix.to_string());
+
+        self
+    }
+
+    pub async fn finish(&mut self) -> Result<Arc<dyn Accessor>> {
+        info!("backend build started: {:?}", &self);
+
+        let parent = match &self.parent {
+            Some(v) => PathBuf::from(v),
+            None => std::env::temp_dir(),
+        };
+        let name = format!(
+            "{}{}",
+            self.prefix.as_deref().unwrap_or("opendal-"),
+            Uuid::new_v4()
+        );
+        let root = parent.join(name);
+        let root = root.to_str().ok_or_else(|| Error::Backend {
+            kind: Kind::BackendConfigurationInvalid,
+            context: HashMap::from([("scheme".to_string(), "tempfs".to_string())]),
+            source: anyhow!("temp dir {:?} is not valid utf-8", root),
+        })?;
+
+        let dir = root.to_string();
+        unblock(|| std::fs::create_dir_all(dir))
+            .await
+            .map_err(|e| Error::Backend {
+                kind: Kind::BackendConfigurationInvalid,
+                context: HashMap::from([
+                    ("scheme".to_string(), "tempfs".to_string()),
+                    ("root".to_string(), root.to_string()),
+                ]),
+                source: anyhow!("create temp dir failed: {}", e),
+            })?;
+        // From now on, the dir will be removed even if building fails.
+        let guard = Arc::new(TempDir(PathBuf::from(root)));
+
+        let mut builder = fs::Backend::build();
+        builder.root(root);
+        builder.guard = Some(guard);
+        let backend = builder.finish().await?;
+
+        info!("backend build finished: {:?}", &self);
+        Ok(backend)
+    }
+}
+
+#[async_trait]
+impl AccessorBuilder for Builder {
+    async fn finish(&mut self) -> Result<Arc<dyn Accessor>> {
+        Builder::finish(self).await
+    }
+}
+
+/// TempDir removes the dir recursively on drop.
+#[derive(Debug)]
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        match std::fs::remove_dir_all(&self.0) {
+            Ok(()) => info!("temp dir {:?} removed", self.0),
+            Err(e) => warn!("temp dir {:?} remove failed: {}", self.0, e),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Scratch space on local fs that cleans up itself.
+//!
+//! Every backend is rooted at a new unique dir under the system temp dir,
+//! which will be removed recursively when the last clone of the backend
+//! is dropped, including the ones held by operators and listed objects.
+//!
+//! # Example
+//!
+//! ```
+//! use anyhow::Result;
+//! use opendal::services::tempfs;
+//! use opendal::Operator;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let op = Operator::new(tempfs::Builder::default().finish().await?);
+//!     op.object("scratch").writer().write_bytes(vec![0; 1024]).await?;
+//!
+//!     // The temp dir is removed here.
+//!     drop(op);
+//!
+//!     Ok(())
+//! }
+//! ```
+
+mod backend;
+pub use backend::Builder;
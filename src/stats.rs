@@ -0,0 +1,343 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use futures::AsyncRead;
+
+use crate::error::Result;
+use crate::object::BoxedObjectStream;
+use crate::observe::Instant;
+use crate::observe::Observer;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::Operation;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::readers::CallbackReader;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Metadata;
+use crate::ObjectReader;
+
+pub(crate) const OPERATIONS: [Operation; 14] = [
+    Operation::BucketExists,
+    Operation::Read,
+    Operation::Write,
+    Operation::Append,
+    Operation::Stat,
+    Operation::Delete,
+    Operation::List,
+    Operation::Select,
+    Operation::Presign,
+    Operation::ListMultipartUploads,
+    Operation::AbortMultipartUpload,
+    Operation::Retention,
+    Operation::Copy,
+    Operation::Create,
+];
+
+/// Counters shared by an [`Operator`][crate::Operator] and its clones.
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+    requests: [AtomicU64; OPERATIONS.len()],
+}
+
+impl Stats {
+    fn request(&self, op: Operation) {
+        let idx = OPERATIONS
+            .iter()
+            .position(|v| *v == op)
+            .expect("operation must be counted");
+        self.requests[idx].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> OperatorStats {
+        OperatorStats {
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            requests: OPERATIONS
+                .iter()
+                .zip(&self.requests)
+                .map(|(op, v)| (*op, v.load(Ordering::Relaxed)))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.bytes_read.store(0, Ordering::Relaxed);
+        self.bytes_written.store(0, Ordering::Relaxed);
+        for v in &self.requests {
+            v.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A snapshot of the traffic sent to the backend by an
+/// [`Operator`][crate::Operator], returned by
+/// [`Operator::stats`][crate::Operator::stats].
+///
+/// Requests are counted for every call to the backend, so calls served by
+/// layers (like cache hits) are not counted while retried calls are counted
+/// for every attempt. Bytes are counted while the data is pulled from or
+/// pushed to the backend, a failed transfer still counts the bytes that
+/// have been transferred.
+#[derive(Debug, Clone, Default)]
+pub struct OperatorStats {
+    bytes_read: u64,
+    bytes_written: u64,
+    requests: HashMap<Operation, u64>,
+}
+
+impl OperatorStats {
+    /// Total bytes read from the backend.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Total bytes written (and appended) to the backend.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Total requests of `op` sent to the backend.
+    pub fn requests(&self, op: Operation) -> u64 {
+        self.requests.get(&op).copied().unwrap_or_default()
+    }
+
+    /// Total requests of all operations sent to the backend.
+    pub fn total_requests(&self) -> u64 {
+        self.requests.values().sum()
+    }
+}
+
+/// StatsAccessor wraps the backend directly, so that all layers are above
+/// it.
+#[derive(Debug)]
+pub(crate) struct StatsAccessor {
+    inner: Arc<dyn Accessor>,
+    stats: Arc<Stats>,
+    observer: Arc<Observer>,
+}
+
+impl StatsAccessor {
+    pub(crate) fn new(
+        inner: Arc<dyn Accessor>,
+        stats: Arc<Stats>,
+        observer: Arc<Observer>,
+    ) -> Self {
+        Self {
+            inner,
+            stats,
+            observer,
+        }
+    }
+
+    /// Count the request of `op` and record a sample of it without data.
+    async fn observed<T, F>(&self, op: Operation, f: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        self.stats.request(op);
+        let start = self.observer.start();
+        let result = f.await;
+        self.observer.record_since(op, 0, start);
+        result
+    }
+
+    /// Count the write request of `op` and record a sample with the
+    /// written size.
+    async fn observed_write<F>(&self, op: Operation, f: F) -> Result<usize>
+    where
+        F: Future<Output = Result<usize>>,
+    {
+        self.stats.request(op);
+        let start = self.observer.start();
+        let result = f.await;
+        let n = *result.as_ref().unwrap_or(&0);
+        self.observer.record_since(op, n as u64, start);
+        result
+    }
+
+    fn count_written(&self, r: BoxedAsyncReader) -> BoxedAsyncReader {
+        let stats = self.stats.clone();
+        Box::new(CallbackReader::new(r, move |n| {
+            stats.bytes_written.fetch_add(n as u64, Ordering::Relaxed);
+        }))
+    }
+}
+
+#[async_trait]
+impl Accessor for StatsAccessor {
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        self.stats.request(Operation::Read);
+        let start = self.observer.start();
+        let or = match self.inner.read(args).await {
+            Ok(or) => or,
+            Err(e) => {
+                self.observer.record_since(Operation::Read, 0, start);
+                return Err(e);
+            }
+        };
+        let buffered = or.is_buffered();
+        let provenance = or.provenance().clone();
+        let (r, meta) = or.into_parts();
+
+        let r = StatsReader {
+            inner: r,
+            stats: self.stats.clone(),
+            observed: start.map(|start| (self.observer.clone(), start)),
+            bytes: 0,
+        };
+        let r = ObjectReader::new(Box::new(r))
+            .with_metadata(meta)
+            .with_provenance(provenance);
+        Ok(if buffered { r.with_buffered() } else { r })
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        self.observed_write(
+            Operation::Write,
+            self.inner.write(self.count_written(r), args),
+        )
+        .await
+    }
+    async fn append(&self, r: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        self.observed_write(
+            Operation::Append,
+            self.inner.append(self.count_written(r), args),
+        )
+        .await
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.observed(Operation::Stat, self.inner.stat(args)).await
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        self.observed(Operation::Delete, self.inner.delete(args))
+            .await
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        self.observed(Operation::List, self.inner.list(args)).await
+    }
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.observed(Operation::BucketExists, self.inner.bucket_exists())
+            .await
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.metadata()
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.observed(Operation::Select, self.inner.select(args))
+            .await
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        self.observed(Operation::Presign, self.inner.presign(args))
+            .await
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.observed(
+            Operation::ListMultipartUploads,
+            self.inner.list_multipart_uploads(args),
+        )
+        .await
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.observed(
+            Operation::AbortMultipartUpload,
+            self.inner.abort_multipart_upload(args),
+        )
+        .await
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.observed(Operation::Retention, self.inner.retention(args))
+            .await
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        self.observed(Operation::Copy, self.inner.copy(args)).await
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        self.observed(Operation::Create, self.inner.create(args))
+            .await
+    }
+}
+
+/// StatsReader counts bytes read, and records a sample of `Read` once it
+/// reaches EOF or is dropped if observed.
+struct StatsReader {
+    inner: BoxedAsyncReader,
+    stats: Arc<Stats>,
+    /// The observer and the time the read started, taken after recorded.
+    observed: Option<(Arc<Observer>, Instant)>,
+    bytes: u64,
+}
+
+impl StatsReader {
+    fn finish(&mut self) {
+        if let Some((observer, start)) = self.observed.take() {
+            observer.record(Operation::Read, self.bytes, start.elapsed());
+        }
+    }
+}
+
+impl AsyncRead for StatsReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        match &result {
+            Poll::Ready(Ok(0)) if !buf.is_empty() => self.finish(),
+            Poll::Ready(Ok(n)) => {
+                self.stats
+                    .bytes_read
+                    .fetch_add(*n as u64, Ordering::Relaxed);
+                self.bytes += *n as u64;
+            }
+            _ => {}
+        }
+        result
+    }
+}
+
+impl Drop for StatsReader {
+    fn drop(&mut self) {
+        self.finish()
+    }
+}
@@ -0,0 +1,700 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for testing code built on OpenDAL without a real backend.
+//!
+//! Only available with the `testing` feature.
+
+pub mod bench;
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use futures::io;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::layers::decode_hex;
+use crate::layers::Record;
+use crate::layers::Response;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpAppend;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpDelete;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::PresignedRequest;
+use crate::ops::Retention;
+use crate::ops::RetentionMode;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Metadata;
+use crate::Object;
+use crate::ObjectReader;
+
+/// MockAccessor is an [`Accessor`] whose responses are programmed by tests.
+///
+/// Every operation has a queue of responses, and every call pops the next
+/// one. Calling an operation without programmed responses returns an error
+/// with [`Kind::Unexpected`]. All clones share the same state, so tests
+/// could keep a clone to program and inspect it after handing it to an
+/// [`Operator`][crate::Operator].
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use anyhow::anyhow;
+/// use anyhow::Result;
+/// use futures::io::Cursor;
+/// use futures::AsyncReadExt;
+/// use opendal::error::Error;
+/// use opendal::error::Kind;
+/// use opendal::testing::MockAccessor;
+/// use opendal::ObjectReader;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let mock = MockAccessor::new();
+///     mock.push_read(Err(Error::Object {
+///         kind: Kind::Unexpected,
+///         op: "read",
+///         path: "test".to_string(),
+///         source: anyhow!("injected"),
+///     }))
+///     .push_read(Ok(ObjectReader::new(Box::new(Cursor::new(b"Hello".to_vec())))));
+///
+///     let op = Operator::new(Arc::new(mock.clone()));
+///     let mut buf = Vec::new();
+///     assert!(op.object("test").reader().read_to_end(&mut buf).await.is_err());
+///     op.object("test").reader().read_to_end(&mut buf).await?;
+///     assert_eq!(buf, b"Hello");
+///     assert_eq!(mock.calls("read"), 2);
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct MockAccessor {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    metadata: AccessorMetadata,
+    calls: HashMap<&'static str, usize>,
+    writes: Vec<(OpWrite, Vec<u8>)>,
+    reads: Vec<OpRead>,
+    lists: Vec<OpList>,
+    copies: Vec<OpCopy>,
+
+    bucket_exists: VecDeque<Result<bool>>,
+    read: VecDeque<Result<ObjectReader>>,
+    write: VecDeque<Result<usize>>,
+    append: VecDeque<Result<usize>>,
+    stat: VecDeque<Result<Metadata>>,
+    delete: VecDeque<Result<()>>,
+    list: VecDeque<Result<BoxedObjectStream>>,
+    select: VecDeque<Result<BoxedAsyncReader>>,
+    presign: VecDeque<Result<PresignedRequest>>,
+    list_multipart_uploads: VecDeque<Result<Vec<MultipartUpload>>>,
+    abort_multipart_upload: VecDeque<Result<()>>,
+    retention: VecDeque<Result<Retention>>,
+    copy: VecDeque<Result<()>>,
+    create: VecDeque<Result<()>>,
+}
+
+impl Debug for MockAccessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().expect("lock poisoned");
+        f.debug_struct("MockAccessor")
+            .field("calls", &state.calls)
+            .finish()
+    }
+}
+
+impl MockAccessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the metadata that returned by [`Accessor::metadata`].
+    pub fn set_metadata(&self, metadata: AccessorMetadata) -> &Self {
+        self.state.lock().expect("lock poisoned").metadata = metadata;
+        self
+    }
+
+    pub fn push_bucket_exists(&self, resp: Result<bool>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .bucket_exists
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_read(&self, resp: Result<ObjectReader>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .read
+            .push_back(resp);
+        self
+    }
+
+    /// Program the result of `write`, the data will be consumed and
+    /// recorded if the result is `Ok`.
+    pub fn push_write(&self, resp: Result<usize>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .write
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_append(&self, resp: Result<usize>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .append
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_stat(&self, resp: Result<Metadata>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .stat
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_delete(&self, resp: Result<()>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .delete
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_list(&self, resp: Result<BoxedObjectStream>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .list
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_select(&self, resp: Result<BoxedAsyncReader>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .select
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_presign(&self, resp: Result<PresignedRequest>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .presign
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_list_multipart_uploads(&self, resp: Result<Vec<MultipartUpload>>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .list_multipart_uploads
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_abort_multipart_upload(&self, resp: Result<()>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .abort_multipart_upload
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_retention(&self, resp: Result<Retention>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .retention
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_copy(&self, resp: Result<()>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .copy
+            .push_back(resp);
+        self
+    }
+
+    pub fn push_create(&self, resp: Result<()>) -> &Self {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .create
+            .push_back(resp);
+        self
+    }
+
+    /// Returns how many times the operation has been called, including the
+    /// calls without programmed responses.
+    pub fn calls(&self, op: &str) -> usize {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .calls
+            .get(op)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns all succeeded writes with the data written.
+    pub fn writes(&self) -> Vec<(OpWrite, Vec<u8>)> {
+        self.state.lock().expect("lock poisoned").writes.clone()
+    }
+
+    /// Returns the args of all read calls, including the failed ones.
+    pub fn reads(&self) -> Vec<OpRead> {
+        self.state.lock().expect("lock poisoned").reads.clone()
+    }
+
+    /// Returns the args of all list calls, including the failed ones.
+    pub fn lists(&self) -> Vec<OpList> {
+        self.state.lock().expect("lock poisoned").lists.clone()
+    }
+
+    /// Returns the args of all copy calls, including the failed ones.
+    pub fn copies(&self) -> Vec<OpCopy> {
+        self.state.lock().expect("lock poisoned").copies.clone()
+    }
+
+    /// Count the call and pop the next programmed response.
+    fn pop<T>(
+        &self,
+        op: &'static str,
+        path: &str,
+        f: impl FnOnce(&mut State) -> &mut VecDeque<Result<T>>,
+    ) -> Result<T> {
+        let mut state = self.state.lock().expect("lock poisoned");
+        *state.calls.entry(op).or_default() += 1;
+
+        f(&mut state).pop_front().unwrap_or_else(|| {
+            Err(Error::Object {
+                kind: Kind::Unexpected,
+                op,
+                path: path.to_string(),
+                source: anyhow!("no response programmed for {}", op),
+            })
+        })
+    }
+}
+
+#[async_trait]
+impl Accessor for MockAccessor {
+    async fn bucket_exists(&self) -> Result<bool> {
+        self.pop("bucket_exists", "/", |s| &mut s.bucket_exists)
+    }
+    fn metadata(&self) -> AccessorMetadata {
+        self.state.lock().expect("lock poisoned").metadata
+    }
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .reads
+            .push(args.clone());
+        self.pop("read", &args.path, |s| &mut s.read)
+    }
+    async fn write(&self, mut r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        let n = self.pop("write", &args.path, |s| &mut s.write)?;
+
+        let mut buf = Vec::new();
+        io::copy(&mut r, &mut buf)
+            .await
+            .map_err(|e| Error::Object {
+                kind: Kind::Unexpected,
+                op: "write",
+                path: args.path.to_string(),
+                source: anyhow::Error::from(e),
+            })?;
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .writes
+            .push((args.clone(), buf));
+
+        Ok(n)
+    }
+    async fn append(&self, _: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        self.pop("append", &args.path, |s| &mut s.append)
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        self.pop("stat", &args.path, |s| &mut s.stat)
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        self.pop("delete", &args.path, |s| &mut s.delete)
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .lists
+            .push(args.clone());
+        self.pop("list", &args.path, |s| &mut s.list)
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        self.pop("select", &args.path, |s| &mut s.select)
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        self.pop("presign", &args.path, |s| &mut s.presign)
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        self.pop("list_multipart_uploads", &args.prefix, |s| {
+            &mut s.list_multipart_uploads
+        })
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        self.pop("abort_multipart_upload", &args.path, |s| {
+            &mut s.abort_multipart_upload
+        })
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        self.pop("retention", &args.path, |s| &mut s.retention)
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        self.state
+            .lock()
+            .expect("lock poisoned")
+            .copies
+            .push(args.clone());
+        self.pop("copy", &args.from, |s| &mut s.copy)
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        self.pop("create", &args.path, |s| &mut s.create)
+    }
+}
+
+/// ReplayAccessor serves the responses recorded by
+/// [`RecordLayer`][crate::layers::RecordLayer] without the real backend.
+///
+/// Responses are matched by operation and path, and served in the order
+/// they were recorded. Calling an operation without recorded responses
+/// left returns an error with [`Kind::Unexpected`]. Errors are replayed
+/// with their kind and message only.
+///
+/// Reads serve the recorded data as is, no matter which range is asked.
+/// Selects serve the recorded rows, `copy` is matched by its source path.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use anyhow::Result;
+/// use opendal::layers::RecordLayer;
+/// use opendal::services::memory;
+/// use opendal::testing::ReplayAccessor;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let path = std::env::temp_dir().join(format!("opendal-{}.jsonl", uuid::Uuid::new_v4()));
+///     let op = Operator::new(memory::Backend::build().finish().await?)
+///         .layer(RecordLayer::new(&path)?);
+///     op.object("test").writer().write_bytes(vec![0; 4]).await?;
+///     op.object("test").metadata().await?;
+///
+///     let op = Operator::new(Arc::new(ReplayAccessor::new(&path)?));
+///     assert_eq!(op.object("test").metadata().await?.content_length(), 4);
+///     // Only one stat has been recorded.
+///     assert!(op.object("test").metadata().await.is_err());
+///
+///     std::fs::remove_file(&path)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ReplayAccessor {
+    records: Arc<Mutex<Records>>,
+}
+
+/// Recorded responses keyed by operation and path.
+type Records = HashMap<(String, String), VecDeque<Record>>;
+
+impl ReplayAccessor {
+    /// Load the records from the file written by `RecordLayer`.
+    pub fn new(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+
+        let mut records = Records::new();
+        for line in content.lines().filter(|v| !v.trim().is_empty()) {
+            let record: Record = serde_json::from_str(line)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            records
+                .entry((record.op.clone(), record.path.clone()))
+                .or_default()
+                .push_back(record);
+        }
+
+        Ok(Self {
+            records: Arc::new(Mutex::new(records)),
+        })
+    }
+
+    /// Pop the next recorded response of `op` on `path`.
+    fn pop(&self, op: &'static str, path: &str) -> Result<Response> {
+        let record = self
+            .records
+            .lock()
+            .expect("lock poisoned")
+            .get_mut(&(op.to_string(), path.to_string()))
+            .and_then(|v| v.pop_front())
+            .ok_or_else(|| Error::Object {
+                kind: Kind::Unexpected,
+                op,
+                path: path.to_string(),
+                source: anyhow!("no response recorded for {}", op),
+            })?;
+
+        record.result.map_err(|e| e.into_error(op, path))
+    }
+
+    fn mismatch(op: &'static str, path: &str) -> Error {
+        Error::Object {
+            kind: Kind::Unexpected,
+            op,
+            path: path.to_string(),
+            source: anyhow!("recorded response doesn't match {}", op),
+        }
+    }
+}
+
+#[async_trait]
+impl Accessor for ReplayAccessor {
+    async fn bucket_exists(&self) -> Result<bool> {
+        match self.pop("bucket_exists", "/")? {
+            Response::BucketExists(v) => Ok(v),
+            _ => Err(ReplayAccessor::mismatch("bucket_exists", "/")),
+        }
+    }
+    async fn read(&self, args: &OpRead) -> Result<ObjectReader> {
+        match self.pop("read", &args.path)? {
+            Response::Read {
+                data,
+                meta,
+                provenance,
+            } => {
+                let bs = decode_hex(&data).ok_or_else(|| Error::Object {
+                    kind: Kind::Unexpected,
+                    op: "read",
+                    path: args.path.clone(),
+                    source: anyhow!("recorded data is not valid hex"),
+                })?;
+                Ok(ObjectReader::new(Box::new(io::Cursor::new(bs)))
+                    .with_metadata(meta.into())
+                    .with_provenance(provenance.into_iter().collect()))
+            }
+            _ => Err(ReplayAccessor::mismatch("read", &args.path)),
+        }
+    }
+    async fn write(&self, mut r: BoxedAsyncReader, args: &OpWrite) -> Result<usize> {
+        let n = match self.pop("write", &args.path)? {
+            Response::Write(n) => n,
+            _ => return Err(ReplayAccessor::mismatch("write", &args.path)),
+        };
+        // Drain the data like a real backend does.
+        io::copy(&mut r, &mut io::sink())
+            .await
+            .map_err(|e| Error::Object {
+                kind: Kind::Unexpected,
+                op: "write",
+                path: args.path.to_string(),
+                source: anyhow::Error::from(e),
+            })?;
+        Ok(n)
+    }
+    async fn append(&self, _: BoxedAsyncReader, args: &OpAppend) -> Result<usize> {
+        match self.pop("append", &args.path)? {
+            Response::Append(n) => Ok(n),
+            _ => Err(ReplayAccessor::mismatch("append", &args.path)),
+        }
+    }
+    async fn stat(&self, args: &OpStat) -> Result<Metadata> {
+        match self.pop("stat", &args.path)? {
+            Response::Stat(meta) => Ok(meta.into()),
+            _ => Err(ReplayAccessor::mismatch("stat", &args.path)),
+        }
+    }
+    async fn delete(&self, args: &OpDelete) -> Result<()> {
+        match self.pop("delete", &args.path)? {
+            Response::Delete => Ok(()),
+            _ => Err(ReplayAccessor::mismatch("delete", &args.path)),
+        }
+    }
+    async fn list(&self, args: &OpList) -> Result<BoxedObjectStream> {
+        let metas = match self.pop("list", &args.path)? {
+            Response::List(metas) => metas,
+            _ => return Err(ReplayAccessor::mismatch("list", &args.path)),
+        };
+
+        let acc: Arc<dyn Accessor> = Arc::new(self.clone());
+        let obs: Vec<_> = metas
+            .into_iter()
+            .map(|meta| {
+                let meta = Metadata::from(meta);
+                let mut o = Object::new(acc.clone(), meta.path());
+                *o.metadata_mut() = meta;
+                Ok(o)
+            })
+            .collect();
+        Ok(Box::new(futures::stream::iter(obs)))
+    }
+    async fn select(&self, args: &OpSelect) -> Result<BoxedAsyncReader> {
+        match self.pop("select", &args.path)? {
+            Response::Select(data) => {
+                let bs = decode_hex(&data).ok_or_else(|| Error::Object {
+                    kind: Kind::Unexpected,
+                    op: "select",
+                    path: args.path.clone(),
+                    source: anyhow!("recorded data is not valid hex"),
+                })?;
+                Ok(Box::new(io::Cursor::new(bs)))
+            }
+            _ => Err(ReplayAccessor::mismatch("select", &args.path)),
+        }
+    }
+    async fn presign(&self, args: &OpPresign) -> Result<PresignedRequest> {
+        let (method, uri, headers) = match self.pop("presign", &args.path)? {
+            Response::Presign {
+                method,
+                uri,
+                headers,
+            } => (method, uri, headers),
+            _ => return Err(ReplayAccessor::mismatch("presign", &args.path)),
+        };
+
+        let invalid = || Error::Object {
+            kind: Kind::Unexpected,
+            op: "presign",
+            path: args.path.clone(),
+            source: anyhow!("recorded request is invalid"),
+        };
+        let mut header_map = http::HeaderMap::new();
+        for (k, v) in headers {
+            header_map.append(
+                http::HeaderName::from_bytes(k.as_bytes()).map_err(|_| invalid())?,
+                http::HeaderValue::from_str(&v).map_err(|_| invalid())?,
+            );
+        }
+        Ok(PresignedRequest::new(
+            method.parse().map_err(|_| invalid())?,
+            uri.parse().map_err(|_| invalid())?,
+            header_map,
+        ))
+    }
+    async fn list_multipart_uploads(
+        &self,
+        args: &OpListMultipartUploads,
+    ) -> Result<Vec<MultipartUpload>> {
+        match self.pop("list_multipart_uploads", &args.prefix)? {
+            Response::ListMultipartUploads(v) => Ok(v.into_iter().map(Into::into).collect()),
+            _ => Err(ReplayAccessor::mismatch(
+                "list_multipart_uploads",
+                &args.prefix,
+            )),
+        }
+    }
+    async fn abort_multipart_upload(&self, args: &OpAbortMultipartUpload) -> Result<()> {
+        match self.pop("abort_multipart_upload", &args.path)? {
+            Response::AbortMultipartUpload => Ok(()),
+            _ => Err(ReplayAccessor::mismatch(
+                "abort_multipart_upload",
+                &args.path,
+            )),
+        }
+    }
+    async fn retention(&self, args: &OpRetention) -> Result<Retention> {
+        match self.pop("retention", &args.path)? {
+            Response::Retention {
+                mode,
+                retain_until,
+                legal_hold,
+            } => {
+                let mode = match mode.as_deref() {
+                    Some("governance") => Some(RetentionMode::Governance),
+                    Some("compliance") => Some(RetentionMode::Compliance),
+                    _ => None,
+                };
+                Ok(Retention::new(
+                    mode,
+                    retain_until.map(|v| SystemTime::UNIX_EPOCH + v),
+                    legal_hold,
+                ))
+            }
+            _ => Err(ReplayAccessor::mismatch("retention", &args.path)),
+        }
+    }
+    async fn copy(&self, args: &OpCopy) -> Result<()> {
+        match self.pop("copy", &args.from)? {
+            Response::Copy => Ok(()),
+            _ => Err(ReplayAccessor::mismatch("copy", &args.from)),
+        }
+    }
+    async fn create(&self, args: &OpCreate) -> Result<()> {
+        match self.pop("create", &args.path)? {
+            Response::Create => Ok(()),
+            _ => Err(ReplayAccessor::mismatch("create", &args.path)),
+        }
+    }
+}
@@ -0,0 +1,357 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Workloads for benchmarking backends and layers, shared by `benches/ops`
+//! so that backend authors could measure theirs the same way.
+//!
+//! The harness doesn't depend on any bench framework, every workload is
+//! prepared once and then measured for the given iterations, which maps
+//! to criterion's `iter_custom` directly:
+//!
+//! ```no_run
+//! use anyhow::Result;
+//! use opendal::services::memory;
+//! use opendal::testing::bench::BenchFixture;
+//! use opendal::testing::bench::Workload;
+//! use opendal::Operator;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let fixture = BenchFixture::new(Operator::new(memory::Backend::build().finish().await?));
+//!     for workload in Workload::all() {
+//!         let bench = fixture.prepare(workload).await?;
+//!         let elapsed = bench.measure(100).await?;
+//!         println!("{}/{}: {:?}", workload.group(), workload.param(), elapsed / 100);
+//!     }
+//!     fixture.cleanup().await?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::anyhow;
+use futures::stream;
+use futures::AsyncReadExt;
+use futures::StreamExt;
+use futures::TryStreamExt;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::Operator;
+use crate::Reader;
+
+/// Sizes of objects in workloads: 4 KiB, 256 KiB and 16 MiB.
+pub const OBJECT_SIZES: [u64; 3] = [4 * 1024, 256 * 1024, 16 * 1024 * 1024];
+
+/// Entries of the dir to list.
+pub const LIST_ENTRIES: usize = 10_000;
+
+/// Requests in flight while preparing and cleaning up.
+const CONCURRENCY: usize = 64;
+
+/// Objects prepared at most in one round for deletes, so that memory is
+/// bounded no matter how many iterations are asked.
+const DELETE_BATCH: u64 = 1000;
+
+/// Workload is an operation measured against objects of a given size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    /// Write (overwrite) an object of the size.
+    Write(u64),
+    /// Read an object of the size to the end.
+    Read(u64),
+    /// Read the middle half of an object of the size.
+    RangedRead(u64),
+    /// Stat an existing object of the smallest size.
+    Stat,
+    /// List all entries of a dir containing the given number of objects.
+    List(usize),
+    /// Delete an existing object of the size.
+    Delete(u64),
+}
+
+impl Workload {
+    /// All workloads: writes, reads and ranged reads of [`OBJECT_SIZES`],
+    /// stat, list of [`LIST_ENTRIES`] and deletes of the smallest size.
+    pub fn all() -> Vec<Workload> {
+        let mut workloads = Vec::new();
+        for f in [Workload::Write, Workload::Read, Workload::RangedRead] {
+            workloads.extend(OBJECT_SIZES.iter().map(|size| f(*size)));
+        }
+        workloads.push(Workload::Stat);
+        workloads.push(Workload::List(LIST_ENTRIES));
+        workloads.push(Workload::Delete(OBJECT_SIZES[0]));
+        workloads
+    }
+
+    /// Name of the operation, like `write`.
+    pub fn group(&self) -> &'static str {
+        match self {
+            Workload::Write(_) => "write",
+            Workload::Read(_) => "read",
+            Workload::RangedRead(_) => "read_range",
+            Workload::Stat => "stat",
+            Workload::List(_) => "list",
+            Workload::Delete(_) => "delete",
+        }
+    }
+
+    /// Parameter of the operation, like `4KiB` or `10000`.
+    pub fn param(&self) -> String {
+        match self {
+            Workload::Write(size)
+            | Workload::Read(size)
+            | Workload::RangedRead(size)
+            | Workload::Delete(size) => format_size(*size),
+            Workload::Stat => format_size(OBJECT_SIZES[0]),
+            Workload::List(n) => n.to_string(),
+        }
+    }
+
+    /// Bytes transferred in one iteration, `None` if nothing transferred.
+    pub fn throughput(&self) -> Option<u64> {
+        match self {
+            Workload::Write(size) | Workload::Read(size) => Some(*size),
+            Workload::RangedRead(size) => Some(size / 2),
+            _ => None,
+        }
+    }
+}
+
+fn format_size(size: u64) -> String {
+    match size {
+        v if v >= 1024 * 1024 && v % (1024 * 1024) == 0 => format!("{}MiB", v / 1024 / 1024),
+        v if v >= 1024 && v % 1024 == 0 => format!("{}KiB", v / 1024),
+        v => format!("{}B", v),
+    }
+}
+
+/// Generate `size` bytes of incompressible content from `seed`, the same
+/// seed always generates the same content.
+pub fn gen_content(seed: u64, size: usize) -> Vec<u8> {
+    // xorshift64*, good enough to defeat compression and dedup.
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    let mut content = Vec::with_capacity(size + 8);
+    while content.len() < size {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        content.extend_from_slice(&state.wrapping_mul(0x2545_F491_4F6C_DD1D).to_le_bytes());
+    }
+    content.truncate(size);
+    content
+}
+
+/// BenchFixture owns a unique dir on the operator, all objects of the
+/// workloads are created under it and removed by
+/// [`BenchFixture::cleanup`].
+#[derive(Clone)]
+pub struct BenchFixture {
+    op: Operator,
+    root: String,
+}
+
+impl BenchFixture {
+    pub fn new(op: Operator) -> Self {
+        Self {
+            op,
+            root: format!("opendal-bench-{}/", uuid::Uuid::new_v4()),
+        }
+    }
+
+    pub fn operator(&self) -> &Operator {
+        &self.op
+    }
+
+    /// The dir holding all objects of this fixture.
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// Prepare the objects needed by `workload`.
+    pub async fn prepare(&self, workload: Workload) -> Result<PreparedWorkload> {
+        let dir = format!("{}{}-{}/", self.root, workload.group(), workload.param());
+        let mut content = Vec::new();
+
+        match workload {
+            Workload::Write(size) | Workload::Delete(size) => {
+                content = gen_content(size, size as usize);
+            }
+            Workload::Read(size) | Workload::RangedRead(size) => {
+                self.write(&format!("{dir}object"), gen_content(size, size as usize))
+                    .await?;
+            }
+            Workload::Stat => {
+                self.write(
+                    &format!("{dir}object"),
+                    gen_content(0, OBJECT_SIZES[0] as usize),
+                )
+                .await?;
+            }
+            Workload::List(n) => {
+                stream::iter(0..n)
+                    .map(|i| {
+                        let path = format!("{dir}{i:08}");
+                        async move { self.write(&path, Vec::new()).await }
+                    })
+                    .buffer_unordered(CONCURRENCY)
+                    .try_collect::<Vec<_>>()
+                    .await?;
+            }
+        }
+
+        Ok(PreparedWorkload {
+            op: self.op.clone(),
+            workload,
+            dir,
+            content,
+        })
+    }
+
+    /// Remove all objects created by this fixture.
+    pub async fn cleanup(&self) -> Result<()> {
+        let summary = self.op.object(&self.root).delete_recursive().await;
+        if let Some((path, err)) = summary.failed.into_iter().next() {
+            return Err(Error::Object {
+                kind: err.kind(),
+                op: "delete",
+                path,
+                source: anyhow!("cleanup bench objects: {}", err),
+            });
+        }
+        match self.op.object(&self.root).delete().await {
+            Err(e) if e.kind() != Kind::ObjectNotExist => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    async fn write(&self, path: &str, content: Vec<u8>) -> Result<()> {
+        self.op.object(path).writer().write_bytes(content).await?;
+        Ok(())
+    }
+}
+
+/// A workload with its objects prepared, see [`BenchFixture::prepare`].
+pub struct PreparedWorkload {
+    op: Operator,
+    workload: Workload,
+    dir: String,
+    /// Content to be written, only for writes and deletes.
+    content: Vec<u8>,
+}
+
+impl PreparedWorkload {
+    pub fn workload(&self) -> Workload {
+        self.workload
+    }
+
+    /// Run `iters` iterations of the workload one by one, and return the
+    /// time spent on the operation only.
+    ///
+    /// Objects to delete are written before the deletes in batches, the
+    /// time of writing is not included.
+    pub async fn measure(&self, iters: u64) -> Result<Duration> {
+        let path = format!("{}object", self.dir);
+
+        if let Workload::Delete(_) = self.workload {
+            return self.measure_delete(iters).await;
+        }
+
+        let start = Instant::now();
+        for _ in 0..iters {
+            match self.workload {
+                Workload::Write(_) => {
+                    self.op
+                        .object(&path)
+                        .writer()
+                        .write_bytes(self.content.clone())
+                        .await?;
+                }
+                Workload::Read(size) => {
+                    let r = self.op.object(&path).reader();
+                    read_to_end(&path, r, size).await?;
+                }
+                Workload::RangedRead(size) => {
+                    let r = self.op.object(&path).range_reader(size / 4, size / 2);
+                    read_to_end(&path, r, size / 2).await?;
+                }
+                Workload::Stat => {
+                    self.op.object(&path).metadata().await?;
+                }
+                Workload::List(n) => {
+                    let listed = self
+                        .op
+                        .objects(&self.dir)
+                        .try_fold(0, |n, _| async move { Ok(n + 1) });
+                    let listed: usize = listed.await?;
+                    if listed != n {
+                        return Err(Error::Object {
+                            kind: Kind::Unexpected,
+                            op: "list",
+                            path: self.dir.clone(),
+                            source: anyhow!("listed {} entries, expected {}", listed, n),
+                        });
+                    }
+                }
+                Workload::Delete(_) => unreachable!("deletes are measured in batches"),
+            }
+        }
+        Ok(start.elapsed())
+    }
+
+    async fn measure_delete(&self, iters: u64) -> Result<Duration> {
+        let mut elapsed = Duration::ZERO;
+        let mut done = 0;
+        while done < iters {
+            let batch = DELETE_BATCH.min(iters - done);
+            let paths: Vec<_> = (0..batch)
+                .map(|i| format!("{}{:08}", self.dir, done + i))
+                .collect();
+            stream::iter(&paths)
+                .map(|path| async move {
+                    self.op
+                        .object(path)
+                        .writer()
+                        .write_bytes(self.content.clone())
+                        .await
+                })
+                .buffer_unordered(CONCURRENCY)
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            let start = Instant::now();
+            for path in &paths {
+                self.op.object(path).delete().await?;
+            }
+            elapsed += start.elapsed();
+            done += batch;
+        }
+        Ok(elapsed)
+    }
+}
+
+async fn read_to_end(path: &str, mut r: Reader, size: u64) -> Result<()> {
+    let mut buf = Vec::with_capacity(size as usize);
+    r.read_to_end(&mut buf).await.map_err(|e| Error::Object {
+        kind: Kind::Unexpected,
+        op: "read",
+        path: path.to_string(),
+        source: anyhow!(e),
+    })?;
+    Ok(())
+}
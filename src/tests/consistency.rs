@@ -0,0 +1,77 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use anyhow::Result;
+use futures::AsyncReadExt;
+
+use crate::services::fs;
+use crate::services::memory;
+use crate::tmp::TempDir;
+use crate::Operator;
+use crate::ReadConsistency;
+
+const ROUNDS: usize = 1000;
+
+/// Content of the `i`th version: filled by a single byte and sized by it,
+/// so that a mix of two versions is always detectable.
+fn version(i: usize) -> Vec<u8> {
+    let b = (i % 256) as u8;
+    vec![b; 64 * 1024 + b as usize]
+}
+
+/// Overwrite `path` and read it concurrently [`ROUNDS`] times, every read
+/// must observe a complete version.
+async fn overwrite_while_reading(op: Operator, path: &str) -> Result<()> {
+    assert_eq!(op.metadata().read_consistency(), ReadConsistency::Committed);
+    op.object(path).writer().write_bytes(version(0)).await?;
+
+    let o = op.object(path);
+    let writer = tokio::spawn(async move {
+        for i in 1..=ROUNDS {
+            o.writer().write_bytes(version(i)).await?;
+        }
+        Ok::<_, anyhow::Error>(())
+    });
+
+    let o = op.object(path);
+    let reader = tokio::spawn(async move {
+        for _ in 0..ROUNDS {
+            let mut buf = Vec::new();
+            o.reader().read_to_end(&mut buf).await?;
+            assert_eq!(buf.len(), 64 * 1024 + buf[0] as usize, "torn read");
+            assert!(buf.iter().all(|b| *b == buf[0]), "torn read");
+        }
+        Ok::<_, anyhow::Error>(())
+    });
+
+    writer.await??;
+    reader.await??;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_overwrite_while_reading_memory() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    overwrite_while_reading(op, "test").await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_overwrite_while_reading_fs() -> Result<()> {
+    let op = Operator::new(fs::Backend::build().root("/tmp").finish().await?);
+    // Removed on drop even if the test failed.
+    let mut dir = TempDir::new(&op, "opendal-");
+    let path = dir.object("test").path().to_string();
+    overwrite_while_reading(op, &path).await?;
+    dir.close().await?;
+    Ok(())
+}
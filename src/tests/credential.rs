@@ -0,0 +1,123 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+
+use crate::credential::CachedCredential;
+use crate::credential::Credential;
+use crate::credential::ProvideCredential;
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+
+/// Provide tokens which expire after `ttl`.
+#[derive(Clone, Default)]
+struct TokenProvider {
+    ttl: Duration,
+    loads: Arc<AtomicUsize>,
+    failing: Arc<AtomicBool>,
+}
+
+impl Debug for TokenProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TokenProvider")
+    }
+}
+
+#[async_trait::async_trait]
+impl ProvideCredential for TokenProvider {
+    type Credential = Credential;
+
+    async fn load(&self) -> Result<(Credential, Option<SystemTime>)> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(Error::Unexpected(anyhow!("token service unavailable")));
+        }
+
+        let n = self.loads.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok((
+            Credential::token(&format!("secret-token-{}", n)),
+            Some(SystemTime::now() + self.ttl),
+        ))
+    }
+}
+
+fn token(cred: &Credential) -> String {
+    match cred {
+        Credential::Token(v) => v.clone(),
+        _ => panic!("unexpected credential: {:?}", cred),
+    }
+}
+
+#[tokio::test]
+async fn test_static_credential_never_refresh() -> Result<()> {
+    let cred = CachedCredential::new(Credential::hmac("ak", "sk"));
+    assert!(matches!(cred.get().await?, Credential::HMAC { .. }));
+    assert!(!format!("{:?}", cred).contains("sk"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_credential_refresh_before_expiry() -> Result<()> {
+    let provider = TokenProvider {
+        ttl: Duration::from_secs(3600),
+        ..Default::default()
+    };
+    let cred = CachedCredential::new(provider.clone());
+
+    assert_eq!(token(&cred.get().await?), "secret-token-1");
+    assert_eq!(token(&cred.get().await?), "secret-token-1");
+    assert_eq!(provider.loads.load(Ordering::SeqCst), 1);
+
+    // The token will expire within the refresh window.
+    let cred = CachedCredential::new(provider.clone()).refresh_before(Duration::from_secs(7200));
+    assert_eq!(token(&cred.get().await?), "secret-token-2");
+    assert_eq!(token(&cred.get().await?), "secret-token-3");
+
+    assert!(!format!("{:?}", cred).contains("secret"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_credential_refresh_failure() -> Result<()> {
+    let provider = TokenProvider {
+        ttl: Duration::from_secs(3600),
+        ..Default::default()
+    };
+    let cred = CachedCredential::new(provider.clone()).refresh_before(Duration::from_secs(7200));
+    assert_eq!(token(&cred.get().await?), "secret-token-1");
+
+    // The cached token is still valid, use it.
+    provider.failing.store(true, Ordering::SeqCst);
+    assert_eq!(token(&cred.get().await?), "secret-token-1");
+
+    // Nothing could be used, returns a retryable error.
+    let cred = CachedCredential::new(provider.clone());
+    let err = cred.get().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::Temporary);
+
+    provider.failing.store(false, Ordering::SeqCst);
+    assert_eq!(token(&cred.get().await?), "secret-token-2");
+
+    Ok(())
+}
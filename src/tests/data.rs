@@ -0,0 +1,190 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::Result;
+use bytes::Bytes;
+use futures::AsyncReadExt;
+use futures::TryStreamExt;
+
+use crate::error::Kind;
+use crate::ops::ListMode;
+use crate::services::data;
+use crate::ObjectMode;
+use crate::ObjectStream;
+use crate::Operator;
+
+static FILES: &[(&str, &[u8])] = &[
+    ("a.txt", b"Hello, World!"),
+    ("dir/b.txt", b"b"),
+    ("dir/sub/c.txt", b"c"),
+    ("dir/sub/d.txt", b"d"),
+];
+
+async fn new_operator() -> Result<Operator> {
+    Ok(Operator::new(
+        data::Backend::build()
+            .static_entries(FILES)
+            .entry("dir/e.txt", Bytes::from("e"))
+            .finish()
+            .await?,
+    ))
+}
+
+#[tokio::test]
+async fn test_read() -> Result<()> {
+    let op = new_operator().await?;
+
+    let mut buf = Vec::new();
+    op.object("a.txt").reader().read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"Hello, World!");
+
+    let mut buf = Vec::new();
+    op.object("a.txt")
+        .range_reader(7, 5)
+        .read_to_end(&mut buf)
+        .await?;
+    assert_eq!(buf, b"World");
+
+    let mut buf = Vec::new();
+    op.object("a.txt")
+        .offset_reader(7)
+        .read_to_end(&mut buf)
+        .await?;
+    assert_eq!(buf, b"World!");
+
+    let err = op
+        .object("not_exist")
+        .reader()
+        .read_to_end(&mut buf)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stat() -> Result<()> {
+    let op = new_operator().await?;
+
+    let meta = op.object("dir/b.txt").metadata().await?;
+    assert_eq!(meta.mode(), ObjectMode::FILE);
+    assert_eq!(meta.content_length(), 1);
+    assert!(meta.etag().is_some());
+
+    let meta = op.object("dir/sub/").metadata().await?;
+    assert_eq!(meta.mode(), ObjectMode::DIR);
+
+    for path in ["not_exist", "dir/not_exist/", "dir"] {
+        let err = op.object(path).metadata().await.unwrap_err();
+        assert_eq!(err.kind(), Kind::ObjectNotExist, "{}", path);
+    }
+
+    Ok(())
+}
+
+async fn list_entries(mut obs: ObjectStream) -> Result<Vec<(String, ObjectMode)>> {
+    let mut entries = Vec::new();
+    while let Some(mut o) = obs.try_next().await? {
+        let meta = o.metadata_cached().await?;
+        entries.push((meta.path().to_string(), meta.mode()));
+    }
+    Ok(entries)
+}
+
+#[tokio::test]
+async fn test_list() -> Result<()> {
+    let op = new_operator().await?;
+
+    assert_eq!(
+        list_entries(op.objects("dir/")).await?,
+        vec![
+            ("dir/b.txt".to_string(), ObjectMode::FILE),
+            ("dir/e.txt".to_string(), ObjectMode::FILE),
+            ("dir/sub/".to_string(), ObjectMode::DIR),
+        ]
+    );
+    assert_eq!(
+        list_entries(op.objects("")).await?,
+        vec![
+            ("a.txt".to_string(), ObjectMode::FILE),
+            ("dir/".to_string(), ObjectMode::DIR),
+        ]
+    );
+    assert_eq!(
+        list_entries(op.objects("dir/").start_after("dir/b.txt")).await?,
+        vec![
+            ("dir/e.txt".to_string(), ObjectMode::FILE),
+            ("dir/sub/".to_string(), ObjectMode::DIR),
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_mode() -> Result<()> {
+    let op = new_operator().await?;
+
+    // Dir mode never matches `dir/sub/c.txt` by string prefix `dir/s`.
+    assert_eq!(
+        list_entries(op.objects("dir/sub")).await?,
+        vec![
+            ("dir/sub/c.txt".to_string(), ObjectMode::FILE),
+            ("dir/sub/d.txt".to_string(), ObjectMode::FILE),
+        ]
+    );
+    assert!(list_entries(op.objects("dir/s")).await?.is_empty());
+
+    // Prefix mode lists all keys without folding dirs.
+    assert_eq!(
+        list_entries(op.objects("dir/s").mode(ListMode::Prefix)).await?,
+        vec![
+            ("dir/sub/c.txt".to_string(), ObjectMode::FILE),
+            ("dir/sub/d.txt".to_string(), ObjectMode::FILE),
+        ]
+    );
+    assert_eq!(
+        list_entries(op.objects("a").mode(ListMode::Prefix)).await?,
+        vec![("a.txt".to_string(), ObjectMode::FILE)]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_only() -> Result<()> {
+    let op = new_operator().await?;
+    let o = op.object("a.txt");
+
+    let err = o.writer().write_bytes(vec![0; 4]).await.unwrap_err();
+    assert_eq!(err.kind(), Kind::Unsupported);
+    let err = o.delete().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::Unsupported);
+
+    // Data is not touched.
+    assert_eq!(o.metadata().await?.content_length(), 13);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_entry_must_be_file() {
+    let err = data::Backend::build()
+        .entry("dir/", Bytes::new())
+        .finish()
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::BackendConfigurationInvalid);
+}
@@ -0,0 +1,122 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::Result;
+
+use crate::fmt::ListFormatter;
+use crate::fmt::TimeFormat;
+use crate::services::memory;
+use crate::Accessor;
+use crate::Object;
+use crate::ObjectMode;
+
+/// Build listed objects carrying partial metadata like list does.
+async fn fixtures() -> Result<Vec<crate::error::Result<Object>>> {
+    let acc: std::sync::Arc<dyn Accessor> = memory::Backend::build().finish().await?;
+    let t = SystemTime::UNIX_EPOCH + Duration::from_secs(1_640_995_200);
+
+    let entry = |path: &str, mode: Option<ObjectMode>, size: Option<u64>, modified: bool| {
+        let mut o = Object::new(acc.clone(), path);
+        let meta = o.metadata_mut();
+        if let Some(mode) = mode {
+            meta.set_mode(mode);
+        }
+        if let Some(size) = size {
+            meta.set_content_length(size);
+        }
+        if modified {
+            meta.set_last_modified(t);
+        }
+        Ok(o)
+    };
+
+    Ok(vec![
+        entry("dir/", Some(ObjectMode::DIR), None, false),
+        entry("dir/a", Some(ObjectMode::FILE), Some(13), true),
+        entry("dir/b", Some(ObjectMode::FILE), Some(1536), true),
+        entry("dir/c", Some(ObjectMode::FILE), Some(5 << 30), false),
+        entry("dir/d", None, None, false),
+    ])
+}
+
+#[tokio::test]
+async fn test_list_formatter() -> Result<()> {
+    let mut out = Vec::new();
+    let n = ListFormatter::new()
+        .write(futures::stream::iter(fixtures().await?), &mut out)
+        .await?;
+    assert_eq!(n, 5);
+    assert_eq!(
+        String::from_utf8(out)?,
+        "\
+d       - -                    dir/
+f    13 B 2022-01-01T00:00:00Z dir/a
+f 1.5 KiB 2022-01-01T00:00:00Z dir/b
+f 5.0 GiB -                    dir/c
+-       - -                    dir/d
+"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_formatter_relative() -> Result<()> {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_640_995_200 + 300);
+
+    let mut out = Vec::new();
+    ListFormatter::new()
+        .time_format(TimeFormat::Relative)
+        .now(now)
+        .write(futures::stream::iter(fixtures().await?), &mut out)
+        .await?;
+    assert_eq!(
+        String::from_utf8(out)?,
+        "\
+d       - -      dir/
+f    13 B 5m ago dir/a
+f 1.5 KiB 5m ago dir/b
+f 5.0 GiB -      dir/c
+-       - -      dir/d
+"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_formatter_streaming() -> Result<()> {
+    // Widths are computed over the first two rows only, the following rows
+    // are streamed with them.
+    let mut out = Vec::new();
+    ListFormatter::new()
+        .window(2)
+        .write(futures::stream::iter(fixtures().await?), &mut out)
+        .await?;
+    assert_eq!(
+        String::from_utf8(out)?,
+        "\
+d    - -                    dir/
+f 13 B 2022-01-01T00:00:00Z dir/a
+f 1.5 KiB 2022-01-01T00:00:00Z dir/b
+f 5.0 GiB -                    dir/c
+-    - -                    dir/d
+"
+    );
+
+    Ok(())
+}
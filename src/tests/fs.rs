@@ -0,0 +1,230 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::env;
+use std::fs as std_fs;
+
+use futures::AsyncReadExt;
+use futures::StreamExt;
+use rand::RngCore;
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::ops::ListMode;
+use crate::ops::WriteOptions;
+use crate::services::fs;
+use crate::Operator;
+
+#[tokio::test]
+async fn test_builder_root_must_be_absolute() {
+    let err = fs::Backend::build()
+        .root("tmp/opendal")
+        .finish()
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::BackendConfigurationInvalid);
+    assert!(err.to_string().contains("absolute"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_builder_root_must_be_dir() {
+    let path = env::temp_dir().join(format!("opendal-{}", Uuid::new_v4()));
+    std_fs::write(&path, b"Hello, World!").unwrap();
+
+    let err = fs::Backend::build()
+        .root(&path.to_string_lossy())
+        .finish()
+        .await
+        .unwrap_err();
+    std_fs::remove_file(&path).unwrap();
+
+    assert_eq!(err.kind(), Kind::BackendConfigurationInvalid);
+    match err {
+        Error::Backend { context, .. } => {
+            assert_eq!(context.get("scheme").map(|v| v.as_str()), Some("fs"));
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+}
+
+#[tokio::test]
+async fn test_list_prefix_unsupported() -> anyhow::Result<()> {
+    let root = env::temp_dir().join(format!("opendal-{}", Uuid::new_v4()));
+    let op = Operator::new(
+        fs::Backend::build()
+            .root(&root.to_string_lossy())
+            .finish()
+            .await?,
+    );
+    op.object("abc").writer().write_bytes(vec![0; 4]).await?;
+
+    let err = op
+        .objects("ab")
+        .mode(ListMode::Prefix)
+        .next()
+        .await
+        .expect("must have an entry")
+        .expect_err("must fail");
+    assert_eq!(err.kind(), Kind::Unsupported);
+
+    std_fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_options_strict() -> anyhow::Result<()> {
+    let root = env::temp_dir().join(format!("opendal-{}", Uuid::new_v4()));
+    let op = Operator::new(
+        fs::Backend::build()
+            .root(&root.to_string_lossy())
+            .finish()
+            .await?,
+    );
+
+    // fs can't store content type, it's ignored by default.
+    let opts = WriteOptions::new().content_type("text/plain");
+    op.object("a").write_with(opts.clone(), vec![0; 4]).await?;
+
+    let err = op
+        .object("b")
+        .write_with(opts.strict(true), vec![0; 4])
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::Unsupported);
+    assert!(err.to_string().contains("content_type"), "{}", err);
+    assert!(!op.object("b").is_exist().await?);
+
+    // Supported options are still allowed in strict mode.
+    let opts = WriteOptions::new().commit_visible(true).strict(true);
+    op.object("c").write_with(opts, vec![0; 4]).await?;
+
+    std_fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_path_limits() {
+    let root = env::temp_dir().join(format!("opendal-{}", Uuid::new_v4()));
+    let op = Operator::new(
+        fs::Backend::build()
+            .root(&root.to_string_lossy())
+            .finish()
+            .await
+            .unwrap(),
+    );
+    assert_eq!(op.metadata().max_segment_len(), Some(255));
+    assert!(op.metadata().max_path_len().is_some());
+
+    let path = format!("dir/{}", "a".repeat(256));
+    let err = op.object(&path).metadata().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::ObjectPathInvalid);
+    assert!(
+        err.to_string()
+            .contains("path segment length 256 exceeds the limit 255"),
+        "{}",
+        err
+    );
+
+    let path = "a/".repeat(op.metadata().max_path_len().unwrap() / 2 + 1);
+    let err = op.object(&path).metadata().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::ObjectPathInvalid);
+    assert!(err.to_string().contains("path length"), "{}", err);
+
+    std_fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn test_io_block_size() {
+    let mut rng = rand::thread_rng();
+    let mut content = vec![0; 4 * 1024 * 1024 + 7];
+    rng.fill_bytes(&mut content);
+
+    // Block sizes that are smaller, unaligned and larger than the object.
+    for block_size in [4096, 1000 * 1000 + 3, 16 * 1024 * 1024] {
+        let root = env::temp_dir().join(format!("opendal-{}", Uuid::new_v4()));
+        let op = Operator::new(
+            fs::Backend::build()
+                .root(&root.to_string_lossy())
+                .io_block_size(block_size)
+                .finish()
+                .await
+                .unwrap(),
+        );
+
+        let o = op.object("large");
+        o.writer().write_bytes(content.clone()).await.unwrap();
+        o.append(content[..1024].to_vec()).await.unwrap();
+
+        let mut buf = Vec::new();
+        o.reader().read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf.len(), content.len() + 1024, "block size {}", block_size);
+        assert!(
+            buf[..content.len()] == content[..],
+            "block size {}",
+            block_size
+        );
+        assert!(
+            buf[content.len()..] == content[..1024],
+            "block size {}",
+            block_size
+        );
+
+        let mut buf = Vec::new();
+        o.range_reader(12345, 3 * 1024 * 1024)
+            .read_to_end(&mut buf)
+            .await
+            .unwrap();
+        assert!(
+            buf[..] == content[12345..12345 + 3 * 1024 * 1024],
+            "block size {}",
+            block_size
+        );
+
+        std_fs::remove_dir_all(&root).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_io_block_size_must_be_positive() {
+    let err = fs::Backend::build()
+        .root("/tmp")
+        .io_block_size(0)
+        .finish()
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::BackendConfigurationInvalid);
+}
+
+#[tokio::test]
+async fn test_create_dir_all() -> anyhow::Result<()> {
+    let root = env::temp_dir().join(format!("opendal-{}", Uuid::new_v4()));
+    let op = Operator::new(
+        fs::Backend::build()
+            .root(&root.to_string_lossy())
+            .finish()
+            .await?,
+    );
+
+    op.create_dir_all("a/b/c/").await?;
+    assert!(root.join("a/b/c").is_dir());
+    op.create_dir_all("a/b").await?;
+
+    // A file is in the way.
+    op.object("a/f").writer().write_bytes(vec![0; 4]).await?;
+    assert!(op.create_dir_all("a/f/g/").await.is_err());
+
+    std_fs::remove_dir_all(&root)?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::TryStreamExt;
+
+use crate::error::Kind;
+use crate::health::Checker;
+use crate::health::Health;
+use crate::health::ProbeKind;
+use crate::services::memory;
+use crate::testing::MockAccessor;
+use crate::Operator;
+
+/// Let the background task run, time is paused so it's advanced instantly.
+async fn wait(d: Duration) {
+    tokio::time::sleep(d).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_checker_healthy() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("exists")
+        .writer()
+        .write_bytes("ok".into())
+        .await?;
+
+    let checker = Checker::new(op.clone())
+        .probe(ProbeKind::Stat("exists".to_string()))
+        .probe(ProbeKind::List)
+        .probe(ProbeKind::WriteReadDelete("healthz/".to_string()))
+        .start();
+    assert_eq!(checker.status().health, Health::Unhealthy);
+
+    wait(Duration::from_millis(1)).await;
+    let status = checker.status();
+    assert_eq!(status.health, Health::Healthy);
+    assert_eq!(status.probes.len(), 3);
+    assert!(status.probes.iter().all(|v| v.error.is_none()));
+    assert!(status.last_error.is_none());
+
+    // The temp object of WriteReadDelete is always cleaned up.
+    let obs: Vec<_> = op.objects("healthz/").try_collect().await?;
+    assert!(obs.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_checker_degraded_and_recover() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let checker = Checker::new(op.clone())
+        .interval(Duration::from_secs(10))
+        .probe(ProbeKind::Stat("missing".to_string()))
+        .probe(ProbeKind::List)
+        .start();
+
+    wait(Duration::from_millis(1)).await;
+    let status = checker.status();
+    assert_eq!(status.health, Health::Degraded);
+    assert_eq!(
+        status.last_error.as_ref().map(|v| v.kind()),
+        Some(Kind::ObjectNotExist)
+    );
+
+    op.object("missing")
+        .writer()
+        .write_bytes("ok".into())
+        .await?;
+    wait(Duration::from_secs(10)).await;
+    let status = checker.status();
+    assert_eq!(status.health, Health::Healthy);
+    // The last error is kept after recovering.
+    assert!(status.last_error.is_some());
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_checker_hung_probe() -> Result<()> {
+    let mut builder = memory::Backend::build();
+    builder.latency(Duration::from_secs(3600));
+    let op = Operator::new(builder.finish().await?);
+
+    let checker = Checker::new(op)
+        .interval(Duration::from_secs(10))
+        .timeout(Duration::from_secs(1))
+        .probe(ProbeKind::Stat("slow".to_string()))
+        .start();
+
+    wait(Duration::from_secs(2)).await;
+    let status = checker.status();
+    assert_eq!(status.health, Health::Unhealthy);
+    let first = status.probes[0].checked_at;
+    assert_eq!(
+        status.last_error.as_ref().map(|v| v.kind()),
+        Some(Kind::Temporary)
+    );
+
+    // The checker keeps probing instead of being wedged.
+    wait(Duration::from_secs(10)).await;
+    assert!(checker.status().probes[0].checked_at > first);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_checker_degraded_latency() -> Result<()> {
+    let mut builder = memory::Backend::build();
+    builder.latency(Duration::from_millis(200));
+    let op = Operator::new(builder.finish().await?);
+    op.object("exists")
+        .writer()
+        .write_bytes("ok".into())
+        .await?;
+
+    let checker = Checker::new(op)
+        .degraded_latency(Duration::from_millis(100))
+        .probe(ProbeKind::Stat("exists".to_string()))
+        .start();
+
+    wait(Duration::from_secs(1)).await;
+    let status = checker.status();
+    assert_eq!(status.health, Health::Degraded);
+    assert!(status.probes[0].latency >= Duration::from_millis(200));
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_checker_cancel_on_drop() -> Result<()> {
+    let acc = Arc::new(MockAccessor::new());
+    let checker = Checker::new(Operator::new(acc.clone()))
+        .interval(Duration::from_secs(10))
+        .probe(ProbeKind::Stat("x".to_string()))
+        .start();
+
+    wait(Duration::from_secs(25)).await;
+    assert_eq!(acc.calls("stat"), 3);
+    assert_eq!(checker.status().health, Health::Unhealthy);
+
+    drop(checker);
+    wait(Duration::from_secs(60)).await;
+    assert_eq!(acc.calls("stat"), 3);
+
+    Ok(())
+}
@@ -11,14 +11,46 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::io;
 use std::io::SeekFrom;
+use std::pin::Pin;
 use std::str::from_utf8;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
 
+use anyhow::anyhow;
 use anyhow::Result;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::io::Cursor;
+use futures::AsyncRead;
 use futures::AsyncReadExt;
 use futures::AsyncSeekExt;
+use futures::TryStreamExt;
 
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result as OpResult;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::ReadOptions;
+use crate::ops::WriteOptions;
+use crate::readers::CallbackReader;
 use crate::services::fs;
+use crate::services::memory;
+use crate::testing::MockAccessor;
+use crate::Accessor;
+use crate::BoxedAsyncReader;
+use crate::Clock;
+use crate::Metadata;
+use crate::ObjectMode;
+use crate::ObjectReader;
 use crate::Operator;
 
 #[tokio::test]
@@ -96,6 +128,45 @@ async fn test_range_reader() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_range_reader_over_read() -> Result<()> {
+    for op in [
+        Operator::new(fs::Backend::build().finish().await?),
+        Operator::new(memory::Backend::build().finish().await?),
+    ] {
+        let path = format!("/tmp/{}", uuid::Uuid::new_v4());
+        op.object(&path)
+            .writer()
+            .write_bytes("Hello, world!".to_string().into_bytes())
+            .await?;
+
+        // Only 3 bytes left after offset 10.
+        let mut r = op.object(&path).range_reader(10, 100);
+        assert_eq!(r.available().await?, 3);
+
+        let mut buf = vec![];
+        let n = r.read_to_end(&mut buf).await?;
+        assert_eq!(n, 3);
+        assert_eq!("ld!", from_utf8(&buf)?);
+
+        // Seek to end uses the truncated size.
+        let n = r.seek(SeekFrom::End(0)).await?;
+        assert_eq!(n, 3);
+
+        // Size is resolved by the first read too.
+        let mut r = op.object(&path).range_reader(10, 100);
+        let mut buf = vec![0; 1];
+        r.read_exact(&mut buf).await?;
+        assert_eq!(r.available().await?, 3);
+        let n = r.seek(SeekFrom::End(-1)).await?;
+        assert_eq!(n, 2);
+
+        op.object(&path).delete().await?;
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_offset_reader() -> Result<()> {
     let f = Operator::new(fs::Backend::build().finish().await.unwrap());
@@ -153,3 +224,627 @@ async fn test_limited_reader() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_writer_commit_visible() -> Result<()> {
+    let f = Operator::new(fs::Backend::build().finish().await.unwrap());
+    assert!(f.metadata().can_commit_visible());
+
+    let dir = format!("/tmp/{}", uuid::Uuid::new_v4());
+    let path = format!("{}/test_file", &dir);
+
+    let x = f
+        .object(&path)
+        .writer()
+        .commit_visible(true)
+        .write_bytes("Hello, world!".to_string().into_bytes())
+        .await?;
+    assert_eq!(x, 13);
+
+    let mut buf = vec![];
+    f.object(&path).reader().read_to_end(&mut buf).await?;
+    assert_eq!("Hello, world!", from_utf8(&buf).unwrap());
+
+    // The temp file must have been renamed.
+    let obs: Vec<_> = f.objects(&dir).try_collect().await?;
+    assert_eq!(obs.len(), 1);
+
+    Ok(())
+}
+
+#[derive(Debug)]
+struct NoCommitVisible;
+
+impl Accessor for NoCommitVisible {}
+
+#[tokio::test]
+async fn test_writer_commit_visible_unsupported() {
+    let f = Operator::new(Arc::new(NoCommitVisible));
+    assert!(!f.metadata().can_commit_visible());
+
+    let err = f
+        .object("test_file")
+        .writer()
+        .commit_visible(true)
+        .write_bytes("Hello, world!".to_string().into_bytes())
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::Unsupported);
+}
+
+/// DelayedVisibility makes written objects invisible to `stat` until
+/// `delay` elapsed, like eventually consistent gateways.
+#[derive(Debug)]
+struct DelayedVisibility {
+    inner: Arc<dyn Accessor>,
+    delay: Duration,
+    written: Mutex<Option<tokio::time::Instant>>,
+    stats: AtomicUsize,
+}
+
+impl DelayedVisibility {
+    async fn new(delay: Duration) -> Result<Arc<Self>> {
+        Ok(Arc::new(Self {
+            inner: memory::Backend::build().finish().await?,
+            delay,
+            written: Mutex::new(None),
+            stats: AtomicUsize::new(0),
+        }))
+    }
+}
+
+#[async_trait]
+impl Accessor for DelayedVisibility {
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> OpResult<usize> {
+        let n = self.inner.write(r, args).await?;
+        *self.written.lock().unwrap() = Some(tokio::time::Instant::now());
+        Ok(n)
+    }
+
+    async fn stat(&self, args: &OpStat) -> OpResult<Metadata> {
+        self.stats.fetch_add(1, Ordering::Relaxed);
+        let written = self.written.lock().unwrap().expect("must be written");
+        if written.elapsed() < self.delay {
+            return Err(Error::Object {
+                kind: Kind::ObjectNotExist,
+                op: "stat",
+                path: args.path.clone(),
+                source: anyhow!("not visible yet"),
+            });
+        }
+        self.inner.stat(args).await
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_writer_await_visibility() -> Result<()> {
+    let acc = DelayedVisibility::new(Duration::from_secs(2)).await?;
+    let op = Operator::new(acc.clone());
+
+    let opts = WriteOptions::new().await_visibility(Duration::from_secs(10));
+    let started = tokio::time::Instant::now();
+    op.object("test").write_with(opts, vec![0; 13]).await?;
+    assert!(started.elapsed() >= Duration::from_secs(2));
+    assert!(acc.stats.load(Ordering::Relaxed) > 1);
+    // The object is visible once the write returned.
+    assert_eq!(op.object("test").metadata().await?.content_length(), 13);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_writer_await_visibility_timeout() -> Result<()> {
+    let acc = DelayedVisibility::new(Duration::from_secs(60)).await?;
+    let op = Operator::new(acc.clone());
+
+    let opts = WriteOptions::new().await_visibility(Duration::from_secs(5));
+    let started = tokio::time::Instant::now();
+    let err = op
+        .object("test")
+        .write_with(opts, vec![0; 13])
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::VisibilityTimeout);
+    // Never wait beyond the timeout.
+    assert_eq!(started.elapsed(), Duration::from_secs(5));
+
+    Ok(())
+}
+
+/// StepClock advances its time by every sleep and returns immediately.
+#[derive(Debug)]
+struct StepClock {
+    now: Mutex<Instant>,
+    sleeps: Mutex<Vec<Duration>>,
+}
+
+impl Clock for StepClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, dur: Duration) -> BoxFuture<'static, ()> {
+        *self.now.lock().unwrap() += dur;
+        self.sleeps.lock().unwrap().push(dur);
+        Box::pin(futures::future::ready(()))
+    }
+}
+
+#[tokio::test]
+async fn test_writer_await_visibility_with_clock() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_write(Ok(13));
+    for _ in 0..16 {
+        mock.push_stat(Err(Error::Object {
+            kind: Kind::ObjectNotExist,
+            op: "stat",
+            path: "test".to_string(),
+            source: anyhow!("not visible yet"),
+        }));
+    }
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let clock = Arc::new(StepClock {
+        now: Mutex::new(Instant::now()),
+        sleeps: Mutex::default(),
+    });
+    let opts = WriteOptions::new().await_visibility(Duration::from_secs(5));
+    let err = op
+        .object("test")
+        .writer_with(opts)
+        .with_clock(clock.clone())
+        .write_bytes(vec![0; 13])
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::VisibilityTimeout);
+    let waited: Duration = clock.sleeps.lock().unwrap().iter().sum();
+    assert_eq!(waited, Duration::from_secs(5));
+    assert_eq!(mock.calls("stat"), clock.sleeps.lock().unwrap().len() + 1);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_writer_await_length() -> Result<()> {
+    let mut stale = Metadata::default();
+    stale.set_mode(ObjectMode::FILE).set_content_length(3);
+    let mut fresh = Metadata::default();
+    fresh.set_mode(ObjectMode::FILE).set_content_length(13);
+
+    let mock = MockAccessor::new();
+    mock.push_write(Ok(13))
+        .push_stat(Ok(stale.clone()))
+        .push_stat(Ok(fresh));
+    let op = Operator::new(Arc::new(mock.clone()));
+    let opts = WriteOptions::new()
+        .await_visibility(Duration::from_secs(5))
+        .await_length(true);
+    op.object("test").write_with(opts, vec![0; 13]).await?;
+    assert_eq!(mock.calls("stat"), 2);
+
+    // Without await_length, the stale object is enough.
+    mock.push_write(Ok(13)).push_stat(Ok(stale));
+    let opts = WriteOptions::new().await_visibility(Duration::from_secs(5));
+    op.object("test").write_with(opts, vec![0; 13]).await?;
+    assert_eq!(mock.calls("stat"), 3);
+
+    // Nothing is sent if not enabled.
+    mock.push_write(Ok(13));
+    op.object("test").writer().write_bytes(vec![0; 13]).await?;
+    assert_eq!(mock.calls("stat"), 3);
+
+    Ok(())
+}
+
+/// BrokenReader fails every read, like a connection dropped by peer.
+struct BrokenReader;
+
+impl AsyncRead for BrokenReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        _: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Err(io::Error::from(io::ErrorKind::ConnectionReset)))
+    }
+}
+
+/// Build a read response that yields `content` and then fails if `broken`.
+fn mock_read(content: &'static [u8], etag: &str, broken: bool) -> ObjectReader {
+    let mut meta = Metadata::default();
+    meta.set_etag(etag);
+
+    let r = Cursor::new(content);
+    let r = if broken {
+        ObjectReader::new(Box::new(r.chain(BrokenReader)))
+    } else {
+        ObjectReader::new(Box::new(r))
+    };
+    r.with_metadata(meta)
+}
+
+/// Read 4 KiB in 16 bytes and returns how many times the backend's reader
+/// has been polled.
+async fn count_backend_reads(
+    buffered: bool,
+    f: impl FnOnce(crate::Reader) -> crate::Reader,
+) -> Result<usize> {
+    let polls = Arc::new(AtomicUsize::new(0));
+    let counter = polls.clone();
+    let r = CallbackReader::new(Box::new(Cursor::new(vec![0; 4096])), move |_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    let r = ObjectReader::new(Box::new(r));
+    let r = if buffered { r.with_buffered() } else { r };
+
+    let mock = MockAccessor::new();
+    mock.push_read(Ok(r));
+    let op = Operator::new(Arc::new(mock));
+
+    let mut r = f(op.object("test").reader());
+    let mut buf = [0; 16];
+    while r.read(&mut buf).await? > 0 {}
+
+    Ok(polls.load(Ordering::Relaxed))
+}
+
+#[tokio::test]
+async fn test_reader_buffer() -> Result<()> {
+    // The whole object is buffered by the first read.
+    assert_eq!(count_backend_reads(false, |r| r).await?, 2);
+    assert_eq!(count_backend_reads(false, |r| r.buffer(1024)).await?, 5);
+    // Unbuffered and already buffered readers are polled for every read.
+    assert_eq!(count_backend_reads(false, |r| r.buffer(0)).await?, 257);
+    assert_eq!(count_backend_reads(true, |r| r).await?, 257);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resumable_reader() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_read(Ok(mock_read(b"Hello, ", "etag", true)))
+        .push_read(Ok(mock_read(b"Wor", "etag", true)))
+        .push_read(Ok(mock_read(b"ld!", "etag", false)));
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let mut r = op.object("test").reader().resumable(1);
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"Hello, World!");
+    assert_eq!(mock.calls("read"), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resumable_reader_exhausted() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_read(Ok(mock_read(b"Hello, ", "etag", true)))
+        .push_read(Ok(mock_read(b"", "etag", true)));
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let mut r = op.object("test").reader().resumable(1);
+    let mut buf = Vec::new();
+    let err = r.read_to_end(&mut buf).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+    assert_eq!(mock.calls("read"), 2);
+
+    // Readers are not resumable by default.
+    let mock = MockAccessor::new();
+    mock.push_read(Ok(mock_read(b"Hello, ", "etag", true)));
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let mut buf = Vec::new();
+    assert!(op
+        .object("test")
+        .reader()
+        .read_to_end(&mut buf)
+        .await
+        .is_err());
+    assert_eq!(mock.calls("read"), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resumable_reader_object_changed() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_read(Ok(mock_read(b"Hello, ", "etag", true)))
+        .push_read(Ok(mock_read(b"World!", "changed", false)));
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let mut r = op.object("test").reader().resumable(3);
+    let mut buf = Vec::new();
+    let err = r.read_to_end(&mut buf).await.unwrap_err();
+    let err = err.into_inner().unwrap();
+    let err = err.downcast_ref::<crate::error::Error>().unwrap();
+    assert_eq!(err.kind(), Kind::PreconditionFailed);
+
+    Ok(())
+}
+
+fn range_error_kind(err: io::Error) -> Kind {
+    let err = err.into_inner().unwrap();
+    err.downcast_ref::<crate::error::Error>().unwrap().kind()
+}
+
+#[tokio::test]
+async fn test_zero_length_range() -> Result<()> {
+    let mock = MockAccessor::new();
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let mut buf = Vec::new();
+    let n = op
+        .object("test")
+        .range_reader(10, 0)
+        .read_to_end(&mut buf)
+        .await?;
+    assert_eq!(n, 0);
+    assert_eq!(mock.calls("read"), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_range_beyond_cached_length() -> Result<()> {
+    let mock = MockAccessor::new();
+    let mut meta = Metadata::default();
+    meta.set_path("test")
+        .set_mode(ObjectMode::FILE)
+        .set_content_length(13)
+        .set_version_id("1");
+    mock.push_stat(Ok(meta))
+        .push_read(Ok(ObjectReader::new(Box::new(Cursor::new(
+            b"Hello".to_vec(),
+        )))));
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let mut o = op.object("test");
+    o.metadata_cached().await?;
+
+    // The cached length could be stale, the object may have grown since.
+    let mut buf = Vec::new();
+    o.offset_reader(13).read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"Hello");
+    assert_eq!(mock.calls("read"), 1);
+
+    // Pinned versions never change, ranges beyond them are not sent.
+    o.pin_version();
+    let err = o
+        .range_reader(100, 10)
+        .strict_range()
+        .read_to_end(&mut buf)
+        .await
+        .unwrap_err();
+    assert_eq!(range_error_kind(err), Kind::RangeNotSatisfiable);
+    assert_eq!(mock.calls("read"), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_range_not_satisfiable_from_backend() -> Result<()> {
+    let unsatisfiable = || crate::error::Error::Object {
+        kind: Kind::RangeNotSatisfiable,
+        op: "read",
+        path: "test".to_string(),
+        source: anyhow::anyhow!("416"),
+    };
+
+    let mock = MockAccessor::new();
+    mock.push_read(Err(unsatisfiable()))
+        .push_read(Err(unsatisfiable()));
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let mut buf = Vec::new();
+    let n = op
+        .object("test")
+        .offset_reader(100)
+        .read_to_end(&mut buf)
+        .await?;
+    assert_eq!(n, 0);
+
+    let err = op
+        .object("test")
+        .offset_reader(100)
+        .strict_range()
+        .read_to_end(&mut buf)
+        .await
+        .unwrap_err();
+    assert_eq!(range_error_kind(err), Kind::RangeNotSatisfiable);
+    assert_eq!(mock.calls("read"), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_strict_range_fs() -> Result<()> {
+    let f = Operator::new(fs::Backend::build().finish().await?);
+
+    let path = format!("/tmp/{}", uuid::Uuid::new_v4());
+    f.object(&path)
+        .writer()
+        .write_bytes(b"Hello, World!".to_vec())
+        .await?;
+
+    // fs reads nothing beyond the end, strict readers fail like others.
+    let mut buf = Vec::new();
+    let n = f
+        .object(&path)
+        .offset_reader(100)
+        .read_to_end(&mut buf)
+        .await?;
+    assert_eq!(n, 0);
+    let err = f
+        .object(&path)
+        .offset_reader(100)
+        .strict_range()
+        .read_to_end(&mut buf)
+        .await
+        .unwrap_err();
+    assert_eq!(range_error_kind(err), Kind::RangeNotSatisfiable);
+
+    // Seeking to the end of a strict reader still reads nothing.
+    let mut r = f.object(&path).reader().strict_range();
+    r.seek(SeekFrom::End(0)).await?;
+    assert_eq!(r.read_to_end(&mut buf).await?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reader_content_length() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("test")
+        .writer()
+        .write_bytes(b"Hello, world!".to_vec())
+        .await?;
+
+    let mut r = op.object("test").reader();
+    assert_eq!(r.content_length(), None);
+    let mut buf = vec![0; 1];
+    r.read_exact(&mut buf).await?;
+    assert_eq!(r.content_length(), Some(13));
+
+    // Ranges report their own length, truncated by EOF.
+    let mut r = op.object("test").range_reader(2, 5);
+    r.read_exact(&mut buf).await?;
+    assert_eq!(r.content_length(), Some(5));
+    let mut r = op.object("test").range_reader(10, 100);
+    r.read_exact(&mut buf).await?;
+    assert_eq!(r.content_length(), Some(3));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reader_not_exist() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    // The error is surfaced from every poll instead of the first one only.
+    let mut r = op.object("not_exist").reader();
+    let mut buf = Vec::new();
+    for _ in 0..2 {
+        let err = r.read_to_end(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(range_error_kind(err), Kind::ObjectNotExist);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reader_decode_content() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("test")
+        .writer()
+        .write_bytes(b"Hello, World!".to_vec())
+        .await?;
+
+    // Objects without encoding are read as is.
+    let mut r = op
+        .object("test")
+        .reader_with(ReadOptions::new().decode_content(true));
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"Hello, World!");
+
+    // Ranges refer to the encoded content.
+    let mut r = op
+        .object("test")
+        .reader_with(ReadOptions::new().offset(1).decode_content(true));
+    let err = r.read_to_end(&mut buf).await.unwrap_err();
+    assert_eq!(range_error_kind(err), Kind::Unsupported);
+
+    Ok(())
+}
+
+async fn encode(encoding: &str, content: &[u8]) -> Result<Vec<u8>> {
+    use async_compression::futures::bufread;
+
+    let r = Cursor::new(content.to_vec());
+    let mut encoded = Vec::new();
+    match encoding {
+        "gzip" => {
+            bufread::GzipEncoder::new(r)
+                .read_to_end(&mut encoded)
+                .await?
+        }
+        "deflate" => {
+            bufread::ZlibEncoder::new(r)
+                .read_to_end(&mut encoded)
+                .await?
+        }
+        "br" => {
+            bufread::BrotliEncoder::new(r)
+                .read_to_end(&mut encoded)
+                .await?
+        }
+        "zstd" => {
+            bufread::ZstdEncoder::new(r)
+                .read_to_end(&mut encoded)
+                .await?
+        }
+        _ => unreachable!("unknown encoding {}", encoding),
+    };
+    Ok(encoded)
+}
+
+fn encoded_reader(encoding: &str, encoded: &[u8]) -> ObjectReader {
+    let mut meta = Metadata::default();
+    meta.set_content_encoding(encoding)
+        .set_content_length(encoded.len() as u64);
+    ObjectReader::new(Box::new(Cursor::new(encoded.to_vec()))).with_metadata(meta)
+}
+
+#[tokio::test]
+async fn test_reader_decode_content_round_trip() -> Result<()> {
+    let content = "Hello, World!".repeat(1000).into_bytes();
+
+    for encoding in ["gzip", "deflate", "br", "zstd"] {
+        let encoded = encode(encoding, &content).await?;
+        assert_ne!(encoded, content);
+
+        let mock = MockAccessor::new();
+        mock.push_read(Ok(encoded_reader(encoding, &encoded)))
+            .push_read(Ok(encoded_reader(encoding, &encoded)))
+            .push_read(Ok(encoded_reader(encoding, &encoded)));
+        let op = Operator::new(Arc::new(mock));
+
+        let mut buf = Vec::new();
+        op.object("test")
+            .reader_with(ReadOptions::new().decode_content(true))
+            .read_to_end(&mut buf)
+            .await?;
+        assert_eq!(buf, content, "{}", encoding);
+
+        // The encoded bytes are returned by default.
+        let mut buf = Vec::new();
+        op.object("test").reader().read_to_end(&mut buf).await?;
+        assert_eq!(buf, encoded, "{}", encoding);
+
+        // Positions of decoded content can't be mapped to the object.
+        let mut r = op
+            .object("test")
+            .reader_with(ReadOptions::new().decode_content(true));
+        let mut buf = [0; 16];
+        r.read_exact(&mut buf).await?;
+        let err = r.seek(SeekFrom::Start(1)).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported, "{}", encoding);
+    }
+
+    // Unknown encodings are never returned as is.
+    let mock = MockAccessor::new();
+    mock.push_read(Ok(encoded_reader("compress", b"abcd")));
+    let op = Operator::new(Arc::new(mock));
+    let err = op
+        .object("test")
+        .reader_with(ReadOptions::new().decode_content(true))
+        .read_to_end(&mut Vec::new())
+        .await
+        .unwrap_err();
+    assert_eq!(range_error_kind(err), Kind::Unsupported);
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::AsyncReadExt;
+use futures::TryStreamExt;
+
+use crate::error::Kind;
+use crate::ops::WriteOptions;
+use crate::services::kv::Adapter;
+use crate::services::kv::Value;
+use crate::Metadata;
+use crate::Operator;
+use crate::ReadConsistency;
+
+/// Adapter relies on all the default methods.
+#[derive(Debug, Clone, Default)]
+struct MapAdapter(Arc<Mutex<BTreeMap<String, Value>>>);
+
+#[async_trait]
+impl Adapter for MapAdapter {
+    async fn get(&self, key: &str) -> crate::error::Result<Option<Value>> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, data: Bytes, if_not_exists: bool) -> crate::error::Result<bool> {
+        let mut map = self.0.lock().unwrap();
+        if if_not_exists && map.contains_key(key) {
+            return Ok(false);
+        }
+        map.insert(key.to_string(), Value::new(data));
+        Ok(true)
+    }
+
+    async fn delete(
+        &self,
+        key: &str,
+        cond: &(dyn for<'v> Fn(&'v Value) -> bool + Send + Sync),
+    ) -> crate::error::Result<bool> {
+        let mut map = self.0.lock().unwrap();
+        if let Some(v) = map.get(key) {
+            if !cond(v) {
+                return Ok(false);
+            }
+        }
+        map.remove(key);
+        Ok(true)
+    }
+
+    async fn scan(
+        &self,
+        start: Bound<String>,
+        limit: usize,
+    ) -> crate::error::Result<Vec<Metadata>> {
+        let map = self.0.lock().unwrap();
+        Ok(map
+            .range((start, Bound::Unbounded))
+            .take(limit)
+            .map(|(k, v)| v.metadata(k))
+            .collect())
+    }
+}
+
+#[tokio::test]
+async fn test_read_write() -> Result<()> {
+    let op = Operator::new(Arc::new(MapAdapter::default()));
+    let meta = op.metadata();
+    assert!(meta.can_commit_visible());
+    // Adapters have to claim the consistency of their readers.
+    assert_eq!(meta.read_consistency(), ReadConsistency::Torn);
+    let o = op.object("a//b");
+
+    o.writer().write_bytes(b"Hello".to_vec()).await?;
+    o.append(b", World!".to_vec()).await?;
+    let mut buf = Vec::new();
+    op.object("a/b").reader().read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"Hello, World!");
+    let mut buf = Vec::new();
+    o.offset_reader(7).read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"World!");
+
+    let err = o
+        .write_with(WriteOptions::default().if_not_exists(true), b"x".to_vec())
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::PreconditionFailed);
+
+    let meta = o.metadata().await?;
+    assert_eq!(meta.content_length(), 13);
+    assert!(meta.version_id().is_none());
+
+    let err = o.delete_if_match("\"mismatch\"").await.unwrap_err();
+    assert_eq!(err.kind(), Kind::PreconditionFailed);
+    o.delete().await?;
+    assert!(!o.is_exist().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_across_batches() -> Result<()> {
+    let op = Operator::new(Arc::new(MapAdapter::default()));
+    for i in 0..600 {
+        op.object(&format!("dir/{:04}", i))
+            .writer()
+            .write_bytes(b"x".to_vec())
+            .await?;
+    }
+    op.object("dirx")
+        .writer()
+        .write_bytes(b"x".to_vec())
+        .await?;
+
+    let mut paths = Vec::new();
+    let mut obs = op.objects("dir");
+    while let Some(mut o) = obs.try_next().await? {
+        paths.push(o.metadata_cached_for(&[]).await?.path().to_string());
+    }
+    assert_eq!(paths.len(), 600);
+    assert_eq!(paths[0], "dir/0000");
+    assert_eq!(paths[599], "dir/0599");
+
+    Ok(())
+}
@@ -0,0 +1,1541 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::io::Cursor;
+use futures::AsyncReadExt;
+use futures::TryStreamExt;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result as OpResult;
+use crate::layers::Backoff;
+use crate::layers::DiskCacheLayer;
+use crate::layers::FallbackLayer;
+use crate::layers::InMemoryCacheLayer;
+use crate::layers::PathMapLayer;
+use crate::layers::QuotaLayer;
+use crate::layers::RecordLayer;
+use crate::layers::RetryLayer;
+use crate::layers::WriteDefaultsLayer;
+use crate::layers::WriteOnceLayer;
+use crate::layers::WritePolicy;
+use crate::object::BoxedObjectStream;
+use crate::ops::MultipartUpload;
+use crate::ops::OpAbortMultipartUpload;
+use crate::ops::OpCopy;
+use crate::ops::OpCreate;
+use crate::ops::OpList;
+use crate::ops::OpListMultipartUploads;
+use crate::ops::OpPresign;
+use crate::ops::OpRead;
+use crate::ops::OpRetention;
+use crate::ops::OpSelect;
+use crate::ops::OpStat;
+use crate::ops::OpWrite;
+use crate::ops::Operation;
+use crate::ops::PresignOperation;
+use crate::ops::PresignedRequest;
+use crate::ops::ReadOptions;
+use crate::ops::Retention;
+use crate::ops::RetentionMode;
+use crate::ops::SelectInput;
+use crate::ops::SelectOutput;
+use crate::ops::WriteOptions;
+use crate::services::fs;
+use crate::services::memory;
+use crate::testing::MockAccessor;
+use crate::testing::ReplayAccessor;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::BoxedAsyncReader;
+use crate::Clock;
+use crate::Layer;
+use crate::Metadata;
+use crate::ObjectMode;
+use crate::ObjectReader;
+use crate::Operator;
+use crate::Provenance;
+use crate::TokioClock;
+
+fn temporary_error(op: &'static str) -> Error {
+    Error::Object {
+        kind: Kind::Temporary,
+        op,
+        path: "test_file".to_string(),
+        source: anyhow!("injected"),
+    }
+}
+
+async fn read_all(op: &Operator, path: &str) -> Result<String> {
+    let mut s = String::new();
+    op.object(path).reader().read_to_string(&mut s).await?;
+    Ok(s)
+}
+
+#[tokio::test]
+async fn test_in_memory_cache_hit() -> Result<()> {
+    let cache = InMemoryCacheLayer::new();
+    let op = Operator::new(memory::Backend::build().finish().await?).layer(cache.clone());
+
+    op.object("test_file")
+        .writer()
+        .write_bytes(b"Hello, world!".to_vec())
+        .await?;
+
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, world!");
+    assert_eq!((cache.hits(), cache.misses()), (0, 1));
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, world!");
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+    // Ranged reads bypass the cache.
+    let mut s = String::new();
+    op.object("test_file")
+        .range_reader(0, 5)
+        .read_to_string(&mut s)
+        .await?;
+    assert_eq!(s, "Hello");
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+    Ok(())
+}
+
+/// Read the whole object and return who served it.
+async fn served_by(op: &Operator, path: &str) -> Result<Option<String>> {
+    let mut r = op.object(path).reader();
+    r.read_to_end(&mut Vec::new()).await?;
+    Ok(r.provenance()
+        .get(Provenance::SERVED_BY)
+        .map(|v| v.to_string()))
+}
+
+#[tokio::test]
+async fn test_in_memory_cache_provenance() -> Result<()> {
+    let op =
+        Operator::new(memory::Backend::build().finish().await?).layer(InMemoryCacheLayer::new());
+    write(&op, "test_file", "Hello").await?;
+
+    assert_eq!(
+        served_by(&op, "test_file").await?.as_deref(),
+        Some("origin")
+    );
+    assert_eq!(served_by(&op, "test_file").await?.as_deref(), Some("cache"));
+
+    // Overwriting invalidates the entry.
+    write(&op, "test_file", "World").await?;
+    assert_eq!(
+        served_by(&op, "test_file").await?.as_deref(),
+        Some("origin")
+    );
+    assert_eq!(served_by(&op, "test_file").await?.as_deref(), Some("cache"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_in_memory_cache_invalidate_on_overwrite() -> Result<()> {
+    let cache = InMemoryCacheLayer::new();
+    let op = Operator::new(memory::Backend::build().finish().await?).layer(cache.clone());
+
+    let o = op.object("test_file");
+    o.writer().write_bytes(b"Hello, world!".to_vec()).await?;
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, world!");
+
+    o.writer().write_bytes(b"Hello, cache!".to_vec()).await?;
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, cache!");
+    assert_eq!((cache.hits(), cache.misses()), (0, 2));
+
+    o.delete().await?;
+    assert!(!o.is_exist().await?);
+    assert!(op
+        .object("test_file")
+        .reader()
+        .read_to_end(&mut vec![])
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_in_memory_cache_revalidate_after_ttl() -> Result<()> {
+    let cache = InMemoryCacheLayer::new().ttl(Duration::ZERO);
+    let acc = memory::Backend::build().finish().await?;
+    let op = Operator::new(acc.clone()).layer(cache.clone());
+
+    op.object("test_file")
+        .writer()
+        .write_bytes(b"Hello, world!".to_vec())
+        .await?;
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, world!");
+
+    // Etag not changed, entry is still valid.
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, world!");
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+    // Overwrite bypassing the cache.
+    Operator::new(acc)
+        .object("test_file")
+        .writer()
+        .write_bytes(b"Hello, cache!".to_vec())
+        .await?;
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, cache!");
+    assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_in_memory_cache_max_entry_size() -> Result<()> {
+    let cache = InMemoryCacheLayer::new().max_entry_size(4);
+    let op = Operator::new(memory::Backend::build().finish().await?).layer(cache.clone());
+
+    op.object("test_file")
+        .writer()
+        .write_bytes(b"Hello, world!".to_vec())
+        .await?;
+
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, world!");
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, world!");
+    assert_eq!((cache.hits(), cache.misses()), (0, 2));
+
+    Ok(())
+}
+
+/// RecordingClock records all sleeps and returns immediately.
+#[derive(Debug, Default)]
+struct RecordingClock {
+    sleeps: Mutex<Vec<Duration>>,
+}
+
+impl Clock for RecordingClock {
+    fn now(&self) -> Instant {
+        TokioClock.now()
+    }
+
+    fn sleep(&self, dur: Duration) -> BoxFuture<'static, ()> {
+        self.sleeps.lock().unwrap().push(dur);
+        Box::pin(futures::future::ready(()))
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_in_memory_cache_ttl_paused() -> Result<()> {
+    let mut meta = Metadata::default();
+    meta.set_etag("etag").set_content_length(5);
+    let mock = MockAccessor::new();
+    mock.push_read(Ok(ObjectReader::new(Box::new(Cursor::new(
+        b"Hello".to_vec(),
+    )))
+    .with_metadata(meta.clone())))
+        .push_stat(Ok(meta));
+
+    let cache = InMemoryCacheLayer::new().ttl(Duration::from_secs(30));
+    let op = Operator::new(Arc::new(mock.clone())).layer(cache.clone());
+
+    assert_eq!(read_all(&op, "test_file").await?, "Hello");
+    tokio::time::advance(Duration::from_secs(29)).await;
+    assert_eq!(read_all(&op, "test_file").await?, "Hello");
+    assert_eq!(mock.calls("stat"), 0);
+
+    // Expired, the entry must be re-validated.
+    tokio::time::advance(Duration::from_secs(1)).await;
+    assert_eq!(read_all(&op, "test_file").await?, "Hello");
+    assert_eq!(mock.calls("stat"), 1);
+    assert_eq!((cache.hits(), cache.misses()), (2, 1));
+
+    Ok(())
+}
+
+fn cached_files(dir: &std::path::Path) -> usize {
+    std::fs::read_dir(dir)
+        .map(|v| v.count())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_disk_cache_hit() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("opendal-{}", uuid::Uuid::new_v4()));
+    let cache = DiskCacheLayer::new(&dir, 1024);
+    let op = Operator::new(memory::Backend::build().finish().await?).layer(cache.clone());
+
+    let o = op.object("test_file");
+    o.writer().write_bytes(b"Hello, world!".to_vec()).await?;
+
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, world!");
+    assert_eq!((cache.hits(), cache.misses()), (0, 1));
+    assert_eq!(cached_files(&dir), 1);
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, world!");
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+    // Writes and deletes invalidate the cached file.
+    o.writer().write_bytes(b"Hello, cache!".to_vec()).await?;
+    assert_eq!(cached_files(&dir), 0);
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, cache!");
+    assert_eq!((cache.hits(), cache.misses()), (1, 2));
+    o.delete().await?;
+    assert_eq!(cached_files(&dir), 0);
+    assert!(read_all(&op, "test_file").await.is_err());
+
+    // Cached files are removed along with the operator.
+    o.writer().write_bytes(b"Hello, drop!".to_vec()).await?;
+    read_all(&op, "test_file").await?;
+    assert_eq!(cached_files(&dir), 1);
+    drop((o, op));
+    assert_eq!(cached_files(&dir), 0);
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_disk_cache_lru_eviction() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("opendal-{}", uuid::Uuid::new_v4()));
+    let cache = DiskCacheLayer::new(&dir, 12);
+    let op = Operator::new(memory::Backend::build().finish().await?).layer(cache.clone());
+
+    for name in ["a", "b", "c"] {
+        op.object(name)
+            .writer()
+            .write_bytes(name.repeat(5).into_bytes())
+            .await?;
+    }
+
+    read_all(&op, "a").await?;
+    read_all(&op, "b").await?;
+    // `a` is used more recently than `b` now.
+    read_all(&op, "a").await?;
+    assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+    // `b` is evicted to make room for `c`.
+    assert_eq!(read_all(&op, "c").await?, "ccccc");
+    assert_eq!(cached_files(&dir), 2);
+    assert_eq!(read_all(&op, "a").await?, "aaaaa");
+    assert_eq!((cache.hits(), cache.misses()), (2, 3));
+    assert_eq!(read_all(&op, "b").await?, "bbbbb");
+    assert_eq!((cache.hits(), cache.misses()), (2, 4));
+
+    // Objects larger than max bytes are not cached.
+    op.object("large")
+        .writer()
+        .write_bytes(vec![b'x'; 13])
+        .await?;
+    read_all(&op, "large").await?;
+    read_all(&op, "large").await?;
+    assert_eq!((cache.hits(), cache.misses()), (2, 6));
+    assert_eq!(cached_files(&dir), 2);
+
+    drop(op);
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_disk_cache_etag_invalidation() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("opendal-{}", uuid::Uuid::new_v4()));
+    let cache = DiskCacheLayer::new(&dir, 1024);
+    let acc = memory::Backend::build().finish().await?;
+    let op = Operator::new(acc.clone()).layer(cache.clone());
+
+    op.object("test_file")
+        .writer()
+        .write_bytes(b"Hello, world!".to_vec())
+        .await?;
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, world!");
+
+    // Overwrite bypassing the cache, the etag changed.
+    Operator::new(acc.clone())
+        .object("test_file")
+        .writer()
+        .write_bytes(b"Hello, cache!".to_vec())
+        .await?;
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, cache!");
+    assert_eq!((cache.hits(), cache.misses()), (0, 2));
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, cache!");
+    assert_eq!((cache.hits(), cache.misses()), (1, 2));
+    assert_eq!(cached_files(&dir), 1);
+
+    // Delete bypassing the cache.
+    Operator::new(acc).object("test_file").delete().await?;
+    assert!(read_all(&op, "test_file").await.is_err());
+    assert_eq!(cached_files(&dir), 0);
+
+    drop(op);
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+/// Versioned keeps every version of objects in memory. Versions are
+/// numbered from `1` and also used as etags.
+#[derive(Debug, Clone, Default)]
+struct Versioned {
+    objects: Arc<Mutex<HashMap<String, Vec<Vec<u8>>>>>,
+}
+
+impl Versioned {
+    /// Returns the content and metadata of `version`, the latest if `None`.
+    fn get(&self, path: &str, version: Option<&str>) -> OpResult<(Vec<u8>, Metadata)> {
+        let objects = self.objects.lock().unwrap();
+        let versions = objects.get(path).map(Vec::as_slice).unwrap_or_default();
+        let n = match version {
+            Some(v) => v.parse().unwrap_or_default(),
+            None => versions.len(),
+        };
+        let data = n
+            .checked_sub(1)
+            .and_then(|i| versions.get(i))
+            .ok_or_else(|| Error::Object {
+                kind: Kind::ObjectNotExist,
+                op: "read",
+                path: path.to_string(),
+                source: anyhow!("version {:?} not found", version),
+            })?;
+
+        let mut meta = Metadata::default();
+        meta.set_path(path)
+            .set_mode(ObjectMode::FILE)
+            .set_content_length(data.len() as u64)
+            .set_etag(&n.to_string())
+            .set_version_id(&n.to_string());
+        Ok((data.clone(), meta))
+    }
+}
+
+#[async_trait::async_trait]
+impl Accessor for Versioned {
+    fn metadata(&self) -> AccessorMetadata {
+        *AccessorMetadata::default().set_read_version(true)
+    }
+    async fn read(&self, args: &OpRead) -> OpResult<ObjectReader> {
+        let (data, meta) = self.get(&args.path, args.version_id.as_deref())?;
+        let start = (args.offset.unwrap_or_default() as usize).min(data.len());
+        let end = args
+            .size
+            .map_or(data.len(), |size| (start + size as usize).min(data.len()));
+        let r = Box::new(Cursor::new(data[start..end].to_vec()));
+        Ok(ObjectReader::new(r).with_metadata(meta))
+    }
+    async fn write(&self, mut r: BoxedAsyncReader, args: &OpWrite) -> OpResult<usize> {
+        let mut data = Vec::new();
+        r.read_to_end(&mut data).await.map_err(|e| Error::Object {
+            kind: Kind::Unexpected,
+            op: "write",
+            path: args.path.clone(),
+            source: anyhow!(e),
+        })?;
+        let n = data.len();
+        self.objects
+            .lock()
+            .unwrap()
+            .entry(args.path.clone())
+            .or_default()
+            .push(data);
+        Ok(n)
+    }
+    async fn stat(&self, args: &OpStat) -> OpResult<Metadata> {
+        Ok(self.get(&args.path, None)?.1)
+    }
+}
+
+async fn read_version(op: &Operator, path: &str, version: &str) -> Result<String> {
+    let mut s = String::new();
+    op.object(path)
+        .reader_with(ReadOptions::new().version_id(version))
+        .read_to_string(&mut s)
+        .await?;
+    Ok(s)
+}
+
+#[tokio::test]
+async fn test_in_memory_cache_versioned_read() -> Result<()> {
+    let cache = InMemoryCacheLayer::new();
+    let op = Operator::new(Arc::new(Versioned::default())).layer(cache.clone());
+    write(&op, "test_file", "Hello, v1!").await?;
+    write(&op, "test_file", "Hello, v2!").await?;
+
+    // Versioned reads before anything is cached.
+    assert_eq!(read_version(&op, "test_file", "1").await?, "Hello, v1!");
+    assert_eq!((cache.hits(), cache.misses()), (0, 0));
+
+    // The latest version is cached, but never served to versioned reads.
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, v2!");
+    assert_eq!(read_version(&op, "test_file", "1").await?, "Hello, v1!");
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, v2!");
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    assert_eq!(read_version(&op, "test_file", "2").await?, "Hello, v2!");
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_disk_cache_versioned_read() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("opendal-{}", uuid::Uuid::new_v4()));
+    let cache = DiskCacheLayer::new(&dir, 1024);
+    let op = Operator::new(Arc::new(Versioned::default())).layer(cache);
+    write(&op, "test_file", "Hello, v1!").await?;
+    write(&op, "test_file", "Hello, v2!").await?;
+
+    // Versioned reads before anything is cached.
+    assert_eq!(read_version(&op, "test_file", "1").await?, "Hello, v1!");
+    assert_eq!(cached_files(&dir), 0);
+
+    // The latest version is cached, but never served to versioned reads.
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, v2!");
+    assert_eq!(cached_files(&dir), 1);
+    assert_eq!(read_version(&op, "test_file", "1").await?, "Hello, v1!");
+    assert_eq!(read_all(&op, "test_file").await?, "Hello, v2!");
+    assert_eq!(read_version(&op, "test_file", "2").await?, "Hello, v2!");
+
+    drop(op);
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retry_delay_paused() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_stat(Err(temporary_error("stat")))
+        .push_stat(Err(temporary_error("stat")))
+        .push_stat(Ok(Metadata::default()));
+
+    let retry = RetryLayer::new().delay(Duration::from_secs(10));
+    let op = Operator::new(Arc::new(mock.clone())).layer(retry);
+
+    let start = tokio::time::Instant::now();
+    op.object("test_file").metadata().await?;
+    assert_eq!(start.elapsed(), Duration::from_secs(20));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_with_clock() -> Result<()> {
+    let mock = MockAccessor::new();
+    for _ in 0..4 {
+        mock.push_stat(Err(temporary_error("stat")));
+    }
+
+    let clock = Arc::new(RecordingClock::default());
+    let retry = RetryLayer::new()
+        .delay(Duration::from_secs(3600))
+        .with_clock(clock.clone());
+    let op = Operator::new(Arc::new(mock.clone())).layer(retry);
+
+    let err = op.object("test_file").metadata().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::Temporary);
+    assert_eq!(mock.calls("stat"), 4);
+    assert_eq!(
+        *clock.sleeps.lock().unwrap(),
+        vec![Duration::from_secs(3600); 3]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_backoff_without_jitter() {
+    let backoff = Backoff::new()
+        .base(Duration::from_millis(100))
+        .max(Duration::from_millis(1000))
+        .multiplier(3.0)
+        .jitter(false);
+    let delays: Vec<_> = backoff.iter().take(5).map(|v| v.as_millis()).collect();
+    assert_eq!(delays, vec![100, 300, 900, 1000, 1000]);
+
+    let delays: Vec<_> = Backoff::constant(Duration::from_millis(50))
+        .iter()
+        .take(3)
+        .map(|v| v.as_millis())
+        .collect();
+    assert_eq!(delays, vec![50, 50, 50]);
+
+    // Huge multiplier is capped by max instead of overflowing.
+    let mut it = Backoff::new()
+        .max(Duration::from_secs(60))
+        .multiplier(f64::MAX)
+        .jitter(false)
+        .iter();
+    it.next_delay();
+    assert_eq!(it.next_delay(), Duration::from_secs(60));
+    assert_eq!(it.next_delay(), Duration::from_secs(60));
+}
+
+#[test]
+fn test_backoff_seeded_jitter() {
+    let backoff = Backoff::new()
+        .base(Duration::from_millis(100))
+        .max(Duration::from_millis(1000))
+        .seed(42);
+    let delays: Vec<_> = backoff.iter().take(6).collect();
+    assert_eq!(delays, backoff.iter().take(6).collect::<Vec<_>>());
+
+    let expected = [100, 200, 400, 800, 1000, 1000].map(Duration::from_millis);
+    for (delay, expected) in delays.iter().zip(expected) {
+        assert!(*delay >= expected / 2 && *delay <= expected, "{:?}", delay);
+    }
+}
+
+#[tokio::test]
+async fn test_retry_backoff() -> Result<()> {
+    let mock = MockAccessor::new();
+    for _ in 0..4 {
+        mock.push_stat(Err(temporary_error("stat")));
+    }
+
+    let clock = Arc::new(RecordingClock::default());
+    let retry = RetryLayer::new()
+        .backoff(Backoff::new().base(Duration::from_secs(1)).jitter(false))
+        .with_clock(clock.clone());
+    let op = Operator::new(Arc::new(mock.clone())).layer(retry);
+
+    let err = op.object("test_file").metadata().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::Temporary);
+    assert_eq!(
+        *clock.sleeps.lock().unwrap(),
+        vec![
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            Duration::from_secs(4)
+        ]
+    );
+
+    // Every operation starts from the base delay.
+    mock.push_stat(Err(temporary_error("stat")))
+        .push_stat(Ok(Metadata::default()));
+    clock.sleeps.lock().unwrap().clear();
+    op.object("test_file").is_exist().await?;
+    assert_eq!(mock.calls("stat"), 6);
+    assert_eq!(*clock.sleeps.lock().unwrap(), vec![Duration::from_secs(1)]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_idempotent_only() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_read(Err(temporary_error("read")))
+        .push_read(Ok(ObjectReader::new(Box::new(Cursor::new(
+            b"Hello".to_vec(),
+        )))))
+        .push_append(Err(temporary_error("append")))
+        .push_append(Ok(5));
+
+    let retry = RetryLayer::new().delay(Duration::ZERO);
+    let op = Operator::new(Arc::new(mock.clone())).layer(retry);
+
+    assert_eq!(read_all(&op, "test_file").await?, "Hello");
+    assert_eq!(mock.calls("read"), 2);
+
+    // Append is not idempotent, so it must not be retried.
+    let err = op
+        .object("test_file")
+        .append(b"Hello".to_vec())
+        .await
+        .expect_err("append must fail");
+    assert_eq!(err.kind(), Kind::Temporary);
+    assert_eq!(mock.calls("append"), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_write_not_replayable() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_write(Err(temporary_error("write")))
+        .push_write(Ok(5));
+    let retry = RetryLayer::new().delay(Duration::ZERO);
+    let acc = retry.layer(Arc::new(mock.clone()));
+
+    // The failed attempt may have created the object, replaying it would
+    // fail the precondition.
+    let mut args = OpWrite::new("test_file", 5);
+    args.options = WriteOptions::new().if_not_exists(true);
+    let r = Box::new(Cursor::new(b"Hello".to_vec()));
+    let err = acc.write(r, &args).await.unwrap_err();
+    assert_eq!(err.kind(), Kind::Temporary);
+    assert_eq!(mock.calls("write"), 1);
+
+    // A short source fails before anything is written.
+    let r = Box::new(Cursor::new(b"Hel".to_vec()));
+    let err = acc
+        .write(r, &OpWrite::new("test_file", 5))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::ContentLengthMismatch);
+    assert_eq!(mock.calls("write"), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_per_operation() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_write(Err(temporary_error("write")))
+        .push_write(Ok(5))
+        .push_delete(Err(temporary_error("delete")))
+        .push_delete(Ok(()))
+        .push_stat(Err(Error::Object {
+            kind: Kind::ObjectNotExist,
+            op: "stat",
+            path: "test_file".to_string(),
+            source: anyhow!("not found"),
+        }));
+
+    let retry = RetryLayer::new()
+        .delay(Duration::ZERO)
+        .retry(Operation::Delete, false)
+        // Enabling a non-idempotent operation takes no effect.
+        .retry(Operation::Append, true);
+    let op = Operator::new(Arc::new(mock.clone())).layer(retry);
+
+    // Writes are full object uploads, the data is replayed on retry.
+    op.object("test_file")
+        .writer()
+        .write_bytes(b"Hello".to_vec())
+        .await?;
+    assert_eq!(mock.calls("write"), 2);
+    assert_eq!(mock.writes()[0].1, b"Hello");
+
+    assert!(op.object("test_file").delete().await.is_err());
+    assert_eq!(mock.calls("delete"), 1);
+
+    // Only temporary or unexpected errors will be retried.
+    assert!(op.object("test_file").metadata().await.is_err());
+    assert_eq!(mock.calls("stat"), 1);
+
+    mock.push_append(Err(temporary_error("append")));
+    assert!(op
+        .object("test_file")
+        .append(b"Hello".to_vec())
+        .await
+        .is_err());
+    assert_eq!(mock.calls("append"), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_write_once_patterns() {
+    let layer = WriteOnceLayer::new(["audit/**", "/locks/*.lock", "v?/data"]);
+
+    for path in [
+        "audit/a",
+        "audit/a/b/c",
+        "/audit/a",
+        "locks/a.lock",
+        "v1/data",
+    ] {
+        assert!(layer.is_protected(path), "{}", path);
+    }
+    for path in [
+        "audit",
+        "auditx/a",
+        "locks/a/b.lock",
+        "locks/a.lock2",
+        "v10/data",
+    ] {
+        assert!(!layer.is_protected(path), "{}", path);
+    }
+}
+
+async fn write(op: &Operator, path: &str, content: &str) -> crate::error::Result<usize> {
+    op.object(path)
+        .writer()
+        .write_bytes(content.as_bytes().to_vec())
+        .await
+}
+
+async fn check_write_once(op: Operator) -> Result<()> {
+    // Race free on backends that support writing if not exists.
+    assert!(op.metadata().can_write_if_not_exists());
+
+    write(&op, "audit/test_file", "Hello").await?;
+    let err = write(&op, "audit/test_file", "World").await.unwrap_err();
+    assert_eq!(err.kind(), Kind::PreconditionFailed);
+    let err = op
+        .object("audit/test_file")
+        .writer()
+        .commit_visible(true)
+        .write_bytes(b"World".to_vec())
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::PreconditionFailed);
+    assert_eq!(read_all(&op, "audit/test_file").await?, "Hello");
+
+    let err = op.object("audit/test_file").delete().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::ObjectPermissionDenied);
+    let err = op
+        .object("audit/test_file")
+        .append(b"World".to_vec())
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::ObjectPermissionDenied);
+    assert_eq!(read_all(&op, "audit/test_file").await?, "Hello");
+
+    // Presigned requests are sent without the layer, only reads are allowed.
+    let expire = Duration::from_secs(3600);
+    for presign_op in [PresignOperation::Write, PresignOperation::Delete] {
+        let err = op
+            .presign("audit/test_file", presign_op, expire)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), Kind::ObjectPermissionDenied);
+    }
+    let err = op
+        .presign("audit/test_file", PresignOperation::Stat, expire)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::Unsupported);
+
+    // Objects outside the protected prefix are not affected.
+    write(&op, "test_file", "Hello").await?;
+    write(&op, "test_file", "World").await?;
+    assert_eq!(read_all(&op, "test_file").await?, "World");
+    op.object("test_file").delete().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_once_memory() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?)
+        .layer(WriteOnceLayer::new(["audit/**"]));
+    check_write_once(op).await
+}
+
+#[tokio::test]
+async fn test_write_once_fs() -> Result<()> {
+    let root = std::env::temp_dir().join(format!("opendal-{}", uuid::Uuid::new_v4()));
+    let op = Operator::new(
+        fs::Backend::build()
+            .root(&root.to_string_lossy())
+            .finish()
+            .await?,
+    )
+    .layer(WriteOnceLayer::new(["audit/**"]));
+    check_write_once(op).await?;
+
+    std::fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_once_check_before_write() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.set_metadata(AccessorMetadata::default());
+    mock.push_stat(Err(Error::Object {
+        kind: Kind::ObjectNotExist,
+        op: "stat",
+        path: "audit/test_file".to_string(),
+        source: anyhow!("not found"),
+    }))
+    .push_write(Ok(5))
+    .push_stat(Ok(Metadata::default()));
+
+    let op = Operator::new(Arc::new(mock.clone())).layer(WriteOnceLayer::new(["audit/**"]));
+    assert!(!op.metadata().can_write_if_not_exists());
+
+    write(&op, "audit/test_file", "Hello").await?;
+    let err = write(&op, "audit/test_file", "World").await.unwrap_err();
+    assert_eq!(err.kind(), Kind::PreconditionFailed);
+    assert_eq!(mock.calls("stat"), 2);
+    assert_eq!(mock.calls("write"), 1);
+    // The backend doesn't support it, so the check is not forwarded.
+    assert!(!mock.writes()[0].0.options.if_not_exists);
+
+    Ok(())
+}
+
+/// Racer writes the target of every `write` and `copy` right before
+/// forwarding them, like another client winning the race.
+#[derive(Debug, Clone, Default)]
+struct Racer {
+    inner: Option<Arc<dyn Accessor>>,
+}
+
+impl Racer {
+    async fn race(&self, path: &str) -> OpResult<()> {
+        let r = Box::new(Cursor::new(b"raced".to_vec()));
+        self.inner
+            .as_ref()
+            .unwrap()
+            .write(r, &OpWrite::new(path, 5))
+            .await?;
+        Ok(())
+    }
+}
+
+impl Layer for Racer {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(Racer { inner: Some(inner) })
+    }
+}
+
+#[async_trait::async_trait]
+impl Accessor for Racer {
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.as_ref().unwrap().metadata()
+    }
+    async fn read(&self, args: &OpRead) -> OpResult<ObjectReader> {
+        self.inner.as_ref().unwrap().read(args).await
+    }
+    async fn write(&self, r: BoxedAsyncReader, args: &OpWrite) -> OpResult<usize> {
+        self.race(&args.path).await?;
+        self.inner.as_ref().unwrap().write(r, args).await
+    }
+    async fn stat(&self, args: &OpStat) -> OpResult<Metadata> {
+        self.inner.as_ref().unwrap().stat(args).await
+    }
+    async fn copy(&self, args: &OpCopy) -> OpResult<()> {
+        self.race(&args.to).await?;
+        self.inner.as_ref().unwrap().copy(args).await
+    }
+}
+
+#[tokio::test]
+async fn test_write_once_create_race() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?)
+        .layer(Racer::default())
+        .layer(WriteOnceLayer::new(["audit/**"]));
+
+    // The check is done by the backend along with the write.
+    let err = op
+        .inner()
+        .create(&OpCreate::new("audit/test_file", ObjectMode::FILE))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::PreconditionFailed);
+    assert_eq!(read_all(&op, "audit/test_file").await?, "raced");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_once_copy_race() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.set_metadata(*AccessorMetadata::default().set_write_if_not_exists(true));
+    mock.push_stat(Err(Error::Object {
+        kind: Kind::ObjectNotExist,
+        op: "stat",
+        path: "audit/test_file".to_string(),
+        source: anyhow!("not found"),
+    }))
+    .push_write(Ok(5))
+    .push_copy(Ok(()));
+    let op = Operator::new(Arc::new(mock.clone()))
+        .layer(Racer::default())
+        .layer(WriteOnceLayer::new(["audit/**"]));
+
+    // Copies are checked by a stat even if the backend supports conditional
+    // writes, the object created in between is overwritten.
+    op.object("test_file").copy("audit/test_file").await?;
+    assert_eq!(mock.calls("stat"), 1);
+    assert_eq!(mock.writes()[0].0.path, "audit/test_file");
+    assert_eq!(mock.copies().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fallback_read_and_repair() -> Result<()> {
+    let primary = Operator::new(memory::Backend::build().finish().await?);
+    let secondary = Operator::new(memory::Backend::build().finish().await?);
+    write(&secondary, "test_file", "Hello").await?;
+
+    let op = primary
+        .clone()
+        .layer(FallbackLayer::new(secondary.clone()).repair(true));
+
+    // Missing in the primary, served by the secondary.
+    assert_eq!(op.object("test_file").metadata().await?.content_length(), 5);
+    assert_eq!(read_all(&op, "test_file").await?, "Hello");
+    assert_eq!(
+        served_by(&op, "test_file").await?.as_deref(),
+        Some("secondary")
+    );
+
+    // Repaired in background.
+    for _ in 0..100 {
+        if primary.object("test_file").is_exist().await? {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(read_all(&primary, "test_file").await?, "Hello");
+    assert_eq!(
+        served_by(&op, "test_file").await?.as_deref(),
+        Some("origin")
+    );
+
+    // Both failed.
+    let err = op.object("not_exist").metadata().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::ObjectNotExist);
+    assert!(err.to_string().contains("primary:"), "{}", err);
+    assert!(err.to_string().contains("secondary:"), "{}", err);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fallback_on_kinds() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_read(Err(temporary_error("read")))
+        .push_stat(Err(Error::Object {
+            kind: Kind::ObjectNotExist,
+            op: "stat",
+            path: "test_file".to_string(),
+            source: anyhow!("not found"),
+        }));
+    let secondary = Operator::new(memory::Backend::build().finish().await?);
+    write(&secondary, "test_file", "Hello").await?;
+
+    let op = Operator::new(Arc::new(mock.clone()))
+        .layer(FallbackLayer::new(secondary).fallback_on([Kind::Temporary]));
+
+    // Ranged reads are never repaired.
+    let mut s = String::new();
+    op.object("test_file")
+        .range_reader(1, 3)
+        .read_to_string(&mut s)
+        .await?;
+    assert_eq!(s, "ell");
+
+    // `ObjectNotExist` is not configured to fall back.
+    let err = op.object("test_file").metadata().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::ObjectNotExist);
+    assert!(!err.to_string().contains("secondary:"), "{}", err);
+
+    Ok(())
+}
+
+/// Degraded fails `read`, `stat` and `list` with [`Kind::Temporary`] while
+/// it's turned on, and forwards them to the inner accessor otherwise.
+#[derive(Debug, Clone, Default)]
+struct Degraded {
+    inner: Option<Arc<dyn Accessor>>,
+    on: Arc<AtomicBool>,
+}
+
+impl Degraded {
+    fn check(&self, op: &'static str) -> OpResult<()> {
+        if self.on.load(Ordering::SeqCst) {
+            return Err(temporary_error(op));
+        }
+        Ok(())
+    }
+}
+
+impl Layer for Degraded {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(Degraded {
+            inner: Some(inner),
+            on: self.on.clone(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Accessor for Degraded {
+    fn metadata(&self) -> AccessorMetadata {
+        self.inner.as_ref().unwrap().metadata()
+    }
+    async fn read(&self, args: &OpRead) -> OpResult<ObjectReader> {
+        self.check("read")?;
+        self.inner.as_ref().unwrap().read(args).await
+    }
+    async fn stat(&self, args: &OpStat) -> OpResult<Metadata> {
+        self.check("stat")?;
+        self.inner.as_ref().unwrap().stat(args).await
+    }
+    async fn list(&self, args: &OpList) -> OpResult<BoxedObjectStream> {
+        self.check("list")?;
+        self.inner.as_ref().unwrap().list(args).await
+    }
+}
+
+#[tokio::test]
+async fn test_fallback_degraded_primary() -> Result<()> {
+    let primary = Operator::new(memory::Backend::build().finish().await?);
+    let secondary = Operator::new(memory::Backend::build().finish().await?);
+    write(&primary, "dir/test_file", "Hello").await?;
+    write(&secondary, "dir/test_file", "Hello").await?;
+
+    let degraded = Degraded::default();
+    let op = primary
+        .layer(degraded.clone())
+        .layer(FallbackLayer::new(secondary));
+
+    degraded.on.store(true, Ordering::SeqCst);
+    assert_eq!(read_all(&op, "dir/test_file").await?, "Hello");
+    assert_eq!(
+        served_by(&op, "dir/test_file").await?.as_deref(),
+        Some("secondary")
+    );
+    let meta = op.object("dir/test_file").metadata().await?;
+    assert_eq!(meta.content_length(), 5);
+    let entries: Vec<_> = op.objects("dir/").try_collect().await?;
+    let paths: Vec<_> = entries.iter().map(|o| o.path()).collect();
+    assert_eq!(paths, vec!["dir/test_file"]);
+
+    // Back to the primary once it recovered.
+    degraded.on.store(false, Ordering::SeqCst);
+    assert_eq!(
+        served_by(&op, "dir/test_file").await?.as_deref(),
+        Some("origin")
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fallback_write_policy() -> Result<()> {
+    let primary = Operator::new(memory::Backend::build().finish().await?);
+    let secondary = Operator::new(memory::Backend::build().finish().await?);
+
+    let op = primary.clone().layer(FallbackLayer::new(secondary.clone()));
+    write(&op, "primary_only", "Hello").await?;
+    assert!(primary.object("primary_only").is_exist().await?);
+    assert!(!secondary.object("primary_only").is_exist().await?);
+
+    let op = primary
+        .clone()
+        .layer(FallbackLayer::new(secondary.clone()).write_policy(WritePolicy::Mirror));
+    write(&op, "mirror", "Hello").await?;
+    assert_eq!(read_all(&primary, "mirror").await?, "Hello");
+    assert_eq!(read_all(&secondary, "mirror").await?, "Hello");
+
+    op.object("mirror").delete().await?;
+    assert!(!primary.object("mirror").is_exist().await?);
+    assert!(!secondary.object("mirror").is_exist().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_defaults() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_write(Ok(2)).push_write(Ok(2)).push_write(Ok(2));
+
+    let op = Operator::new(Arc::new(mock.clone())).layer(
+        WriteDefaultsLayer::new()
+            .cache_control("max-age=3600")
+            .content_type_fn(|path| {
+                path.ends_with(".json")
+                    .then(|| "application/json".to_string())
+            }),
+    );
+
+    write(&op, "test.json", "{}").await?;
+    write(&op, "test.txt", "Hi").await?;
+    // Values set by the call take precedence.
+    op.object("test.json")
+        .writer()
+        .content_type("text/plain")
+        .cache_control("no-cache")
+        .write_bytes(b"{}".to_vec())
+        .await?;
+
+    let writes = mock.writes();
+    assert_eq!(
+        writes[0].0.options.content_type.as_deref(),
+        Some("application/json")
+    );
+    assert_eq!(
+        writes[0].0.options.cache_control.as_deref(),
+        Some("max-age=3600")
+    );
+    assert_eq!(writes[1].0.options.content_type, None);
+    assert_eq!(
+        writes[1].0.options.cache_control.as_deref(),
+        Some("max-age=3600")
+    );
+    assert_eq!(
+        writes[2].0.options.content_type.as_deref(),
+        Some("text/plain")
+    );
+    assert_eq!(
+        writes[2].0.options.cache_control.as_deref(),
+        Some("no-cache")
+    );
+    assert_eq!(writes[2].0.options.content_language, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_record_and_replay() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("opendal-{}.jsonl", uuid::Uuid::new_v4()));
+    let op = Operator::new(memory::Backend::build().finish().await?)
+        .layer(InMemoryCacheLayer::new())
+        .layer(RecordLayer::new(&path)?);
+
+    op.object("dir/test")
+        .writer()
+        .write_bytes(b"Hello, World!".to_vec())
+        .await?;
+    let mut r = op.object("dir/test").range_reader(7, 5);
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"World");
+    let provenance = r.provenance().clone();
+    assert_eq!(provenance.get(Provenance::SERVED_BY), Some("origin"));
+    let meta = op.object("dir/test").metadata().await?;
+    let err = op.object("not_exist").metadata().await.unwrap_err();
+    let entries: Vec<_> = op.objects("dir/").try_collect().await?;
+    op.object("dir/test").delete().await?;
+
+    // Replay without the backend.
+    let op = Operator::new(Arc::new(ReplayAccessor::new(&path)?));
+    std::fs::remove_file(&path)?;
+
+    let n = op
+        .object("dir/test")
+        .writer()
+        .write_bytes(b"Hello, World!".to_vec())
+        .await?;
+    assert_eq!(n, 13);
+    let mut r = op.object("dir/test").range_reader(7, 5);
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"World");
+    assert_eq!(r.provenance(), &provenance);
+
+    let replayed = op.object("dir/test").metadata().await?;
+    assert_eq!(replayed.mode(), meta.mode());
+    assert_eq!(replayed.content_length(), meta.content_length());
+    assert_eq!(replayed.etag(), meta.etag());
+    assert_eq!(replayed.last_modified(), meta.last_modified());
+    assert_eq!(replayed.is_fully_loaded(), meta.is_fully_loaded());
+
+    let replayed_err = op.object("not_exist").metadata().await.unwrap_err();
+    assert_eq!(replayed_err.kind(), err.kind());
+
+    let replayed_entries: Vec<_> = op.objects("dir/").try_collect().await?;
+    assert_eq!(replayed_entries.len(), entries.len());
+    for (mut a, mut b) in replayed_entries.into_iter().zip(entries) {
+        assert_eq!(
+            a.metadata_cached_for(&[]).await?.path(),
+            b.metadata_cached_for(&[]).await?.path()
+        );
+    }
+
+    op.object("dir/test").delete().await?;
+    // Every record can only be replayed once.
+    let err = op.object("dir/test").delete().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::Unexpected);
+
+    Ok(())
+}
+
+/// Call every operation not covered by `test_record_and_replay` on `acc`,
+/// results are formatted so that recorded and replayed ones could be
+/// compared. Errors are compared by their kinds only.
+async fn call_other_ops(acc: Arc<dyn Accessor>) -> Vec<String> {
+    fn format<T: std::fmt::Debug>(r: OpResult<T>) -> String {
+        match r {
+            Ok(v) => format!("{:?}", v),
+            Err(e) => format!("{:?}", e.kind()),
+        }
+    }
+
+    let select = OpSelect::new(
+        "test.csv",
+        "SELECT * FROM S3Object",
+        SelectInput::Csv { has_header: true },
+        SelectOutput::Csv,
+    );
+    let rows = match acc.select(&select).await {
+        Ok(mut r) => {
+            let mut buf = Vec::new();
+            r.read_to_end(&mut buf).await.unwrap();
+            Ok(buf)
+        }
+        Err(e) => Err(e),
+    };
+    let presign = OpPresign::new("test", PresignOperation::Read, Duration::from_secs(60));
+
+    vec![
+        format(rows),
+        format(acc.presign(&presign).await),
+        format(
+            acc.list_multipart_uploads(&OpListMultipartUploads::new("dir/"))
+                .await,
+        ),
+        format(
+            acc.abort_multipart_upload(&OpAbortMultipartUpload::new("dir/test", "upload"))
+                .await,
+        ),
+        format(acc.retention(&OpRetention::new("test")).await),
+        format(acc.copy(&OpCopy::new("test", "backup/test")).await),
+        format(acc.create(&OpCreate::new("dir/", ObjectMode::DIR)).await),
+    ]
+}
+
+#[tokio::test]
+async fn test_record_and_replay_other_ops() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("opendal-{}.jsonl", uuid::Uuid::new_v4()));
+    let t = SystemTime::UNIX_EPOCH + Duration::from_secs(1767225600);
+    let mut headers = http::HeaderMap::new();
+    headers.insert("x-amz-acl", http::HeaderValue::from_static("private"));
+    let mock = MockAccessor::new();
+    mock.push_select(Ok(Box::new(Cursor::new(b"bob\n".to_vec()))))
+        .push_presign(Ok(PresignedRequest::new(
+            http::Method::GET,
+            "https://example.com/test?sig=abc".parse()?,
+            headers,
+        )))
+        .push_list_multipart_uploads(Ok(vec![MultipartUpload::new(
+            "dir/test",
+            "upload",
+            Some(t),
+        )]))
+        .push_abort_multipart_upload(Ok(()))
+        .push_retention(Ok(Retention::new(
+            Some(RetentionMode::Compliance),
+            Some(t),
+            true,
+        )))
+        .push_copy(Err(temporary_error("copy")))
+        .push_create(Ok(()));
+
+    let recorded = call_other_ops(RecordLayer::new(&path)?.layer(Arc::new(mock))).await;
+    assert_eq!(recorded[0], format!("{:?}", b"bob\n".to_vec()));
+    assert_eq!(recorded[5], "Temporary");
+
+    // Replay without the backend.
+    let replayed = call_other_ops(Arc::new(ReplayAccessor::new(&path)?)).await;
+    std::fs::remove_file(&path)?;
+    assert_eq!(replayed, recorded);
+
+    Ok(())
+}
+
+/// Spread keys across shards by the hash of their top level dir, children
+/// of a dir stay in the same shard so that listing still works.
+fn shard(path: &str) -> String {
+    let top = path.split('/').next().unwrap_or_default();
+    let digest = md5::compute(top.as_bytes());
+    format!("{:02x}/{}", digest[0], path)
+}
+
+fn unshard(path: &str) -> String {
+    path.split_once('/')
+        .map(|(_, p)| p.to_string())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_path_map_layer() -> Result<()> {
+    let backend = memory::Backend::build().finish().await?;
+    let op = Operator::new(backend.clone()).layer(PathMapLayer::new(shard, unshard));
+
+    for path in ["dir/a", "dir/b", "other/c"] {
+        op.object(path)
+            .writer()
+            .write_bytes(path.as_bytes().to_vec())
+            .await?;
+    }
+
+    // Keys are rewritten in backend.
+    let raw = Operator::new(backend);
+    assert!(raw.object(&shard("dir/a")).is_exist().await?);
+    assert!(!raw.object("dir/a").is_exist().await?);
+
+    let o = op.object("dir/a");
+    let mut buf = Vec::new();
+    o.reader().read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"dir/a");
+    assert_eq!(o.metadata().await?.path(), "dir/a");
+
+    let mut paths = Vec::new();
+    let mut obs = op.objects("dir/");
+    while let Some(mut o) = obs.try_next().await? {
+        paths.push(o.metadata_cached_for(&[]).await?.path().to_string());
+        // Listed objects are bound to the layer.
+        let mut buf = Vec::new();
+        o.reader().read_to_end(&mut buf).await?;
+        assert_eq!(buf, paths.last().unwrap().as_bytes());
+    }
+    assert_eq!(paths, vec!["dir/a", "dir/b"]);
+
+    op.object("dir/a").delete().await?;
+    assert!(!raw.object(&shard("dir/a")).is_exist().await?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_quota_concurrent_writers() -> Result<()> {
+    let backend = memory::Backend::build().finish().await?;
+    let quota = QuotaLayer::new(1024 * 1024);
+    let op = Operator::new(backend.clone()).layer(quota.clone());
+
+    let tasks: Vec<_> = (0..64)
+        .map(|i| {
+            let op = op.clone();
+            tokio::spawn(async move {
+                op.object(&format!("tenant/{i}"))
+                    .writer()
+                    .write_bytes(vec![0; 64 * 1024])
+                    .await
+            })
+        })
+        .collect();
+    let mut succeeded = 0;
+    for task in tasks {
+        match task.await? {
+            Ok(_) => succeeded += 1,
+            Err(e) => assert_eq!(e.kind(), Kind::QuotaExceeded),
+        }
+    }
+    assert_eq!(succeeded, 16);
+
+    let raw = Operator::new(backend);
+    let mut stored = 0;
+    for i in 0..64 {
+        let o = raw.object(&format!("tenant/{i}"));
+        if o.is_exist().await? {
+            stored += o.metadata().await?.content_length();
+        }
+    }
+    assert!(stored <= 1024 * 1024);
+    assert_eq!(quota.usage(), stored);
+
+    // Deletes and overwrites give the bytes back.
+    let mut written: Vec<_> = Vec::new();
+    for i in 0..64 {
+        if raw.object(&format!("tenant/{i}")).is_exist().await? {
+            written.push(format!("tenant/{i}"));
+        }
+    }
+    op.object(&written[0]).delete().await?;
+    op.object(&written[1])
+        .writer()
+        .write_bytes(vec![0; 1024])
+        .await?;
+    assert_eq!(quota.usage(), stored - 128 * 1024 + 1024);
+    op.object("tenant/new")
+        .writer()
+        .write_bytes(vec![0; 64 * 1024])
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_quota_overwrite_near_limit() -> Result<()> {
+    let quota = QuotaLayer::new(1024);
+    let op = Operator::new(memory::Backend::build().finish().await?).layer(quota.clone());
+
+    // Only the growth counts, the old object is not counted twice.
+    op.object("a").writer().write_bytes(vec![0; 1000]).await?;
+    op.object("a").writer().write_bytes(vec![0; 1000]).await?;
+    assert_eq!(quota.usage(), 1000);
+    op.object("a").writer().write_bytes(vec![0; 1024]).await?;
+    assert_eq!(quota.usage(), 1024);
+    let err = op
+        .object("a")
+        .writer()
+        .write_bytes(vec![0; 1025])
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::QuotaExceeded);
+    assert_eq!(quota.usage(), 1024);
+
+    // Shrinking frees the difference.
+    op.object("a").writer().write_bytes(vec![0; 24]).await?;
+    assert_eq!(quota.usage(), 24);
+    op.object("b").writer().write_bytes(vec![0; 1000]).await?;
+    assert_eq!(quota.usage(), 1024);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_quota_prefix_and_cut_off() -> Result<()> {
+    let backend = memory::Backend::build().finish().await?;
+    let quota = QuotaLayer::new(1024 * 1024).prefix_limit("tmp/", 16);
+    let op = Operator::new(backend.clone()).layer(quota.clone());
+
+    op.object("tmp/a").writer().write_bytes(vec![0; 16]).await?;
+    let err = op
+        .object("tmp/b")
+        .writer()
+        .write_bytes(vec![0; 1])
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::QuotaExceeded);
+    op.object("data").writer().write_bytes(vec![0; 64]).await?;
+    assert_eq!(quota.prefix_usage("tmp/"), Some(16));
+    assert_eq!(quota.usage(), 80);
+
+    // Readers producing more than the declared size are cut off, even if
+    // the caller doesn't check the length like writers do.
+    let acc = quota.layer(backend.clone());
+    let err = acc
+        .write(Box::new(Cursor::new(vec![0; 64])), &OpWrite::new("c", 4))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::QuotaExceeded);
+    assert!(!Operator::new(backend).object("c").is_exist().await?);
+    assert_eq!(quota.usage(), 80);
+
+    let err = op
+        .presign("data", PresignOperation::Write, Duration::from_secs(60))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::ObjectPermissionDenied);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_quota_usage_fn() -> Result<()> {
+    let prefixes = Arc::new(Mutex::new(Vec::new()));
+    let quota = QuotaLayer::new(1024).usage_fn({
+        let prefixes = prefixes.clone();
+        move |prefix| {
+            prefixes.lock().unwrap().push(prefix.to_string());
+            Box::pin(async { Ok(1000) })
+        }
+    });
+    let op = Operator::new(memory::Backend::build().finish().await?).layer(quota.clone());
+
+    op.object("a").writer().write_bytes(vec![0; 24]).await?;
+    let err = op
+        .object("b")
+        .writer()
+        .write_bytes(vec![0; 25])
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::QuotaExceeded);
+
+    // Written bytes are left to the usage source.
+    assert_eq!(quota.usage(), 1000);
+    assert_eq!(*prefixes.lock().unwrap(), vec!["", ""]);
+
+    Ok(())
+}
@@ -0,0 +1,431 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use futures::AsyncReadExt;
+use futures::StreamExt;
+use futures::TryStreamExt;
+
+use crate::error::Kind;
+use crate::ops::ListMode;
+use crate::ops::PresignOperation;
+use crate::ops::ReadOptions;
+use crate::services::memory;
+use crate::MetaField;
+use crate::ObjectMode;
+use crate::Operator;
+
+#[tokio::test]
+async fn test_delete_if_match() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let o = op.object("test");
+
+    o.writer().write_bytes("Hello, World!".into()).await?;
+    let old = o.metadata().await?;
+
+    o.writer().write_bytes("Hello, OpenDAL!".into()).await?;
+
+    let err = o
+        .delete_if_match(old.etag().expect("etag must exist"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::PreconditionFailed);
+    assert!(o.is_exist().await?);
+
+    let new = o.metadata().await?;
+    o.delete_if_match(new.etag().expect("etag must exist"))
+        .await?;
+    assert!(!o.is_exist().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_if_unmodified_since() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let o = op.object("test");
+
+    let before = SystemTime::now() - Duration::from_secs(60);
+    o.writer().write_bytes("Hello, World!".into()).await?;
+
+    let err = o.delete_if_unmodified_since(before).await.unwrap_err();
+    assert_eq!(err.kind(), Kind::PreconditionFailed);
+    assert!(o.is_exist().await?);
+
+    o.delete_if_unmodified_since(SystemTime::now()).await?;
+    assert!(!o.is_exist().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_if_match_race_with_rewrite() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    for i in 0..64 {
+        let path = format!("race-{}", i);
+        let o = op.object(&path);
+        o.writer().write_bytes("old".into()).await?;
+        let meta = o.metadata().await?;
+        let etag = meta.etag().unwrap().to_string();
+        let old = generation(&meta);
+
+        let writer = op.object(&path);
+        let rewrite = tokio::spawn(async move { writer.writer().write_bytes("new".into()).await });
+        let deleter = op.object(&path);
+        let delete = tokio::spawn(async move { deleter.delete_if_match(&etag).await });
+
+        rewrite.await??;
+        let result = delete.await?;
+
+        // Either way, the new content must be intact.
+        let mut buf = Vec::new();
+        o.reader().read_to_end(&mut buf).await?;
+        assert_eq!(buf, b"new");
+
+        let current = generation(&o.metadata().await?);
+        match result {
+            // Delete won, the old object must be gone before the rewrite
+            // created a new one: the delete bumped the generation between
+            // the two writes.
+            Ok(()) => assert_eq!(current, old + 2, "{}", path),
+            // Rewrite won, nothing must be deleted.
+            Err(e) => {
+                assert_eq!(e.kind(), Kind::PreconditionFailed);
+                assert_eq!(current, old + 1, "{}", path);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn generation(meta: &crate::Metadata) -> u64 {
+    meta.version_id()
+        .expect("version id must exist")
+        .parse()
+        .expect("version id must be a number")
+}
+
+#[tokio::test]
+async fn test_generation_increases_on_write_and_delete() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let o = op.object("test");
+
+    o.writer().write_bytes("Hello, World!".into()).await?;
+    let first = generation(&o.metadata().await?);
+
+    o.writer().write_bytes("Hello, OpenDAL!".into()).await?;
+    let second = generation(&o.metadata().await?);
+    assert!(second > first);
+
+    o.delete().await?;
+    o.writer().write_bytes("Hello, World!".into()).await?;
+    // Delete also bumps the generation.
+    let third = generation(&o.metadata().await?);
+    assert!(third > second + 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reader_sees_snapshot_during_overwrite() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let o = op.object("test");
+    o.writer().write_bytes(vec![b'a'; 1024]).await?;
+
+    let mut r = o.reader();
+    let mut buf = vec![0; 1];
+    r.read_exact(&mut buf).await?;
+
+    // Overwrite while the reader is still open.
+    o.writer().write_bytes(vec![b'b'; 2048]).await?;
+
+    r.read_to_end(&mut buf).await?;
+    assert_eq!(buf, vec![b'a'; 1024]);
+
+    let mut buf = Vec::new();
+    o.reader().read_to_end(&mut buf).await?;
+    assert_eq!(buf, vec![b'b'; 2048]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_append() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let o = op.object("test");
+
+    o.append(b"Hello, ".to_vec()).await?;
+    let first = o.metadata().await?;
+    o.append(b"World!".to_vec()).await?;
+
+    let mut s = String::new();
+    o.reader().read_to_string(&mut s).await?;
+    assert_eq!(s, "Hello, World!");
+    assert_ne!(o.metadata().await?.version_id(), first.version_id());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_snapshot_unsupported() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    assert!(!op.metadata().can_read_version());
+    op.object("dir/test")
+        .writer()
+        .write_bytes(vec![0; 4])
+        .await?;
+
+    let err = op
+        .objects("dir/")
+        .snapshot()
+        .next()
+        .await
+        .expect("must have an entry")
+        .expect_err("must fail");
+    assert_eq!(err.kind(), Kind::Unsupported);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_mode() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    for path in ["abc/d", "abcdef"] {
+        op.object(path).writer().write_bytes(vec![0; 4]).await?;
+    }
+
+    let list = |path: &str, mode: ListMode| {
+        let mut obs = op.objects(path).mode(mode);
+        async move {
+            let mut paths = Vec::new();
+            while let Some(mut o) = obs.try_next().await? {
+                paths.push(o.metadata_cached_for(&[]).await?.path().to_string());
+            }
+            Result::<_>::Ok(paths)
+        }
+    };
+    assert_eq!(list("abc", ListMode::Dir).await?, vec!["abc/d"]);
+    assert_eq!(list("abc/", ListMode::Dir).await?, vec!["abc/d"]);
+    assert_eq!(list("abcdef", ListMode::Dir).await?, vec!["abcdef"]);
+    assert_eq!(
+        list("abc", ListMode::Prefix).await?,
+        vec!["abc/d", "abcdef"]
+    );
+    assert_eq!(list("abcdef", ListMode::Prefix).await?, vec!["abcdef"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_fold_sub_dirs() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    for path in ["d/a", "d/b/1", "d/b/c/2", "d/e"] {
+        op.object(path).writer().write_bytes(vec![0; 4]).await?;
+    }
+
+    let list = |obs: crate::ObjectStream| async move {
+        let obs: Vec<_> = obs.try_collect().await?;
+        Result::<_>::Ok(obs.iter().map(|o| o.path().to_string()).collect::<Vec<_>>())
+    };
+    assert_eq!(list(op.objects("d/")).await?, vec!["d/a", "d/b/", "d/e"]);
+    assert_eq!(list(op.objects("d/b")).await?, vec!["d/b/1", "d/b/c/"]);
+    // Folded dirs are not returned again after resuming.
+    assert_eq!(
+        list(op.objects("d/").start_after("d/b/")).await?,
+        vec!["d/e"]
+    );
+    assert_eq!(
+        list(op.objects("d/").start_after("d/b/1")).await?,
+        vec!["d/e"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dir_marker_with_data() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("p/weird/")
+        .writer()
+        .write_bytes(b"Hello".to_vec())
+        .await?;
+    op.object("p/weird/x")
+        .writer()
+        .write_bytes(vec![0; 4])
+        .await?;
+
+    // Stat as dir with the real length.
+    let meta = op.object("p/weird/").metadata().await?;
+    assert_eq!(meta.mode(), ObjectMode::DIR);
+    assert_eq!(meta.content_length(), 5);
+
+    // Reading is rejected unless allowed explicitly.
+    let mut buf = Vec::new();
+    let err = op
+        .object("p/weird/")
+        .reader()
+        .read_to_end(&mut buf)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+    let res = op.object("p/weird/").reader_checked().await;
+    assert!(matches!(res, Err(e) if e.kind() == Kind::Unsupported));
+    op.object("p/weird/")
+        .reader_with(ReadOptions::new().allow_trailing_slash())
+        .read_to_end(&mut buf)
+        .await?;
+    assert_eq!(buf, b"Hello");
+
+    // Listed only once, as a dir.
+    let mut entries = Vec::new();
+    let mut obs = op.objects("p/");
+    while let Some(mut o) = obs.try_next().await? {
+        let meta = o.metadata_cached_for(&[MetaField::Mode]).await?;
+        entries.push((meta.path().to_string(), meta.mode()));
+    }
+    assert_eq!(entries, vec![("p/weird/".to_string(), ObjectMode::DIR)]);
+
+    // Listed as a dir without the marker itself.
+    let mut entries = Vec::new();
+    let mut obs = op.objects("p/weird/");
+    while let Some(mut o) = obs.try_next().await? {
+        let meta = o.metadata_cached_for(&[MetaField::Mode]).await?;
+        entries.push((meta.path().to_string(), meta.mode()));
+    }
+    assert_eq!(entries, vec![("p/weird/x".to_string(), ObjectMode::FILE)]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retention_unsupported() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("test").writer().write_bytes(vec![0; 4]).await?;
+
+    let err = op.object("test").retention().await.expect_err("must fail");
+    assert_eq!(err.kind(), Kind::Unsupported);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_path_len() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().max_path_len(10).finish().await?);
+    assert_eq!(op.metadata().max_path_len(), Some(10));
+
+    op.object("0123456789")
+        .writer()
+        .write_bytes(vec![0; 4])
+        .await?;
+
+    let err = op
+        .object("dir/0123456")
+        .writer()
+        .write_bytes(vec![0; 4])
+        .await
+        .expect_err("must fail");
+    assert_eq!(err.kind(), Kind::ObjectPathInvalid);
+    assert!(
+        err.to_string()
+            .contains("path length 11 exceeds the limit 10"),
+        "{err}"
+    );
+    // Rejected before reaching the backend.
+    assert_eq!(op.stats().total_requests(), 1);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_latency() -> Result<()> {
+    let mut builder = memory::Backend::build();
+    builder
+        .latency(Duration::from_millis(100))
+        .throughput(1024 * 1024);
+    let op = Operator::new(builder.finish().await?);
+    let o = op.object("test");
+
+    let start = tokio::time::Instant::now();
+    o.writer().write_bytes(vec![0; 1024 * 1024]).await?;
+    assert_eq!(start.elapsed(), Duration::from_millis(1100));
+
+    let start = tokio::time::Instant::now();
+    let mut r = o.reader();
+    let mut buf = vec![0; 1024];
+    r.read_exact(&mut buf).await?;
+    // Only the first chunk has been waited.
+    assert!(start.elapsed() < Duration::from_millis(200));
+    r.read_to_end(&mut buf).await?;
+    assert!(start.elapsed() >= Duration::from_millis(1100));
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_latency_jitter_seed() -> Result<()> {
+    async fn elapsed(seed: u64) -> Result<Vec<Duration>> {
+        let mut builder = memory::Backend::build();
+        builder.jitter(Duration::from_secs(1)).seed(seed);
+        let op = Operator::new(builder.finish().await?);
+
+        let mut res = Vec::new();
+        for _ in 0..3 {
+            let start = tokio::time::Instant::now();
+            op.object("test").is_exist().await?;
+            res.push(start.elapsed());
+        }
+        Ok(res)
+    }
+
+    let delays = elapsed(42).await?;
+    assert_eq!(delays, elapsed(42).await?);
+    assert_ne!(delays, elapsed(7).await?);
+    assert!(delays.iter().all(|d| *d < Duration::from_secs(1)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_presign_unsupported() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    assert!(!op.metadata().can_presign(PresignOperation::Stat));
+
+    let err = op
+        .object("test")
+        .presign_stat(Duration::from_secs(60))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::Unsupported);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_all_versions_unsupported() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    assert!(!op.metadata().can_delete_all_versions());
+
+    let o = op.object("test");
+    o.writer().write_bytes("hello".into()).await?;
+    let err = o.delete_all_versions().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::Unsupported);
+    assert!(o.is_exist().await?);
+
+    Ok(())
+}
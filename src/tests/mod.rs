@@ -12,7 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod consistency;
+mod credential;
+mod data;
+mod fmt;
+mod fs;
+mod health;
 mod io;
+mod kv;
 mod layer;
+mod layers;
+mod memory;
+mod object;
+mod observe;
+mod operator;
 mod ops;
 mod readers;
+mod s3;
+mod tempfs;
+mod testing;
+mod tmp;
+mod writers;
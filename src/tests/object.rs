@@ -0,0 +1,1015 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures::io::Cursor;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use futures::io::SeekFrom;
+use futures::AsyncReadExt;
+use futures::AsyncSeekExt;
+use futures::StreamExt;
+use futures::TryStreamExt;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result as OpResult;
+use crate::object::BoxedObjectStream;
+use crate::ops::ListMode;
+use crate::ops::ListOptions;
+use crate::ops::OpList;
+use crate::ops::OpRead;
+use crate::ops::OpStat;
+use crate::ops::ReadOptions;
+use crate::ops::SelectInput;
+use crate::ops::SelectOutput;
+use crate::ops::WriteOptions;
+use crate::readers::DigestKind;
+use crate::services::fs;
+use crate::services::memory;
+use crate::testing::MockAccessor;
+use crate::Accessor;
+use crate::AccessorMetadata;
+use crate::ErrorPolicy;
+use crate::Layer;
+use crate::MetaField;
+use crate::Metadata;
+use crate::Object;
+use crate::ObjectMode;
+use crate::ObjectReader;
+use crate::ObjectStream;
+use crate::Operator;
+use crate::WriteResult;
+
+/// CountCalls counts the `read` and `stat` calls that reach the inner accessor,
+/// `list` is forwarded without counting.
+#[derive(Debug, Clone, Default)]
+struct CountCalls {
+    inner: Option<Arc<dyn Accessor>>,
+    count: Arc<AtomicUsize>,
+}
+
+impl Layer for CountCalls {
+    fn layer(&self, inner: Arc<dyn Accessor>) -> Arc<dyn Accessor> {
+        Arc::new(CountCalls {
+            inner: Some(inner),
+            count: self.count.clone(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Accessor for CountCalls {
+    async fn read(&self, args: &OpRead) -> OpResult<ObjectReader> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        self.inner.as_ref().unwrap().read(args).await
+    }
+    async fn stat(&self, args: &OpStat) -> OpResult<Metadata> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        self.inner.as_ref().unwrap().stat(args).await
+    }
+    async fn list(&self, args: &OpList) -> OpResult<BoxedObjectStream> {
+        self.inner.as_ref().unwrap().list(args).await
+    }
+}
+
+#[test]
+fn test_metadata_try_accessors() {
+    let mut meta = Metadata::default();
+    assert_eq!(meta.try_mode(), None);
+    assert_eq!(meta.try_content_length(), None);
+
+    meta.set_mode(ObjectMode::FILE).set_content_length(3);
+    assert_eq!(meta.try_mode(), Some(ObjectMode::FILE));
+    assert_eq!(meta.try_content_length(), Some(3));
+}
+
+#[tokio::test]
+async fn test_metadata_cached_for() -> Result<()> {
+    let layer = CountCalls::default();
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("test").writer().write_bytes(vec![0; 16]).await?;
+    let op = op.layer(layer.clone());
+
+    let mut o = op.object("test");
+    assert!(!o.metadata_cached_for(&[]).await?.is_fully_loaded());
+    assert_eq!(layer.count.load(Ordering::SeqCst), 0);
+
+    let meta = o.metadata_cached_for(&[MetaField::ContentLength]).await?;
+    assert!(meta.is_fully_loaded());
+    #[allow(deprecated)]
+    let complete = meta.complete();
+    assert!(complete);
+    assert!(meta.has(MetaField::ETag));
+    assert_eq!(meta.content_length(), 16);
+    assert_eq!(layer.count.load(Ordering::SeqCst), 1);
+
+    // All fields have been loaded, no more stat.
+    o.metadata_cached_for(&[MetaField::LastModified]).await?;
+    o.metadata_cached().await?;
+    assert_eq!(layer.count.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reader_checked_caches_metadata() -> Result<()> {
+    let layer = CountCalls::default();
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("test").writer().write_bytes(vec![0; 16]).await?;
+    let op = op.layer(layer.clone());
+
+    let mut o = op.object("test");
+    let mut buf = Vec::new();
+    o.reader_checked().await?.read_to_end(&mut buf).await?;
+    assert_eq!(buf.len(), 16);
+
+    let meta = o.metadata_cached().await?;
+    assert_eq!(meta.content_length(), 16);
+    assert!(meta.etag().is_some());
+    // Read then metadata only costs one call.
+    assert_eq!(layer.count.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_select_returns_reader() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_select(Ok(Box::new(Cursor::new(b"bob\ncarol\n".to_vec()))));
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let mut r = op
+        .object("test.csv")
+        .select(
+            "SELECT s.name FROM S3Object s",
+            SelectInput::Csv { has_header: true },
+            SelectOutput::Csv,
+        )
+        .await?
+        .with_digest(DigestKind::Crc32c);
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf).await?;
+    assert_eq!(&buf, b"bob\n");
+
+    // Rows are not ranges of the object.
+    assert_eq!(r.seek(SeekFrom::Current(0)).await?, 4);
+    let err = r.seek(SeekFrom::Start(0)).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+    let err = r.seek(SeekFrom::End(0)).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+
+    let mut rows = String::new();
+    r.read_to_string(&mut rows).await?;
+    assert_eq!(rows, "carol\n");
+    assert!(r.digest().is_some(), "digest must cover all rows");
+    // Nothing has been read from the object itself.
+    assert!(mock.reads().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reader_with_options() -> Result<()> {
+    let mock = MockAccessor::new();
+    for data in ["llo", "el", "o, World!", "Hello", "ell", "Hello, World!"] {
+        mock.push_read(Ok(ObjectReader::new(Box::new(Cursor::new(
+            data.as_bytes().to_vec(),
+        )))));
+    }
+    let op = Operator::new(Arc::new(mock.clone()));
+    let o = op.object("test");
+
+    let readers = [
+        o.reader_with(ReadOptions::new().offset(2).size(3).version_id("v1")),
+        o.range_reader(1, 2),
+        o.offset_reader(4),
+        o.limited_reader(5),
+    ];
+    for mut r in readers {
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf).await?;
+    }
+    let bs = o
+        .read_with(ReadOptions::new().offset(1).size(3).version_id("v2"))
+        .await?;
+    assert_eq!(bs, b"ell");
+    assert_eq!(o.read().await?, b"Hello, World!");
+
+    let reads: Vec<_> = mock
+        .reads()
+        .into_iter()
+        .map(|op| (op.path, op.offset, op.size, op.version_id))
+        .collect();
+    assert_eq!(
+        reads,
+        vec![
+            ("test".to_string(), Some(2), Some(3), Some("v1".to_string())),
+            ("test".to_string(), Some(1), Some(2), None),
+            ("test".to_string(), Some(4), None, None),
+            ("test".to_string(), Some(0), Some(5), None),
+            ("test".to_string(), Some(1), Some(3), Some("v2".to_string())),
+            ("test".to_string(), Some(0), None, None),
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_writer_with_options() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.set_metadata(*AccessorMetadata::default().set_commit_visible(true));
+    mock.push_write(Ok(13)).push_write(Ok(13));
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let expires = SystemTime::UNIX_EPOCH + Duration::from_secs(1767225600);
+    let opts = WriteOptions::new()
+        .part_size(5)
+        .parallelism(2)
+        .commit_visible(true)
+        .content_type("text/plain")
+        .content_language("en-US")
+        .cache_control("no-cache")
+        .expires(expires)
+        .tag("temp", "true")
+        .user_metadata("owner", "alice")
+        .acl("private")
+        .storage_class("STANDARD_IA")
+        .if_not_exists(true)
+        .strict(true);
+    op.object("test")
+        .write_with(opts.clone(), b"Hello, World!".to_vec())
+        .await?;
+    // Options set on the writer override the given ones.
+    op.object("test")
+        .writer_with(opts.clone())
+        .cache_control("max-age=3600")
+        .write_bytes(b"Hello, World!".to_vec())
+        .await?;
+
+    let writes = mock.writes();
+    assert_eq!(writes.len(), 2);
+    assert_eq!(writes[0].0.path, "test");
+    assert_eq!(writes[0].0.size, 13);
+    assert_eq!(writes[0].0.options, opts);
+    assert_eq!(writes[1].0.options, opts.cache_control("max-age=3600"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_is_dir_and_is_file() -> Result<()> {
+    let root = std::env::temp_dir().join(format!("opendal-{}", uuid::Uuid::new_v4()));
+    let fs_op = Operator::new(
+        fs::Backend::build()
+            .root(&root.to_string_lossy())
+            .finish()
+            .await?,
+    );
+    let memory_op = Operator::new(memory::Backend::build().finish().await?);
+
+    for op in [fs_op, memory_op] {
+        op.object("dir/test")
+            .writer()
+            .write_bytes(vec![0; 16])
+            .await?;
+
+        let file = op.object("dir/test");
+        assert!(file.is_file().await?);
+        assert!(!file.is_dir().await?);
+
+        let dir = op.object("dir/");
+        assert!(dir.is_dir().await?);
+        assert!(!dir.is_file().await?);
+
+        let missing = op.object("not_exist");
+        assert!(!missing.is_file().await?);
+        assert!(!missing.is_dir().await?);
+    }
+
+    std::fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_is_file_uses_cached_mode() -> Result<()> {
+    let layer = CountCalls::default();
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("dir/test")
+        .writer()
+        .write_bytes(vec![0; 16])
+        .await?;
+    let op = op.layer(layer.clone());
+
+    let mut obs = op.objects("dir/");
+    let o = obs.next().await.expect("must have an entry")?;
+    assert!(o.is_file().await?);
+    // Mode returned by list is used, no stat required.
+    assert_eq!(layer.count.load(Ordering::SeqCst), 0);
+
+    Ok(())
+}
+
+async fn list_paths(mut obs: ObjectStream) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    while let Some(o) = obs.next().await {
+        paths.push(o?.metadata_cached().await?.path().to_string());
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+#[tokio::test]
+async fn test_list_filter_by_size() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    for (name, size) in [("small", 1), ("medium", 16), ("large", 256)] {
+        op.object(&format!("dir/{}", name))
+            .writer()
+            .write_bytes(vec![0; size])
+            .await?;
+    }
+
+    let paths = list_paths(op.objects("dir/").min_size(16)).await?;
+    assert_eq!(paths, vec!["dir/large", "dir/medium"]);
+
+    let paths = list_paths(op.objects("dir/").min_size(2).max_size(16)).await?;
+    assert_eq!(paths, vec!["dir/medium"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_with_options() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.set_metadata(*AccessorMetadata::default().set_read_version(true));
+    for _ in 0..2 {
+        mock.push_list(Ok(Box::new(futures::stream::empty())));
+    }
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let modified_after = SystemTime::UNIX_EPOCH + Duration::from_secs(1767225600);
+    let opts = ListOptions::new()
+        .mode(ListMode::Prefix)
+        .modified_after(modified_after)
+        .min_size(1)
+        .max_size(10)
+        .start_after("dir/a")
+        .snapshot(true)
+        .fetch_owner(true);
+    let _: Vec<_> = op
+        .object("dir/")
+        .list_with(opts.clone())
+        .try_collect()
+        .await?;
+    let _: Vec<_> = op.objects_with("dir/", opts).try_collect().await?;
+
+    let lists = mock.lists();
+    assert_eq!(lists.len(), 2);
+    for args in lists {
+        assert_eq!(args.path, "dir/");
+        assert_eq!(args.mode, ListMode::Prefix);
+        assert_eq!(args.modified_after, Some(modified_after));
+        assert_eq!(args.min_size, Some(1));
+        assert_eq!(args.max_size, Some(10));
+        assert_eq!(args.start_after.as_deref(), Some("dir/a"));
+        assert!(args.snapshot);
+        assert!(args.fetch_owner);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_start_after() -> Result<()> {
+    let root = std::env::temp_dir().join(format!("opendal-{}", uuid::Uuid::new_v4()));
+    let mut fs_builder = fs::Backend::build();
+    fs_builder.root(&root.to_string_lossy());
+
+    for op in [
+        Operator::new(memory::Backend::build().finish().await?),
+        Operator::new(fs_builder.finish().await?),
+    ] {
+        for i in 0..10 {
+            op.object(&format!("dir/{}", i))
+                .writer()
+                .write_bytes(vec![0; 1])
+                .await?;
+        }
+
+        // List the first half, then resume from the last returned path.
+        let mut first = Vec::new();
+        let mut obs = op.objects("dir/");
+        while first.len() < 5 {
+            let mut o = obs.next().await.expect("must have entry")?;
+            first.push(o.metadata_cached().await?.path().to_string());
+        }
+        drop(obs);
+
+        let second = list_paths(op.objects("dir/").start_after(&first[4])).await?;
+        assert_eq!(second.len(), 5);
+
+        let mut paths = [first, second].concat();
+        paths.sort();
+        let expected: Vec<_> = (0..10).map(|i| format!("dir/{}", i)).collect();
+        assert_eq!(paths, expected);
+    }
+
+    std::fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_resume_token() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let expected: Vec<_> = (0..10000).map(|i| format!("dir/{:05}", i)).collect();
+    for path in &expected {
+        op.object(path).writer().write_bytes(vec![0; 1]).await?;
+    }
+
+    // Stop halfway, then resume with the token.
+    let mut first = Vec::new();
+    let mut obs = op.objects("dir/");
+    while first.len() < 5000 {
+        first.push(
+            obs.next()
+                .await
+                .expect("must have entry")?
+                .path()
+                .to_string(),
+        );
+    }
+    let token = obs.resume_token().expect("must have token");
+    drop(obs);
+
+    let obs = op.objects("dir/").resume_from(&token);
+    let second = list_paths(obs).await?;
+
+    let mut paths = [first, second].concat();
+    paths.sort();
+    assert_eq!(paths.len(), expected.len(), "must have no duplicates");
+    assert_eq!(paths, expected);
+
+    // A resumed stream returns the same token before making progress.
+    let obs = op.objects("dir/").resume_from(&token);
+    assert_eq!(obs.resume_token(), Some(token));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_resume_token_invalid() -> Result<()> {
+    let root = std::env::temp_dir().join(format!("opendal-{}", uuid::Uuid::new_v4()));
+    let mut fs_builder = fs::Backend::build();
+    fs_builder.root(&root.to_string_lossy());
+    let fs_op = Operator::new(fs_builder.finish().await?);
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    for op in [&fs_op, &op] {
+        op.object("dir/a").writer().write_bytes(vec![0; 1]).await?;
+        op.object("other/a")
+            .writer()
+            .write_bytes(vec![0; 1])
+            .await?;
+    }
+
+    let mut obs = fs_op.objects("dir/");
+    obs.next().await.expect("must have entry")?;
+    let fs_token = obs.resume_token().expect("must have token");
+    let mut obs = op.objects("dir/");
+    obs.next().await.expect("must have entry")?;
+    let token = obs.resume_token().expect("must have token");
+
+    for (path, token) in [
+        ("dir/", fs_token.as_str()),
+        ("other/", token.as_str()),
+        ("dir/", "not a token"),
+    ] {
+        let err = op
+            .objects(path)
+            .resume_from(token)
+            .next()
+            .await
+            .expect("must have entry")
+            .unwrap_err();
+        assert_eq!(err.kind(), Kind::ResumeTokenInvalid, "{}", err);
+    }
+
+    std::fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+/// Counts calls of `for_each_concurrent`, fails the objects whose index is a
+/// multiple of 1000.
+#[derive(Debug, Clone, Default)]
+struct Visitor {
+    finished: Arc<AtomicUsize>,
+    running: Arc<AtomicUsize>,
+    max_running: Arc<AtomicUsize>,
+}
+
+impl Visitor {
+    async fn visit(self, mut o: Object) -> OpResult<()> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_running.fetch_max(running, Ordering::SeqCst);
+        tokio::task::yield_now().await;
+
+        let path = o.metadata_cached().await?.path().to_string();
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        self.finished.fetch_add(1, Ordering::SeqCst);
+
+        let idx: usize = path
+            .trim_start_matches("dir/")
+            .parse()
+            .expect("must be index");
+        if idx.is_multiple_of(1000) {
+            return Err(Error::Object {
+                kind: Kind::Unexpected,
+                op: "visit",
+                path,
+                source: anyhow!("injected failure"),
+            });
+        }
+        Ok(())
+    }
+}
+
+async fn prepare_entries(n: usize) -> Result<Operator> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    for i in 0..n {
+        op.object(&format!("dir/{:05}", i))
+            .writer()
+            .write_bytes(vec![0; 1])
+            .await?;
+    }
+    Ok(op)
+}
+
+#[tokio::test]
+async fn test_for_each_concurrent_abort() -> Result<()> {
+    let op = prepare_entries(10000).await?;
+
+    let visitor = Visitor::default();
+    let err = op
+        .objects("dir/")
+        .for_each_concurrent(16, |o| visitor.clone().visit(o))
+        .await
+        .expect_err("must abort on injected failure");
+    assert_eq!(err.kind(), Kind::Unexpected);
+    assert!(err.to_string().contains("dir/00000"), "{err}");
+
+    // The listing is not pulled ahead of processing.
+    assert!(visitor.max_running.load(Ordering::SeqCst) <= 16);
+    assert!(visitor.finished.load(Ordering::SeqCst) <= 16);
+
+    let visitor = Visitor::default();
+    let errs = op
+        .objects("dir/")
+        .try_for_each_concurrent(16, ErrorPolicy::Abort, |o| visitor.clone().visit(o))
+        .await
+        .expect_err("must abort on injected failure");
+    assert_eq!(errs.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_for_each_concurrent_continue() -> Result<()> {
+    let op = prepare_entries(10000).await?;
+
+    let visitor = Visitor::default();
+    let errs = op
+        .objects("dir/")
+        .try_for_each_concurrent(16, ErrorPolicy::Continue, |o| visitor.clone().visit(o))
+        .await
+        .expect_err("must return injected failures");
+
+    let mut paths: Vec<_> = errs
+        .iter()
+        .map(|e| {
+            assert_eq!(e.kind(), Kind::Unexpected);
+            match e {
+                Error::Object { path, .. } => path.clone(),
+                _ => unreachable!("must be object error"),
+            }
+        })
+        .collect();
+    paths.sort();
+    let expected: Vec<_> = (0..10).map(|i| format!("dir/{:05}", i * 1000)).collect();
+    assert_eq!(paths, expected);
+
+    assert_eq!(visitor.finished.load(Ordering::SeqCst), 10000);
+    assert!(visitor.max_running.load(Ordering::SeqCst) <= 16);
+
+    // Succeeds if nothing failed.
+    op.objects("dir/")
+        .try_for_each_concurrent(4, ErrorPolicy::Continue, |_| async { Ok(()) })
+        .await
+        .expect("must succeed");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sample() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let content: Vec<u8> = (0..10_000).map(|v| (v % 251) as u8).collect();
+    op.object("test")
+        .writer()
+        .write_bytes(content.clone())
+        .await?;
+
+    // The first chunk starts at 0 and the last one ends at the end.
+    let bs = op.object("test").sample(5, 10).await?;
+    let expected: Vec<u8> = [0, 2497, 4995, 7492, 9990]
+        .iter()
+        .flat_map(|&offset| content[offset..offset + 10].to_vec())
+        .collect();
+    assert_eq!(bs, expected);
+
+    assert_eq!(op.object("test").sample(1, 10).await?, content[..10]);
+    assert!(op.object("test").sample(0, 10).await?.is_empty());
+
+    // Objects smaller than the sampling are read entirely.
+    assert_eq!(op.object("test").sample(100, 100).await?, content);
+    op.object("small").writer().write_bytes(vec![1; 7]).await?;
+    assert_eq!(op.object("small").sample(5, 10).await?, vec![1; 7]);
+
+    let err = op.object("not_exist").sample(5, 10).await.unwrap_err();
+    assert_eq!(err.kind(), Kind::ObjectNotExist);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sample_offsets() -> Result<()> {
+    let mock = MockAccessor::new();
+    let mut meta = Metadata::default();
+    meta.set_mode(ObjectMode::FILE).set_content_length(1000);
+    mock.push_stat(Ok(meta));
+    for _ in 0..3 {
+        mock.push_read(Ok(ObjectReader::new(Box::new(Cursor::new(vec![0; 4])))));
+    }
+
+    let op = Operator::new(Arc::new(mock.clone()));
+    let bs = op.object("test").sample(3, 4).await?;
+    assert_eq!(bs.len(), 12);
+
+    let mut ranges: Vec<_> = mock.reads().iter().map(|v| (v.offset, v.size)).collect();
+    ranges.sort();
+    assert_eq!(
+        ranges,
+        vec![
+            (Some(0), Some(4)),
+            (Some(498), Some(4)),
+            (Some(996), Some(4))
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_range_concurrent() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let content: Vec<u8> = (0..10_000).map(|v| v as u8).collect();
+    op.object("test")
+        .writer()
+        .write_bytes(content.clone())
+        .await?;
+
+    for (part_size, concurrency) in [(1, 8), (999, 1), (1000, 4), (20_000, 2)] {
+        let mut buf = Vec::new();
+        let n = op
+            .object("test")
+            .fetch_range_concurrent(&mut buf, part_size, concurrency)
+            .await?;
+        assert_eq!(n, 10_000);
+        assert_eq!(buf, content, "part size {part_size}");
+    }
+
+    op.object("empty").writer().write_bytes(vec![]).await?;
+    let mut buf = Vec::new();
+    assert_eq!(
+        op.object("empty")
+            .fetch_range_concurrent(&mut buf, 1024, 4)
+            .await?,
+        0
+    );
+
+    let err = op
+        .object("test")
+        .fetch_range_concurrent(&mut buf, 0, 4)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), Kind::Unsupported);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_range_concurrent_changed() -> Result<()> {
+    let mock = MockAccessor::new();
+    let mut meta = Metadata::default();
+    meta.set_mode(ObjectMode::FILE)
+        .set_content_length(8)
+        .set_etag("\"a\"");
+    let mut changed = Metadata::default();
+    changed.set_etag("\"b\"");
+    mock.push_stat(Ok(meta)).push_read(Ok(
+        ObjectReader::new(Box::new(Cursor::new(vec![0; 4]))).with_metadata(changed)
+    ));
+
+    let op = Operator::new(Arc::new(mock.clone()));
+    let mut buf = Vec::new();
+    let err = op
+        .object("test")
+        .fetch_range_concurrent(&mut buf, 4, 1)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("object changed"), "{err}");
+    // Parts are pinned to the object learned by stat.
+    assert!(buf.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fetch_to_path_concurrent() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let content: Vec<u8> = (0..10_000).map(|v| v as u8).collect();
+    op.object("test")
+        .writer()
+        .write_bytes(content.clone())
+        .await?;
+
+    let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    let local = dir.join("test");
+    let n = op
+        .object("test")
+        .fetch_to_path_concurrent(&local, 999, 4)
+        .await?;
+    assert_eq!(n, 10_000);
+    assert_eq!(std::fs::read(&local)?, content);
+    // No temp files are left.
+    assert_eq!(std::fs::read_dir(&dir)?.count(), 1);
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+fn mock_entry(acc: Arc<dyn Accessor>, path: &str, mode: ObjectMode) -> Object {
+    let mut o = Object::new(acc, path);
+    o.metadata_mut().set_path(path).set_mode(mode);
+    o
+}
+
+#[tokio::test]
+async fn test_delete_recursive_partial_failure() -> Result<()> {
+    let mock = MockAccessor::new();
+    let acc: Arc<dyn Accessor> = Arc::new(mock.clone());
+    let entries: Vec<OpResult<Object>> = vec![
+        Ok(mock_entry(acc.clone(), "dir/a", ObjectMode::FILE)),
+        Ok(mock_entry(acc.clone(), "dir/b", ObjectMode::FILE)),
+        Ok(mock_entry(acc.clone(), "dir/c", ObjectMode::FILE)),
+    ];
+    mock.push_list(Ok(Box::new(futures::stream::iter(entries))))
+        .push_delete(Ok(()))
+        .push_delete(Err(Error::Object {
+            kind: Kind::ObjectPermissionDenied,
+            op: "delete",
+            path: "dir/b".to_string(),
+            source: anyhow!("injected"),
+        }))
+        .push_delete(Ok(()))
+        .push_delete(Ok(()));
+
+    let op = Operator::new(acc);
+    let summary = op.object("dir/").delete_recursive().await;
+
+    assert!(!summary.is_success());
+    assert_eq!(summary.succeeded, vec!["dir/a", "dir/c", "dir/"]);
+    assert_eq!(summary.failed.len(), 1);
+    assert_eq!(summary.failed[0].0, "dir/b");
+    assert_eq!(summary.failed[0].1.kind(), Kind::ObjectPermissionDenied);
+    // Keep going after the failure.
+    assert_eq!(mock.calls("delete"), 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_recursive_retry_not_empty() -> Result<()> {
+    let mock = MockAccessor::new();
+    let acc: Arc<dyn Accessor> = Arc::new(mock.clone());
+    let first: Vec<OpResult<Object>> = vec![Ok(mock_entry(acc.clone(), "dir/a", ObjectMode::FILE))];
+    // `dir/b` is written while we are deleting.
+    let second: Vec<OpResult<Object>> =
+        vec![Ok(mock_entry(acc.clone(), "dir/b", ObjectMode::FILE))];
+    mock.push_list(Ok(Box::new(futures::stream::iter(first))))
+        .push_list(Ok(Box::new(futures::stream::iter(second))))
+        // `dir/a` has been deleted by others.
+        .push_delete(Err(Error::Object {
+            kind: Kind::ObjectNotExist,
+            op: "delete",
+            path: "dir/a".to_string(),
+            source: anyhow!("injected"),
+        }))
+        .push_delete(Err(Error::Object {
+            kind: Kind::Unexpected,
+            op: "delete",
+            path: "dir/".to_string(),
+            source: anyhow!("directory not empty"),
+        }))
+        .push_delete(Ok(()))
+        .push_delete(Ok(()));
+
+    let op = Operator::new(acc);
+    let summary = op.object("dir/").delete_recursive().await;
+
+    assert!(summary.is_success(), "{:?}", summary.failed);
+    assert_eq!(summary.succeeded, vec!["dir/a", "dir/b", "dir/"]);
+    assert_eq!(mock.calls("list"), 2);
+    assert_eq!(mock.calls("delete"), 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_recursive_not_retry_failed_children() -> Result<()> {
+    let mock = MockAccessor::new();
+    let acc: Arc<dyn Accessor> = Arc::new(mock.clone());
+    let entries: Vec<OpResult<Object>> =
+        vec![Ok(mock_entry(acc.clone(), "dir/a", ObjectMode::FILE))];
+    mock.push_list(Ok(Box::new(futures::stream::iter(entries))))
+        .push_delete(Err(Error::Object {
+            kind: Kind::ObjectPermissionDenied,
+            op: "delete",
+            path: "dir/a".to_string(),
+            source: anyhow!("injected"),
+        }))
+        .push_delete(Err(Error::Object {
+            kind: Kind::Unexpected,
+            op: "delete",
+            path: "dir/".to_string(),
+            source: anyhow!("directory not empty"),
+        }));
+
+    let op = Operator::new(acc);
+    let summary = op.object("dir/").delete_recursive().await;
+
+    let failed: Vec<_> = summary.failed.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(failed, vec!["dir/a", "dir/"]);
+    assert_eq!(mock.calls("list"), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_copy_recursive_partial_failure() -> Result<()> {
+    let mock = MockAccessor::new();
+    let acc: Arc<dyn Accessor> = Arc::new(mock.clone());
+    let entries: Vec<OpResult<Object>> = vec![
+        Ok(mock_entry(acc.clone(), "dir/a", ObjectMode::FILE)),
+        Ok(mock_entry(acc.clone(), "dir/sub/", ObjectMode::DIR)),
+        Ok(mock_entry(acc.clone(), "dir/b", ObjectMode::FILE)),
+    ];
+    let sub: Vec<OpResult<Object>> =
+        vec![Ok(mock_entry(acc.clone(), "dir/sub/c", ObjectMode::FILE))];
+    mock.push_list(Ok(Box::new(futures::stream::iter(entries))))
+        .push_list(Ok(Box::new(futures::stream::iter(sub))))
+        .push_copy(Ok(()))
+        .push_copy(Err(Error::Object {
+            kind: Kind::ObjectPermissionDenied,
+            op: "copy",
+            path: "dir/".to_string(),
+            source: anyhow!("injected"),
+        }))
+        .push_copy(Ok(()));
+
+    let op = Operator::new(acc);
+    let summary = op.object("dir/").copy_recursive("backup/").await;
+
+    let copies = mock.copies();
+    let mut targets: Vec<_> = copies
+        .iter()
+        .map(|c| (c.from.as_str(), c.to.as_str()))
+        .collect();
+    targets.sort();
+    assert_eq!(
+        targets,
+        vec![
+            ("dir/a", "backup/a"),
+            ("dir/b", "backup/b"),
+            ("dir/sub/c", "backup/sub/c")
+        ]
+    );
+    // Keep going after the failure, which is the second copy sent.
+    assert_eq!(summary.succeeded.len(), 2);
+    assert_eq!(summary.failed.len(), 1);
+    assert_eq!(summary.failed[0].0, copies[1].from);
+    assert_eq!(summary.failed[0].1.kind(), Kind::ObjectPermissionDenied);
+
+    // A dir can't be copied into a file.
+    let summary = op.object("dir/").copy_recursive("backup").await;
+    assert_eq!(summary.failed[0].1.kind(), Kind::Unsupported);
+    assert_eq!(mock.calls("copy"), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_recursive() -> Result<()> {
+    let root = std::env::temp_dir().join(format!("opendal-{}", uuid::Uuid::new_v4()));
+    let op = Operator::new(
+        fs::Backend::build()
+            .root(&root.to_string_lossy())
+            .finish()
+            .await?,
+    );
+    for path in ["dir/a", "dir/sub/b", "dir/sub/deep/c", "other"] {
+        op.object(path).writer().write_bytes(vec![0; 4]).await?;
+    }
+
+    let summary = op.object("dir/").delete_recursive().await;
+    assert!(summary.is_success(), "{:?}", summary.failed);
+    let mut succeeded = summary.succeeded.clone();
+    succeeded.sort();
+    assert_eq!(
+        succeeded,
+        vec![
+            "dir/",
+            "dir/a",
+            "dir/sub/",
+            "dir/sub/b",
+            "dir/sub/deep/",
+            "dir/sub/deep/c"
+        ]
+    );
+    assert!(!op.object("dir/").is_exist().await?);
+    assert!(op.object("other").is_exist().await?);
+
+    // Single file works as delete.
+    let summary = op.object("other").delete_recursive().await;
+    assert_eq!(summary.succeeded, vec!["other"]);
+    assert!(!op.object("other").is_exist().await?);
+
+    std::fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_if_changed_unchanged() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let o = op.object("test");
+    o.writer().write_bytes(b"Hello, World!".to_vec()).await?;
+    let before = o.metadata().await?;
+
+    let res = o.write_if_changed(b"Hello, World!".to_vec()).await?;
+    assert_eq!(res, WriteResult::Unchanged);
+    // Nothing written, the version stays the same.
+    assert_eq!(o.metadata().await?.version_id(), before.version_id());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_if_changed_changed() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let o = op.object("test");
+    o.writer().write_bytes(b"Hello, World!".to_vec()).await?;
+
+    let res = o.write_if_changed(b"Hello, OpenDAL!".to_vec()).await?;
+    assert_eq!(res, WriteResult::Written(15));
+    let mut buf = Vec::new();
+    o.reader().read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"Hello, OpenDAL!");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_if_changed_missing() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let o = op.object("test");
+
+    let res = o.write_if_changed(b"Hello, World!".to_vec()).await?;
+    assert_eq!(res, WriteResult::Written(13));
+    assert!(o.is_exist().await?);
+
+    Ok(())
+}
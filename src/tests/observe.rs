@@ -0,0 +1,205 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::ready;
+use futures::AsyncReadExt;
+use futures::AsyncWrite;
+use tokio::time::Instant;
+
+use crate::ops::Operation;
+use crate::services::memory;
+use crate::transfer::read_ahead_depth;
+use crate::Operator;
+
+#[tokio::test]
+async fn test_observations() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("test").writer().write_bytes(vec![0; 16]).await?;
+    op.object("test").metadata().await?;
+    let mut buf = Vec::new();
+    op.object("test").reader().read_to_end(&mut buf).await?;
+
+    let obs = op.observations();
+    let samples: Vec<_> = obs.samples().iter().map(|v| (v.op, v.bytes)).collect();
+    assert_eq!(
+        samples,
+        vec![
+            (Operation::Write, 16),
+            (Operation::Stat, 0),
+            (Operation::Read, 16)
+        ]
+    );
+    assert!(obs.latency(Operation::Read, 0.5).is_some());
+    assert!(obs.latency(Operation::List, 0.5).is_none());
+    assert!(obs.throughput(Operation::Stat, 0.5).is_none());
+
+    // Clones share the observations.
+    op.clone().set_observations_enabled(false);
+    op.object("test").metadata().await?;
+    assert_eq!(op.observations().samples().len(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_observations_read_dropped() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("test").writer().write_bytes(vec![0; 16]).await?;
+
+    let mut r = op.object("test").reader().buffer(0);
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf).await?;
+    // Not recorded until reaching EOF or dropped.
+    assert_eq!(op.observations().samples().len(), 1);
+    drop(r);
+
+    let obs = op.observations();
+    let last = obs.samples().last().expect("must have sample");
+    assert_eq!((last.op, last.bytes), (Operation::Read, 4));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_observations_percentile() -> Result<()> {
+    let mut builder = memory::Backend::build();
+    builder.latency(Duration::from_millis(100));
+    let op = Operator::new(builder.finish().await?);
+    for _ in 0..10 {
+        op.object("test").is_exist().await?;
+    }
+
+    let obs = op.observations();
+    assert_eq!(
+        obs.latency(Operation::Stat, 0.99),
+        Some(Duration::from_millis(100))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_read_ahead_depth() {
+    let ms = Duration::from_millis;
+    // Fast consumers read ahead as much as possible.
+    assert_eq!(read_ahead_depth(ms(100), Duration::ZERO, 8), 8);
+    // One part is fetched while the consumer drains another.
+    assert_eq!(read_ahead_depth(ms(100), ms(100), 8), 2);
+    assert_eq!(read_ahead_depth(ms(100), ms(30), 8), 4);
+    // Slow consumers don't need parts ahead.
+    assert_eq!(read_ahead_depth(ms(100), ms(200), 8), 1);
+    assert_eq!(read_ahead_depth(Duration::ZERO, Duration::ZERO, 8), 1);
+}
+
+/// A writer which takes `delay` for every write.
+struct SlowWriter {
+    buf: Vec<u8>,
+    delay: Duration,
+    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl SlowWriter {
+    fn new(delay: Duration) -> Self {
+        Self {
+            buf: Vec::new(),
+            delay,
+            sleep: None,
+        }
+    }
+}
+
+impl AsyncWrite for SlowWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let delay = self.delay;
+        let sleep = self
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(delay)));
+        ready!(sleep.as_mut().poll(cx));
+        self.sleep = None;
+        self.buf.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+async fn latency_operator(content: &[u8]) -> Result<Operator> {
+    let mut builder = memory::Backend::build();
+    builder.latency(Duration::from_millis(100));
+    let op = Operator::new(builder.finish().await?);
+    op.object("test")
+        .writer()
+        .write_bytes(content.to_vec())
+        .await?;
+    Ok(op)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_fetch_range_adaptive_fast_consumer() -> Result<()> {
+    let content: Vec<u8> = (0..8 * 1024).map(|i| i as u8).collect();
+    let op = latency_operator(&content).await?;
+
+    let start = Instant::now();
+    let mut buf = Vec::new();
+    let n = op
+        .object("test")
+        .fetch_range_adaptive(&mut buf, 1024, 8)
+        .await?;
+    assert_eq!(n, content.len() as u64);
+    assert_eq!(buf, content);
+    // stat, the first part, then all the others at the same time.
+    assert_eq!(start.elapsed(), Duration::from_millis(300));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_fetch_range_adaptive_slow_consumer() -> Result<()> {
+    let content: Vec<u8> = (0..4 * 1024).map(|i| i as u8).collect();
+    let op = latency_operator(&content).await?;
+
+    let start = Instant::now();
+    let mut w = SlowWriter::new(Duration::from_millis(200));
+    op.object("test")
+        .fetch_range_adaptive(&mut w, 1024, 4)
+        .await?;
+    assert_eq!(w.buf, content);
+    // Draining is slower than fetching, so parts are fetched one by one:
+    // stat, then 4 parts of fetching and draining.
+    assert_eq!(start.elapsed(), Duration::from_millis(100 + 4 * 300));
+
+    // Without observations, all parts are fetched ahead.
+    op.set_observations_enabled(false);
+    let start = Instant::now();
+    let mut w = SlowWriter::new(Duration::from_millis(200));
+    op.object("test")
+        .fetch_range_adaptive(&mut w, 1024, 4)
+        .await?;
+    assert_eq!(w.buf, content);
+    assert_eq!(start.elapsed(), Duration::from_millis(100 + 100 + 4 * 200));
+    Ok(())
+}
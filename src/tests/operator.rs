@@ -0,0 +1,318 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use futures::AsyncReadExt;
+use futures::FutureExt;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::layers::InMemoryCacheLayer;
+use crate::ops::Operation;
+use crate::register_scheme;
+use crate::services::memory;
+use crate::Accessor;
+use crate::AccessorBuilder;
+use crate::Operator;
+
+/// Builder that counts how many times the backend has been constructed.
+struct CountFinish {
+    finished: Arc<AtomicUsize>,
+    fail: bool,
+}
+
+#[async_trait]
+impl AccessorBuilder for CountFinish {
+    async fn finish(&mut self) -> Result<Arc<dyn Accessor>> {
+        self.finished.fetch_add(1, Ordering::SeqCst);
+
+        if self.fail {
+            return Err(Error::Backend {
+                kind: Kind::BackendConfigurationInvalid,
+                context: Default::default(),
+                source: anyhow!("config load failed"),
+            });
+        }
+        memory::Backend::build().finish().await
+    }
+}
+
+#[tokio::test]
+async fn test_lazy() -> anyhow::Result<()> {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let op = Operator::lazy(CountFinish {
+        finished: finished.clone(),
+        fail: false,
+    });
+
+    // Nothing happens before the first operation.
+    let o = op.object("test_file");
+    assert_eq!(finished.load(Ordering::SeqCst), 0);
+
+    o.writer().write_bytes(b"Hello, world!".to_vec()).await?;
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+
+    // Backend should be cached.
+    let meta = op.object("test_file").metadata().await?;
+    assert_eq!(meta.content_length(), 13);
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lazy_error_on_first_use() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let op = Operator::lazy(CountFinish {
+        finished: finished.clone(),
+        fail: true,
+    });
+    assert_eq!(finished.load(Ordering::SeqCst), 0);
+
+    let err = op.object("test_file").metadata().await.unwrap_err();
+    assert_eq!(err.kind(), Kind::BackendConfigurationInvalid);
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_stats() -> anyhow::Result<()> {
+    let op =
+        Operator::new(memory::Backend::build().finish().await?).layer(InMemoryCacheLayer::new());
+
+    op.object("a").writer().write_bytes(vec![1; 100]).await?;
+    op.object("b").append(vec![2; 30]).await?;
+    op.object("b").append(vec![3; 20]).await?;
+
+    let mut buf = Vec::new();
+    op.object("a")
+        .range_reader(10, 50)
+        .read_to_end(&mut buf)
+        .await?;
+    op.object("b").reader().read_to_end(&mut buf).await?;
+    // Served by the cache, nothing will be read from the backend.
+    op.object("b").reader().read_to_end(&mut buf).await?;
+    assert_eq!(buf.len(), 150);
+
+    op.object("a").metadata().await?;
+    op.object("a").delete().await?;
+
+    // Clones share the same counters.
+    let stats = op.clone().stats();
+    assert_eq!(stats.bytes_written(), 150);
+    assert_eq!(stats.bytes_read(), 100);
+    assert_eq!(stats.requests(Operation::Write), 1);
+    assert_eq!(stats.requests(Operation::Append), 2);
+    assert_eq!(stats.requests(Operation::Read), 2);
+    assert_eq!(stats.requests(Operation::Stat), 1);
+    assert_eq!(stats.requests(Operation::Delete), 1);
+    assert_eq!(stats.total_requests(), 7);
+
+    op.reset_stats();
+    let stats = op.stats();
+    assert_eq!(stats.bytes_read(), 0);
+    assert_eq!(stats.bytes_written(), 0);
+    assert_eq!(stats.total_requests(), 0);
+
+    Ok(())
+}
+
+/// Register a scheme which records the configs it received.
+fn register_recording_scheme(scheme: &str) -> Arc<Mutex<Vec<HashMap<String, String>>>> {
+    let configs = Arc::new(Mutex::new(Vec::new()));
+    let recorded = configs.clone();
+    register_scheme(scheme, move |config| {
+        recorded.lock().unwrap().push(config);
+        async { memory::Backend::build().finish().await }.boxed()
+    })
+    .expect("register must succeed");
+    configs
+}
+
+#[tokio::test]
+async fn test_from_uri_registered_scheme() -> Result<()> {
+    let configs = register_recording_scheme("acme-uri");
+
+    let op = Operator::from_uri("ACME-URI://cluster-1/root/dir?region=west&debug").await?;
+    op.object("test")
+        .writer()
+        .write_bytes(b"Hello".to_vec())
+        .await?;
+    assert!(op.object("test").is_exist().await?);
+
+    let configs = configs.lock().unwrap();
+    assert_eq!(
+        configs[0],
+        HashMap::from([
+            ("host".to_string(), "cluster-1".to_string()),
+            ("root".to_string(), "/root/dir".to_string()),
+            ("region".to_string(), "west".to_string()),
+            ("debug".to_string(), "".to_string()),
+        ])
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_from_env_registered_scheme() -> Result<()> {
+    let configs = register_recording_scheme("acme-env");
+    std::env::set_var("OPENDAL_ACME-ENV_ACCESS_KEY_ID", "ak");
+
+    Operator::from_env("acme-env").await?;
+    assert_eq!(
+        configs.lock().unwrap()[0],
+        HashMap::from([("access_key_id".to_string(), "ak".to_string())])
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_register_scheme_conflicts() -> Result<()> {
+    register_recording_scheme("acme-dup");
+    let err = register_scheme("acme-dup", |_| {
+        async { memory::Backend::build().finish().await }.boxed()
+    })
+    .unwrap_err();
+    assert_eq!(err.kind(), Kind::BackendConfigurationInvalid);
+
+    // Built-in schemes and their aliases can't be overridden.
+    for scheme in ["s3", "FS", "memory", "local"] {
+        let err = register_scheme(scheme, |_| {
+            async { memory::Backend::build().finish().await }.boxed()
+        })
+        .unwrap_err();
+        assert_eq!(err.kind(), Kind::BackendConfigurationInvalid);
+    }
+
+    let err = Operator::from_uri("unknown://bucket")
+        .await
+        .err()
+        .expect("must fail");
+    assert_eq!(err.kind(), Kind::BackendNotSupported);
+    let err = Operator::from_uri("no-scheme")
+        .await
+        .err()
+        .expect("must fail");
+    assert_eq!(err.kind(), Kind::BackendConfigurationInvalid);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_from_uri_builtin_scheme() -> Result<()> {
+    let op = Operator::from_uri("memory://").await?;
+    op.object("test")
+        .writer()
+        .write_bytes(b"Hello".to_vec())
+        .await?;
+    assert!(op.object("test").is_exist().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_try_object() -> anyhow::Result<()> {
+    let acc = memory::Backend::build().finish().await?;
+    let op = Operator::new(acc);
+
+    for (path, expected) in [
+        ("dir/file", "dir/file"),
+        ("/dir//file", "dir/file"),
+        ("//dir///", "dir/"),
+        ("", "/"),
+        ("///", "/"),
+        ("dir/.hidden", "dir/.hidden"),
+    ] {
+        assert_eq!(op.try_object(path)?.path(), expected, "path: {path:?}");
+    }
+
+    for path in ["a/../b", "./a", "a/.", "..", "a\0b", "a\nb"] {
+        let err = op
+            .try_object(path)
+            .err()
+            .unwrap_or_else(|| panic!("{path:?} must be rejected"));
+        assert_eq!(err.kind(), Kind::ObjectPathInvalid, "path: {path:?}");
+        assert!(op.try_objects(path).is_err(), "path: {path:?}");
+    }
+
+    // The normalized path is used by following operations.
+    op.try_object("/dir//file")?
+        .writer()
+        .write_bytes(b"Hello".to_vec())
+        .await?;
+    assert!(op.object("dir/file").is_exist().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_try_object_limits() -> anyhow::Result<()> {
+    let mock = crate::testing::MockAccessor::new();
+    let mut meta = crate::AccessorMetadata::default();
+    meta.set_max_path_len(8).set_max_segment_len(4);
+    mock.set_metadata(meta);
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    assert_eq!(op.try_object("//ab/cd")?.path(), "ab/cd");
+    for path in ["abcd/efgh/i", "abcde"] {
+        let err = op
+            .try_object(path)
+            .err()
+            .unwrap_or_else(|| panic!("{path:?} must be rejected"));
+        assert_eq!(err.kind(), Kind::ObjectPathInvalid, "path: {path:?}");
+    }
+    // Nothing is sent to the backend.
+    assert_eq!(mock.calls("stat"), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_dir_all() -> anyhow::Result<()> {
+    use futures::TryStreamExt;
+
+    use crate::ObjectMode;
+
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.create_dir_all("/a//b/c").await?;
+    // Creating existing dirs is fine.
+    op.create_dir_all("a/b/").await?;
+    assert_eq!(op.stats().requests(Operation::Create), 5);
+
+    for path in ["a/", "a/b/", "a/b/c/"] {
+        let meta = op.object(path).metadata().await?;
+        assert_eq!(meta.mode(), ObjectMode::DIR, "path: {path}");
+    }
+
+    // Markers of the listed dir are skipped.
+    let obs: Vec<_> = op.objects("a/b/").try_collect().await?;
+    let paths: Vec<_> = obs.iter().map(|o| o.path()).collect();
+    assert_eq!(paths, vec!["a/b/c/"]);
+    let obs: Vec<_> = op.objects("a/b/c/").try_collect().await?;
+    assert!(obs.is_empty());
+
+    let err = op.create_dir_all("a/../b").await.unwrap_err();
+    assert_eq!(err.kind(), Kind::ObjectPathInvalid);
+
+    Ok(())
+}
@@ -16,12 +16,20 @@ use crate::ops::HeaderRange;
 
 #[test]
 fn test_header_range() {
-    let h = HeaderRange::new(None, Some(1024));
+    let h = HeaderRange::new(None, Some(1024)).unwrap();
     assert_eq!(h.to_string(), "bytes=0-1023");
 
-    let h = HeaderRange::new(Some(1024), None);
+    let h = HeaderRange::new(Some(1024), None).unwrap();
     assert_eq!(h.to_string(), "bytes=1024-");
 
-    let h = HeaderRange::new(Some(1024), Some(1024));
+    let h = HeaderRange::new(Some(1024), Some(1024)).unwrap();
     assert_eq!(h.to_string(), "bytes=1024-2047");
 }
+
+#[test]
+fn test_header_range_invalid() {
+    assert!(HeaderRange::new(None, None).is_err());
+    assert!(HeaderRange::new(None, Some(0)).is_err());
+    assert!(HeaderRange::new(Some(1024), Some(0)).is_err());
+    assert!(HeaderRange::new(Some(u64::MAX), Some(2)).is_err());
+}
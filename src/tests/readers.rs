@@ -17,9 +17,16 @@ use std::time::Instant;
 
 use futures::io::copy;
 use futures::io::Cursor;
+use futures::io::SeekFrom;
+use futures::AsyncReadExt;
+use futures::AsyncSeekExt;
 use futures::StreamExt;
 
+use crate::error::Kind;
+use crate::ops::ReadOptions;
 use crate::readers::*;
+use crate::services::memory;
+use crate::Operator;
 
 #[tokio::test]
 async fn reader_stream() {
@@ -77,3 +84,252 @@ async fn observe_reader() {
     assert_eq!(n, 13);
     assert!(!read_cost.is_zero());
 }
+
+#[test]
+fn blocking_reader() {
+    use std::io::Read;
+    use std::io::Seek;
+    use std::io::SeekFrom;
+
+    use crate::services::memory;
+    use crate::Operator;
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let op = rt.block_on(async {
+        let op = Operator::new(memory::Backend::build().finish().await.unwrap());
+        op.object("test")
+            .writer()
+            .write_bytes("Hello, world!".as_bytes().to_vec())
+            .await
+            .unwrap();
+        op
+    });
+
+    let mut r = BlockingReader::new(rt.handle().clone(), op.object("test").reader());
+
+    let mut s = String::new();
+    r.read_to_string(&mut s).unwrap();
+    assert_eq!(s, "Hello, world!");
+
+    let n = r.seek(SeekFrom::Start(7)).unwrap();
+    assert_eq!(n, 7);
+    let mut s = String::new();
+    r.read_to_string(&mut s).unwrap();
+    assert_eq!(s, "world!");
+}
+
+#[tokio::test]
+async fn length_checked_reader() {
+    let (reader, check) = LengthCheckedReader::new(Box::new(Cursor::new("Hello, world!")), 13);
+    let n = copy(reader, &mut Vec::new()).await.unwrap();
+    assert_eq!(n, 13);
+    assert!(check.error("write", "test").is_none());
+
+    // Source ends before the declared size.
+    let (reader, check) = LengthCheckedReader::new(Box::new(Cursor::new("Hello, world!")), 20);
+    let err = copy(reader, &mut Vec::new()).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    let err = check.error("write", "test").unwrap();
+    assert_eq!(err.kind(), Kind::ContentLengthMismatch);
+    assert!(err.to_string().contains("expected 20 bytes, got 13"));
+
+    // Source produces more than the declared size.
+    let (reader, check) = LengthCheckedReader::new(Box::new(Cursor::new("Hello, world!")), 5);
+    let err = copy(reader, &mut Vec::new()).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let err = check.error("write", "test").unwrap();
+    assert_eq!(err.kind(), Kind::ContentLengthMismatch);
+    assert!(err
+        .to_string()
+        .contains("expected 5 bytes, got at least 13"));
+}
+
+#[tokio::test]
+async fn write_reader_size_mismatch() {
+    let op = Operator::new(memory::Backend::build().finish().await.unwrap());
+
+    for size in [5, 20] {
+        let err = op
+            .object("test")
+            .writer()
+            .write_reader(Box::new(Cursor::new("Hello, world!")), size)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), Kind::ContentLengthMismatch);
+    }
+    assert!(!op.object("test").is_exist().await.unwrap());
+}
+
+#[tokio::test]
+async fn digest_reader() {
+    let cases = [
+        (DigestKind::Crc32c, "123456789", "e3069283"),
+        (
+            DigestKind::Sha256,
+            "abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+    ];
+
+    for (kind, content, expected) in cases {
+        let (mut r, handle) = DigestReader::new(Box::new(Cursor::new(content)), kind);
+        // Read byte by byte to cover incremental hashing.
+        let mut buf = [0; 1];
+        assert_eq!(r.read(&mut buf).await.unwrap(), 1);
+        assert!(handle.digest().is_none(), "digest must wait for EOF");
+
+        copy(&mut r, &mut futures::io::sink()).await.unwrap();
+        let digest = handle.digest().expect("digest must be ready");
+        assert_eq!(digest.kind(), kind);
+        assert_eq!(digest.to_string(), expected);
+    }
+}
+
+#[cfg(feature = "digest-xxh3")]
+#[tokio::test]
+async fn digest_reader_xxh3() {
+    let (mut r, handle) = DigestReader::new(Box::new(Cursor::new("")), DigestKind::Xxh3);
+    copy(&mut r, &mut futures::io::sink()).await.unwrap();
+    let digest = handle.digest().expect("digest must be ready");
+    assert_eq!(digest.kind(), DigestKind::Xxh3);
+    assert_eq!(digest.to_string(), "2d06800538d394c2");
+}
+
+#[tokio::test]
+async fn reader_with_digest() -> anyhow::Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    op.object("test")
+        .writer()
+        .write_bytes(b"123456789".to_vec())
+        .await?;
+
+    let mut r = op.object("test").reader().with_digest(DigestKind::Crc32c);
+    copy(&mut r, &mut futures::io::sink()).await?;
+    assert_eq!(
+        r.digest().expect("digest must be ready").to_string(),
+        "e3069283"
+    );
+
+    let mut r = op
+        .object("test")
+        .reader_with(ReadOptions::new().with_digest(DigestKind::Sha256));
+    copy(&mut r, &mut futures::io::sink()).await?;
+    assert_eq!(
+        r.digest().expect("digest must be ready").to_string(),
+        "15e2b0d3c33891ebb0f1ef609ec419420c20e320ce94c65fbc8c3312448eb225"
+    );
+
+    // Seeking discards the digest.
+    let mut r = op.object("test").reader().with_digest(DigestKind::Crc32c);
+    r.seek(SeekFrom::Start(1)).await?;
+    copy(&mut r, &mut futures::io::sink()).await?;
+    assert!(r.digest().is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn writer_with_digest() -> anyhow::Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    let written = op
+        .object("bytes")
+        .writer()
+        .digest(DigestKind::Crc32c)
+        .finish_bytes(b"123456789".to_vec())
+        .await?;
+    assert_eq!(written.size, 9);
+    assert_eq!(
+        written.digest.expect("digest must be set").to_string(),
+        "e3069283"
+    );
+
+    let written = op
+        .object("reader")
+        .writer()
+        .digest(DigestKind::Crc32c)
+        .finish_reader(Box::new(Cursor::new(b"123456789".to_vec())), 9)
+        .await?;
+    assert_eq!(written.size, 9);
+    assert_eq!(
+        written.digest.expect("digest must be set").to_string(),
+        "e3069283"
+    );
+
+    let written = op
+        .object("none")
+        .writer()
+        .finish_bytes(b"123456789".to_vec())
+        .await?;
+    assert!(written.digest.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn compress_algorithm_from_path() {
+    let cases = [
+        ("a.gz", Some(CompressAlgorithm::Gzip)),
+        ("dir/a.tar.GZ", Some(CompressAlgorithm::Gzip)),
+        ("a.gzip", Some(CompressAlgorithm::Gzip)),
+        ("a.zst", Some(CompressAlgorithm::Zstd)),
+        ("a.zstd", Some(CompressAlgorithm::Zstd)),
+        ("a.txt", None),
+        ("a", None),
+        (".gz", None),
+        ("dir.gz/a", None),
+        ("dir.gz/", None),
+    ];
+    for (path, expected) in cases {
+        assert_eq!(CompressAlgorithm::from_path(path), expected, "{}", path);
+    }
+}
+
+#[tokio::test]
+async fn auto_reader() -> anyhow::Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    // Plain files are read as is.
+    op.object("test.txt")
+        .writer()
+        .write_bytes(b"Hello, World!".to_vec())
+        .await?;
+    let mut buf = Vec::new();
+    op.object("test.txt")
+        .auto_reader()?
+        .read_to_end(&mut buf)
+        .await?;
+    assert_eq!(buf, b"Hello, World!");
+
+    // Fixtures are compressed by `gzip -9 -n` and `zstd -19`.
+    let expected: String = (0..1000)
+        .map(|i| format!("line {i}: Hello, World!\n"))
+        .collect();
+    let fixtures: [(&str, &[u8]); 3] = [
+        ("hello.txt.gz", include_bytes!("fixtures/hello.txt.gz")),
+        ("hello.txt.zst", include_bytes!("fixtures/hello.txt.zst")),
+        ("HELLO.TXT.GZ", include_bytes!("fixtures/hello.txt.gz")),
+    ];
+    for (path, bs) in fixtures {
+        op.object(path).writer().write_bytes(bs.to_vec()).await?;
+
+        let mut buf = Vec::new();
+        op.object(path).auto_reader()?.read_to_end(&mut buf).await?;
+        assert_eq!(String::from_utf8(buf)?, expected, "{}", path);
+    }
+
+    // Corrupted content fails the read instead of being returned as is.
+    op.object("broken.gz")
+        .writer()
+        .write_bytes(b"Hello, World!".to_vec())
+        .await?;
+    let err = op
+        .object("broken.gz")
+        .auto_reader()?
+        .read_to_end(&mut buf)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+
+use anyhow::Result;
+use futures::AsyncReadExt;
+use futures::TryStreamExt;
+
+use crate::services::tempfs;
+use crate::Operator;
+
+/// Returns all entries under `dir`.
+fn entries(dir: &str) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        names.push(entry?.file_name().to_string_lossy().to_string());
+    }
+    Ok(names)
+}
+
+#[tokio::test]
+async fn test_tempfs_removed_on_drop() -> Result<()> {
+    let parent = format!("/tmp/{}", uuid::Uuid::new_v4());
+    fs::create_dir_all(&parent)?;
+
+    let mut builder = tempfs::Builder::default();
+    builder.parent(&parent).prefix("test-");
+    let op = Operator::new(builder.finish().await?);
+
+    let names = entries(&parent)?;
+    assert_eq!(names.len(), 1);
+    assert!(names[0].starts_with("test-"), "{:?}", names);
+
+    op.object("a").writer().write_bytes(b"a".to_vec()).await?;
+    op.object("dir/b")
+        .writer()
+        .write_bytes(b"b".to_vec())
+        .await?;
+
+    // Clones share the same temp dir.
+    let cloned = op.clone();
+    drop(op);
+    assert_eq!(entries(&parent)?.len(), 1);
+    assert!(cloned.object("dir/b").is_exist().await?);
+
+    drop(cloned);
+    assert!(entries(&parent)?.is_empty());
+
+    fs::remove_dir(&parent)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tempfs_kept_by_listed_objects() -> Result<()> {
+    let parent = format!("/tmp/{}", uuid::Uuid::new_v4());
+    fs::create_dir_all(&parent)?;
+
+    let mut builder = tempfs::Builder::default();
+    builder.parent(&parent);
+    let op = Operator::new(builder.finish().await?);
+    op.object("a")
+        .writer()
+        .write_bytes(b"Hello".to_vec())
+        .await?;
+
+    let objects: Vec<_> = op.objects("").try_collect().await?;
+    drop(op);
+
+    // Listed objects still work after the operator is dropped.
+    let mut buf = String::new();
+    objects[0].reader().read_to_string(&mut buf).await?;
+    assert_eq!(buf, "Hello");
+
+    drop(objects);
+    assert!(entries(&parent)?.is_empty());
+
+    fs::remove_dir(&parent)?;
+    Ok(())
+}
@@ -0,0 +1,114 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use futures::io::Cursor;
+use futures::AsyncReadExt;
+use futures::TryStreamExt;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::services::memory;
+use crate::testing::bench::gen_content;
+use crate::testing::bench::BenchFixture;
+use crate::testing::bench::Workload;
+use crate::testing::MockAccessor;
+use crate::ObjectReader;
+use crate::Operator;
+
+#[tokio::test]
+async fn test_mock_read_failure_then_success() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_read(Err(Error::Object {
+        kind: Kind::ObjectPermissionDenied,
+        op: "read",
+        path: "test".to_string(),
+        source: anyhow!("injected"),
+    }))
+    .push_read(Ok(ObjectReader::new(Box::new(Cursor::new(
+        b"Hello, World!".to_vec(),
+    )))));
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let mut buf = Vec::new();
+    let err = op
+        .object("test")
+        .reader()
+        .read_to_end(&mut buf)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+
+    op.object("test").reader().read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"Hello, World!");
+    assert_eq!(mock.calls("read"), 2);
+
+    // No more programmed responses.
+    let err = op
+        .object("test")
+        .reader()
+        .read_to_end(&mut buf)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Other);
+    assert_eq!(mock.calls("read"), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_write_records_data() -> Result<()> {
+    let mock = MockAccessor::new();
+    mock.push_write(Ok(13));
+    let op = Operator::new(Arc::new(mock.clone()));
+
+    let n = op
+        .object("test")
+        .writer()
+        .write_bytes(b"Hello, World!".to_vec())
+        .await?;
+    assert_eq!(n, 13);
+
+    let writes = mock.writes();
+    assert_eq!(writes.len(), 1);
+    assert_eq!(writes[0].0.path, "test");
+    assert_eq!(writes[0].1, b"Hello, World!");
+    assert_eq!(mock.calls("write"), 1);
+    assert_eq!(mock.calls("stat"), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bench_workloads() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let fixture = BenchFixture::new(op.clone());
+
+    for workload in Workload::all() {
+        let bench = fixture.prepare(workload).await?;
+        bench.measure(3).await?;
+    }
+    assert_eq!(gen_content(7, 100), gen_content(7, 100));
+    assert_ne!(gen_content(7, 100), gen_content(8, 100));
+
+    // Nothing is left after cleanup.
+    fixture.cleanup().await?;
+    let left: Vec<_> = op.objects("").try_collect().await?;
+    assert!(left.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::services::memory;
+use crate::tmp::TempDir;
+use crate::tmp::TempObject;
+use crate::Object;
+use crate::Operator;
+
+/// Wait for the cleanup spawned by `Drop` to remove `o`.
+async fn wait_deleted(o: &Object) -> Result<()> {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while o.is_exist().await? {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        Ok(())
+    })
+    .await?
+}
+
+#[tokio::test]
+async fn test_temp_object_close() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    let mut tmp = TempObject::new(&op, "scratch/");
+    assert!(tmp.path().starts_with("scratch/"));
+    assert_ne!(tmp.path(), TempObject::new(&op, "scratch/").path());
+
+    tmp.object().writer().write_bytes(vec![0; 4]).await?;
+    let o = tmp.object().clone();
+    assert!(o.is_exist().await?);
+
+    tmp.close().await?;
+    assert!(!o.is_exist().await?);
+    // Closing again is harmless.
+    tmp.close().await?;
+
+    // Temp objects that have never been written can be closed too.
+    TempObject::new(&op, "scratch/").close().await?;
+    Ok(())
+}
+
+#[test]
+fn test_temp_object_drop() -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let op = rt.block_on(memory::Backend::build().finish())?;
+    let op = Operator::new(op);
+
+    let tmp = TempObject::new(&op, "scratch/");
+    let o = tmp.object().clone();
+    rt.block_on(o.writer().write_bytes(vec![0; 4]))?;
+
+    // Dropped with a runtime handle entered, but not in an async context.
+    let guard = rt.enter();
+    drop(tmp);
+    drop(guard);
+
+    rt.block_on(wait_deleted(&o))
+}
+
+#[test]
+fn test_temp_object_drop_without_runtime() -> Result<()> {
+    let rt = tokio::runtime::Runtime::new()?;
+    let op = Operator::new(rt.block_on(memory::Backend::build().finish())?);
+
+    let tmp = TempObject::new(&op, "scratch/");
+    let o = tmp.object().clone();
+    rt.block_on(o.writer().write_bytes(vec![0; 4]))?;
+
+    // Nothing to spawn the cleanup, the object is left without panic.
+    drop(tmp);
+    assert!(rt.block_on(o.is_exist())?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_temp_dir_close() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    let mut tmp = TempDir::new(&op, "scratch/");
+    assert!(tmp.path().starts_with("scratch/") && tmp.path().ends_with('/'));
+
+    let a = tmp.object("a");
+    a.writer().write_bytes(vec![0; 4]).await?;
+    let b = tmp.object("sub/b");
+    b.writer().write_bytes(vec![0; 4]).await?;
+    // Written under the dir without being tracked.
+    let c = op.object(&format!("{}c", tmp.path()));
+    c.writer().write_bytes(vec![0; 4]).await?;
+
+    tmp.close().await?;
+    for o in [&a, &b, &c] {
+        assert!(!o.is_exist().await?, "{} must be deleted", o.path());
+    }
+    tmp.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_temp_dir_drop() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    let tmp = TempDir::new(&op, "scratch/");
+    let a = tmp.object("a");
+    a.writer().write_bytes(vec![0; 4]).await?;
+    let b = tmp.object("sub/b");
+    b.writer().write_bytes(vec![0; 4]).await?;
+    drop(tmp);
+
+    wait_deleted(&a).await?;
+    wait_deleted(&b).await
+}
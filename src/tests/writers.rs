@@ -0,0 +1,224 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::stream;
+use futures::AsyncReadExt;
+use futures::TryStreamExt;
+use serde_json::json;
+
+use crate::error::Kind;
+use crate::readers::DigestKind;
+use crate::services::memory;
+use crate::Operator;
+
+#[tokio::test]
+async fn write_then_name() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    let path = op
+        .object("tmp/upload")
+        .write_then_name()
+        .write_bytes("Hello, World!".as_bytes().to_vec(), |digest| {
+            let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("cas/{}", hex)
+        })
+        .await?;
+    assert_eq!(
+        path,
+        "cas/dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f"
+    );
+
+    let mut buf = Vec::new();
+    op.object(&path).reader().read_to_end(&mut buf).await?;
+    assert_eq!(buf, b"Hello, World!");
+
+    assert!(!op.object("tmp/upload").is_exist().await?);
+
+    Ok(())
+}
+
+/// Build a reader that doesn't know its total length.
+fn unknown_length_reader(chunks: Vec<&'static [u8]>) -> crate::BoxedAsyncReader {
+    Box::new(stream::iter(chunks.into_iter().map(Ok::<_, std::io::Error>)).into_async_read())
+}
+
+#[tokio::test]
+async fn spooled_writer() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    // Exceeds the threshold, data will be spilled into temp file.
+    let r = unknown_length_reader(vec![b"Hello", b", ", b"World", b"!"]);
+    let n = op
+        .object("spooled/large")
+        .spooled_writer()
+        .threshold(4)
+        .write_reader(r)
+        .await?;
+    assert_eq!(n, 13);
+
+    let mut buf = Vec::new();
+    op.object("spooled/large")
+        .reader()
+        .read_to_end(&mut buf)
+        .await?;
+    assert_eq!(buf, b"Hello, World!");
+
+    // Within the threshold, data will be buffered in memory.
+    let r = unknown_length_reader(vec![b"Hello"]);
+    let n = op
+        .object("spooled/small")
+        .spooled_writer()
+        .threshold(5)
+        .write_reader(r)
+        .await?;
+    assert_eq!(n, 5);
+
+    let mut buf = Vec::new();
+    op.object("spooled/small")
+        .reader()
+        .read_to_end(&mut buf)
+        .await?;
+    assert_eq!(buf, b"Hello");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn json_lines_writer() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    let records = vec![
+        json!({"level": "info", "msg": "started"}),
+        json!({"level": "warn", "msg": "slow request", "cost_ms": 1200}),
+        json!({"level": "info", "msg": "stopped"}),
+    ];
+
+    // Flush after every record.
+    let mut w = op.object("logs.jsonl").json_lines_writer().flush_size(1);
+    for record in &records {
+        w.write(record).await?;
+    }
+    w.close().await?;
+
+    let mut buf = String::new();
+    op.object("logs.jsonl")
+        .reader()
+        .read_to_string(&mut buf)
+        .await?;
+    let lines: Vec<serde_json::Value> = buf
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<std::result::Result<_, _>>()?;
+    assert_eq!(lines, records);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn json_lines_writer_invalid_record() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    // Maps with non-string keys can't be serialized into json.
+    let invalid: HashMap<(u8, u8), u8> = HashMap::from([((1, 2), 3)]);
+
+    let mut w = op.object("logs.jsonl").json_lines_writer();
+    let err = w.write(&invalid).await.expect_err("must fail");
+    assert_eq!(err.kind(), Kind::Unexpected);
+
+    w.write(&json!({"msg": "ok"})).await?;
+    w.close().await?;
+
+    let mut buf = String::new();
+    op.object("logs.jsonl")
+        .reader()
+        .read_to_string(&mut buf)
+        .await?;
+    assert_eq!(buf, "{\"msg\":\"ok\"}\n");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn partitioned_writer() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    let content: Vec<u8> = (0..10 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    let mut w = op
+        .partitioned_writer("logs/part-{n}.log")?
+        .max_part_size(1024 * 1024);
+    // Chunks not aligned to parts, so that parts are split in the middle.
+    for chunk in content.chunks(300 * 1024) {
+        w.write(chunk).await?;
+    }
+    let parts = w.close().await?;
+
+    assert_eq!(parts.len(), 10);
+    let mut written = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        assert_eq!(part.path, format!("logs/part-{:05}.log", i + 1));
+        assert_eq!(part.size, 1024 * 1024);
+
+        let mut r = op
+            .object(&part.path)
+            .reader()
+            .with_digest(DigestKind::Crc32c);
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf).await?;
+        assert_eq!(buf.len() as u64, part.size);
+        assert_eq!(r.digest(), Some(&part.digest));
+        written.extend_from_slice(&buf);
+    }
+    assert_eq!(written, content);
+
+    // No empty part is created after the last full one.
+    assert!(!op.object("logs/part-00011.log").is_exist().await?);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn partitioned_writer_max_age() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    let mut w = op
+        .partitioned_writer("logs/{n}")?
+        .max_part_age(Duration::from_secs(60));
+    w.write(b"a").await?;
+    w.write(b"b").await?;
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    // The part is rolled over at the next write.
+    w.write(b"c").await?;
+    assert_eq!(w.parts().len(), 1);
+
+    let parts = w.close().await?;
+    let sizes: Vec<_> = parts.iter().map(|p| (p.path.as_str(), p.size)).collect();
+    assert_eq!(sizes, vec![("logs/00001", 2), ("logs/00002", 1)]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn partitioned_writer_invalid_template() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+
+    let err = op.partitioned_writer("logs/part").err().unwrap();
+    assert_eq!(err.kind(), Kind::ObjectPathInvalid);
+
+    // Nothing is written if closed without writes.
+    let parts = op.partitioned_writer("logs/{n}")?.close().await?;
+    assert!(parts.is_empty());
+    Ok(())
+}
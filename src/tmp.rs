@@ -0,0 +1,212 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Scratch objects which will be removed after use, for tests and ETL jobs.
+//!
+//! # Example
+//!
+//! ```
+//! use anyhow::Result;
+//! use opendal::services::memory;
+//! use opendal::tmp::TempObject;
+//! use opendal::Operator;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let op = Operator::new(memory::Backend::build().finish().await?);
+//!
+//!     let mut tmp = TempObject::new(&op, "scratch/");
+//!     tmp.object().writer().write_bytes(vec![0; 4]).await?;
+//!     // Delete it now and get the error, or leave it to `Drop`.
+//!     tmp.close().await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use log::warn;
+
+use crate::error::Kind;
+use crate::error::Result;
+use crate::Object;
+use crate::Operator;
+
+/// A uniquely named object under a prefix, which will be deleted on
+/// [`TempObject::close`] or `Drop`.
+///
+/// Deleting on `Drop` is best-effort: it's spawned on the current tokio
+/// runtime and failures are only logged. The object will be left if
+/// dropped outside of a runtime.
+#[derive(Debug)]
+pub struct TempObject {
+    object: Object,
+    closed: bool,
+}
+
+impl TempObject {
+    /// Create a temp object whose path is `prefix` followed by a uuid.
+    ///
+    /// Nothing will be written until the returned object is written.
+    pub fn new(op: &Operator, prefix: &str) -> Self {
+        let path = format!("{}{}", prefix, uuid::Uuid::new_v4());
+        Self {
+            object: op.object(&path),
+            closed: false,
+        }
+    }
+
+    /// Returns the object to operate.
+    pub fn object(&self) -> &Object {
+        &self.object
+    }
+
+    /// Returns the generated path.
+    pub fn path(&self) -> &str {
+        self.object.path()
+    }
+
+    /// Delete the object now and return the error if failed.
+    ///
+    /// Objects that have never been written or have been removed count as
+    /// deleted. Closing more than once does nothing.
+    pub async fn close(&mut self) -> Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        delete(&self.object).await?;
+        self.closed = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempObject {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+        let object = self.object.clone();
+        spawn_cleanup(
+            object.path().to_string(),
+            async move { delete(&object).await },
+        );
+    }
+}
+
+/// A uniquely named dir under a prefix, all objects under it will be
+/// deleted on [`TempDir::close`] or `Drop`.
+///
+/// Objects created by [`TempDir::object`] are tracked and deleted one by
+/// one, then the dir is deleted recursively to catch the others written
+/// under it. Deleting on `Drop` is best-effort like [`TempObject`].
+pub struct TempDir {
+    op: Operator,
+    path: String,
+    tracked: Arc<Mutex<BTreeSet<String>>>,
+    closed: bool,
+}
+
+impl TempDir {
+    /// Create a temp dir whose path is `prefix` followed by a uuid and `/`.
+    pub fn new(op: &Operator, prefix: &str) -> Self {
+        Self {
+            op: op.clone(),
+            path: format!("{}{}/", prefix, uuid::Uuid::new_v4()),
+            tracked: Arc::default(),
+            closed: false,
+        }
+    }
+
+    /// Returns the generated path of this dir, which ends with `/`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the object at `name` under this dir, and track it for
+    /// deleting.
+    pub fn object(&self, name: &str) -> Object {
+        let path = format!("{}{}", self.path, name.trim_start_matches('/'));
+        self.tracked
+            .lock()
+            .expect("lock must succeed")
+            .insert(path.clone());
+        self.op.object(&path)
+    }
+
+    /// Delete all objects under this dir now and return the first error.
+    ///
+    /// Closing more than once does nothing.
+    pub async fn close(&mut self) -> Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        Self::cleanup(&self.op, &self.path, &self.tracked).await?;
+        self.closed = true;
+        Ok(())
+    }
+
+    async fn cleanup(op: &Operator, path: &str, tracked: &Mutex<BTreeSet<String>>) -> Result<()> {
+        let paths = std::mem::take(&mut *tracked.lock().expect("lock must succeed"));
+        for p in &paths {
+            delete(&op.object(p)).await?;
+        }
+
+        let summary = op.object(path).delete_recursive().await;
+        if let Some((_, e)) = summary.failed.into_iter().next() {
+            return Err(e);
+        }
+        delete(&op.object(path)).await
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+        let (op, path, tracked) = (self.op.clone(), self.path.clone(), self.tracked.clone());
+        spawn_cleanup(self.path.clone(), async move {
+            TempDir::cleanup(&op, &path, &tracked).await
+        });
+    }
+}
+
+/// Delete `o`, objects that don't exist count as deleted.
+async fn delete(o: &Object) -> Result<()> {
+    match o.delete().await {
+        Err(e) if e.kind() == Kind::ObjectNotExist => Ok(()),
+        r => r,
+    }
+}
+
+/// Spawn `f` on the current runtime if there is one, failures are logged.
+fn spawn_cleanup<F>(path: String, f: F)
+where
+    F: std::future::Future<Output = Result<()>> + Send + 'static,
+{
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn(async move {
+                if let Err(e) = f.await {
+                    warn!("temp object {} cleanup: {:?}", path, e);
+                }
+            });
+        }
+        Err(_) => warn!(
+            "temp object {} left: dropped outside of tokio runtime",
+            path
+        ),
+    }
+}
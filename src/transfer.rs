@@ -0,0 +1,521 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transfer data between objects and local files.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use blocking::unblock;
+use blocking::Unblock;
+use futures::future::select;
+use futures::future::Either;
+use futures::stream::FuturesOrdered;
+use futures::AsyncRead;
+use futures::AsyncReadExt;
+use futures::AsyncWrite;
+use futures::AsyncWriteExt;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use log::warn;
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::observe::Instant;
+use crate::observe::Observer;
+use crate::ops::OpRead;
+use crate::ops::Operation;
+use crate::readers::ObserveReader;
+use crate::readers::ReadEvent;
+use crate::services::fs::error::parse_io_error;
+use crate::Accessor;
+use crate::MetaField;
+use crate::Metadata;
+use crate::Reader;
+use crate::Writer;
+
+/// Buffer size used while transferring between objects and local files.
+const BUFFER_SIZE: usize = 256 * 1024;
+
+/// Max resumes of every part while fetching concurrently.
+pub(crate) const PART_RESUMES: usize = 3;
+
+/// A range of object to fetch, `reader` must read exactly `size` bytes
+/// starting at `offset`.
+pub(crate) struct Part {
+    pub offset: u64,
+    pub size: u64,
+    pub reader: Reader,
+}
+
+/// Split `total` bytes into `(offset, size)` ranges of at most `part_size`.
+pub(crate) fn split_parts(total: u64, part_size: u64) -> Vec<(u64, u64)> {
+    debug_assert!(part_size > 0, "part size must be positive");
+
+    (0..total)
+        .step_by(part_size as usize)
+        .map(|offset| (offset, part_size.min(total - offset)))
+        .collect()
+}
+
+/// Fetch `parts` with at most `concurrency` of them in flight, and write
+/// them into `w` in order.
+///
+/// Every part is buffered in memory before written, so at most
+/// `concurrency * part_size` bytes will be held.
+pub(crate) async fn fetch_parts_to_writer<W>(
+    path: &str,
+    parts: Vec<Part>,
+    total: u64,
+    concurrency: usize,
+    w: &mut W,
+) -> Result<u64>
+where
+    W: AsyncWrite + Unpin,
+{
+    let op = "fetch_range_concurrent";
+
+    let mut parts = futures::stream::iter(parts)
+        .map(|part| read_part(op, path, part))
+        .buffered(concurrency);
+
+    let mut n = 0;
+    while let Some(bs) = parts.try_next().await? {
+        w.write_all(&bs).await.map_err(|e| Error::Object {
+            kind: Kind::Unexpected,
+            op,
+            path: path.to_string(),
+            source: anyhow::Error::from(e),
+        })?;
+        n += bs.len() as u64;
+    }
+    w.flush().await.map_err(|e| Error::Object {
+        kind: Kind::Unexpected,
+        op,
+        path: path.to_string(),
+        source: anyhow::Error::from(e),
+    })?;
+
+    verify_total(op, path, total, n)?;
+    Ok(n)
+}
+
+/// Same as [`fetch_parts_to_writer`], but the number of parts in flight
+/// adapts between 1 and `max_concurrency` by [`read_ahead_depth`].
+///
+/// Without `observer`, `max_concurrency` parts will be read ahead.
+pub(crate) async fn fetch_parts_to_writer_adaptive<W>(
+    path: &str,
+    parts: Vec<Part>,
+    total: u64,
+    max_concurrency: usize,
+    observer: Option<Arc<Observer>>,
+    w: &mut W,
+) -> Result<u64>
+where
+    W: AsyncWrite + Unpin,
+{
+    let op = "fetch_range_adaptive";
+    let write_err = |e: std::io::Error| Error::Object {
+        kind: Kind::Unexpected,
+        op,
+        path: path.to_string(),
+        source: anyhow::Error::from(e),
+    };
+
+    let mut depth = if observer.is_some() {
+        1
+    } else {
+        max_concurrency
+    };
+    let mut parts = parts.into_iter();
+    let mut running = FuturesOrdered::new();
+    // Parts fetched while the consumer is draining, in order.
+    let mut fetched = VecDeque::new();
+    let mut n = 0;
+    loop {
+        while running.len() + fetched.len() < depth {
+            match parts.next() {
+                Some(part) => running.push_back(read_part(op, path, part)),
+                None => break,
+            }
+        }
+        let bs = match fetched.pop_front() {
+            Some(bs) => bs,
+            None => match running.next().await {
+                Some(bs) => bs,
+                None => break,
+            },
+        }?;
+
+        // Keep fetching parts ahead while writing.
+        let start = Instant::now();
+        let mut write = w.write_all(&bs);
+        loop {
+            match select(&mut write, running.next()).await {
+                Either::Left((r, _)) => break r.map_err(write_err)?,
+                Either::Right((Some(part), _)) => fetched.push_back(part),
+                Either::Right((None, _)) => break write.await.map_err(write_err)?,
+            }
+        }
+        n += bs.len() as u64;
+
+        let latency = observer
+            .as_ref()
+            .and_then(|v| v.snapshot().latency(Operation::Read, 0.5));
+        if let Some(latency) = latency {
+            depth = read_ahead_depth(latency, start.elapsed(), max_concurrency);
+        }
+    }
+    w.flush().await.map_err(write_err)?;
+
+    verify_total(op, path, total, n)?;
+    Ok(n)
+}
+
+/// Returns the number of parts to read ahead, so that fetching a part
+/// which takes `latency` finishes before the consumer, which takes `drain`
+/// for every part, runs out of parts.
+pub(crate) fn read_ahead_depth(latency: Duration, drain: Duration, max: usize) -> usize {
+    let drain = drain.max(Duration::from_micros(1));
+    let depth = (latency.as_nanos() / drain.as_nanos()) as usize + 1;
+    depth.clamp(1, max.max(1))
+}
+
+/// Read the whole part into memory.
+async fn read_part(op: &'static str, path: &str, mut part: Part) -> Result<Vec<u8>> {
+    let mut bs = Vec::with_capacity(part.size as usize);
+    part.reader
+        .read_to_end(&mut bs)
+        .await
+        .map_err(|e| parse_io_error(e, op, path))?;
+
+    verify_part(op, path, &part, bs.len() as u64)?;
+    Ok(bs)
+}
+
+/// Fetch `parts` with at most `concurrency` of them in flight, and write
+/// them into `local` at their offsets.
+///
+/// Same as [`fetch_to_path`], data will be written into a temp file aside
+/// `local` first, and renamed to `local` after synced and verified.
+pub(crate) async fn fetch_parts_to_path(
+    path: &str,
+    parts: Vec<Part>,
+    total: u64,
+    concurrency: usize,
+    local: &Path,
+) -> Result<u64> {
+    let op = "fetch_to_path_concurrent";
+    let local_path = local.to_string_lossy().to_string();
+
+    if let Some(parent) = local.parent() {
+        let capture_parent = parent.to_path_buf();
+        unblock(|| fs::create_dir_all(capture_parent))
+            .await
+            .map_err(|e| parse_io_error(e, op, &parent.to_string_lossy()))?;
+    }
+
+    let tmp_path = format!("{}.opendal.{}", &local_path, Uuid::new_v4());
+    let result = write_parts(path, parts, total, concurrency, &tmp_path).await;
+    let n = match result {
+        Ok(n) => n,
+        Err(e) => {
+            let capture_path = tmp_path.clone();
+            if let Err(err) = unblock(|| fs::remove_file(capture_path)).await {
+                warn!("object {} remove temp file {}: {:?}", path, &tmp_path, err);
+            }
+            return Err(e);
+        }
+    };
+
+    let capture_local = local.to_path_buf();
+    unblock(|| fs::rename(tmp_path, capture_local))
+        .await
+        .map_err(|e| parse_io_error(e, op, &local_path))?;
+
+    Ok(n)
+}
+
+/// Write all parts into a new file at `tmp_path` and sync it to disk.
+async fn write_parts(
+    path: &str,
+    parts: Vec<Part>,
+    total: u64,
+    concurrency: usize,
+    tmp_path: &str,
+) -> Result<u64> {
+    let op = "fetch_to_path_concurrent";
+
+    let capture_path = tmp_path.to_string();
+    let f = unblock(move || {
+        let f = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(capture_path)?;
+        f.set_len(total)?;
+        Ok::<_, std::io::Error>(f)
+    })
+    .await
+    .map_err(|e| parse_io_error(e, op, tmp_path))?;
+
+    let n = futures::stream::iter(parts)
+        .map(|part| write_part(path, part, tmp_path))
+        .buffer_unordered(concurrency)
+        .try_fold(0, |n, size| async move { Ok(n + size) })
+        .await?;
+    verify_total(op, path, total, n)?;
+
+    unblock(move || f.sync_all())
+        .await
+        .map_err(|e| parse_io_error(e, op, tmp_path))?;
+
+    Ok(n)
+}
+
+/// Write the part into `tmp_path` at its offset via a dedicated handle.
+async fn write_part(path: &str, mut part: Part, tmp_path: &str) -> Result<u64> {
+    let op = "fetch_to_path_concurrent";
+
+    let capture_path = tmp_path.to_string();
+    let offset = part.offset;
+    let f = unblock(move || {
+        let mut f = fs::OpenOptions::new().write(true).open(capture_path)?;
+        f.seek(SeekFrom::Start(offset))?;
+        Ok::<_, std::io::Error>(f)
+    })
+    .await
+    .map_err(|e| parse_io_error(e, op, tmp_path))?;
+    let mut f = Unblock::with_capacity(BUFFER_SIZE, f);
+
+    let n = futures::io::copy(&mut part.reader, &mut f)
+        .await
+        .map_err(|e| parse_io_error(e, op, path))?;
+    f.flush()
+        .await
+        .map_err(|e| parse_io_error(e, op, tmp_path))?;
+
+    verify_part(op, path, &part, n)?;
+    Ok(n)
+}
+
+fn verify_part(op: &'static str, path: &str, part: &Part, n: u64) -> Result<()> {
+    if n != part.size {
+        return Err(Error::Object {
+            kind: Kind::ContentLengthMismatch,
+            op,
+            path: path.to_string(),
+            source: anyhow!(
+                "part at offset {} length mismatch: expected {}, actual {}",
+                part.offset,
+                part.size,
+                n
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+fn verify_total(op: &'static str, path: &str, total: u64, n: u64) -> Result<()> {
+    if n != total {
+        return Err(Error::Object {
+            kind: Kind::ContentLengthMismatch,
+            op,
+            path: path.to_string(),
+            source: anyhow!("length mismatch: expected {}, actual {}", total, n),
+        });
+    }
+
+    Ok(())
+}
+
+/// Download object at `path` into `local`, reads the `version_id` if
+/// it's set.
+///
+/// Data will be written into a temp file aside `local` first, and renamed
+/// to `local` after synced and verified.
+pub(crate) async fn fetch_to_path<F>(
+    acc: Arc<dyn Accessor>,
+    path: &str,
+    version_id: Option<String>,
+    local: &Path,
+    f: F,
+) -> Result<u64>
+where
+    F: FnMut(ReadEvent) + Send + Unpin,
+{
+    let local_path = local.to_string_lossy().to_string();
+
+    let op = OpRead {
+        path: path.to_string(),
+        offset: None,
+        size: None,
+        version_id,
+    };
+    let (r, meta) = acc.read(&op).await?.into_parts();
+    let mut r = ObserveReader::new(r, f);
+
+    if let Some(parent) = local.parent() {
+        let capture_parent = parent.to_path_buf();
+        unblock(|| fs::create_dir_all(capture_parent))
+            .await
+            .map_err(|e| parse_io_error(e, "fetch_to_path", &parent.to_string_lossy()))?;
+    }
+
+    let tmp_path = format!("{}.opendal.{}", &local_path, Uuid::new_v4());
+    let result = match copy_to_file(&mut r, &tmp_path).await {
+        Ok((n, digest)) => verify(path, &meta, n, &digest).map(|_| n),
+        Err(e) => Err(e),
+    };
+    let n = match result {
+        Ok(n) => n,
+        Err(e) => {
+            let capture_path = tmp_path.clone();
+            if let Err(err) = unblock(|| fs::remove_file(capture_path)).await {
+                warn!("object {} remove temp file {}: {:?}", path, &tmp_path, err);
+            }
+            return Err(e);
+        }
+    };
+
+    let capture_local = local.to_path_buf();
+    unblock(|| fs::rename(tmp_path, capture_local))
+        .await
+        .map_err(|e| parse_io_error(e, "fetch_to_path", &local_path))?;
+
+    Ok(n)
+}
+
+/// Copy all data into a new file at `path` and sync it to disk.
+///
+/// Returns the bytes copied and their md5 digest.
+async fn copy_to_file<R>(r: &mut R, path: &str) -> Result<(u64, md5::Digest)>
+where
+    R: AsyncRead + Unpin,
+{
+    let capture_path = path.to_string();
+    let f = unblock(|| {
+        fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(capture_path)
+    })
+    .await
+    .map_err(|e| parse_io_error(e, "fetch_to_path", path))?;
+    let mut f = Unblock::with_capacity(BUFFER_SIZE, f);
+
+    let mut ctx = md5::Context::new();
+    let mut buf = vec![0; BUFFER_SIZE];
+    let mut n = 0;
+    loop {
+        let size = r
+            .read(&mut buf)
+            .await
+            .map_err(|e| parse_io_error(e, "fetch_to_path", path))?;
+        if size == 0 {
+            break;
+        }
+        ctx.consume(&buf[..size]);
+        f.write_all(&buf[..size])
+            .await
+            .map_err(|e| parse_io_error(e, "fetch_to_path", path))?;
+        n += size as u64;
+    }
+
+    f.flush()
+        .await
+        .map_err(|e| parse_io_error(e, "fetch_to_path", path))?;
+    let f = f.into_inner().await;
+    unblock(move || f.sync_all())
+        .await
+        .map_err(|e| parse_io_error(e, "fetch_to_path", path))?;
+
+    Ok((n, ctx.compute()))
+}
+
+/// Verify the fetched data against the metadata learned while reading.
+///
+/// Checksum will only be verified if the etag is a plain md5, which is not
+/// true for s3 multipart uploads or fs.
+fn verify(path: &str, meta: &Metadata, n: u64, digest: &md5::Digest) -> Result<()> {
+    if meta.has(MetaField::ContentLength) && meta.content_length() != n {
+        return Err(Error::Object {
+            kind: Kind::ChecksumMismatch,
+            op: "fetch_to_path",
+            path: path.to_string(),
+            source: anyhow!(
+                "length mismatch: expected {}, actual {}",
+                meta.content_length(),
+                n
+            ),
+        });
+    }
+
+    if let Some(etag) = meta.etag() {
+        let etag = etag.trim_matches('"');
+        let is_md5 = etag.len() == 32 && etag.chars().all(|c| c.is_ascii_hexdigit());
+        let actual = format!("{:x}", digest);
+        if is_md5 && !etag.eq_ignore_ascii_case(&actual) {
+            return Err(Error::Object {
+                kind: Kind::ChecksumMismatch,
+                op: "fetch_to_path",
+                path: path.to_string(),
+                source: anyhow!("md5 mismatch: expected {}, actual {}", etag, actual),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Upload the local file at `local` via writer.
+pub(crate) async fn upload_from_path<F>(w: Writer, path: &str, local: &Path, f: F) -> Result<u64>
+where
+    F: FnMut(ReadEvent) + Send + Unpin + 'static,
+{
+    let local_path = local.to_string_lossy().to_string();
+
+    let capture_local = local.to_path_buf();
+    let (file, size) = unblock(|| {
+        let f = fs::File::open(capture_local)?;
+        let size = f.metadata()?.len();
+        Ok::<_, std::io::Error>((f, size))
+    })
+    .await
+    .map_err(|e| parse_io_error(e, "upload_from_path", &local_path))?;
+
+    let r = ObserveReader::new(Unblock::with_capacity(BUFFER_SIZE, file), f);
+    let n = w.write_reader(Box::new(r), size).await? as u64;
+    if n != size {
+        return Err(Error::Object {
+            kind: Kind::ChecksumMismatch,
+            op: "upload_from_path",
+            path: path.to_string(),
+            source: anyhow!("length mismatch: expected {}, actual {}", size, n),
+        });
+    }
+
+    Ok(n)
+}
@@ -0,0 +1,150 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::channel::mpsc;
+use futures::future::select;
+use futures::future::BoxFuture;
+use futures::future::Either;
+use futures::SinkExt;
+use futures::TryStreamExt;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::writers::SpooledWriter;
+use crate::Accessor;
+
+/// JsonLinesWriter serializes records into an object as [JSON Lines](https://jsonlines.org/),
+/// one record per line.
+///
+/// - Lines are buffered in memory and flushed into the underlying
+///   [`SpooledWriter`] once more than `flush_size` bytes are buffered, so
+///   producers with lots of records don't need to hold all of them.
+/// - The object will only be written after [`JsonLinesWriter::close`],
+///   dropping the writer discards all records.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let op = Operator::new(memory::Backend::build().finish().await?);
+///
+///     let mut w = op.object("events.jsonl").json_lines_writer();
+///     w.write(&("login", 1)).await?;
+///     w.write(&("logout", 2)).await?;
+///     let n = w.close().await?;
+///     assert_eq!(n, 25);
+///
+///     Ok(())
+/// }
+/// ```
+pub struct JsonLinesWriter {
+    path: String,
+    flush_size: usize,
+
+    buf: Vec<u8>,
+    tx: mpsc::Sender<std::io::Result<Bytes>>,
+    /// The spooled write which consumes the flushed lines.
+    task: BoxFuture<'static, Result<usize>>,
+}
+
+impl JsonLinesWriter {
+    /// Create a new JsonLinesWriter which writes into `path`.
+    pub fn new(acc: Arc<dyn Accessor>, path: &str) -> Self {
+        let (tx, rx) = mpsc::channel(1);
+        let w = SpooledWriter::new(acc, path);
+
+        Self {
+            path: path.to_string(),
+            flush_size: 64 * 1024,
+            buf: Vec::new(),
+            tx,
+            task: Box::pin(w.write_reader(Box::new(rx.into_async_read()))),
+        }
+    }
+
+    /// Flush buffered lines once more than `n` bytes are buffered, default
+    /// to 64 KiB.
+    #[must_use]
+    pub fn flush_size(mut self, n: usize) -> Self {
+        self.flush_size = n;
+        self
+    }
+
+    /// Serialize `record` as a line.
+    ///
+    /// Returns an error with [`Kind::Unexpected`] if `record` can't be
+    /// serialized, nothing of it will be written.
+    pub async fn write<T: Serialize + ?Sized>(&mut self, record: &T) -> Result<()> {
+        let line = serde_json::to_vec(record).map_err(|e| Error::Object {
+            kind: Kind::Unexpected,
+            op: "write",
+            path: self.path.clone(),
+            source: anyhow::Error::from(e),
+        })?;
+        self.buf.extend_from_slice(&line);
+        self.buf.push(b'\n');
+
+        if self.buf.len() >= self.flush_size {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Flush all buffered lines into the underlying writer.
+    pub async fn flush(&mut self) -> Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+
+        let bs = Bytes::from(std::mem::take(&mut self.buf));
+        // The spooled write must be driven while we are waiting for the
+        // channel, or it will never be drained.
+        match select(self.tx.send(Ok(bs)), &mut self.task).await {
+            Either::Left((Ok(()), _)) => Ok(()),
+            Either::Left((Err(e), _)) => Err(Error::Object {
+                kind: Kind::Unexpected,
+                op: "write",
+                path: self.path.clone(),
+                source: anyhow::Error::from(e),
+            }),
+            // The write can't finish before the channel is closed, it must
+            // have failed.
+            Either::Right((result, _)) => Err(result.err().unwrap_or_else(|| Error::Object {
+                kind: Kind::Unexpected,
+                op: "write",
+                path: self.path.clone(),
+                source: anyhow::anyhow!("write finished before all lines are flushed"),
+            })),
+        }
+    }
+
+    /// Flush all buffered lines and write the object, returns the total
+    /// size.
+    pub async fn close(mut self) -> Result<usize> {
+        self.flush().await?;
+        self.tx.close_channel();
+
+        self.task.await
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use bytes::Bytes;
+use futures::channel::mpsc;
+use futures::future::select;
+use futures::future::BoxFuture;
+use futures::future::Either;
+use futures::SinkExt;
+use futures::TryStreamExt;
+use tokio::time::Instant;
+
+use crate::error::Error;
+use crate::error::Kind;
+use crate::error::Result;
+use crate::ops::WriteOptions;
+use crate::readers::Digest;
+use crate::readers::DigestKind;
+use crate::readers::Hasher;
+use crate::writers::SpooledWriter;
+use crate::Accessor;
+
+/// Placeholder of the part number in the path template.
+const COUNTER_PLACEHOLDER: &str = "{n}";
+
+/// PartitionedWriter splits one unbounded stream into objects of at most
+/// `max_part_size` bytes, like `logs/part-00001`, `logs/part-00002`.
+///
+/// - Paths are generated by replacing `{n}` in the template with the part
+///   number, starting from 1 and zero-padded to 5 digits.
+/// - A part is finished once it reaches `max_part_size`, or when it's
+///   older than `max_part_age` at the next write. Parts are never empty.
+/// - Every part is spooled by [`SpooledWriter`] and written after it's
+///   finished, with `commit_visible` if the backend supports it. So only
+///   fully written parts will be visible, even if the process crashes.
+/// - The current part is discarded if the writer is dropped without
+///   [`PartitionedWriter::close`].
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let op = Operator::new(memory::Backend::build().finish().await?);
+///
+///     let mut w = op.partitioned_writer("logs/part-{n}")?.max_part_size(8);
+///     w.write(b"Hello, World!").await?;
+///     let parts = w.close().await?;
+///     assert_eq!(parts[0].path, "logs/part-00001");
+///     assert_eq!(parts[1].size, 5);
+///
+///     Ok(())
+/// }
+/// ```
+pub struct PartitionedWriter {
+    acc: Arc<dyn Accessor>,
+    template: String,
+    max_part_size: u64,
+    max_part_age: Option<Duration>,
+    digest: DigestKind,
+
+    parts: Vec<Part>,
+    current: Option<CurrentPart>,
+}
+
+/// A part written by [`PartitionedWriter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub path: String,
+    pub size: u64,
+    /// Digest of the part's content, see [`PartitionedWriter::digest`].
+    pub digest: Digest,
+}
+
+/// The part being written.
+struct CurrentPart {
+    path: String,
+    size: u64,
+    started: Instant,
+    hasher: Hasher,
+    tx: mpsc::Sender<std::io::Result<Bytes>>,
+    /// The spooled write which consumes the part's data.
+    task: BoxFuture<'static, Result<usize>>,
+}
+
+impl PartitionedWriter {
+    /// Create a new PartitionedWriter which writes parts at paths generated
+    /// by `template`.
+    ///
+    /// Returns an error with [`Kind::ObjectPathInvalid`] if `template`
+    /// doesn't contain `{n}`.
+    pub fn new(acc: Arc<dyn Accessor>, template: &str) -> Result<Self> {
+        if !template.contains(COUNTER_PLACEHOLDER) {
+            return Err(Error::Object {
+                kind: Kind::ObjectPathInvalid,
+                op: "write",
+                path: template.to_string(),
+                source: anyhow!("path template must contain {}", COUNTER_PLACEHOLDER),
+            });
+        }
+
+        Ok(Self {
+            acc,
+            template: template.to_string(),
+            max_part_size: 128 * 1024 * 1024,
+            max_part_age: None,
+            digest: DigestKind::Crc32c,
+            parts: Vec::new(),
+            current: None,
+        })
+    }
+
+    /// Finish a part once it reaches `n` bytes, default to 128 MiB.
+    #[must_use]
+    pub fn max_part_size(mut self, n: u64) -> Self {
+        self.max_part_size = n.max(1);
+        self
+    }
+
+    /// Finish a part if it's older than `d` at the next write.
+    ///
+    /// The age is only checked while writing, idle parts will not be
+    /// finished until the next write or close.
+    #[must_use]
+    pub fn max_part_age(mut self, d: Duration) -> Self {
+        self.max_part_age = Some(d);
+        self
+    }
+
+    /// Compute digests of parts in `kind`, default to [`DigestKind::Crc32c`].
+    #[must_use]
+    pub fn digest(mut self, kind: DigestKind) -> Self {
+        self.digest = kind;
+        self
+    }
+
+    /// Returns the parts that have been finished.
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    /// Write `bs` into the current part, parts will be rolled over as
+    /// needed.
+    pub async fn write(&mut self, mut bs: &[u8]) -> Result<()> {
+        while !bs.is_empty() {
+            if self.should_roll() {
+                self.finish_part().await?;
+            }
+            if self.current.is_none() {
+                self.start_part();
+            }
+
+            let cur = self.current.as_mut().expect("current part must exist");
+            let n = (self.max_part_size - cur.size).min(bs.len() as u64) as usize;
+            let (chunk, rest) = bs.split_at(n);
+            cur.hasher.update(chunk);
+            cur.size += n as u64;
+            Self::send(cur, Bytes::copy_from_slice(chunk)).await?;
+            bs = rest;
+        }
+        Ok(())
+    }
+
+    /// Finish the current part and returns all written parts.
+    pub async fn close(mut self) -> Result<Vec<Part>> {
+        self.finish_part().await?;
+        Ok(self.parts)
+    }
+
+    fn should_roll(&self) -> bool {
+        match &self.current {
+            None => false,
+            Some(cur) => {
+                cur.size >= self.max_part_size
+                    || self
+                        .max_part_age
+                        .is_some_and(|age| cur.started.elapsed() >= age)
+            }
+        }
+    }
+
+    fn start_part(&mut self) {
+        let number = self.parts.len() + 1;
+        let path = self
+            .template
+            .replace(COUNTER_PLACEHOLDER, &format!("{:05}", number));
+
+        let mut opts = WriteOptions::default();
+        if self.acc.metadata().can_commit_visible() {
+            opts = opts.commit_visible(true);
+        }
+        let (tx, rx) = mpsc::channel(1);
+        let w = SpooledWriter::new(self.acc.clone(), &path).with_options(opts);
+
+        self.current = Some(CurrentPart {
+            path,
+            size: 0,
+            started: Instant::now(),
+            hasher: Hasher::new(self.digest),
+            tx,
+            task: Box::pin(w.write_reader(Box::new(rx.into_async_read()))),
+        });
+    }
+
+    /// Send `bs` into the spooled write of `cur`.
+    async fn send(cur: &mut CurrentPart, bs: Bytes) -> Result<()> {
+        // The spooled write must be driven while we are waiting for the
+        // channel, or it will never be drained.
+        match select(cur.tx.send(Ok(bs)), &mut cur.task).await {
+            Either::Left((Ok(()), _)) => Ok(()),
+            Either::Left((Err(e), _)) => Err(Error::Object {
+                kind: Kind::Unexpected,
+                op: "write",
+                path: cur.path.clone(),
+                source: anyhow::Error::from(e),
+            }),
+            // The write can't finish before the channel is closed, it must
+            // have failed.
+            Either::Right((result, _)) => Err(result.err().unwrap_or_else(|| Error::Object {
+                kind: Kind::Unexpected,
+                op: "write",
+                path: cur.path.clone(),
+                source: anyhow!("write finished before all data are sent"),
+            })),
+        }
+    }
+
+    /// Close the current part's channel and wait for it to be written.
+    async fn finish_part(&mut self) -> Result<()> {
+        let mut cur = match self.current.take() {
+            None => return Ok(()),
+            Some(cur) => cur,
+        };
+        cur.tx.close_channel();
+        cur.task.await?;
+
+        self.parts.push(Part {
+            path: cur.path,
+            size: cur.size,
+            digest: cur.hasher.finalize(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::env;
+use std::fs;
+use std::io::SeekFrom;
+use std::sync::Arc;
+
+use blocking::unblock;
+use blocking::Unblock;
+use futures::io;
+use futures::AsyncReadExt;
+use futures::AsyncSeekExt;
+use futures::AsyncWriteExt;
+use log::warn;
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::ops::WriteOptions;
+use crate::services::fs::error::parse_io_error;
+use crate::Accessor;
+use crate::BoxedAsyncReader;
+use crate::Writer;
+
+/// SpooledWriter writes data of unknown length into backends that require
+/// the size upfront (like s3's single part upload).
+///
+/// Data will be buffered in memory up to `threshold`, and spilled into a
+/// temp file after that. The total size is learned after the source reached
+/// EOF, then the sized write will be issued.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use futures::io::Cursor;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let op = Operator::new(memory::Backend::build().finish().await?);
+///
+///     let r = Box::new(Cursor::new("Hello, World!".as_bytes().to_vec()));
+///     let n = op
+///         .object("test")
+///         .spooled_writer()
+///         .threshold(4)
+///         .write_reader(r)
+///         .await?;
+///     assert_eq!(n, 13);
+///
+///     Ok(())
+/// }
+/// ```
+pub struct SpooledWriter {
+    acc: Arc<dyn Accessor>,
+    path: String,
+    threshold: usize,
+    opts: WriteOptions,
+}
+
+impl SpooledWriter {
+    /// Create a new SpooledWriter which writes into `path`.
+    pub fn new(acc: Arc<dyn Accessor>, path: &str) -> Self {
+        Self {
+            acc,
+            path: path.to_string(),
+            threshold: 8 * 1024 * 1024,
+            opts: WriteOptions::default(),
+        }
+    }
+
+    /// Spill data into a temp file once more than `n` bytes have been
+    /// buffered, default to 8 MiB.
+    #[must_use]
+    pub fn threshold(mut self, n: usize) -> Self {
+        self.threshold = n;
+        self
+    }
+
+    /// Replace all options of the sized write with `opts`.
+    pub(crate) fn with_options(mut self, opts: WriteOptions) -> Self {
+        self.opts = opts;
+        self
+    }
+
+    /// Read all data from `r` and write them into the object, returns the
+    /// total size.
+    pub async fn write_reader(self, mut r: BoxedAsyncReader) -> Result<usize> {
+        let mut buf = Vec::new();
+        (&mut r)
+            .take(self.threshold as u64 + 1)
+            .read_to_end(&mut buf)
+            .await
+            .map_err(|e| parse_io_error(e, "write", &self.path))?;
+
+        let w = Writer::new(self.acc.clone(), &self.path).with_options(self.opts.clone());
+        if buf.len() <= self.threshold {
+            return w.write_bytes(buf).await;
+        }
+
+        let tmp_path = env::temp_dir()
+            .join(format!("opendal-spool-{}", Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string();
+        let result = self.write_spilled(w, buf, r, &tmp_path).await;
+
+        let capture_path = tmp_path.clone();
+        if let Err(err) = unblock(|| fs::remove_file(capture_path)).await {
+            warn!(
+                "object {} remove spool file {}: {:?}",
+                &self.path, &tmp_path, err
+            );
+        }
+
+        result
+    }
+
+    /// Spill the buffered data and the rest of `r` into the temp file at
+    /// `tmp_path`, then write the file into the object.
+    async fn write_spilled(
+        &self,
+        w: Writer,
+        buf: Vec<u8>,
+        mut r: BoxedAsyncReader,
+        tmp_path: &str,
+    ) -> Result<usize> {
+        let capture_path = tmp_path.to_string();
+        let f = unblock(|| {
+            fs::OpenOptions::new()
+                .create_new(true)
+                .read(true)
+                .write(true)
+                .open(capture_path)
+        })
+        .await
+        .map_err(|e| parse_io_error(e, "write", tmp_path))?;
+        let mut f = Unblock::new(f);
+
+        f.write_all(&buf)
+            .await
+            .map_err(|e| parse_io_error(e, "write", tmp_path))?;
+        let n = io::copy(&mut r, &mut f)
+            .await
+            .map_err(|e| parse_io_error(e, "write", &self.path))?;
+        f.flush()
+            .await
+            .map_err(|e| parse_io_error(e, "write", tmp_path))?;
+        f.seek(SeekFrom::Start(0))
+            .await
+            .map_err(|e| parse_io_error(e, "write", tmp_path))?;
+
+        w.write_reader(Box::new(f), buf.len() as u64 + n).await
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+
+use futures::AsyncRead;
+use log::warn;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::error::Result;
+use crate::ops::OpDelete;
+use crate::ops::OpRead;
+use crate::ops::OpWrite;
+use crate::Accessor;
+use crate::BoxedAsyncReader;
+
+/// WriteThenName writes data into a temporary object while computing its
+/// SHA256 digest, and then moves the data to a path derived from the digest.
+///
+/// It's the core of a content-addressed storage built on OpenDAL.
+///
+/// # Note
+///
+/// Backends don't support rename for now, so the data will be copied from
+/// the temporary object to the final one. The temporary object will always
+/// be removed, whether the write succeeds or not.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::services::memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let op = Operator::new(memory::Backend::build().finish().await?);
+///
+///     let path = op
+///         .object("tmp/upload")
+///         .write_then_name()
+///         .write_bytes("Hello, World!".as_bytes().to_vec(), |digest| {
+///             let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+///             format!("cas/{}", hex)
+///         })
+///         .await?;
+///     assert!(path.starts_with("cas/"));
+///
+///     Ok(())
+/// }
+/// ```
+pub struct WriteThenName {
+    acc: Arc<dyn Accessor>,
+    path: String,
+}
+
+impl WriteThenName {
+    /// Create a new WriteThenName which uses `path` as the temporary object.
+    pub fn new(acc: Arc<dyn Accessor>, path: &str) -> Self {
+        Self {
+            acc,
+            path: path.to_string(),
+        }
+    }
+
+    pub async fn write_bytes<F>(self, bs: Vec<u8>, name: F) -> Result<String>
+    where
+        F: FnOnce(&[u8]) -> String,
+    {
+        let size = bs.len() as u64;
+        let r = Box::new(futures::io::Cursor::new(bs));
+
+        self.write_reader(r, size, name).await
+    }
+
+    /// Write all data from `r`, and name the final object via `name`.
+    ///
+    /// `name` will be called with the SHA256 digest of the data, and returns
+    /// the final path.
+    pub async fn write_reader<F>(self, r: BoxedAsyncReader, size: u64, name: F) -> Result<String>
+    where
+        F: FnOnce(&[u8]) -> String,
+    {
+        let result = self.write_and_copy(r, size, name).await;
+
+        // Always cleanup the temporary object. The final object has been
+        // written if we reach here with `Ok`, so a failed cleanup is not
+        // worth to fail the whole write.
+        if let Err(e) = self.acc.delete(&OpDelete::new(&self.path)).await {
+            warn!("object {} cleanup temporary: {:?}", &self.path, e);
+        }
+
+        result
+    }
+
+    async fn write_and_copy<F>(&self, r: BoxedAsyncReader, size: u64, name: F) -> Result<String>
+    where
+        F: FnOnce(&[u8]) -> String,
+    {
+        let hasher = Arc::new(Mutex::new(Sha256::new()));
+        let r = Box::new(HashReader {
+            inner: r,
+            hasher: hasher.clone(),
+        });
+
+        let n = self.acc.write(r, &OpWrite::new(&self.path, size)).await?;
+
+        let digest = hasher.lock().expect("lock poisoned").clone().finalize();
+        let path = name(&digest);
+
+        let r = self
+            .acc
+            .read(&OpRead {
+                path: self.path.clone(),
+                offset: None,
+                size: None,
+                version_id: None,
+            })
+            .await?
+            .into_reader();
+        self.acc.write(r, &OpWrite::new(&path, n as u64)).await?;
+
+        Ok(path)
+    }
+}
+
+/// HashReader feeds all data it reads into a SHA256 hasher.
+struct HashReader {
+    inner: BoxedAsyncReader,
+    hasher: Arc<Mutex<Sha256>>,
+}
+
+impl AsyncRead for HashReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let r = Pin::new(&mut self.inner).poll_read(cx, buf);
+
+        if let Poll::Ready(Ok(n)) = r {
+            self.hasher.lock().expect("lock poisoned").update(&buf[..n]);
+        }
+
+        r
+    }
+}
@@ -20,12 +20,23 @@
 //!
 //! For examples, we depends `write` to create a file before testing `read`. If `write` doesn't works well, we can't test `read` correctly too.
 
+use std::env;
+use std::fs;
 use std::io::SeekFrom;
 
 use anyhow::Result;
 use futures::AsyncReadExt;
 use futures::AsyncSeekExt;
 use futures::StreamExt;
+use futures::TryStreamExt;
+use opendal::error::Kind;
+use opendal::ops::ListMode;
+use opendal::ops::ListOptions;
+use opendal::ops::ReadOptions;
+use opendal::ops::SelectInput;
+use opendal::ops::SelectOutput;
+use opendal::readers::ReadEvent;
+use opendal::MetaField;
 use opendal::ObjectMode;
 use opendal::Operator;
 use rand::prelude::*;
@@ -49,6 +60,19 @@ impl BehaviorTest {
 
     pub async fn run(&mut self) -> Result<()> {
         self.test_normal().await?;
+        self.test_select().await?;
+        self.test_fetch_to_path().await?;
+        self.test_fetch_concurrent().await?;
+        self.test_multipart_uploads().await?;
+        self.test_long_path().await?;
+        self.test_read_beyond_end().await?;
+        self.test_read_not_exist().await?;
+        self.test_retention().await?;
+        self.test_list_fetch_owner().await?;
+        self.test_list_mode().await?;
+        self.test_dir_marker_with_data().await?;
+        self.test_create_dir_all().await?;
+        self.test_root().await?;
 
         Ok(())
     }
@@ -115,6 +139,14 @@ impl BehaviorTest {
         }
         assert!(found, "file should be found in iterator");
 
+        // Step 5.1: List this file, we should get this file only.
+        let obs: Vec<_> = self.op.objects(&path).try_collect().await?;
+        assert_eq!(obs.len(), 1, "list file should return itself only");
+        let meta = obs[0].metadata().await?;
+        assert_eq!(meta.path(), path, "list file");
+        assert_eq!(meta.mode(), ObjectMode::FILE, "list file");
+        assert_eq!(meta.content_length(), size as u64, "list file");
+
         // Step 6: Delete this file
         let result = self.op.object(&path).delete().await;
         assert!(result.is_ok(), "delete file: {}", result.unwrap_err());
@@ -126,6 +158,537 @@ impl BehaviorTest {
         Ok(())
     }
 
+    /// Download a file by fetching its ranges concurrently.
+    async fn test_fetch_concurrent(&mut self) -> Result<()> {
+        let path = uuid::Uuid::new_v4().to_string();
+        println!("Generate a random file: {}", &path);
+        let (content, size) = self.gen_bytes();
+        let part_size = (size as u64 / 7).max(1);
+
+        self.op
+            .object(&path)
+            .writer()
+            .write_bytes(content.clone())
+            .await?;
+
+        let mut buf = Vec::new();
+        let n = self
+            .op
+            .object(&path)
+            .fetch_range_concurrent(&mut buf, part_size, 4)
+            .await?;
+        assert_eq!(n, size as u64, "fetch range concurrent");
+        assert_eq!(
+            format!("{:x}", Sha256::digest(&buf)),
+            format!("{:x}", Sha256::digest(&content)),
+            "check hash in fetch range concurrent"
+        );
+
+        let local = env::temp_dir()
+            .join(uuid::Uuid::new_v4().to_string())
+            .join(&path);
+        let n = self
+            .op
+            .object(&path)
+            .fetch_to_path_concurrent(&local, part_size, 4)
+            .await?;
+        assert_eq!(n, size as u64, "fetch to path concurrent");
+        assert_eq!(
+            format!("{:x}", Sha256::digest(&fs::read(&local)?)),
+            format!("{:x}", Sha256::digest(&content)),
+            "check hash in fetch to path concurrent"
+        );
+
+        fs::remove_dir_all(local.parent().expect("parent must exist"))?;
+        self.op.object(&path).delete().await?;
+        Ok(())
+    }
+
+    /// This case is use to test transfer between objects and local files.
+    async fn test_fetch_to_path(&mut self) -> Result<()> {
+        let path = uuid::Uuid::new_v4().to_string();
+        println!("Generate a random file: {}", &path);
+        let (content, size) = self.gen_bytes();
+
+        self.op
+            .object(&path)
+            .writer()
+            .write_bytes(content.clone())
+            .await?;
+
+        // Download into a not existing dir.
+        let local = env::temp_dir()
+            .join(uuid::Uuid::new_v4().to_string())
+            .join(&path);
+        let mut read = 0;
+        let n = self
+            .op
+            .object(&path)
+            .fetch_to_path_with(&local, |e| {
+                if let ReadEvent::Read(n) = e {
+                    read += n
+                }
+            })
+            .await?;
+        assert_eq!(n, size as u64, "fetch to path");
+        assert_eq!(read, size, "fetch to path progress");
+        assert_eq!(
+            format!("{:x}", Sha256::digest(&fs::read(&local)?)),
+            format!("{:x}", Sha256::digest(&content)),
+            "check hash in fetch to path"
+        );
+
+        // Upload it back as a new object.
+        let upload_path = uuid::Uuid::new_v4().to_string();
+        let n = self
+            .op
+            .object(&upload_path)
+            .upload_from_path(&local)
+            .await?;
+        assert_eq!(n, size as u64, "upload from path");
+
+        let mut buf = Vec::new();
+        self.op
+            .object(&upload_path)
+            .reader()
+            .read_to_end(&mut buf)
+            .await?;
+        assert_eq!(
+            format!("{:x}", Sha256::digest(&buf)),
+            format!("{:x}", Sha256::digest(&content)),
+            "check hash in upload from path"
+        );
+
+        fs::remove_dir_all(local.parent().expect("parent must exist"))?;
+        self.op.object(&path).delete().await?;
+        self.op.object(&upload_path).delete().await?;
+        Ok(())
+    }
+
+    /// This case is use to test service's select support.
+    ///
+    /// Services that don't support select will be skipped.
+    async fn test_select(&mut self) -> Result<()> {
+        let path = format!("{}.csv", uuid::Uuid::new_v4());
+        println!("Generate a csv file: {}", &path);
+        let content = "name,age\nalice,17\nbob,18\ncarol,19\n";
+
+        let w = self.op.object(&path).writer();
+        w.write_bytes(content.as_bytes().to_vec()).await?;
+
+        let r = self
+            .op
+            .object(&path)
+            .select(
+                "SELECT s.name FROM S3Object s WHERE CAST(s.age AS INT) >= 18",
+                SelectInput::Csv { has_header: true },
+                SelectOutput::Csv,
+            )
+            .await;
+        let mut r = match r {
+            Ok(r) => r,
+            Err(e) if e.kind() == Kind::Unsupported => {
+                println!("select is not supported, skip");
+                self.op.object(&path).delete().await?;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut rows = String::new();
+        r.read_to_string(&mut rows).await?;
+        assert_eq!(rows, "bob\ncarol\n", "select rows");
+
+        self.op.object(&path).delete().await?;
+        Ok(())
+    }
+
+    /// This case is use to test listing and aborting of incomplete
+    /// multipart uploads.
+    async fn test_multipart_uploads(&mut self) -> Result<()> {
+        let prefix = format!("{}/", uuid::Uuid::new_v4());
+
+        let uploads = match self.op.list_multipart_uploads(&prefix).await {
+            Ok(uploads) => uploads,
+            Err(e) if e.kind() == Kind::Unsupported => {
+                println!("multipart upload is not supported, skip");
+                let err = self
+                    .op
+                    .abort_multipart_upload(&format!("{prefix}a"), "upload-id")
+                    .await
+                    .expect_err("abort must fail if list is unsupported");
+                assert_eq!(err.kind(), Kind::Unsupported);
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        assert!(uploads.is_empty(), "no uploads under a fresh prefix");
+
+        // Abort an upload that never exists.
+        let err = self
+            .op
+            .abort_multipart_upload(&format!("{prefix}a"), "not-exist-upload-id")
+            .await
+            .expect_err("abort of not exist upload must fail");
+        println!("abort not exist upload: {}", err);
+
+        Ok(())
+    }
+
+    /// This case is use to test deeply nested and long paths, they must
+    /// either work or be rejected with `ObjectPathInvalid`.
+    async fn test_long_path(&mut self) -> Result<()> {
+        let content = b"Hello, World!".to_vec();
+
+        let deep = format!("{}/{}file", uuid::Uuid::new_v4(), "a/".repeat(999));
+        let long_segment = format!("{}/{}", uuid::Uuid::new_v4(), "a".repeat(300));
+
+        for path in [deep, long_segment] {
+            let o = self.op.object(&path);
+            match o.writer().write_bytes(content.clone()).await {
+                Ok(_) => {}
+                Err(e) if e.kind() == Kind::ObjectPathInvalid => {
+                    println!("path of {} bytes is rejected: {}", path.len(), e);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            }
+
+            let mut buf = Vec::new();
+            o.reader().read_to_end(&mut buf).await?;
+            assert_eq!(buf, content, "read of path with {} bytes", path.len());
+            o.delete().await?;
+        }
+
+        Ok(())
+    }
+
+    /// This case is use to test ranges starting at or beyond the end of
+    /// objects, they read nothing unless the reader is strict.
+    async fn test_read_beyond_end(&mut self) -> Result<()> {
+        let path = uuid::Uuid::new_v4().to_string();
+        let o = self.op.object(&path);
+        o.writer().write_bytes(b"Hello, World!".to_vec()).await?;
+
+        for offset in [13, 100] {
+            let mut buf = Vec::new();
+            let n = o.offset_reader(offset).read_to_end(&mut buf).await?;
+            assert_eq!(n, 0, "read from offset {}", offset);
+
+            let err = o
+                .offset_reader(offset)
+                .strict_range()
+                .read_to_end(&mut buf)
+                .await
+                .expect_err("strict read beyond end must fail");
+            let err = err.into_inner().expect("must be opendal error");
+            let err = err
+                .downcast_ref::<opendal::error::Error>()
+                .expect("must be opendal error");
+            assert_eq!(err.kind(), Kind::RangeNotSatisfiable, "strict read");
+        }
+        o.delete().await?;
+
+        // Read the whole empty object is fine.
+        let o = self.op.object(&uuid::Uuid::new_v4().to_string());
+        o.writer().write_bytes(Vec::new()).await?;
+        let mut buf = Vec::new();
+        let n = o.reader().strict_range().read_to_end(&mut buf).await?;
+        assert_eq!(n, 0, "read empty object");
+        o.delete().await?;
+
+        Ok(())
+    }
+
+    /// This case is use to test reading missing objects fails with
+    /// `ObjectNotExist` on every service, both from `reader_checked` and
+    /// from the first poll of the lazy reader.
+    async fn test_read_not_exist(&mut self) -> Result<()> {
+        let path = uuid::Uuid::new_v4().to_string();
+        let mut o = self.op.object(&path);
+
+        let err = o
+            .reader_checked()
+            .await
+            .err()
+            .expect("read missing object must fail");
+        assert_eq!(err.kind(), Kind::ObjectNotExist, "reader_checked");
+
+        let mut r = o.reader();
+        let mut buf = Vec::new();
+        for _ in 0..2 {
+            let err = r
+                .read_to_end(&mut buf)
+                .await
+                .expect_err("read missing object must fail");
+            assert_eq!(err.kind(), std::io::ErrorKind::NotFound, "reader poll");
+            let err = err.into_inner().expect("must be opendal error");
+            let err = err
+                .downcast_ref::<opendal::error::Error>()
+                .expect("must be opendal error");
+            assert_eq!(err.kind(), Kind::ObjectNotExist, "reader poll");
+        }
+
+        // Children of a file don't exist either.
+        o.writer().write_bytes(b"Hello, World!".to_vec()).await?;
+        let err = self
+            .op
+            .object(&format!("{}/child", path))
+            .reader_checked()
+            .await
+            .err()
+            .expect("read child of file must fail");
+        assert_eq!(err.kind(), Kind::ObjectNotExist, "read child of file");
+        o.delete().await?;
+
+        Ok(())
+    }
+
+    /// This case is use to test reading the lock status of objects, which
+    /// needs a bucket with object lock enabled.
+    async fn test_retention(&mut self) -> Result<()> {
+        let path = uuid::Uuid::new_v4().to_string();
+        let o = self.op.object(&path);
+        o.writer().write_bytes(b"Hello, World!".to_vec()).await?;
+
+        let retention = match o.retention().await {
+            Ok(v) => v,
+            Err(e) if e.kind() == Kind::Unsupported => {
+                println!("object lock is not supported, skip");
+                o.delete().await?;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        // Buckets may have a default retention, but never a legal hold.
+        assert!(!retention.legal_hold(), "new object has no legal hold");
+        if retention.mode().is_some() {
+            assert!(
+                retention.retain_until().is_some(),
+                "retention mode must come with a date"
+            );
+            println!("object is locked by default retention, skip delete");
+            return Ok(());
+        }
+        assert!(retention.retain_until().is_none());
+
+        let err = self
+            .op
+            .object(&uuid::Uuid::new_v4().to_string())
+            .retention()
+            .await
+            .expect_err("retention of not exist object must fail");
+        assert_eq!(err.kind(), Kind::ObjectNotExist);
+
+        o.delete().await?;
+        Ok(())
+    }
+
+    /// This case is use to test listing with owners, which must list the
+    /// same entries as normal listing.
+    async fn test_list_fetch_owner(&mut self) -> Result<()> {
+        let dir = format!("{}/", uuid::Uuid::new_v4());
+        for name in ["a", "b", "c"] {
+            self.op
+                .object(&format!("{dir}{name}"))
+                .writer()
+                .write_bytes(b"Hello, World!".to_vec())
+                .await?;
+        }
+
+        let mut paths = Vec::new();
+        let mut owners = Vec::new();
+        let mut obs = self.op.objects(&dir).fetch_owner();
+        while let Some(mut o) = obs.try_next().await? {
+            let meta = o.metadata_cached_for(&[MetaField::Mode]).await?;
+            println!("{} owned by {:?}", meta.path(), meta.owner_id());
+            paths.push(meta.path().to_string());
+            owners.push(meta.owner_id().is_some());
+        }
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![format!("{dir}a"), format!("{dir}b"), format!("{dir}c")]
+        );
+        // Owner is either returned for all objects or none of them.
+        assert!(
+            owners.iter().all(|v| *v == owners[0]),
+            "owners: {:?}",
+            owners
+        );
+
+        for name in ["a", "b", "c"] {
+            self.op.object(&format!("{dir}{name}")).delete().await?;
+        }
+        Ok(())
+    }
+
+    async fn test_list_mode(&mut self) -> Result<()> {
+        let base = uuid::Uuid::new_v4().to_string();
+        let (dir_file, prefix_file) = (format!("{base}/abc/x"), format!("{base}/abcdef"));
+        for path in [&dir_file, &prefix_file] {
+            self.op
+                .object(path)
+                .writer()
+                .write_bytes(b"Hello, World!".to_vec())
+                .await?;
+        }
+
+        // Dir mode only returns the children of `abc/`, never `abcdef`.
+        for path in [format!("{base}/abc"), format!("{base}/abc/")] {
+            let mut paths = Vec::new();
+            let mut obs = self.op.object(&path).list();
+            while let Some(mut o) = obs.try_next().await? {
+                let meta = o.metadata_cached_for(&[MetaField::Mode]).await?;
+                paths.push(meta.path().to_string());
+            }
+            assert_eq!(paths, vec![dir_file.clone()], "list dir {path}");
+        }
+
+        // Prefix mode returns every key starting with `abc`.
+        let mut obs = self
+            .op
+            .object(&format!("{base}/abc"))
+            .list_with(ListOptions::new().mode(ListMode::Prefix));
+        let mut paths = Vec::new();
+        loop {
+            match obs.next().await {
+                Some(Ok(mut o)) => {
+                    let meta = o.metadata_cached_for(&[MetaField::Mode]).await?;
+                    paths.push(meta.path().to_string());
+                }
+                Some(Err(e)) if e.kind() == Kind::Unsupported => {
+                    println!("list by prefix is not supported, skip");
+                    paths = vec![dir_file.clone(), prefix_file.clone()];
+                    break;
+                }
+                Some(Err(e)) => return Err(e.into()),
+                None => break,
+            }
+        }
+        paths.sort();
+        assert_eq!(paths, vec![dir_file.clone(), prefix_file.clone()]);
+
+        for path in [&dir_file, &prefix_file] {
+            self.op.object(path).delete().await?;
+        }
+        Ok(())
+    }
+
+    /// Keys like `abc/` could carry data on services like s3.
+    async fn test_dir_marker_with_data(&mut self) -> Result<()> {
+        let parent = format!("{}/", uuid::Uuid::new_v4());
+        let path = format!("{parent}weird/");
+        if let Err(e) = self
+            .op
+            .object(&path)
+            .writer()
+            .write_bytes(b"Hello".to_vec())
+            .await
+        {
+            println!("write data into dir path is not supported, skip: {e}");
+            return Ok(());
+        }
+
+        let meta = self.op.object(&path).metadata().await?;
+        assert_eq!(meta.mode(), ObjectMode::DIR);
+        assert_eq!(meta.content_length(), 5);
+
+        let mut buf = Vec::new();
+        assert!(self
+            .op
+            .object(&path)
+            .reader()
+            .read_to_end(&mut buf)
+            .await
+            .is_err());
+        self.op
+            .object(&path)
+            .reader_with(ReadOptions::new().allow_trailing_slash())
+            .read_to_end(&mut buf)
+            .await?;
+        assert_eq!(buf, b"Hello");
+
+        let mut entries = Vec::new();
+        let mut obs = self.op.objects(&parent);
+        while let Some(mut o) = obs.try_next().await? {
+            let meta = o.metadata_cached_for(&[MetaField::Mode]).await?;
+            entries.push((meta.path().to_string(), meta.mode()));
+        }
+        assert_eq!(entries, vec![(path.clone(), ObjectMode::DIR)]);
+
+        self.op.object(&path).delete().await?;
+        Ok(())
+    }
+
+    /// Dirs created by `create_dir_all` behave the same whether they are
+    /// real (fs) or markers (s3, memory).
+    async fn test_create_dir_all(&mut self) -> Result<()> {
+        let base = uuid::Uuid::new_v4().to_string();
+        let dirs = [
+            format!("{base}/a/"),
+            format!("{base}/a/b/"),
+            format!("{base}/a/b/c/"),
+        ];
+        self.op.create_dir_all(&dirs[2]).await?;
+
+        for dir in &dirs {
+            let meta = self.op.object(dir).metadata().await?;
+            assert_eq!(meta.mode(), ObjectMode::DIR, "stat {dir}");
+        }
+
+        let list = |path: &str| {
+            let mut obs = self.op.objects(path);
+            async move {
+                let mut entries = Vec::new();
+                while let Some(mut o) = obs.try_next().await? {
+                    let meta = o.metadata_cached_for(&[MetaField::Mode]).await?;
+                    entries.push((meta.path().to_string(), meta.mode()));
+                }
+                Result::<_>::Ok(entries)
+            }
+        };
+        assert_eq!(
+            list(&dirs[0]).await?,
+            vec![(dirs[1].clone(), ObjectMode::DIR)],
+            "list {}",
+            dirs[0]
+        );
+        assert_eq!(list(&dirs[2]).await?, vec![], "list {}", dirs[2]);
+
+        for dir in dirs.iter().rev() {
+            self.op.object(dir).delete().await?;
+        }
+        Ok(())
+    }
+
+    /// This case is use to test the behavior of the root object, which is
+    /// the same for all services.
+    async fn test_root(&mut self) -> Result<()> {
+        for path in ["", "/"] {
+            let o = self.op.object(path);
+
+            let meta = o.metadata().await?;
+            assert_eq!(meta.mode(), ObjectMode::DIR, "stat root");
+            assert!(meta.is_fully_loaded(), "stat root");
+            assert!(!meta.has(MetaField::ContentLength), "stat root");
+
+            assert!(o.is_exist().await?, "root exists");
+
+            let err = o.delete().await.expect_err("delete root");
+            assert_eq!(err.kind(), Kind::Unsupported, "delete root");
+
+            let err = o
+                .reader()
+                .read_to_end(&mut Vec::new())
+                .await
+                .expect_err("read root");
+            assert_eq!(err.kind(), std::io::ErrorKind::Unsupported, "read root");
+        }
+
+        Ok(())
+    }
+
     fn gen_bytes(&mut self) -> (Vec<u8>, usize) {
         let size = self.rng.gen_range(1..4 * 1024 * 1024);
         let mut content = vec![0; size as usize];
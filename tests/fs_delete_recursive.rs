@@ -0,0 +1,111 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recursive deletion must stream the tree instead of holding all paths
+//! in memory.
+//!
+//! This test lives in its own binary because it installs a global allocator
+//! to measure memory usage.
+
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use anyhow::Result;
+use opendal::services::fs;
+use opendal::Operator;
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const DIRS: usize = 100;
+const FILES_PER_DIR: usize = 1000;
+
+#[tokio::test]
+async fn test_delete_large_tree_in_bounded_memory() -> Result<()> {
+    let root = std::env::temp_dir().join(format!("opendal-{}", uuid::Uuid::new_v4()));
+    for i in 0..DIRS {
+        let dir = root.join(format!("large/{:04}/nested", i));
+        std::fs::create_dir_all(&dir)?;
+        for j in 0..FILES_PER_DIR {
+            std::fs::write(dir.join(format!("{:08}", j)), b"")?;
+        }
+    }
+
+    let op = Operator::new(
+        fs::Backend::build()
+            .root(&root.to_string_lossy())
+            .finish()
+            .await?,
+    );
+
+    // Warm up the blocking threads used by concurrent deletes.
+    for i in 0..64 {
+        op.object(&format!("warmup/{}", i))
+            .writer()
+            .write_bytes(vec![0])
+            .await?;
+    }
+    assert!(op.object("warmup/").delete_recursive().await.is_success());
+
+    let baseline = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+
+    let mut deleted = 0;
+    let mut failed = 0;
+    op.object("large/")
+        .delete_recursive_with(|_, r| match r {
+            Ok(()) => deleted += 1,
+            Err(_) => failed += 1,
+        })
+        .await;
+    assert_eq!(failed, 0);
+    // Files, their `nested/` dirs and the dirs above.
+    assert_eq!(deleted, DIRS * FILES_PER_DIR + DIRS * 2 + 1);
+    assert!(!root.join("large").exists());
+
+    // Collecting all paths would take several MiB.
+    let additional = PEAK.load(Ordering::SeqCst) - baseline;
+    assert!(
+        additional < 1024 * 1024,
+        "deleting used {} bytes additional memory",
+        additional
+    );
+
+    std::fs::remove_dir_all(&root)?;
+    Ok(())
+}
@@ -0,0 +1,32 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading unfilled metadata must not panic with `lenient-metadata`.
+//!
+//! Run with `cargo test --features lenient-metadata --test lenient_metadata`.
+
+#![cfg(feature = "lenient-metadata")]
+
+use opendal::Metadata;
+use opendal::ObjectMode;
+
+#[test]
+fn test_unfilled_metadata() {
+    let meta = Metadata::default();
+
+    assert_eq!(meta.try_mode(), None);
+    assert_eq!(meta.mode(), ObjectMode::Unknown);
+    assert_eq!(meta.try_content_length(), None);
+    assert_eq!(meta.content_length(), 0);
+}
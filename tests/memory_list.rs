@@ -0,0 +1,87 @@
+// Copyright 2022 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Listing must stream entries instead of holding all of them in memory.
+//!
+//! This test lives in its own binary because it installs a global allocator
+//! to measure memory usage.
+
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use anyhow::Result;
+use futures::StreamExt;
+use opendal::services::memory;
+use opendal::Operator;
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const KEYS: usize = 1_000_000;
+
+#[tokio::test]
+async fn test_list_large_prefix_in_bounded_memory() -> Result<()> {
+    let op = Operator::new(memory::Backend::build().finish().await?);
+    for i in 0..KEYS {
+        op.object(&format!("large/{:08}", i))
+            .writer()
+            .write_bytes(vec![0])
+            .await?;
+    }
+
+    let baseline = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+
+    let mut count = 0;
+    let mut obs = op.objects("large/");
+    while let Some(o) = obs.next().await {
+        o?;
+        count += 1;
+    }
+    assert_eq!(count, KEYS);
+
+    // Copying all keys would take tens of MiB.
+    let additional = PEAK.load(Ordering::SeqCst) - baseline;
+    assert!(
+        additional < 1024 * 1024,
+        "listing used {} bytes additional memory",
+        additional
+    );
+
+    Ok(())
+}